@@ -1,38 +1,85 @@
-use crate::output::ClassInfo;
-use crate::parser::{expr_to_string, extract_params, extract_returns, parse_file, ParsedFile};
+use super::complexity::cyclomatic_complexity;
+use super::visit::{is_generator, never_returns};
+use super::{module_statements, ExtractOptions};
+use crate::output::{ClassInfo, ClassKind, ClassMap, Entry, MemberMap, Meta, ModelKind, PropertyAccessors};
+use crate::parser::{
+    annotation_to_string, expr_to_string, extract_decorators, extract_params, extract_returns, format_params,
+    format_type_params, normalize_signature, parse_file, ParsedFile,
+};
+use crate::pattern::{extract_class_name, extract_function_name};
 use eyre::Result;
-use rustpython_parser::ast::{self, Arguments, Stmt};
-use std::collections::BTreeMap;
+use rustpython_parser::ast::{self, Arguments, Stmt, TypeParam};
+use std::collections::{HashMap, HashSet};
 use std::path::Path;
 
-/// Build a method signature string (without class prefix since it's nested under class)
-fn build_method_signature(method_name: &str, args: &Arguments, returns: Option<String>, is_async: bool) -> String {
-    let params = extract_params(args);
-    let params_str: Vec<String> = params
-        .iter()
-        .map(
-            |(name, typ)| {
-                if typ == "..." {
-                    name.clone()
-                } else {
-                    format!("{}: {}", name, typ)
-                }
-            },
-        )
-        .collect();
+/// Decorators that mark a method as abstract
+const ABSTRACT_DECORATORS: &[&str] = &[
+    "abstractmethod",
+    "abstractproperty",
+    "abstractclassmethod",
+    "abstractstaticmethod",
+];
+
+/// Bases or metaclasses that make a class abstract
+const ABSTRACT_BASES: &[&str] = &["ABC", "ABCMeta"];
+
+/// Pydantic bases whose subclasses are data models
+const PYDANTIC_BASES: &[&str] = &["BaseModel", "BaseSettings"];
+
+/// Class decorators (as written, before any call arguments) that make an attrs
+/// class. Bare names count only when imported from their module (`from attrs import define`).
+const ATTRS_DECORATORS: &[&str] = &[
+    "attr.s",
+    "attr.attrs",
+    "attr.define",
+    "attr.frozen",
+    "attr.mutable",
+    "attrs.define",
+    "attrs.frozen",
+    "attrs.mutable",
+];
+
+/// Field specifier calls whose arguments hold a model field's default
+const FIELD_SPECIFIERS: &[&str] = &["Field", "field", "ib", "attrib"];
 
+/// Build a method signature string (without class prefix since it's nested under class).
+/// With `drop_receiver`, the first positional parameter (`self`/`cls`) is omitted.
+pub fn build_method_signature(
+    method_name: &str,
+    type_params: &[TypeParam],
+    args: &Arguments,
+    returns: Option<String>,
+    is_async: bool,
+    drop_receiver: bool,
+) -> String {
+    let receiver = if drop_receiver { receiver_name(args) } else { None };
     let prefix = if is_async { "async def" } else { "def" };
     let returns_str = returns.map(|r| format!(" -> {}", r)).unwrap_or_default();
 
-    format!("{} {}({}){}", prefix, method_name, params_str.join(", "), returns_str)
+    normalize_signature(&format!(
+        "{} {}{}({}){}",
+        prefix,
+        method_name,
+        format_type_params(type_params),
+        format_params(args, receiver),
+        returns_str
+    ))
+}
+
+/// Name of the first positional parameter if it is a method receiver (`self` or `cls`)
+pub fn receiver_name(args: &Arguments) -> Option<&str> {
+    let first = args.posonlyargs.first().or_else(|| args.args.first())?;
+    let name = first.def.arg.as_str();
+    matches!(name, "self" | "cls").then_some(name)
 }
 
 /// Build a class signature string
-fn build_class_signature(name: &str, bases: &[String]) -> String {
+fn build_class_signature(name: &str, type_params: &[TypeParam], bases: &[String]) -> String {
+    let type_params = format_type_params(type_params);
     if bases.is_empty() {
-        format!("class {}", name)
+        format!("class {}{}", name, type_params)
     } else {
-        format!("class {}({})", name, bases.join(", "))
+        format!("class {}{}({})", name, type_params, bases.join(", "))
     }
 }
 
@@ -46,29 +93,174 @@ fn build_field_signature(name: &str, annotation: Option<&str>) -> String {
 
 /// Extract all top-level classes from a Python file (excluding enums)
 /// Returns a map: class_signature -> ClassInfo (with fields and methods)
-pub fn extract_classes(path: &Path) -> Result<BTreeMap<String, ClassInfo>> {
+pub fn extract_classes(path: &Path, options: &ExtractOptions) -> Result<ClassMap> {
     let parsed = parse_file(path)?;
-    let mut results = BTreeMap::new();
+    let mut results = ClassMap::new();
+    let attrs_names = if options.models { attrs_imports(&parsed.module.body) } else { HashMap::new() };
 
-    for stmt in &parsed.module.body {
-        if let Stmt::ClassDef(class) = stmt {
-            // Skip if this is an enum (handled by enums module)
-            if is_enum(class) {
-                continue;
+    for stmt in module_statements(&parsed.module.body, options) {
+        match stmt {
+            Stmt::ClassDef(class) => {
+                // Skip if this is an enum (handled by enums module)
+                if is_enum(class) {
+                    continue;
+                }
+                let (class_signature, info) = class_info(class, &parsed, options, &attrs_names, options.class_depth);
+                results.insert(class_signature, info);
             }
+            // Functional forms: `Point = NamedTuple("Point", [("x", int)])`
+            Stmt::Assign(assign) if options.functional_classes => {
+                if let Some((class_signature, info)) = functional_class_info(assign, &parsed) {
+                    results.insert(class_signature, info);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok(results)
+}
 
-            let name = class.name.to_string();
-            let bases: Vec<String> = class.bases.iter().map(expr_to_string).collect();
-            let class_signature = build_class_signature(&name, &bases);
+/// Signature and details of a class, with classes nested in its body down to
+/// `depth` more levels. `attrs_names` maps decorators imported by name to
+/// their qualified form (see `attrs_imports`).
+fn class_info(
+    class: &ast::StmtClassDef,
+    parsed: &ParsedFile,
+    options: &ExtractOptions,
+    attrs_names: &HashMap<String, String>,
+    depth: usize,
+) -> (String, ClassInfo) {
+    let name = class.name.to_string();
+    let bases: Vec<String> = class.bases.iter().map(expr_to_string).collect();
+    let class_signature = build_class_signature(&name, &class.type_params, &bases);
+    let metaclass = class
+        .keywords
+        .iter()
+        .find(|kw| kw.arg.as_ref().is_some_and(|arg| arg.as_str() == "metaclass"))
+        .map(|kw| expr_to_string(&kw.value));
+
+    let model = if options.models { model_kind(class, attrs_names) } else { None };
+    let kind = class_kind(&bases);
 
-            // Extract fields and methods for this class
-            let (fields, methods) = extract_class_members(&class.body, &parsed);
+    // Extract fields and methods for this class
+    let (fields, methods) = extract_class_members(&class.body, parsed, options, model);
 
-            results.insert(class_signature, ClassInfo { fields, methods });
+    let mut classes = ClassMap::new();
+    if depth > 0 {
+        for stmt in &class.body {
+            if let Stmt::ClassDef(nested) = stmt {
+                if !is_enum(nested) {
+                    let (signature, info) = class_info(nested, parsed, options, attrs_names, depth - 1);
+                    classes.insert(signature, info);
+                }
+            }
         }
     }
 
-    Ok(results)
+    let info = ClassInfo {
+        signature: None,
+        fields,
+        methods,
+        classes,
+        bases,
+        metaclass,
+        context_manager: is_context_manager_class(&class.body),
+        kind,
+        line: parsed.offset_to_line(class.range.start().into()),
+        end_line: parsed.offset_to_line(class.range.end().into()),
+        meta: Meta {
+            source: options.with_source.then(|| parsed.snippet(class.range).to_string()),
+            model,
+            ..Default::default()
+        },
+    };
+    (class_signature, info)
+}
+
+/// A `NamedTuple(...)` or `TypedDict(...)` call assigned to a single name,
+/// as if it were the equivalent class statement
+fn functional_class_info(assign: &ast::StmtAssign, parsed: &ParsedFile) -> Option<(String, ClassInfo)> {
+    let [ast::Expr::Name(target)] = assign.targets.as_slice() else {
+        return None;
+    };
+    let ast::Expr::Call(call) = assign.value.as_ref() else {
+        return None;
+    };
+    let base = expr_to_string(&call.func);
+    let kind = class_kind(std::slice::from_ref(&base))?;
+
+    let line = parsed.offset_to_line(assign.range.start().into());
+    let mut fields = MemberMap::new();
+    let mut add_field = |name: &str, annotation: &ast::Expr| {
+        let signature = build_field_signature(name, Some(&annotation_to_string(annotation)));
+        fields.insert(signature, Entry::new(line));
+    };
+    match call.args.get(1) {
+        // NamedTuple("Point", [("x", int), ("y", int)])
+        Some(ast::Expr::List(ast::ExprList { elts, .. }) | ast::Expr::Tuple(ast::ExprTuple { elts, .. })) => {
+            for elt in elts {
+                if let ast::Expr::Tuple(pair) = elt {
+                    if let [name, annotation] = pair.elts.as_slice() {
+                        if let Some(name) = string_constant(name) {
+                            add_field(name, annotation);
+                        }
+                    }
+                }
+            }
+        }
+        // TypedDict("Movie", {"name": str, "year": int})
+        Some(ast::Expr::Dict(dict)) => {
+            for (key, value) in dict.keys.iter().zip(&dict.values) {
+                if let Some(name) = key.as_ref().and_then(string_constant) {
+                    add_field(name, value);
+                }
+            }
+        }
+        _ => {}
+    }
+    // NamedTuple("Point", x=int, y=int)
+    if kind == ClassKind::NamedTuple {
+        for keyword in &call.keywords {
+            if let Some(arg) = &keyword.arg {
+                add_field(arg.as_str(), &keyword.value);
+            }
+        }
+    }
+
+    let info = ClassInfo {
+        fields,
+        line,
+        end_line: parsed.offset_to_line(assign.range.end().into()),
+        bases: vec![base],
+        kind: Some(kind),
+        ..Default::default()
+    };
+    Some((build_class_signature(&target.id, &[], &info.bases), info))
+}
+
+/// The structural type a class declares through its bases, if any
+fn class_kind(bases: &[String]) -> Option<ClassKind> {
+    bases.iter().find_map(|base| {
+        // "Protocol[T]" -> "Protocol"
+        match unqualified(base.split('[').next().unwrap_or(base)) {
+            "Protocol" => Some(ClassKind::Protocol),
+            "TypedDict" => Some(ClassKind::TypedDict),
+            "NamedTuple" => Some(ClassKind::NamedTuple),
+            _ => None,
+        }
+    })
+}
+
+/// The value of a string literal
+fn string_constant(expr: &ast::Expr) -> Option<&str> {
+    match expr {
+        ast::Expr::Constant(c) => match &c.value {
+            ast::Constant::Str(s) => Some(s),
+            _ => None,
+        },
+        _ => None,
+    }
 }
 
 /// Check if a class is an enum based on its base classes
@@ -79,11 +271,207 @@ fn is_enum(class: &ast::StmtClassDef) -> bool {
     })
 }
 
+/// Local names bound by `from attr import s` / `from attrs import define as d`
+/// style imports at module level, mapped to the qualified decorator they stand for
+fn attrs_imports(body: &[Stmt]) -> HashMap<String, String> {
+    let mut names = HashMap::new();
+    for stmt in body {
+        let Stmt::ImportFrom(import) = stmt else {
+            continue;
+        };
+        let Some(module) = import
+            .module
+            .as_ref()
+            .filter(|_| import.level.is_none_or(|level| level.to_usize() == 0))
+        else {
+            continue;
+        };
+        for alias in &import.names {
+            let qualified = format!("{}.{}", module, alias.name);
+            if ATTRS_DECORATORS.contains(&qualified.as_str()) {
+                names.insert(alias.asname.as_ref().unwrap_or(&alias.name).to_string(), qualified);
+            }
+        }
+    }
+    names
+}
+
+/// The data model library a class is declared with, if any
+fn model_kind(class: &ast::StmtClassDef, attrs_names: &HashMap<String, String>) -> Option<ModelKind> {
+    if class
+        .bases
+        .iter()
+        .any(|base| PYDANTIC_BASES.contains(&unqualified(&expr_to_string(base))))
+    {
+        return Some(ModelKind::Pydantic);
+    }
+
+    extract_decorators(&class.decorator_list).iter().find_map(|decorator| {
+        // "dataclass(frozen=True)" -> "dataclass"
+        let name = decorator.split('(').next().unwrap_or(decorator);
+        let name = attrs_names.get(name).map_or(name, String::as_str);
+        if unqualified(name) == "dataclass" {
+            Some(ModelKind::Dataclass)
+        } else if ATTRS_DECORATORS.contains(&name) {
+            Some(ModelKind::Attrs)
+        } else {
+            None
+        }
+    })
+}
+
+/// Whether a call is a field specifier such as `Field(...)`, `field(...)` or `attr.ib(...)`
+fn is_field_specifier(value: &ast::Expr) -> bool {
+    matches!(value, ast::Expr::Call(call) if FIELD_SPECIFIERS.contains(&unqualified(&expr_to_string(&call.func))))
+}
+
+/// A model field's default, rendered compactly: `Field(18, ge=0)` -> `18`,
+/// `field(default_factory=list)` -> `list()`. `None` when the field is required.
+fn compact_default(value: &ast::Expr) -> Option<String> {
+    let ast::Expr::Call(call) = value else {
+        return Some(expr_to_string(value));
+    };
+    if !is_field_specifier(value) {
+        return Some(expr_to_string(value));
+    }
+
+    for keyword in &call.keywords {
+        match keyword.arg.as_ref().map(|arg| arg.as_str()) {
+            Some("default") => return Some(expr_to_string(&keyword.value)),
+            Some("default_factory" | "factory") => return Some(format!("{}()", expr_to_string(&keyword.value))),
+            _ => {}
+        }
+    }
+    // `Field(...)` marks a required field; otherwise the first argument is the default
+    match call.args.first() {
+        Some(ast::Expr::Constant(c)) if matches!(c.value, ast::Constant::Ellipsis) => None,
+        first => first.map(expr_to_string),
+    }
+}
+
+/// Build a model field signature, appending the compact default if there is one
+fn build_model_field_signature(name: &str, annotation: Option<&str>, value: Option<&ast::Expr>) -> String {
+    let signature = build_field_signature(name, annotation);
+    match value.and_then(compact_default) {
+        Some(default) => format!("{} = {}", signature, default),
+        None => signature,
+    }
+}
+
+/// Strip a module qualifier from a rendered name: "abc.ABC" -> "ABC"
+fn unqualified(name: &str) -> &str {
+    name.rsplit('.').next().unwrap_or(name)
+}
+
+/// Check if a method is abstract (decorated with `@abstractmethod` or one of its variants)
+pub fn is_abstract_method(entry: &Entry) -> bool {
+    entry
+        .decorators
+        .iter()
+        .any(|d| ABSTRACT_DECORATORS.contains(&unqualified(d)))
+}
+
+/// Check if a class is abstract: it has an `ABC`/`ABCMeta` base or metaclass, or any abstract method
+pub fn is_abstract_class(info: &ClassInfo) -> bool {
+    let abstract_base = info
+        .bases
+        .iter()
+        .chain(info.metaclass.iter())
+        .any(|base| ABSTRACT_BASES.contains(&unqualified(base)));
+
+    abstract_base || info.methods.values().any(is_abstract_method)
+}
+
+/// Mark methods that shadow a method of a base class defined among `classes`,
+/// recording the nearest such base under `overrides`. Bases are resolved by
+/// name only and searched depth-first in declaration order.
+pub fn mark_overrides(classes: &mut ClassMap) {
+    let by_name: HashMap<&str, &ClassInfo> = classes
+        .iter()
+        .map(|(sig, info)| (extract_class_name(sig), info))
+        .collect();
+
+    let mut overrides = Vec::new();
+    for (index, info) in classes.values().enumerate() {
+        for method_sig in info.methods.keys() {
+            let method = extract_function_name(method_sig);
+            if let Some(base) = defining_base(info, method, &by_name, &mut HashSet::new()) {
+                overrides.push((index, method_sig.clone(), base.to_string()));
+            }
+        }
+    }
+
+    for (index, method_sig, base) in overrides {
+        if let Some(entry) = classes[index].methods.get_mut(&method_sig) {
+            entry.meta.overrides = Some(base);
+        }
+    }
+}
+
+/// The first base of `info` (transitively) that defines `method`
+fn defining_base<'a>(
+    info: &ClassInfo,
+    method: &str,
+    classes: &HashMap<&'a str, &'a ClassInfo>,
+    seen: &mut HashSet<&'a str>,
+) -> Option<&'a str> {
+    for base in &info.bases {
+        // "pkg.Base[int]" -> "Base"
+        let name = unqualified(base.split('[').next().unwrap_or(base));
+        let Some((&name, &base_info)) = classes.get_key_value(name) else {
+            continue;
+        };
+        // Guards against cycles and diamonds
+        if !seen.insert(name) {
+            continue;
+        }
+        if base_info.methods.keys().any(|sig| extract_function_name(sig) == method) {
+            return Some(name);
+        }
+        if let Some(found) = defining_base(base_info, method, classes, seen) {
+            return Some(found);
+        }
+    }
+    None
+}
+
+/// Check if a class holds tests: a pytest-style `Test*` name or a `*TestCase` base
+pub fn is_test_class(name: &str, info: &ClassInfo) -> bool {
+    name.starts_with("Test") || info.bases.iter().any(|base| unqualified(base).ends_with("TestCase"))
+}
+
+/// Attribute names declared by a `__slots__` value: a string, or a
+/// tuple/list/set of strings
+fn slot_names(value: &ast::Expr) -> Vec<String> {
+    let elts = match value {
+        ast::Expr::Tuple(tuple) => &tuple.elts,
+        ast::Expr::List(list) => &list.elts,
+        ast::Expr::Set(set) => &set.elts,
+        single => std::slice::from_ref(single),
+    };
+
+    elts.iter()
+        .filter_map(|elt| match elt {
+            ast::Expr::Constant(c) => match &c.value {
+                ast::Constant::Str(s) => Some(s.clone()),
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect()
+}
+
 /// Extract fields and methods from a class body
-/// Returns (fields, methods) where each is a map of signature -> line_number
-fn extract_class_members(body: &[Stmt], parsed: &ParsedFile) -> (BTreeMap<String, usize>, BTreeMap<String, usize>) {
-    let mut fields = BTreeMap::new();
-    let mut methods = BTreeMap::new();
+/// Returns (fields, methods) where each is a map of signature -> entry
+fn extract_class_members(
+    body: &[Stmt],
+    parsed: &ParsedFile,
+    options: &ExtractOptions,
+    model: Option<ModelKind>,
+) -> (MemberMap, MemberMap) {
+    let mut fields = MemberMap::new();
+    // Kept in definition order with names until property accessors are grouped
+    let mut methods = Vec::new();
 
     for stmt in body {
         match stmt {
@@ -92,24 +480,77 @@ fn extract_class_members(body: &[Stmt], parsed: &ParsedFile) -> (BTreeMap<String
                 let name = func.name.to_string();
                 let line = parsed.offset_to_line(func.range.start().into());
                 let returns = extract_returns(func.returns.as_deref());
-                let signature = build_method_signature(&name, &func.args, returns, false);
-                methods.insert(signature, line);
+                let noreturn = never_returns(returns.as_deref(), &func.body);
+                let decorators = extract_decorators(&func.decorator_list);
+                let context_manager = is_context_manager(&decorators);
+                let drop_receiver = options.no_self && !is_static(&decorators);
+                let signature =
+                    build_method_signature(&name, &func.type_params, &func.args, returns, false, drop_receiver);
+                let params = extract_params(&func.args).len();
+                let entry = Entry {
+                    end_line: parsed.offset_to_line(func.range.end().into()),
+                    complexity: cyclomatic_complexity(&func.body),
+                    generator: is_generator(&func.body),
+                    noreturn,
+                    context_manager,
+                    params,
+                    decorators,
+                    meta: Meta {
+                        source: options.with_source.then(|| parsed.snippet(func.range).to_string()),
+                        arity: options.with_arity.then_some(params),
+                        ..Default::default()
+                    },
+                    ..Entry::new(line)
+                };
+                methods.push((name, signature, entry));
             }
             Stmt::AsyncFunctionDef(func) => {
                 let name = func.name.to_string();
                 let line = parsed.offset_to_line(func.range.start().into());
                 let returns = extract_returns(func.returns.as_deref());
-                let signature = build_method_signature(&name, &func.args, returns, true);
-                methods.insert(signature, line);
+                let noreturn = never_returns(returns.as_deref(), &func.body);
+                let decorators = extract_decorators(&func.decorator_list);
+                let context_manager = is_context_manager(&decorators);
+                let drop_receiver = options.no_self && !is_static(&decorators);
+                let signature =
+                    build_method_signature(&name, &func.type_params, &func.args, returns, true, drop_receiver);
+                let params = extract_params(&func.args).len();
+                let entry = Entry {
+                    end_line: parsed.offset_to_line(func.range.end().into()),
+                    complexity: cyclomatic_complexity(&func.body),
+                    generator: is_generator(&func.body),
+                    noreturn,
+                    context_manager,
+                    params,
+                    decorators,
+                    meta: Meta {
+                        source: options.with_source.then(|| parsed.snippet(func.range).to_string()),
+                        arity: options.with_arity.then_some(params),
+                        ..Default::default()
+                    },
+                    ..Entry::new(line)
+                };
+                methods.push((name, signature, entry));
             }
             // Annotated fields: field_name: Type = value or field_name: Type
             Stmt::AnnAssign(ann) => {
                 if let ast::Expr::Name(name_expr) = ann.target.as_ref() {
                     let field_name = name_expr.id.to_string();
                     let line = parsed.offset_to_line(ann.range.start().into());
-                    let annotation = expr_to_string(&ann.annotation);
-                    let signature = build_field_signature(&field_name, Some(&annotation));
-                    fields.insert(signature, line);
+                    let annotation = annotation_to_string(&ann.annotation);
+                    // In a model every annotated attribute is a field, except `ClassVar`s
+                    if model.is_some() && unqualified(annotation.split('[').next().unwrap_or_default()) != "ClassVar" {
+                        let signature =
+                            build_model_field_signature(&field_name, Some(&annotation), ann.value.as_deref());
+                        let entry = Entry {
+                            model_field: true,
+                            ..Entry::new(line)
+                        };
+                        fields.insert(signature, entry);
+                    } else {
+                        let signature = build_field_signature(&field_name, Some(&annotation));
+                        fields.insert(signature, Entry::new(line));
+                    }
                 }
             }
             // Simple assignments at class level: field_name = value
@@ -117,11 +558,25 @@ fn extract_class_members(body: &[Stmt], parsed: &ParsedFile) -> (BTreeMap<String
                 for target in &assign.targets {
                     if let ast::Expr::Name(name_expr) = target {
                         let field_name = name_expr.id.to_string();
-                        // Skip dunder attributes like __slots__
-                        if !field_name.starts_with("__") {
+                        // __slots__ lists the instance attributes; other dunders are skipped
+                        if field_name == "__slots__" {
+                            let line = parsed.offset_to_line(assign.range.start().into());
+                            for slot in slot_names(&assign.value) {
+                                fields.insert(build_field_signature(&slot, None), Entry::new(line));
+                            }
+                        } else if model == Some(ModelKind::Attrs) && is_field_specifier(&assign.value) {
+                            // attrs fields may be declared without annotations: `x = attr.ib()`
+                            let line = parsed.offset_to_line(assign.range.start().into());
+                            let signature = build_model_field_signature(&field_name, None, Some(&assign.value));
+                            let entry = Entry {
+                                model_field: true,
+                                ..Entry::new(line)
+                            };
+                            fields.insert(signature, entry);
+                        } else if !field_name.starts_with("__") {
                             let line = parsed.offset_to_line(assign.range.start().into());
                             let signature = build_field_signature(&field_name, None);
-                            fields.insert(signature, line);
+                            fields.insert(signature, Entry::new(line));
                         }
                     }
                 }
@@ -130,7 +585,73 @@ fn extract_class_members(body: &[Stmt], parsed: &ParsedFile) -> (BTreeMap<String
         }
     }
 
-    (fields, methods)
+    (fields, group_properties(methods))
+}
+
+/// Whether a method is a `@staticmethod` (which has no receiver)
+pub fn is_static(decorators: &[String]) -> bool {
+    decorators.iter().any(|d| unqualified(d) == "staticmethod")
+}
+
+/// Whether a function is a `@contextmanager` or `@asynccontextmanager` generator
+pub fn is_context_manager(decorators: &[String]) -> bool {
+    decorators
+        .iter()
+        .any(|d| matches!(unqualified(d), "contextmanager" | "asynccontextmanager"))
+}
+
+/// Whether a class body defines `__enter__` and `__exit__`, or `__aenter__` and `__aexit__`
+fn is_context_manager_class(body: &[Stmt]) -> bool {
+    let methods: HashSet<&str> = body
+        .iter()
+        .filter_map(|stmt| match stmt {
+            Stmt::FunctionDef(func) => Some(func.name.as_str()),
+            Stmt::AsyncFunctionDef(func) => Some(func.name.as_str()),
+            _ => None,
+        })
+        .collect();
+    let defines = |enter, exit| methods.contains(enter) && methods.contains(exit);
+    defines("__enter__", "__exit__") || defines("__aenter__", "__aexit__")
+}
+
+/// Fold `@<name>.setter` and `@<name>.deleter` methods into their `@property`
+/// getter, which then records the line of each accessor
+fn group_properties(methods: Vec<(String, String, Entry)>) -> MemberMap {
+    let is_getter = |entry: &Entry| entry.decorators.iter().any(|d| d == "property");
+
+    let mut properties: HashMap<String, PropertyAccessors> = HashMap::new();
+    for (name, _, entry) in methods.iter().filter(|(_, _, entry)| is_getter(entry)) {
+        properties.entry(name.clone()).or_default().get = entry.line;
+    }
+    for (name, _, entry) in &methods {
+        if let Some(property) = properties.get_mut(name) {
+            if entry.decorators.contains(&format!("{}.setter", name)) {
+                property.set = Some(entry.line);
+            } else if entry.decorators.contains(&format!("{}.deleter", name)) {
+                property.delete = Some(entry.line);
+            }
+        }
+    }
+
+    let mut grouped = MemberMap::new();
+    for (name, signature, mut entry) in methods {
+        if is_getter(&entry) {
+            entry.meta.property = properties.get(&name).cloned();
+        } else if properties.contains_key(&name) && is_accessor(&name, &entry) {
+            // Represented by the getter
+            continue;
+        }
+        grouped.insert(signature, entry);
+    }
+    grouped
+}
+
+/// Whether a method is the setter or deleter of the property `name`
+fn is_accessor(name: &str, entry: &Entry) -> bool {
+    entry
+        .decorators
+        .iter()
+        .any(|d| *d == format!("{}.setter", name) || *d == format!("{}.deleter", name))
 }
 
 #[cfg(test)]
@@ -145,16 +666,55 @@ mod tests {
     #[test]
     fn test_extract_classes_simple() {
         let path = fixtures_dir().join("classes.py");
-        let result = extract_classes(&path);
+        let result = extract_classes(&path, &ExtractOptions::default());
         assert!(result.is_ok());
         let classes = result.unwrap();
         assert!(!classes.is_empty());
     }
 
+    #[test]
+    fn test_extract_classes_type_params() {
+        let path = fixtures_dir().join("generics.py");
+        let classes = extract_classes(&path, &ExtractOptions::default()).unwrap();
+        let sigs: Vec<&str> = classes.keys().map(String::as_str).collect();
+        assert_eq!(sigs, vec!["class Stack[T]", "class Registry[K: str, V](dict[K, V])"]);
+        let stack = &classes["class Stack[T]"];
+        assert!(stack.methods.contains_key("def map[U](self, func) -> Stack[U]"));
+    }
+
+    #[test]
+    fn test_mark_overrides() {
+        let path = fixtures_dir().join("overrides.py");
+        let mut classes = extract_classes(&path, &ExtractOptions::default()).unwrap();
+        mark_overrides(&mut classes);
+        let overrides = |class: &str, method: &str| classes[class].methods[method].meta.overrides.clone();
+
+        assert_eq!(overrides("class Shape", "def area(self) -> float"), None);
+        assert_eq!(
+            overrides("class Rectangle(Shape)", "def area(self) -> float"),
+            Some("Shape".to_string())
+        );
+        assert_eq!(overrides("class Rectangle(Shape)", "def corners(self) -> int"), None);
+        // The nearest base wins; inherited-only methods resolve further up
+        assert_eq!(
+            overrides("class Square(Rectangle)", "def area(self) -> float"),
+            Some("Rectangle".to_string())
+        );
+        assert_eq!(
+            overrides("class Square(Rectangle)", "def describe(self) -> str"),
+            Some("Shape".to_string())
+        );
+        // Bases outside the file are not resolved
+        assert_eq!(
+            overrides("class Widget(external.Base)", "def render(self) -> None"),
+            None
+        );
+    }
+
     #[test]
     fn test_extract_classes_excludes_enums() {
         let path = fixtures_dir().join("enums.py");
-        let classes = extract_classes(&path).unwrap();
+        let classes = extract_classes(&path, &ExtractOptions::default()).unwrap();
         // Enums (classes that inherit from *Enum) should be excluded
         // Note: Flag is not detected as enum because it doesn't contain "Enum" in name
         let has_color = classes.keys().any(|k| k.contains("Color"));
@@ -172,7 +732,7 @@ mod tests {
     #[test]
     fn test_extract_classes_with_bases() {
         let path = fixtures_dir().join("classes.py");
-        let classes = extract_classes(&path).unwrap();
+        let classes = extract_classes(&path, &ExtractOptions::default()).unwrap();
 
         let has_base = classes.keys().any(|k| k.contains("ClassWithBase(object)"));
         assert!(has_base, "Should contain ClassWithBase(object)");
@@ -181,7 +741,7 @@ mod tests {
     #[test]
     fn test_extract_classes_multiple_bases() {
         let path = fixtures_dir().join("classes.py");
-        let classes = extract_classes(&path).unwrap();
+        let classes = extract_classes(&path, &ExtractOptions::default()).unwrap();
 
         let has_multi = classes.keys().any(|k| k.contains("ClassWithMultipleBases"));
         assert!(has_multi, "Should contain ClassWithMultipleBases");
@@ -191,10 +751,20 @@ mod tests {
         assert!(multi.contains("list"));
     }
 
+    #[test]
+    fn test_extract_classes_forward_references() {
+        let path = fixtures_dir().join("forward_refs.py");
+        let classes = extract_classes(&path, &ExtractOptions::default()).unwrap();
+        let node = &classes["class Node"];
+        let fields: Vec<&str> = node.fields.keys().map(String::as_str).collect();
+        assert_eq!(fields, vec!["parent: Node | None", "children: list[Node]"]);
+        assert!(node.methods.contains_key("def add(self, child: Node) -> Node"));
+    }
+
     #[test]
     fn test_extract_classes_fields() {
         let path = fixtures_dir().join("classes.py");
-        let classes = extract_classes(&path).unwrap();
+        let classes = extract_classes(&path, &ExtractOptions::default()).unwrap();
 
         let class_info = classes
             .iter()
@@ -214,7 +784,7 @@ mod tests {
     #[test]
     fn test_extract_classes_methods() {
         let path = fixtures_dir().join("classes.py");
-        let classes = extract_classes(&path).unwrap();
+        let classes = extract_classes(&path, &ExtractOptions::default()).unwrap();
 
         let class_info = classes
             .iter()
@@ -233,10 +803,29 @@ mod tests {
         assert!(has_async, "Should have async_method");
     }
 
+    #[test]
+    fn test_extract_classes_methods_in_source_order() {
+        let path = fixtures_dir().join("classes.py");
+        let classes = extract_classes(&path, &ExtractOptions::default()).unwrap();
+
+        let info = &classes["class ClassWithMethods"];
+        let names: Vec<&str> = info.methods.keys().map(|k| k.split('(').next().unwrap()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "def public_method",
+                "def _private_method",
+                "async def async_method",
+                "def static_method",
+                "def class_method",
+            ]
+        );
+    }
+
     #[test]
     fn test_extract_classes_async_methods() {
         let path = fixtures_dir().join("classes.py");
-        let classes = extract_classes(&path).unwrap();
+        let classes = extract_classes(&path, &ExtractOptions::default()).unwrap();
 
         let class_info = classes
             .iter()
@@ -253,7 +842,7 @@ mod tests {
     #[test]
     fn test_extract_classes_private_class() {
         let path = fixtures_dir().join("classes.py");
-        let classes = extract_classes(&path).unwrap();
+        let classes = extract_classes(&path, &ExtractOptions::default()).unwrap();
 
         let has_private = classes.keys().any(|k| k.contains("_PrivateClass"));
         assert!(has_private, "Should contain _PrivateClass");
@@ -262,7 +851,7 @@ mod tests {
     #[test]
     fn test_extract_classes_mixed_file() {
         let path = fixtures_dir().join("mixed.py");
-        let classes = extract_classes(&path).unwrap();
+        let classes = extract_classes(&path, &ExtractOptions::default()).unwrap();
 
         // Should contain DataProcessor and ComplexTypes but NOT Priority (enum)
         let has_processor = classes.keys().any(|k| k.contains("DataProcessor"));
@@ -277,20 +866,20 @@ mod tests {
     #[test]
     fn test_extract_classes_empty_file() {
         let path = fixtures_dir().join("empty.py");
-        let classes = extract_classes(&path).unwrap();
+        let classes = extract_classes(&path, &ExtractOptions::default()).unwrap();
         assert!(classes.is_empty());
     }
 
     #[test]
     fn test_build_class_signature_no_bases() {
-        let sig = build_class_signature("MyClass", &[]);
+        let sig = build_class_signature("MyClass", &[], &[]);
         assert_eq!(sig, "class MyClass");
     }
 
     #[test]
     fn test_build_class_signature_with_bases() {
         let bases = vec!["Base".to_string(), "Mixin".to_string()];
-        let sig = build_class_signature("MyClass", &bases);
+        let sig = build_class_signature("MyClass", &[], &bases);
         assert_eq!(sig, "class MyClass(Base, Mixin)");
     }
 
@@ -317,7 +906,7 @@ mod tests {
             range: Default::default(),
         };
 
-        let sig = build_method_signature("test", &args, Some("int".to_string()), false);
+        let sig = build_method_signature("test", &[], &args, Some("int".to_string()), false, false);
         assert_eq!(sig, "def test() -> int");
     }
 
@@ -332,7 +921,396 @@ mod tests {
             range: Default::default(),
         };
 
-        let sig = build_method_signature("test", &args, None, true);
+        let sig = build_method_signature("test", &[], &args, None, true, false);
         assert_eq!(sig, "async def test()");
     }
+
+    #[test]
+    fn test_extract_classes_decorators() {
+        let path = fixtures_dir().join("classes.py");
+        let classes = extract_classes(&path, &ExtractOptions::default()).unwrap();
+
+        let info = classes.iter().find(|(k, _)| k.contains("ClassWithMethods")).unwrap().1;
+        let (_, static_method) = info.methods.iter().find(|(k, _)| k.contains("static_method")).unwrap();
+        assert_eq!(static_method.decorators, vec!["staticmethod".to_string()]);
+        let (_, public_method) = info.methods.iter().find(|(k, _)| k.contains("public_method")).unwrap();
+        assert!(public_method.decorators.is_empty());
+    }
+
+    #[test]
+    fn test_extract_classes_context_managers() {
+        let path = fixtures_dir().join("context_managers.py");
+        let classes = extract_classes(&path, &ExtractOptions::default()).unwrap();
+        let managers: Vec<&str> = classes
+            .iter()
+            .filter(|(_, info)| info.context_manager)
+            .map(|(sig, _)| sig.as_str())
+            .collect();
+        // `HalfOpen` has no `__exit__`
+        assert_eq!(managers, vec!["class Lock", "class AsyncPool"]);
+
+        let begin = &classes["class Transactions"].methods["async def begin(self)"];
+        assert!(begin.context_manager);
+        assert!(!begin.meta.context_manager);
+    }
+
+    #[test]
+    fn test_is_abstract_class_abc_base() {
+        let path = fixtures_dir().join("abstract.py");
+        let classes = extract_classes(&path, &ExtractOptions::default()).unwrap();
+
+        let shape = classes.get("class Shape(ABC)").unwrap();
+        assert!(is_abstract_class(shape));
+
+        let area = shape.methods.iter().find(|(k, _)| k.contains("area")).unwrap().1;
+        let describe = shape.methods.iter().find(|(k, _)| k.contains("describe")).unwrap().1;
+        assert!(is_abstract_method(area));
+        assert!(!is_abstract_method(describe));
+    }
+
+    #[test]
+    fn test_is_abstract_class_metaclass_and_qualified_decorator() {
+        let path = fixtures_dir().join("abstract.py");
+        let classes = extract_classes(&path, &ExtractOptions::default()).unwrap();
+
+        let repo = classes.get("class Repository").unwrap();
+        assert_eq!(repo.metaclass.as_deref(), Some("ABCMeta"));
+        assert!(is_abstract_class(repo));
+        assert!(repo.methods.values().all(is_abstract_method));
+    }
+
+    #[test]
+    fn test_is_abstract_class_abstract_method_only() {
+        let path = fixtures_dir().join("abstract.py");
+        let classes = extract_classes(&path, &ExtractOptions::default()).unwrap();
+
+        // No ABC base, but an @abstractmethod member still makes it abstract
+        assert!(is_abstract_class(classes.get("class Plugin").unwrap()));
+        assert!(!is_abstract_class(classes.get("class Concrete").unwrap()));
+    }
+
+    #[test]
+    fn test_extract_classes_slots_as_fields() {
+        let path = fixtures_dir().join("slots.py");
+        let classes = extract_classes(&path, &ExtractOptions::default()).unwrap();
+
+        let point = &classes["class Point"];
+        assert_eq!(point.fields.keys().collect::<Vec<_>>(), vec!["x", "y"]);
+        assert!(point.fields.values().all(|entry| entry.line == 2));
+
+        let named = &classes["class Named"];
+        assert_eq!(named.fields.keys().collect::<Vec<_>>(), vec!["name", "kind"]);
+        assert_eq!(named.fields["name"].line, 10);
+
+        assert_eq!(classes["class Single"].fields.keys().collect::<Vec<_>>(), vec!["value"]);
+    }
+
+    #[test]
+    fn test_extract_classes_groups_property_accessors() {
+        let path = fixtures_dir().join("properties.py");
+        let classes = extract_classes(&path, &ExtractOptions::default()).unwrap();
+        let info = &classes["class Temperature"];
+
+        // One entry for celsius (getter, setter and deleter), one for fahrenheit
+        let celsius: Vec<_> = info
+            .methods
+            .iter()
+            .filter(|(k, _)| k.starts_with("def celsius"))
+            .collect();
+        assert_eq!(celsius.len(), 1);
+        let (signature, entry) = celsius[0];
+        assert_eq!(signature, "def celsius(self)");
+        assert_eq!(
+            entry.meta.property,
+            Some(PropertyAccessors {
+                get: 6,
+                set: Some(10),
+                delete: Some(14),
+            })
+        );
+
+        let fahrenheit = &info.methods["def fahrenheit(self) -> float"];
+        assert_eq!(
+            fahrenheit.meta.property,
+            Some(PropertyAccessors {
+                get: 18,
+                ..Default::default()
+            })
+        );
+        let (_, init) = info.methods.iter().find(|(k, _)| k.contains("__init__")).unwrap();
+        assert!(init.meta.property.is_none());
+    }
+
+    #[test]
+    fn test_extract_classes_no_self() {
+        let path = fixtures_dir().join("receivers.py");
+        let options = ExtractOptions {
+            no_self: true,
+            ..Default::default()
+        };
+        let classes = extract_classes(&path, &options).unwrap();
+        let methods = &classes["class Account"].methods;
+
+        assert!(methods.contains_key("def deposit(amount: int) -> None"));
+        assert!(methods.contains_key("def open(owner: str) -> Account"));
+        // A staticmethod has no receiver, whatever its first parameter is called
+        assert!(methods.contains_key("def validate(self, amount: int) -> bool"));
+        // `self` is only dropped when it is the first parameter
+        assert!(methods.contains_key("def transfer(target, self) -> None"));
+    }
+
+    #[test]
+    fn test_extract_classes_positional_only() {
+        let path = fixtures_dir().join("positional_only.py");
+        let classes = extract_classes(&path, &ExtractOptions::default()).unwrap();
+        let methods: Vec<&String> = classes["class Point"].methods.keys().collect();
+        assert_eq!(
+            methods,
+            vec![
+                "def __init__(self, x, /, y)",
+                "def scale(self, /, factor: float) -> Point"
+            ]
+        );
+
+        let options = ExtractOptions {
+            no_self: true,
+            ..Default::default()
+        };
+        let classes = extract_classes(&path, &options).unwrap();
+        let methods: Vec<&String> = classes["class Point"].methods.keys().collect();
+        // A dropped receiver that was the only positional-only parameter takes the `/` with it
+        assert_eq!(
+            methods,
+            vec!["def __init__(x, /, y)", "def scale(factor: float) -> Point"]
+        );
+    }
+
+    #[test]
+    fn test_extract_classes_keeps_self_by_default() {
+        let path = fixtures_dir().join("receivers.py");
+        let classes = extract_classes(&path, &ExtractOptions::default()).unwrap();
+        let methods = &classes["class Account"].methods;
+        assert!(methods
+            .keys()
+            .any(|m| m.starts_with("def deposit(") && m.contains("self")));
+        assert!(methods.keys().any(|m| m.starts_with("def open(") && m.contains("cls")));
+    }
+
+    #[test]
+    fn test_extract_classes_with_source() {
+        let path = fixtures_dir().join("receivers.py");
+        let options = ExtractOptions {
+            with_source: true,
+            ..Default::default()
+        };
+        let classes = extract_classes(&path, &options).unwrap();
+        let account = &classes["class Account"];
+        let snippet = account.meta.source.as_deref().unwrap();
+        assert!(snippet.starts_with("class Account:"));
+        assert!(snippet.ends_with("def transfer(target, self) -> None:\n        pass"));
+
+        let validate = account.methods.values().find(|e| e.line == 10).unwrap();
+        assert!(validate
+            .meta
+            .source
+            .as_deref()
+            .unwrap()
+            .starts_with("def validate(self"));
+    }
+
+    fn models(options: &ExtractOptions) -> ClassMap {
+        extract_classes(&fixtures_dir().join("models.py"), options).unwrap()
+    }
+
+    fn model_fields(info: &ClassInfo) -> Vec<&str> {
+        info.fields
+            .iter()
+            .filter(|(_, entry)| entry.model_field)
+            .map(|(sig, _)| sig.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_extract_classes_pydantic_model() {
+        let options = ExtractOptions {
+            models: true,
+            ..Default::default()
+        };
+        let classes = models(&options);
+        let user = &classes["class User(BaseModel)"];
+
+        assert_eq!(user.meta.model, Some(ModelKind::Pydantic));
+        let mut fields = model_fields(user);
+        fields.sort();
+        assert_eq!(
+            fields,
+            vec![
+                "age: int = 18",
+                "email: str | None = None",
+                "id: int",
+                "name: str",
+                "tags: list[str] = list()",
+            ]
+        );
+        // ClassVar is a class variable, not a model field
+        assert!(!user.fields["registry: ClassVar[dict]"].model_field);
+    }
+
+    #[test]
+    fn test_extract_classes_dataclass_and_attrs() {
+        let options = ExtractOptions {
+            models: true,
+            ..Default::default()
+        };
+        let classes = models(&options);
+
+        let point = &classes["class Point"];
+        assert_eq!(point.meta.model, Some(ModelKind::Dataclass));
+        let mut fields = model_fields(point);
+        fields.sort();
+        assert_eq!(fields, vec!["labels: list[str] = list()", "x: float", "y: float = 1.5"]);
+        assert!(!point.fields["origin"].model_field);
+
+        let config = &classes["class Config"];
+        assert_eq!(config.meta.model, Some(ModelKind::Attrs));
+        let mut fields = model_fields(config);
+        fields.sort();
+        assert_eq!(fields, vec!["path", "retries = 3"]);
+
+        assert_eq!(classes["class Plain"].meta.model, None);
+    }
+
+    #[test]
+    fn test_extract_classes_attrs_imported_decorators() {
+        let options = ExtractOptions {
+            models: true,
+            ..Default::default()
+        };
+        let classes = extract_classes(&fixtures_dir().join("attrs_models.py"), &options).unwrap();
+
+        assert_eq!(classes["class Imported"].meta.model, Some(ModelKind::Attrs));
+        assert_eq!(classes["class Aliased"].meta.model, Some(ModelKind::Attrs));
+        // A bare `mutable` imported from anywhere else isn't attrs
+        assert_eq!(classes["class Unrelated"].meta.model, None);
+    }
+
+    #[test]
+    fn test_extract_classes_models_off_by_default() {
+        let classes = models(&ExtractOptions::default());
+        let user = &classes["class User(BaseModel)"];
+        assert_eq!(user.meta.model, None);
+        assert!(user.fields.contains_key("age: int"));
+        assert!(user.fields.values().all(|e| !e.model_field));
+    }
+
+    #[test]
+    fn test_is_test_class() {
+        let path = fixtures_dir().join("test_sample.py");
+        let classes = extract_classes(&path, &ExtractOptions::default()).unwrap();
+
+        assert!(is_test_class("TestBar", &classes["class TestBar"]));
+        assert!(is_test_class(
+            "ArithmeticCase",
+            &classes["class ArithmeticCase(unittest.TestCase)"]
+        ));
+        assert!(!is_test_class("Plain", &classes["class Plain"]));
+    }
+
+    #[test]
+    fn test_extract_classes_conditional() {
+        let path = fixtures_dir().join("conditional.py");
+
+        let classes = extract_classes(&path, &ExtractOptions::default()).unwrap();
+        assert_eq!(classes.keys().collect::<Vec<_>>(), vec!["class Always"]);
+
+        let options = ExtractOptions {
+            include_conditional: true,
+            ..Default::default()
+        };
+        let classes = extract_classes(&path, &options).unwrap();
+        assert!(classes.contains_key("class JSONDecoder"));
+        assert!(classes["class JSONDecoder"]
+            .methods
+            .contains_key("def decode(self, text: str) -> dict"));
+        assert!(classes.contains_key("class Always"));
+    }
+
+    #[test]
+    fn test_extract_classes_nested_depth() {
+        let path = fixtures_dir().join("nested_classes.py");
+        let at_depth = |class_depth| {
+            let options = ExtractOptions {
+                class_depth,
+                ..Default::default()
+            };
+            extract_classes(&path, &options).unwrap()
+        };
+
+        let classes = at_depth(0);
+        assert_eq!(classes.keys().collect::<Vec<_>>(), vec!["class Outer", "class Flat"]);
+        assert!(classes["class Outer"].classes.is_empty());
+
+        // One level: Middle is listed, but not what is nested inside it
+        let classes = at_depth(1);
+        let middle = &classes["class Outer"].classes["class Middle"];
+        assert!(middle.methods.contains_key("def middle(self)"));
+        assert_eq!(middle.line, 2);
+        assert!(middle.classes.is_empty());
+        assert!(classes["class Flat"].classes.is_empty());
+
+        let classes = at_depth(3);
+        let inner = &classes["class Outer"].classes["class Middle"].classes["class Inner"];
+        assert!(inner.methods.contains_key("def inner(self)"));
+        assert!(inner.classes["class Core"].methods.contains_key("def core(self)"));
+    }
+
+    #[test]
+    fn test_extract_classes_structural_kinds() {
+        let path = fixtures_dir().join("structural_types.py");
+        let options = ExtractOptions {
+            functional_classes: true,
+            ..Default::default()
+        };
+        let classes = extract_classes(&path, &options).unwrap();
+        let kinds: Vec<_> = classes.iter().map(|(sig, info)| (sig.as_str(), info.kind)).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                ("class Closeable(Protocol)", Some(ClassKind::Protocol)),
+                ("class SupportsRead(typing.Protocol[T])", Some(ClassKind::Protocol)),
+                ("class Movie(TypedDict)", Some(ClassKind::TypedDict)),
+                ("class Point(NamedTuple)", Some(ClassKind::NamedTuple)),
+                ("class Pair(NamedTuple)", Some(ClassKind::NamedTuple)),
+                ("class Size(NamedTuple)", Some(ClassKind::NamedTuple)),
+                ("class Album(TypedDict)", Some(ClassKind::TypedDict)),
+                ("class Triple(typing.NamedTuple)", Some(ClassKind::NamedTuple)),
+                ("class Plain", None),
+            ]
+        );
+
+        // The annotated fields are the payload of TypedDicts and NamedTuples
+        let fields = |sig: &str| classes[sig].fields.keys().cloned().collect::<Vec<_>>();
+        assert_eq!(fields("class Movie(TypedDict)"), vec!["title: str", "year: int"]);
+        assert_eq!(fields("class Point(NamedTuple)"), vec!["x: float", "y: float"]);
+        assert!(classes["class Point(NamedTuple)"]
+            .methods
+            .contains_key("def norm(self) -> float"));
+        assert_eq!(fields("class Pair(NamedTuple)"), vec!["left: int", "right: int"]);
+        assert_eq!(fields("class Size(NamedTuple)"), vec!["width: int", "height: int"]);
+        assert_eq!(fields("class Album(TypedDict)"), vec!["name: str", "tracks: list[str]"]);
+        assert_eq!(classes["class Album(TypedDict)"].line, 28);
+        assert_eq!(
+            fields("class Triple(typing.NamedTuple)"),
+            vec!["a: int", "b: int", "c: int"]
+        );
+        assert!(classes["class Closeable(Protocol)"]
+            .methods
+            .contains_key("def close(self) -> None"));
+
+        // Only listed with `--kind`
+        let classes = extract_classes(&path, &ExtractOptions::default()).unwrap();
+        assert!(classes
+            .keys()
+            .all(|sig| !["Pair", "Size", "Album", "Triple"].contains(&extract_class_name(sig))));
+    }
 }
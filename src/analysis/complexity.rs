@@ -0,0 +1,78 @@
+use super::visit::{walk_body, walk_body_exprs};
+use rustpython_parser::ast::{Expr, Stmt};
+
+/// Cyclomatic complexity of a function body: 1 plus one per decision point.
+/// Decision points are `if`/`elif`, loops, `except` handlers, `match` cases,
+/// conditional expressions, extra `and`/`or` operands, and comprehension
+/// `for`/`if` clauses. Nested definitions are not counted.
+pub fn cyclomatic_complexity(body: &[Stmt]) -> usize {
+    let mut complexity = 1;
+
+    walk_body(body, &mut |stmt| {
+        complexity += match stmt {
+            Stmt::If(_) | Stmt::For(_) | Stmt::AsyncFor(_) | Stmt::While(_) => 1,
+            Stmt::Try(s) => s.handlers.len(),
+            Stmt::TryStar(s) => s.handlers.len(),
+            Stmt::Match(s) => s.cases.len(),
+            _ => 0,
+        };
+    });
+
+    walk_body_exprs(body, &mut |expr| {
+        complexity += match expr {
+            Expr::BoolOp(e) => e.values.len().saturating_sub(1),
+            Expr::IfExp(_) => 1,
+            Expr::ListComp(e) => e.generators.iter().map(|g| 1 + g.ifs.len()).sum(),
+            Expr::SetComp(e) => e.generators.iter().map(|g| 1 + g.ifs.len()).sum(),
+            Expr::DictComp(e) => e.generators.iter().map(|g| 1 + g.ifs.len()).sum(),
+            Expr::GeneratorExp(e) => e.generators.iter().map(|g| 1 + g.ifs.len()).sum(),
+            _ => 0,
+        };
+    });
+
+    complexity
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rustpython_parser::{ast, Parse};
+
+    fn complexity_of(source: &str) -> usize {
+        let body = ast::Suite::parse(source, "test.py").unwrap();
+        let Stmt::FunctionDef(func) = &body[0] else {
+            panic!("expected function");
+        };
+        cyclomatic_complexity(&func.body)
+    }
+
+    #[test]
+    fn test_complexity_straight_line() {
+        assert_eq!(complexity_of("def f():\n    x = 1\n    return x\n"), 1);
+    }
+
+    #[test]
+    fn test_complexity_branches_and_loops() {
+        let source = "def f(x):\n    if x:\n        pass\n    elif x > 1:\n        pass\n    for i in x:\n        while i:\n            break\n    return x\n";
+        // if, elif, for, while
+        assert_eq!(complexity_of(source), 5);
+    }
+
+    #[test]
+    fn test_complexity_boolean_operators_and_handlers() {
+        let source = "def f(a, b, c):\n    try:\n        return a and b and c\n    except ValueError:\n        pass\n    except KeyError:\n        pass\n";
+        // two extra `and` operands, two handlers
+        assert_eq!(complexity_of(source), 5);
+    }
+
+    #[test]
+    fn test_complexity_comprehension() {
+        assert_eq!(complexity_of("def f(xs):\n    return [x for x in xs if x]\n"), 3);
+    }
+
+    #[test]
+    fn test_complexity_ignores_nested_functions() {
+        let source = "def f():\n    def g(x):\n        if x:\n            pass\n    return g\n";
+        assert_eq!(complexity_of(source), 1);
+    }
+}
@@ -0,0 +1,113 @@
+use super::{module_statements, ExtractOptions};
+use crate::output::{Entry, EntryMap};
+use crate::parser::{annotation_to_string, expr_to_string, parse_file};
+use eyre::Result;
+use rustpython_parser::ast::{Expr, Stmt};
+use std::path::Path;
+
+/// Build a constant signature string: `NAME = value` or `NAME: Type = value`
+fn build_constant_signature(name: &str, annotation: Option<&Expr>, value: &Expr) -> String {
+    match annotation {
+        Some(annotation) => format!(
+            "{}: {} = {}",
+            name,
+            annotation_to_string(annotation),
+            expr_to_string(value)
+        ),
+        None => format!("{} = {}", name, expr_to_string(value)),
+    }
+}
+
+/// Check if a name follows the UPPER_CASE constant convention (dunders excluded)
+fn is_constant_name(name: &str) -> bool {
+    !name.starts_with("__")
+        && name.chars().any(|c| c.is_ascii_uppercase())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_')
+}
+
+/// Extract module-level constants (UPPER_CASE names assigned a value)
+/// Returns a map of signature -> entry
+pub fn extract_constants(path: &Path, options: &ExtractOptions) -> Result<EntryMap> {
+    let parsed = parse_file(path)?;
+    let mut constants = EntryMap::new();
+
+    for stmt in module_statements(&parsed.module.body, options) {
+        let (targets, annotation, value, range) = match stmt {
+            Stmt::Assign(assign) => (
+                assign.targets.iter().collect::<Vec<_>>(),
+                None,
+                &*assign.value,
+                assign.range,
+            ),
+            Stmt::AnnAssign(ann) => match &ann.value {
+                Some(value) => (vec![&*ann.target], Some(&*ann.annotation), &**value, ann.range),
+                None => continue,
+            },
+            _ => continue,
+        };
+
+        for target in targets {
+            if let Expr::Name(name) = target {
+                if is_constant_name(name.id.as_str()) {
+                    let line = parsed.offset_to_line(range.start().into());
+                    let entry = Entry {
+                        end_line: parsed.offset_to_line(range.end().into()),
+                        ..Entry::new(line)
+                    };
+                    constants.insert(build_constant_signature(name.id.as_str(), annotation, value), entry);
+                }
+            }
+        }
+    }
+
+    Ok(constants)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn fixtures_dir() -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures")
+    }
+
+    #[test]
+    fn test_extract_constants() {
+        let path = fixtures_dir().join("constants.py");
+        let constants = extract_constants(&path, &ExtractOptions::default()).unwrap();
+        let keys: Vec<_> = constants.keys().map(String::as_str).collect();
+        assert_eq!(
+            keys,
+            vec![
+                "MAX_RETRIES = 3",
+                "DEFAULT_TIMEOUT: float = 2.5",
+                "API_URL: Final = \"https://example.com\"",
+                "SUPPORTED_FORMATS = (\"json\", \"yaml\")",
+                "_PRIVATE_LIMIT = 10",
+            ]
+        );
+        assert_eq!(constants["MAX_RETRIES = 3"].line, 4);
+    }
+
+    #[test]
+    fn test_extract_constants_forward_reference() {
+        let path = fixtures_dir().join("forward_refs.py");
+        let constants = extract_constants(&path, &ExtractOptions::default()).unwrap();
+        // Only the annotation is unquoted, not the value
+        assert!(constants.contains_key("MODE: str = \"dfs\""));
+    }
+
+    #[test]
+    fn test_is_constant_name() {
+        assert!(is_constant_name("MAX_RETRIES"));
+        assert!(is_constant_name("_PRIVATE"));
+        assert!(is_constant_name("HTTP2"));
+        assert!(!is_constant_name("logger"));
+        assert!(!is_constant_name("MixedCase"));
+        assert!(!is_constant_name("__VERSION__"));
+        assert!(!is_constant_name("_"));
+    }
+}
@@ -0,0 +1,191 @@
+use super::classes::{build_method_signature, is_static, receiver_name};
+use super::functions::build_function_signature;
+use super::{module_statements, ExtractOptions};
+use crate::output::{AnnotationCoverage, Entry, EntryMap};
+use crate::parser::{extract_decorators, extract_params, extract_returns, parse_file, ParsedFile};
+use eyre::Result;
+use rustpython_parser::ast::{text_size::TextRange, Arguments, Expr, Stmt, TypeParam};
+use std::path::Path;
+
+/// A top-level function or a method of a top-level class
+struct Callable<'a> {
+    class: Option<&'a str>,
+    name: &'a str,
+    type_params: &'a [TypeParam],
+    args: &'a Arguments,
+    returns: Option<&'a Expr>,
+    is_async: bool,
+    is_static: bool,
+    range: TextRange,
+}
+
+impl Callable<'_> {
+    /// Annotated vs. total parameters and return type of this one definition.
+    /// The receiver is never expected to be annotated, so it is not counted.
+    fn coverage(&self) -> AnnotationCoverage {
+        let mut params = extract_params(self.args);
+        // Every method except a `@staticmethod` takes a `self`/`cls` receiver
+        if self.class.is_some() && !self.is_static {
+            if let Some(receiver) = receiver_name(self.args) {
+                params.remove(receiver);
+            }
+        }
+
+        let mut coverage = AnnotationCoverage::default();
+        for typ in params.values() {
+            // `extract_params` renders a missing annotation as "..."
+            coverage.params.record(typ != "...");
+        }
+        coverage.returns.record(extract_returns(self.returns).is_some());
+        coverage
+    }
+
+    /// Signature as listed by `dump`: `def f(...)` or `Class.def m(...)`
+    fn signature(&self) -> String {
+        let returns = extract_returns(self.returns);
+        match self.class {
+            Some(class) => format!(
+                "{}.{}",
+                class,
+                build_method_signature(self.name, self.type_params, self.args, returns, self.is_async, false)
+            ),
+            None => build_function_signature(self.name, self.type_params, self.args, returns, self.is_async),
+        }
+    }
+}
+
+fn callables<'a>(parsed: &'a ParsedFile, options: &ExtractOptions) -> Vec<Callable<'a>> {
+    let mut callables = Vec::new();
+
+    for stmt in module_statements(&parsed.module.body, options) {
+        match stmt {
+            Stmt::ClassDef(class) => {
+                for member in &class.body {
+                    if let Some(callable) = callable(member) {
+                        callables.push(Callable {
+                            class: Some(class.name.as_str()),
+                            ..callable
+                        });
+                    }
+                }
+            }
+            stmt => callables.extend(callable(stmt)),
+        }
+    }
+
+    callables
+}
+
+fn callable(stmt: &Stmt) -> Option<Callable<'_>> {
+    match stmt {
+        Stmt::FunctionDef(func) => Some(Callable {
+            class: None,
+            name: func.name.as_str(),
+            type_params: &func.type_params,
+            args: &func.args,
+            returns: func.returns.as_deref(),
+            is_async: false,
+            is_static: is_static(&extract_decorators(&func.decorator_list)),
+            range: func.range,
+        }),
+        Stmt::AsyncFunctionDef(func) => Some(Callable {
+            class: None,
+            name: func.name.as_str(),
+            type_params: &func.type_params,
+            args: &func.args,
+            returns: func.returns.as_deref(),
+            is_async: true,
+            is_static: is_static(&extract_decorators(&func.decorator_list)),
+            range: func.range,
+        }),
+        _ => None,
+    }
+}
+
+/// Count annotated parameters and return types of a file's functions and
+/// methods. A method's `self`/`cls` receiver is left out of the parameter count.
+pub fn annotation_coverage(path: &Path, options: &ExtractOptions) -> Result<AnnotationCoverage> {
+    let parsed = parse_file(path)?;
+    let mut coverage = AnnotationCoverage::default();
+    for callable in callables(&parsed, options) {
+        coverage += callable.coverage();
+    }
+    Ok(coverage)
+}
+
+/// Functions and methods missing a return annotation or with any unannotated
+/// parameter, keyed like `dump` (`Class.def method(...)` for methods)
+pub fn extract_untyped(path: &Path, options: &ExtractOptions) -> Result<EntryMap> {
+    let parsed = parse_file(path)?;
+    let mut untyped = EntryMap::new();
+
+    for callable in callables(&parsed, options) {
+        let coverage = callable.coverage();
+        if coverage.params.annotated < coverage.params.total || coverage.returns.annotated < coverage.returns.total {
+            let entry = Entry {
+                end_line: parsed.offset_to_line(callable.range.end().into()),
+                ..Entry::new(parsed.offset_to_line(callable.range.start().into()))
+            };
+            untyped.insert(callable.signature(), entry);
+        }
+    }
+
+    Ok(untyped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::types::Ratio;
+    use std::path::PathBuf;
+
+    fn fixtures_dir() -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures")
+    }
+
+    #[test]
+    fn test_annotation_coverage() {
+        let path = fixtures_dir().join("coverage.py");
+        let coverage = annotation_coverage(&path, &ExtractOptions::default()).unwrap();
+
+        // typed 2/2, untyped 0/2, partial 1/3, handle 1/1 (self excluded), close 0/0, build 0/1
+        assert_eq!(coverage.params, Ratio { annotated: 4, total: 9 });
+        assert_eq!(coverage.params.percent(), 44.4);
+        // typed, partial and handle declare a return type
+        assert_eq!(coverage.returns, Ratio { annotated: 3, total: 6 });
+        assert_eq!(coverage.returns.percent(), 50.0);
+    }
+
+    #[test]
+    fn test_annotation_coverage_fully_typed() {
+        let path = fixtures_dir().join("receivers.py");
+        let coverage = annotation_coverage(&path, &ExtractOptions::default()).unwrap();
+        assert_eq!(coverage.returns.percent(), 100.0);
+    }
+
+    #[test]
+    fn test_ratio_percent_with_nothing_to_annotate() {
+        assert_eq!(Ratio::default().percent(), 100.0);
+    }
+
+    #[test]
+    fn test_extract_untyped() {
+        let path = fixtures_dir().join("coverage.py");
+        let untyped = extract_untyped(&path, &ExtractOptions::default()).unwrap();
+        // untyped, partial, Service.close and Service.build
+        let lines: Vec<usize> = untyped.values().map(|e| e.line).collect();
+        assert_eq!(lines, vec![5, 9, 17, 21]);
+        assert!(untyped.contains_key("def untyped(a, b)"));
+        assert!(untyped.contains_key("Service.def build(config)"));
+        assert_eq!(untyped["Service.def close(self)"].line, 17);
+    }
+
+    #[test]
+    fn test_extract_untyped_excludes_fully_typed() {
+        let path = fixtures_dir().join("coverage.py");
+        let untyped = extract_untyped(&path, &ExtractOptions::default()).unwrap();
+        assert!(!untyped.keys().any(|k| k.contains("typed(a: int")));
+        // `self` alone doesn't make a method untyped
+        assert!(!untyped.keys().any(|k| k.contains("handle")));
+    }
+}
@@ -0,0 +1,346 @@
+use super::modules::{dotted_module_path, qualify};
+use super::visit::walk_body;
+use crate::output::DepsOutput;
+use crate::parser::{parse_file, ParsedFile};
+use eyre::Result;
+use rustpython_parser::ast::{Expr, Stmt};
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+
+/// A single `import x` or `from x import y` statement target
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Import {
+    /// Number of leading dots (0 for absolute imports)
+    pub level: usize,
+    /// Dotted module path as written (empty for `from . import x`)
+    pub module: String,
+    /// Names imported from the module (empty for plain `import`)
+    pub names: Vec<String>,
+    /// Names the import binds: the `as` alias, the imported name, or the first
+    /// component of a plain `import a.b` (none for `*`)
+    pub bindings: Vec<String>,
+    pub line: usize,
+    /// Directly in the module body, not nested in a definition or block
+    pub module_level: bool,
+    /// Inside a top-level `if TYPE_CHECKING:` block, so only seen by type checkers
+    pub type_checking: bool,
+}
+
+impl Import {
+    /// The module as written in source, e.g. `..pkg.mod`
+    pub fn display(&self) -> String {
+        format!("{}{}", ".".repeat(self.level), self.module)
+    }
+}
+
+/// Extract every import in a file, including those inside functions, classes
+/// and compound statements. Imports in the body of a top-level
+/// `if TYPE_CHECKING:` are marked `type_checking`.
+pub fn extract_imports(path: &Path) -> Result<Vec<Import>> {
+    Ok(parsed_imports(&parse_file(path)?))
+}
+
+/// The imports of an already parsed file, as `extract_imports` finds them
+pub fn parsed_imports(parsed: &ParsedFile) -> Vec<Import> {
+    let mut imports = Vec::new();
+    for stmt in &parsed.module.body {
+        match stmt {
+            Stmt::If(s) if is_type_checking_guard(&s.test) => {
+                let start = imports.len();
+                collect_imports(&s.body, parsed, &mut imports);
+                for import in &mut imports[start..] {
+                    import.type_checking = true;
+                }
+                collect_imports(&s.orelse, parsed, &mut imports);
+            }
+            Stmt::Import(_) | Stmt::ImportFrom(_) => {
+                let start = imports.len();
+                collect_imports(std::slice::from_ref(stmt), parsed, &mut imports);
+                for import in &mut imports[start..] {
+                    import.module_level = true;
+                }
+            }
+            stmt => collect_imports(std::slice::from_ref(stmt), parsed, &mut imports),
+        }
+    }
+    imports
+}
+
+/// Whether an `if` test is the `TYPE_CHECKING` constant, bare or qualified
+/// (`typing.TYPE_CHECKING`)
+pub fn is_type_checking_guard(test: &Expr) -> bool {
+    match test {
+        Expr::Name(name) => name.id.as_str() == "TYPE_CHECKING",
+        Expr::Attribute(attr) => attr.attr.as_str() == "TYPE_CHECKING",
+        _ => false,
+    }
+}
+
+fn collect_imports(body: &[Stmt], parsed: &ParsedFile, imports: &mut Vec<Import>) {
+    walk_body(body, &mut |stmt| match stmt {
+        Stmt::Import(s) => imports.extend(s.names.iter().map(|alias| {
+            let binding = match &alias.asname {
+                Some(asname) => asname.to_string(),
+                None => alias.name.split('.').next().unwrap_or_default().to_string(),
+            };
+            Import {
+                module: alias.name.to_string(),
+                bindings: vec![binding],
+                line: parsed.offset_to_line(s.range.start().into()),
+                ..Default::default()
+            }
+        })),
+        Stmt::ImportFrom(s) => imports.push(Import {
+            level: s.level.map(|level| level.to_usize()).unwrap_or(0),
+            module: s.module.as_ref().map(|m| m.to_string()).unwrap_or_default(),
+            names: s.names.iter().map(|alias| alias.name.to_string()).collect(),
+            bindings: s
+                .names
+                .iter()
+                .filter(|alias| alias.name.as_str() != "*")
+                .map(|alias| alias.asname.as_ref().unwrap_or(&alias.name).to_string())
+                .collect(),
+            line: parsed.offset_to_line(s.range.start().into()),
+            ..Default::default()
+        }),
+        Stmt::FunctionDef(s) => collect_imports(&s.body, parsed, imports),
+        Stmt::AsyncFunctionDef(s) => collect_imports(&s.body, parsed, imports),
+        Stmt::ClassDef(s) => collect_imports(&s.body, parsed, imports),
+        _ => {}
+    });
+}
+
+/// Directory that dotted module names are resolved against: the nearest
+/// ancestor of `base` (or `base` itself) that is not a package
+pub fn import_root(base: &Path) -> PathBuf {
+    let mut root = base.to_path_buf();
+    while crate::git::is_file(&root.join("__init__.py")) {
+        match root.parent() {
+            Some(parent) => root = parent.to_path_buf(),
+            None => break,
+        }
+    }
+    root
+}
+
+/// Build the import graph between `files`. Imports that resolve to one of the
+/// files become edges; everything else is listed under `external` as written.
+/// Dependencies only imported under `if TYPE_CHECKING:` are also listed under
+/// `type_checking`. Files that can't be parsed are passed to `on_error` and left out.
+pub fn build_dependency_graph(
+    files: &[PathBuf],
+    base_path: &Path,
+    on_error: &dyn Fn(&Path, eyre::Report),
+) -> DepsOutput {
+    let root = import_root(&absolute(base_path));
+    let modules: Vec<(String, &PathBuf)> = files
+        .iter()
+        .map(|file| (dotted_module_path(&absolute(file), &root), file))
+        .collect();
+    let index: HashMap<&str, &PathBuf> = modules.iter().map(|(module, file)| (module.as_str(), *file)).collect();
+
+    let mut output = DepsOutput::default();
+
+    for (module, file) in &modules {
+        let imports = match extract_imports(file) {
+            Ok(imports) => imports,
+            Err(err) => {
+                on_error(file, err);
+                continue;
+            }
+        };
+        let key = file.to_string_lossy().to_string();
+        let is_package = file.file_stem().is_some_and(|stem| stem == "__init__");
+        let mut runtime = BTreeSet::new();
+        let mut typing = BTreeSet::new();
+
+        for import in imports {
+            let mut targets = Vec::new();
+            let mut unresolved = false;
+
+            match absolute_module(&import, module, is_package) {
+                Some(base) => {
+                    // `from pkg import sub` may name a submodule rather than an attribute
+                    let mut needs_base = import.names.is_empty();
+                    for name in &import.names {
+                        match index.get(qualify(&base, name).as_str()) {
+                            Some(target) => targets.push(*target),
+                            None => needs_base = true,
+                        }
+                    }
+                    if needs_base {
+                        match resolve_prefix(&index, &base) {
+                            Some(target) => targets.push(target),
+                            None => unresolved = true,
+                        }
+                    }
+                }
+                None => unresolved = true,
+            }
+
+            let seen = if import.type_checking { &mut typing } else { &mut runtime };
+            if unresolved {
+                output.external.entry(key.clone()).or_default().insert(import.display());
+                seen.insert(import.display());
+            }
+            for target in targets.into_iter().filter(|target| target != file) {
+                let target = target.to_string_lossy().to_string();
+                output.deps.entry(key.clone()).or_default().insert(target.clone());
+                seen.insert(target);
+            }
+        }
+
+        let typing_only: BTreeSet<String> = typing.difference(&runtime).cloned().collect();
+        if !typing_only.is_empty() {
+            output.type_checking.insert(key, typing_only);
+        }
+    }
+
+    output
+}
+
+/// Absolute dotted module an import refers to, resolving leading dots against
+/// the importing module. `None` if a relative import climbs above the root.
+fn absolute_module(import: &Import, module: &str, is_package: bool) -> Option<String> {
+    if import.level == 0 {
+        return Some(import.module.clone());
+    }
+
+    let mut parts: Vec<&str> = module.split('.').filter(|part| !part.is_empty()).collect();
+    if !is_package {
+        parts.pop();
+    }
+    let keep = parts.len().checked_sub(import.level - 1)?;
+    parts.truncate(keep);
+    if !import.module.is_empty() {
+        parts.push(&import.module);
+    }
+    Some(parts.join("."))
+}
+
+/// The file for the longest prefix of a dotted module that is in the index
+/// ("pkg.mod.attr" -> "pkg/mod.py")
+fn resolve_prefix<'a>(index: &HashMap<&str, &'a PathBuf>, module: &str) -> Option<&'a PathBuf> {
+    let mut candidate = module;
+    loop {
+        if let Some(file) = index.get(candidate) {
+            return Some(file);
+        }
+        candidate = candidate.rsplit_once('.')?.0;
+    }
+}
+
+fn absolute(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixtures_dir() -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures")
+    }
+
+    fn deps_files() -> Vec<PathBuf> {
+        let dir = fixtures_dir().join("deps");
+        ["__init__.py", "a.py", "b.py", "c.py"]
+            .iter()
+            .map(|f| dir.join(f))
+            .collect()
+    }
+
+    fn key(file: &str) -> String {
+        fixtures_dir().join("deps").join(file).to_string_lossy().to_string()
+    }
+
+    #[test]
+    fn test_extract_imports() {
+        let imports = extract_imports(&fixtures_dir().join("deps/a.py")).unwrap();
+        let written: Vec<String> = imports.iter().map(Import::display).collect();
+        assert_eq!(written, vec!["json", ".b", "deps", "os.path"]);
+        assert_eq!(imports[1].names, vec!["helper"]);
+        assert_eq!(imports[1].level, 1);
+    }
+
+    #[test]
+    fn test_extract_imports_type_checking() {
+        let imports = extract_imports(&fixtures_dir().join("deps/typed.py")).unwrap();
+        let marked: Vec<(String, bool)> = imports
+            .iter()
+            .map(|import| (import.display(), import.type_checking))
+            .collect();
+        assert_eq!(
+            marked,
+            vec![
+                ("typing".to_string(), false),
+                (".b".to_string(), false),
+                (".c".to_string(), true),
+                ("collections.abc".to_string(), true),
+                (".b".to_string(), true),
+                ("json".to_string(), false),
+                ("os".to_string(), false),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_build_dependency_graph_type_checking() {
+        let mut files = deps_files();
+        files.push(fixtures_dir().join("deps/typed.py"));
+        let graph = build_dependency_graph(&files, &fixtures_dir().join("deps"), &|_, _| {});
+        let typed = key("typed.py");
+        assert!(graph.deps[&typed].contains(&key("c.py")));
+        // `.b` is imported at runtime too, so it isn't type-checking only
+        assert_eq!(
+            graph.type_checking[&typed],
+            BTreeSet::from([key("c.py"), "collections.abc".to_string()])
+        );
+        assert!(!graph.type_checking.contains_key(&key("a.py")));
+    }
+
+    #[test]
+    fn test_build_dependency_graph_edges() {
+        let graph = build_dependency_graph(&deps_files(), &fixtures_dir().join("deps"), &|_, _| {});
+        let a_deps = &graph.deps[&key("a.py")];
+        assert!(a_deps.contains(&key("b.py")));
+        assert!(a_deps.contains(&key("c.py")));
+        assert!(!graph.deps.contains_key(&key("b.py")));
+    }
+
+    #[test]
+    fn test_build_dependency_graph_external() {
+        let graph = build_dependency_graph(&deps_files(), &fixtures_dir().join("deps"), &|_, _| {});
+        let external = &graph.external[&key("a.py")];
+        assert!(external.contains("json"));
+        assert!(external.contains("os.path"));
+        assert!(!external.contains(".b"));
+    }
+
+    #[test]
+    fn test_import_root_climbs_out_of_packages() {
+        let deps = fixtures_dir().join("deps");
+        assert_eq!(import_root(&deps), fixtures_dir());
+        assert_eq!(import_root(&fixtures_dir()), fixtures_dir());
+    }
+
+    #[test]
+    fn test_absolute_module_relative_levels() {
+        let import = |level, module: &str| Import {
+            level,
+            module: module.to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            absolute_module(&import(1, "b"), "pkg.a", false).as_deref(),
+            Some("pkg.b")
+        );
+        assert_eq!(absolute_module(&import(1, "b"), "pkg", true).as_deref(), Some("pkg.b"));
+        assert_eq!(
+            absolute_module(&import(2, "x"), "pkg.sub.a", false).as_deref(),
+            Some("pkg.x")
+        );
+        assert_eq!(absolute_module(&import(3, "x"), "pkg.a", false), None);
+        assert_eq!(absolute_module(&import(0, "os"), "pkg.a", false).as_deref(), Some("os"));
+    }
+}
@@ -0,0 +1,108 @@
+use super::deps::is_type_checking_guard;
+use super::entrypoints::is_main_guard;
+use super::visit::{stmt_exprs, walk_expr};
+use crate::output::{Effect, EffectKind};
+use crate::parser::{parse_file, ParsedFile};
+use eyre::Result;
+use rustpython_parser::ast::{Expr, Ranged, Stmt};
+use std::path::Path;
+
+/// Module-level statements in a file that do work when it is imported: calls
+/// (bare or assigned), loops, conditionals, `try` and `with` blocks, and
+/// anything else beyond definitions, imports and plain assignments. The
+/// `__main__` guard and `if TYPE_CHECKING:` don't run on import and are left out.
+pub fn find_effects(path: &Path) -> Result<Vec<Effect>> {
+    let parsed = parse_file(path)?;
+    Ok(scan_module(&parsed))
+}
+
+fn scan_module(parsed: &ParsedFile) -> Vec<Effect> {
+    parsed
+        .module
+        .body
+        .iter()
+        .filter_map(|stmt| {
+            let kind = effect_kind(stmt)?;
+            let range = stmt.range();
+            let code = parsed.snippet(range).lines().next().unwrap_or_default().trim_end();
+            Some(Effect {
+                line: parsed.offset_to_line(range.start().into()),
+                kind,
+                code: code.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// What kind of import-time work a module-level statement does, if any
+fn effect_kind(stmt: &Stmt) -> Option<EffectKind> {
+    match stmt {
+        Stmt::FunctionDef(_)
+        | Stmt::AsyncFunctionDef(_)
+        | Stmt::ClassDef(_)
+        | Stmt::Import(_)
+        | Stmt::ImportFrom(_)
+        | Stmt::TypeAlias(_)
+        | Stmt::Pass(_) => None,
+        // Docstrings and `...`
+        Stmt::Expr(s) if matches!(s.value.as_ref(), Expr::Constant(_)) => None,
+        Stmt::Expr(_) => Some(if calls(stmt) { EffectKind::Call } else { EffectKind::Other }),
+        Stmt::Assign(_) | Stmt::AnnAssign(_) | Stmt::AugAssign(_) => calls(stmt).then_some(EffectKind::Call),
+        Stmt::For(_) | Stmt::AsyncFor(_) | Stmt::While(_) => Some(EffectKind::Loop),
+        Stmt::If(s) if is_main_guard(&s.test) || is_type_checking_guard(&s.test) => None,
+        Stmt::If(_) | Stmt::Match(_) => Some(EffectKind::Conditional),
+        Stmt::Try(_) | Stmt::TryStar(_) => Some(EffectKind::Try),
+        Stmt::With(_) | Stmt::AsyncWith(_) => Some(EffectKind::With),
+        _ => Some(EffectKind::Other),
+    }
+}
+
+/// Whether a simple statement's expressions contain a call
+fn calls(stmt: &Stmt) -> bool {
+    let mut found = false;
+    for expr in stmt_exprs(stmt) {
+        walk_expr(expr, &mut |expr| found = found || matches!(expr, Expr::Call(_)));
+    }
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn fixtures_dir() -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures")
+    }
+
+    #[test]
+    fn test_find_effects() {
+        let effects = find_effects(&fixtures_dir().join("effects.py")).unwrap();
+        let found: Vec<(usize, EffectKind, &str)> = effects
+            .iter()
+            .map(|effect| (effect.line, effect.kind, effect.code.as_str()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (11, EffectKind::Call, "logger = logging.getLogger(__name__)"),
+                (13, EffectKind::Call, "print(\"loading\")"),
+                (16, EffectKind::Loop, "for name in (\"a\", \"b\"):"),
+                (19, EffectKind::Try, "try:"),
+                (24, EffectKind::Conditional, "if logger.isEnabledFor(logging.DEBUG):"),
+                (27, EffectKind::With, "with open(__file__) as source:"),
+                (30, EffectKind::Other, "assert LIMIT > 0"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_main_guard_is_not_an_effect() {
+        let source = "import sys\n\nif __name__ == \"__main__\":\n    main()\nelif sys.argv:\n    pass\n";
+        let parsed = crate::parser::parse_source(source.to_string(), Path::new("test.py")).unwrap();
+        assert!(scan_module(&parsed).is_empty());
+
+        let parsed = crate::parser::parse_source("if sys.argv:\n    pass\n".to_string(), Path::new("test.py")).unwrap();
+        assert_eq!(scan_module(&parsed)[0].kind, EffectKind::Conditional);
+    }
+}
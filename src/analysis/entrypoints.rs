@@ -0,0 +1,95 @@
+use crate::output::{Entry, EntryMap};
+use crate::parser::parse_file;
+use eyre::Result;
+use rustpython_parser::ast::{self, Expr, Stmt};
+use std::path::Path;
+
+/// Synthetic symbol name reported for a file with a `__main__` guard
+pub const MAIN_ENTRY: &str = "__main__";
+
+/// Find the `if __name__ == "__main__":` guard at module level
+/// Returns a map with a single `__main__` entry at the guard's line, or an empty map
+pub fn extract_entrypoints(path: &Path) -> Result<EntryMap> {
+    let parsed = parse_file(path)?;
+    let mut entrypoints = EntryMap::new();
+
+    for stmt in &parsed.module.body {
+        if let Stmt::If(if_stmt) = stmt {
+            if is_main_guard(&if_stmt.test) {
+                let line = parsed.offset_to_line(if_stmt.range.start().into());
+                let entry = Entry {
+                    end_line: parsed.offset_to_line(if_stmt.range.end().into()),
+                    ..Entry::new(line)
+                };
+                entrypoints.insert(MAIN_ENTRY.to_string(), entry);
+                break;
+            }
+        }
+    }
+
+    Ok(entrypoints)
+}
+
+/// Check if an expression is `__name__ == "__main__"` (in either operand order)
+pub fn is_main_guard(test: &Expr) -> bool {
+    let Expr::Compare(compare) = test else {
+        return false;
+    };
+    if compare.ops.as_slice() != [ast::CmpOp::Eq] || compare.comparators.len() != 1 {
+        return false;
+    }
+
+    let (left, right) = (compare.left.as_ref(), &compare.comparators[0]);
+    (is_dunder_name(left) && is_main_str(right)) || (is_main_str(left) && is_dunder_name(right))
+}
+
+fn is_dunder_name(expr: &Expr) -> bool {
+    matches!(expr, Expr::Name(name) if name.id.as_str() == "__name__")
+}
+
+fn is_main_str(expr: &Expr) -> bool {
+    matches!(expr, Expr::Constant(c) if matches!(&c.value, ast::Constant::Str(s) if s == MAIN_ENTRY))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rustpython_parser::Parse;
+    use std::path::PathBuf;
+
+    fn fixtures_dir() -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures")
+    }
+
+    fn guard_of(source: &str) -> bool {
+        let body = ast::Suite::parse(source, "test.py").unwrap();
+        let Stmt::If(if_stmt) = &body[0] else {
+            panic!("expected if statement");
+        };
+        is_main_guard(&if_stmt.test)
+    }
+
+    #[test]
+    fn test_extract_entrypoints_with_guard() {
+        let path = fixtures_dir().join("entrypoint.py");
+        let entrypoints = extract_entrypoints(&path).unwrap();
+        assert_eq!(entrypoints.len(), 1);
+        assert_eq!(entrypoints[MAIN_ENTRY].line, 9);
+    }
+
+    #[test]
+    fn test_extract_entrypoints_without_guard() {
+        let path = fixtures_dir().join("functions.py");
+        let entrypoints = extract_entrypoints(&path).unwrap();
+        assert!(entrypoints.is_empty());
+    }
+
+    #[test]
+    fn test_is_main_guard_variants() {
+        assert!(guard_of("if __name__ == \"__main__\":\n    pass\n"));
+        assert!(guard_of("if '__main__' == __name__:\n    pass\n"));
+        assert!(!guard_of("if __name__ != \"__main__\":\n    pass\n"));
+        assert!(!guard_of("if __name__ == \"main\":\n    pass\n"));
+        assert!(!guard_of("if name == \"__main__\":\n    pass\n"));
+    }
+}
@@ -1,7 +1,8 @@
-use crate::parser::{expr_to_string, parse_file};
+use super::{module_statements, ExtractOptions};
+use crate::output::{Entry, EntryMap};
+use crate::parser::{expr_to_string, extract_decorators, parse_file};
 use eyre::Result;
 use rustpython_parser::ast::{self, Stmt};
-use std::collections::BTreeMap;
 use std::path::Path;
 
 /// Build an enum signature string
@@ -10,12 +11,12 @@ fn build_enum_signature(name: &str, bases: &[String]) -> String {
 }
 
 /// Extract all enum definitions from a Python file
-/// Returns a map of signature -> line number
-pub fn extract_enums(path: &Path) -> Result<BTreeMap<String, usize>> {
+/// Returns a map of signature -> entry
+pub fn extract_enums(path: &Path, options: &ExtractOptions) -> Result<EntryMap> {
     let parsed = parse_file(path)?;
-    let mut enums = BTreeMap::new();
+    let mut enums = EntryMap::new();
 
-    for stmt in &parsed.module.body {
+    for stmt in module_statements(&parsed.module.body, options) {
         if let Stmt::ClassDef(class) = stmt {
             if !is_enum(class) {
                 continue;
@@ -25,8 +26,13 @@ pub fn extract_enums(path: &Path) -> Result<BTreeMap<String, usize>> {
             let line = parsed.offset_to_line(class.range.start().into());
             let bases: Vec<String> = class.bases.iter().map(expr_to_string).collect();
             let signature = build_enum_signature(&name, &bases);
+            let entry = Entry {
+                end_line: parsed.offset_to_line(class.range.end().into()),
+                decorators: extract_decorators(&class.decorator_list),
+                ..Entry::new(line)
+            };
 
-            enums.insert(signature, line);
+            enums.insert(signature, entry);
         }
     }
 
@@ -53,7 +59,7 @@ mod tests {
     #[test]
     fn test_extract_enums_basic() {
         let path = fixtures_dir().join("enums.py");
-        let result = extract_enums(&path);
+        let result = extract_enums(&path, &ExtractOptions::default());
         assert!(result.is_ok());
         let enums = result.unwrap();
         assert!(!enums.is_empty());
@@ -62,7 +68,7 @@ mod tests {
     #[test]
     fn test_extract_enums_contains_color() {
         let path = fixtures_dir().join("enums.py");
-        let enums = extract_enums(&path).unwrap();
+        let enums = extract_enums(&path, &ExtractOptions::default()).unwrap();
 
         let has_color = enums.keys().any(|k| k.contains("Color") && k.contains("Enum"));
         assert!(has_color, "Should contain Color(Enum)");
@@ -71,7 +77,7 @@ mod tests {
     #[test]
     fn test_extract_enums_contains_int_enum() {
         let path = fixtures_dir().join("enums.py");
-        let enums = extract_enums(&path).unwrap();
+        let enums = extract_enums(&path, &ExtractOptions::default()).unwrap();
 
         let has_status = enums.keys().any(|k| k.contains("Status") && k.contains("IntEnum"));
         assert!(has_status, "Should contain Status(IntEnum)");
@@ -80,7 +86,7 @@ mod tests {
     #[test]
     fn test_extract_enums_contains_str_enum() {
         let path = fixtures_dir().join("enums.py");
-        let enums = extract_enums(&path).unwrap();
+        let enums = extract_enums(&path, &ExtractOptions::default()).unwrap();
 
         let has_direction = enums.keys().any(|k| k.contains("Direction") && k.contains("StrEnum"));
         assert!(has_direction, "Should contain Direction(StrEnum)");
@@ -89,7 +95,7 @@ mod tests {
     #[test]
     fn test_extract_enums_contains_flag() {
         let path = fixtures_dir().join("enums.py");
-        let enums = extract_enums(&path).unwrap();
+        let enums = extract_enums(&path, &ExtractOptions::default()).unwrap();
 
         // Flag doesn't contain "Enum" but we check for it anyway
         // Actually Flag is from enum module but doesn't have Enum in name
@@ -101,7 +107,7 @@ mod tests {
     #[test]
     fn test_extract_enums_excludes_non_enums() {
         let path = fixtures_dir().join("enums.py");
-        let enums = extract_enums(&path).unwrap();
+        let enums = extract_enums(&path, &ExtractOptions::default()).unwrap();
 
         let has_not_enum = enums.keys().any(|k| k.contains("NotAnEnum"));
         let has_also_not = enums.keys().any(|k| k.contains("AlsoNotAnEnum"));
@@ -113,14 +119,14 @@ mod tests {
     #[test]
     fn test_extract_enums_empty_file() {
         let path = fixtures_dir().join("empty.py");
-        let enums = extract_enums(&path).unwrap();
+        let enums = extract_enums(&path, &ExtractOptions::default()).unwrap();
         assert!(enums.is_empty());
     }
 
     #[test]
     fn test_extract_enums_classes_file() {
         let path = fixtures_dir().join("classes.py");
-        let enums = extract_enums(&path).unwrap();
+        let enums = extract_enums(&path, &ExtractOptions::default()).unwrap();
         // classes.py should have no enums
         assert!(enums.is_empty());
     }
@@ -128,7 +134,7 @@ mod tests {
     #[test]
     fn test_extract_enums_mixed_file() {
         let path = fixtures_dir().join("mixed.py");
-        let enums = extract_enums(&path).unwrap();
+        let enums = extract_enums(&path, &ExtractOptions::default()).unwrap();
 
         // Should contain Priority enum
         let has_priority = enums.keys().any(|k| k.contains("Priority"));
@@ -138,10 +144,10 @@ mod tests {
     #[test]
     fn test_extract_enums_line_numbers() {
         let path = fixtures_dir().join("enums.py");
-        let enums = extract_enums(&path).unwrap();
+        let enums = extract_enums(&path, &ExtractOptions::default()).unwrap();
 
-        for (_, line) in &enums {
-            assert!(*line > 0, "Line numbers should be positive");
+        for entry in enums.values() {
+            assert!(entry.line > 0, "Line numbers should be positive");
         }
     }
 
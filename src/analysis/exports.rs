@@ -0,0 +1,134 @@
+use crate::parser::parse_file;
+use eyre::Result;
+use rustpython_parser::ast::{self, Expr, Stmt};
+use std::collections::BTreeSet;
+use std::path::Path;
+
+/// Extract the names listed in a module-level `__all__`
+/// Returns None when the module doesn't define `__all__`
+pub fn extract_exports(path: &Path) -> Result<Option<BTreeSet<String>>> {
+    let parsed = parse_file(path)?;
+    Ok(exports_from_body(&parsed.module.body))
+}
+
+/// Collect `__all__` from module-level statements, following `=`, annotated
+/// assignment, and `+=` (only string-constant list/tuple elements are read)
+pub fn exports_from_body(body: &[Stmt]) -> Option<BTreeSet<String>> {
+    let mut exports: Option<BTreeSet<String>> = None;
+
+    for stmt in body {
+        match stmt {
+            Stmt::Assign(assign) if assign.targets.iter().any(is_dunder_all) => {
+                exports = Some(string_elements(&assign.value));
+            }
+            Stmt::AnnAssign(ann) if is_dunder_all(&ann.target) => {
+                if let Some(value) = &ann.value {
+                    exports = Some(string_elements(value));
+                }
+            }
+            Stmt::AugAssign(aug) if is_dunder_all(&aug.target) && matches!(aug.op, ast::Operator::Add) => {
+                exports
+                    .get_or_insert_with(BTreeSet::new)
+                    .extend(string_elements(&aug.value));
+            }
+            _ => {}
+        }
+    }
+
+    exports
+}
+
+/// Whether a symbol is exported: listed in `__all__`, or, when the module has
+/// no `__all__`, not underscore-prefixed
+pub fn is_exported(name: &str, exports: Option<&BTreeSet<String>>) -> bool {
+    match exports {
+        Some(exports) => exports.contains(name),
+        None => !name.starts_with('_'),
+    }
+}
+
+fn is_dunder_all(expr: &Expr) -> bool {
+    matches!(expr, Expr::Name(name) if name.id.as_str() == "__all__")
+}
+
+/// String constants inside a list or tuple literal
+fn string_elements(expr: &Expr) -> BTreeSet<String> {
+    let elts = match expr {
+        Expr::List(list) => &list.elts,
+        Expr::Tuple(tuple) => &tuple.elts,
+        _ => return BTreeSet::new(),
+    };
+
+    elts.iter()
+        .filter_map(|elt| match elt {
+            Expr::Constant(c) => match &c.value {
+                ast::Constant::Str(s) => Some(s.clone()),
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rustpython_parser::Parse;
+    use std::path::PathBuf;
+
+    fn fixtures_dir() -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures")
+    }
+
+    fn exports_of(source: &str) -> Option<BTreeSet<String>> {
+        let body = ast::Suite::parse(source, "test.py").unwrap();
+        exports_from_body(&body)
+    }
+
+    #[test]
+    fn test_extract_exports_fixture() {
+        let path = fixtures_dir().join("exports.py");
+        let exports = extract_exports(&path).unwrap().unwrap();
+        let expected: BTreeSet<String> = ["Client", "Mode", "connect"].iter().map(|s| s.to_string()).collect();
+        assert_eq!(exports, expected);
+    }
+
+    #[test]
+    fn test_extract_exports_absent() {
+        let path = fixtures_dir().join("functions.py");
+        assert!(extract_exports(&path).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_exports_tuple_and_annotated() {
+        let exports = exports_of("__all__: list[str] = ('a', 'b')\n").unwrap();
+        assert_eq!(exports.len(), 2);
+        assert!(exports.contains("a"));
+    }
+
+    #[test]
+    fn test_exports_augmented() {
+        let exports = exports_of("__all__ = ['a']\n__all__ += ['b']\n").unwrap();
+        assert!(exports.contains("a"));
+        assert!(exports.contains("b"));
+    }
+
+    #[test]
+    fn test_is_exported_with_dunder_all() {
+        let exports = exports_of("__all__ = ['_special']\n");
+        assert!(is_exported("_special", exports.as_ref()));
+        assert!(!is_exported("public", exports.as_ref()));
+    }
+
+    #[test]
+    fn test_is_exported_without_dunder_all() {
+        assert!(is_exported("public", None));
+        assert!(!is_exported("_private", None));
+    }
+
+    #[test]
+    fn test_exports_ignores_non_strings() {
+        let exports = exports_of("__all__ = ['a', name, 1]\n").unwrap();
+        assert_eq!(exports.len(), 1);
+    }
+}
@@ -1,73 +1,224 @@
-use crate::parser::{extract_params, extract_returns, parse_file};
+use super::classes::is_context_manager;
+use super::complexity::cyclomatic_complexity;
+use super::visit::{is_generator, never_returns};
+use super::{module_statements, ExtractOptions};
+use crate::output::{Entry, EntryMap, Meta};
+use crate::parser::{
+    extract_decorators, extract_params, extract_returns, format_params, format_type_params, normalize_signature,
+    parse_file,
+};
 use eyre::Result;
-use rustpython_parser::ast::{Arguments, Stmt};
-use std::collections::BTreeMap;
+use rustpython_parser::ast::{Arguments, Stmt, TypeParam};
 use std::path::Path;
 
 /// Build a function signature string
-fn build_function_signature(name: &str, args: &Arguments, returns: Option<String>, is_async: bool) -> String {
-    let params = extract_params(args);
-    let params_str: Vec<String> = params
-        .iter()
-        .map(
-            |(name, typ)| {
-                if typ == "..." {
-                    name.clone()
-                } else {
-                    format!("{}: {}", name, typ)
-                }
-            },
-        )
-        .collect();
-
+pub fn build_function_signature(
+    name: &str,
+    type_params: &[TypeParam],
+    args: &Arguments,
+    returns: Option<String>,
+    is_async: bool,
+) -> String {
     let prefix = if is_async { "async def" } else { "def" };
     let returns_str = returns.map(|r| format!(" -> {}", r)).unwrap_or_default();
 
-    format!("{} {}({}){}", prefix, name, params_str.join(", "), returns_str)
+    normalize_signature(&format!(
+        "{} {}{}({}){}",
+        prefix,
+        name,
+        format_type_params(type_params),
+        format_params(args, None),
+        returns_str
+    ))
 }
 
 /// Extract all top-level functions from a Python file
-/// Returns a map of signature -> line number
-pub fn extract_functions(path: &Path) -> Result<BTreeMap<String, usize>> {
+/// Returns a map of signature -> entry
+pub fn extract_functions(path: &Path, options: &ExtractOptions) -> Result<EntryMap> {
     let parsed = parse_file(path)?;
-    let mut functions = BTreeMap::new();
+    let mut functions = EntryMap::new();
 
-    for stmt in &parsed.module.body {
+    for stmt in module_statements(&parsed.module.body, options) {
         if let Stmt::FunctionDef(func) = stmt {
             let name = func.name.to_string();
             let line = parsed.offset_to_line(func.range.start().into());
             let returns = extract_returns(func.returns.as_deref());
-            let signature = build_function_signature(&name, &func.args, returns, false);
-
-            functions.insert(signature, line);
+            let noreturn = never_returns(returns.as_deref(), &func.body);
+            let signature = build_function_signature(&name, &func.type_params, &func.args, returns, false);
+            let params = extract_params(&func.args).len();
+            let decorators = extract_decorators(&func.decorator_list);
+            let context_manager = is_context_manager(&decorators);
+            let entry = Entry {
+                end_line: parsed.offset_to_line(func.range.end().into()),
+                complexity: cyclomatic_complexity(&func.body),
+                generator: is_generator(&func.body),
+                noreturn,
+                context_manager,
+                params,
+                decorators,
+                meta: Meta {
+                    source: options.with_source.then(|| parsed.snippet(func.range).to_string()),
+                    arity: options.with_arity.then_some(params),
+                    ..Default::default()
+                },
+                ..Entry::new(line)
+            };
+
+            functions.insert(signature, entry);
         }
         // Also handle async functions
         if let Stmt::AsyncFunctionDef(func) = stmt {
             let name = func.name.to_string();
             let line = parsed.offset_to_line(func.range.start().into());
             let returns = extract_returns(func.returns.as_deref());
-            let signature = build_function_signature(&name, &func.args, returns, true);
-
-            functions.insert(signature, line);
+            let noreturn = never_returns(returns.as_deref(), &func.body);
+            let signature = build_function_signature(&name, &func.type_params, &func.args, returns, true);
+            let params = extract_params(&func.args).len();
+            let decorators = extract_decorators(&func.decorator_list);
+            let context_manager = is_context_manager(&decorators);
+            let entry = Entry {
+                end_line: parsed.offset_to_line(func.range.end().into()),
+                complexity: cyclomatic_complexity(&func.body),
+                generator: is_generator(&func.body),
+                noreturn,
+                context_manager,
+                params,
+                decorators,
+                meta: Meta {
+                    source: options.with_source.then(|| parsed.snippet(func.range).to_string()),
+                    arity: options.with_arity.then_some(params),
+                    ..Default::default()
+                },
+                ..Entry::new(line)
+            };
+
+            functions.insert(signature, entry);
         }
     }
 
     Ok(functions)
 }
 
+/// Check if a function name follows the pytest/unittest `test` prefix convention
+pub fn is_test_function(name: &str) -> bool {
+    name.starts_with("test")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::pattern::extract_function_name;
     use std::path::PathBuf;
 
     fn fixtures_dir() -> PathBuf {
         PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures")
     }
 
+    #[test]
+    fn test_extract_functions_with_source() {
+        let path = fixtures_dir().join("functions.py");
+        let options = ExtractOptions {
+            with_source: true,
+            ..Default::default()
+        };
+        let functions = extract_functions(&path, &options).unwrap();
+        let source = std::fs::read_to_string(&path).unwrap();
+
+        for (sig, entry) in &functions {
+            let snippet = entry.meta.source.as_deref().unwrap();
+            assert!(
+                snippet.starts_with("def ") || snippet.starts_with("async def "),
+                "{}",
+                sig
+            );
+            // Ends exactly at the node boundary: the body's last line, without the trailing newline
+            let last_line = source.lines().nth(entry.end_line - 1).unwrap();
+            assert!(snippet.ends_with(last_line.trim_start()), "{}", sig);
+            assert!(!snippet.ends_with('\n'));
+        }
+    }
+
+    #[test]
+    fn test_extract_functions_with_arity() {
+        let path = fixtures_dir().join("functions.py");
+        let options = ExtractOptions {
+            with_arity: true,
+            ..Default::default()
+        };
+        let functions = extract_functions(&path, &options).unwrap();
+        for entry in functions.values() {
+            assert_eq!(entry.meta.arity, Some(entry.params));
+        }
+        let arity = |name: &str| {
+            functions
+                .iter()
+                .find(|(sig, _)| extract_function_name(sig) == name)
+                .and_then(|(_, entry)| entry.meta.arity)
+                .unwrap()
+        };
+        assert_eq!(arity("simple_function"), 0);
+        assert_eq!(arity("function_with_args"), 3);
+        assert_eq!(arity("function_with_varargs"), 2);
+        assert_eq!(arity("function_with_kwonly"), 2);
+
+        let functions = extract_functions(&path, &ExtractOptions::default()).unwrap();
+        assert!(functions.values().all(|e| e.meta.arity.is_none()));
+    }
+
+    #[test]
+    fn test_extract_functions_type_params() {
+        let path = fixtures_dir().join("generics.py");
+        let functions = extract_functions(&path, &ExtractOptions::default()).unwrap();
+        let sigs: Vec<&str> = functions.keys().map(String::as_str).collect();
+        assert_eq!(sigs[0], "def first[T](items: list[T]) -> T");
+        assert_eq!(sigs[1], "def pair[K: str, V](key: K, value: V) -> tuple[K, V]");
+        assert!(sigs[2].starts_with("def call[**P, *Ts]("), "{}", sigs[2]);
+    }
+
+    #[test]
+    fn test_extract_functions_positional_only() {
+        let path = fixtures_dir().join("positional_only.py");
+        let functions = extract_functions(&path, &ExtractOptions::default()).unwrap();
+        let (sig, entry) = functions.first().unwrap();
+        assert_eq!(sig, "def clamp(value: float, /, low: float, high: float) -> float");
+        assert_eq!(entry.params, 3);
+    }
+
+    #[test]
+    fn test_extract_functions_context_managers() {
+        let path = fixtures_dir().join("context_managers.py");
+        let functions = extract_functions(&path, &ExtractOptions::default()).unwrap();
+        let managers: Vec<bool> = functions.values().map(|entry| entry.context_manager).collect();
+        // `@contextmanager`, qualified `@contextlib.asynccontextmanager`, and a plain function
+        assert_eq!(managers, vec![true, true, false]);
+    }
+
+    #[test]
+    fn test_extract_functions_forward_references() {
+        let path = fixtures_dir().join("forward_refs.py");
+        let functions = extract_functions(&path, &ExtractOptions::default()).unwrap();
+        let sigs: Vec<&str> = functions.keys().map(String::as_str).collect();
+        // Literal values and Annotated metadata are strings, not forward references
+        assert_eq!(
+            sigs,
+            vec![
+                r#"def find(root: Optional[Node], mode: Literal["dfs", "bfs"]) -> Node | None"#,
+                r#"def label(node: Annotated[Node, "display"], render: Callable[[Node], str]) -> str"#,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_extract_functions_without_source_by_default() {
+        let path = fixtures_dir().join("functions.py");
+        let functions = extract_functions(&path, &ExtractOptions::default()).unwrap();
+        assert!(functions.values().all(|e| e.meta.source.is_none()));
+    }
+
     #[test]
     fn test_extract_functions_simple() {
         let path = fixtures_dir().join("functions.py");
-        let result = extract_functions(&path);
+        let result = extract_functions(&path, &ExtractOptions::default());
         assert!(result.is_ok());
         let functions = result.unwrap();
         assert!(!functions.is_empty());
@@ -76,7 +227,7 @@ mod tests {
     #[test]
     fn test_extract_functions_contains_simple_function() {
         let path = fixtures_dir().join("functions.py");
-        let functions = extract_functions(&path).unwrap();
+        let functions = extract_functions(&path, &ExtractOptions::default()).unwrap();
 
         let has_simple = functions.keys().any(|k| k.contains("simple_function"));
         assert!(has_simple, "Should contain simple_function");
@@ -85,7 +236,7 @@ mod tests {
     #[test]
     fn test_extract_functions_async() {
         let path = fixtures_dir().join("functions.py");
-        let functions = extract_functions(&path).unwrap();
+        let functions = extract_functions(&path, &ExtractOptions::default()).unwrap();
 
         let has_async = functions.keys().any(|k| k.starts_with("async def"));
         assert!(has_async, "Should contain async functions");
@@ -94,7 +245,7 @@ mod tests {
     #[test]
     fn test_extract_functions_with_types() {
         let path = fixtures_dir().join("functions.py");
-        let functions = extract_functions(&path).unwrap();
+        let functions = extract_functions(&path, &ExtractOptions::default()).unwrap();
 
         let typed = functions.keys().find(|k| k.contains("function_with_types"));
         assert!(typed.is_some());
@@ -107,7 +258,7 @@ mod tests {
     #[test]
     fn test_extract_functions_with_varargs() {
         let path = fixtures_dir().join("functions.py");
-        let functions = extract_functions(&path).unwrap();
+        let functions = extract_functions(&path, &ExtractOptions::default()).unwrap();
 
         let varargs = functions.keys().find(|k| k.contains("function_with_varargs"));
         assert!(varargs.is_some());
@@ -119,25 +270,25 @@ mod tests {
     #[test]
     fn test_extract_functions_line_numbers() {
         let path = fixtures_dir().join("functions.py");
-        let functions = extract_functions(&path).unwrap();
+        let functions = extract_functions(&path, &ExtractOptions::default()).unwrap();
 
         // All line numbers should be positive
-        for (_, line) in &functions {
-            assert!(*line > 0);
+        for entry in functions.values() {
+            assert!(entry.line > 0);
         }
     }
 
     #[test]
     fn test_extract_functions_empty_file() {
         let path = fixtures_dir().join("empty.py");
-        let functions = extract_functions(&path).unwrap();
+        let functions = extract_functions(&path, &ExtractOptions::default()).unwrap();
         assert!(functions.is_empty());
     }
 
     #[test]
     fn test_extract_functions_mixed_file() {
         let path = fixtures_dir().join("mixed.py");
-        let functions = extract_functions(&path).unwrap();
+        let functions = extract_functions(&path, &ExtractOptions::default()).unwrap();
 
         // Should contain top-level functions but not methods
         let has_helper = functions.keys().any(|k| k.contains("helper_function"));
@@ -160,7 +311,7 @@ mod tests {
     #[test]
     fn test_extract_functions_private() {
         let path = fixtures_dir().join("functions.py");
-        let functions = extract_functions(&path).unwrap();
+        let functions = extract_functions(&path, &ExtractOptions::default()).unwrap();
 
         let has_private = functions.keys().any(|k| k.contains("_private_function"));
         assert!(has_private, "Should contain _private_function");
@@ -177,7 +328,7 @@ mod tests {
             range: Default::default(),
         };
 
-        let sig = build_function_signature("test", &args, Some("int".to_string()), false);
+        let sig = build_function_signature("test", &[], &args, Some("int".to_string()), false);
         assert_eq!(sig, "def test() -> int");
     }
 
@@ -192,7 +343,53 @@ mod tests {
             range: Default::default(),
         };
 
-        let sig = build_function_signature("test", &args, None, true);
+        let sig = build_function_signature("test", &[], &args, None, true);
         assert_eq!(sig, "async def test()");
     }
+
+    #[test]
+    fn test_extract_functions_end_line_and_complexity() {
+        let path = fixtures_dir().join("functions.py");
+        let functions = extract_functions(&path, &ExtractOptions::default()).unwrap();
+        let entry = &functions["def _private_function() -> None"];
+        assert_eq!(entry.line, 30);
+        assert_eq!(entry.end_line, 32);
+        assert_eq!(entry.lines(), 3);
+        assert_eq!(entry.complexity, 1);
+    }
+
+    #[test]
+    fn test_extract_functions_conditional() {
+        let path = fixtures_dir().join("conditional.py");
+
+        let functions = extract_functions(&path, &ExtractOptions::default()).unwrap();
+        assert!(functions.is_empty());
+
+        let options = ExtractOptions {
+            include_conditional: true,
+            ..Default::default()
+        };
+        let functions = extract_functions(&path, &options).unwrap();
+        assert!(functions.contains_key("def parse_toml(text: str) -> dict"));
+        assert!(functions.contains_key("def parse_toml_legacy(text: str) -> dict"));
+    }
+
+    #[test]
+    fn test_extract_functions_normalizes_signature_spacing() {
+        let path = fixtures_dir().join("spacing.py");
+        let functions = extract_functions(&path, &ExtractOptions::default()).unwrap();
+        let signature = functions.keys().next().unwrap();
+        assert_eq!(
+            signature,
+            "def messy(mapping: Dict[str, int], pair: Tuple[int, str]) -> List[str]"
+        );
+    }
+
+    #[test]
+    fn test_is_test_function() {
+        assert!(is_test_function("test_foo"));
+        assert!(is_test_function("test"));
+        assert!(!is_test_function("helper"));
+        assert!(!is_test_function("_test_helper"));
+    }
 }
@@ -0,0 +1,67 @@
+use crate::output::LineCounts;
+use crate::parser::parse_file;
+use eyre::Result;
+use std::path::Path;
+
+/// Count a file's total, blank, comment and code lines. A comment line's
+/// first non-blank character is `#`; every other non-blank line is code,
+/// including docstrings and code followed by a trailing comment.
+pub fn count_lines(path: &Path) -> Result<LineCounts> {
+    let parsed = parse_file(path)?;
+    Ok(count_source_lines(&parsed.source))
+}
+
+fn count_source_lines(source: &str) -> LineCounts {
+    let mut counts = LineCounts::default();
+    for line in source.lines() {
+        let line = line.trim_start();
+        counts.lines += 1;
+        if line.is_empty() {
+            counts.blank += 1;
+        } else if line.starts_with('#') {
+            counts.comment += 1;
+        } else {
+            counts.code += 1;
+        }
+    }
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn fixtures_dir() -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures")
+    }
+
+    #[test]
+    fn test_count_lines() {
+        let counts = count_lines(&fixtures_dir().join("loc.py")).unwrap();
+        assert_eq!(
+            counts,
+            LineCounts {
+                lines: 19,
+                blank: 5,
+                comment: 4,
+                code: 10,
+            }
+        );
+    }
+
+    #[test]
+    fn test_count_lines_whitespace_and_crlf() {
+        let counts = count_source_lines("x = 1\r\n   \r\n\t# note\r\n");
+        assert_eq!(
+            counts,
+            LineCounts {
+                lines: 3,
+                blank: 1,
+                comment: 1,
+                code: 1,
+            }
+        );
+        assert_eq!(count_source_lines(""), LineCounts::default());
+    }
+}
@@ -1,9 +1,126 @@
 pub mod classes;
+pub mod complexity;
+pub mod constants;
+pub mod coverage;
+pub mod deps;
+pub mod effects;
+pub mod entrypoints;
 pub mod enums;
+pub mod exports;
 pub mod functions;
+pub mod loc;
 pub mod modules;
+pub mod refs;
+pub mod shadows;
+pub mod stubs;
+pub mod visit;
 
-pub use classes::extract_classes;
+pub use classes::{extract_classes, is_abstract_class, is_abstract_method, is_test_class, mark_overrides};
+pub use constants::extract_constants;
+pub use coverage::{annotation_coverage, extract_untyped};
+pub use deps::build_dependency_graph;
+pub use effects::find_effects;
+pub use entrypoints::extract_entrypoints;
 pub use enums::extract_enums;
-pub use functions::extract_functions;
-pub use modules::build_module_tree;
+pub use exports::{extract_exports, is_exported};
+pub use functions::{extract_functions, is_test_function};
+pub use loc::count_lines;
+pub use modules::{build_module_tree, dotted_module_path, qualify};
+pub use refs::find_references;
+pub use shadows::find_import_conflicts;
+pub use stubs::generate_stub;
+
+use rustpython_parser::ast::{self, Stmt};
+
+/// Options controlling which statements definitions are extracted from and how they are rendered
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ExtractOptions {
+    /// Also look inside top-level `if`/`try`/`with` blocks
+    pub include_conditional: bool,
+    /// Drop a leading `self`/`cls` from method signatures
+    pub no_self: bool,
+    /// Attach each function's and class's source text
+    pub with_source: bool,
+    /// Attach each function's parameter count
+    pub with_arity: bool,
+    /// Recognize data model classes, tagging their fields and rendering defaults
+    pub models: bool,
+    /// Levels of nested classes to extract inside each class (0 = none)
+    pub class_depth: usize,
+    /// Render call decorators as just their callee, `@app.route` for `@app.route("/")`
+    pub strip_decorator_args: bool,
+    /// List `NamedTuple(...)`/`TypedDict(...)` assignments as the equivalent class (`--kind`)
+    pub functional_classes: bool,
+}
+
+/// The module-level statements to extract definitions from.
+/// With `include_conditional`, definitions nested in top-level `if`, `try`
+/// and `with` blocks (e.g. `except ImportError:` fallbacks) are included too.
+pub fn module_statements<'a>(body: &'a [Stmt], options: &ExtractOptions) -> Vec<&'a Stmt> {
+    let mut statements = Vec::new();
+    collect_statements(body, options, &mut statements);
+    statements
+}
+
+fn collect_statements<'a>(body: &'a [Stmt], options: &ExtractOptions, out: &mut Vec<&'a Stmt>) {
+    for stmt in body {
+        if !options.include_conditional {
+            out.push(stmt);
+            continue;
+        }
+        match stmt {
+            Stmt::If(s) => {
+                collect_statements(&s.body, options, out);
+                collect_statements(&s.orelse, options, out);
+            }
+            Stmt::Try(s) => {
+                collect_statements(&s.body, options, out);
+                for handler in &s.handlers {
+                    let ast::ExceptHandler::ExceptHandler(h) = handler;
+                    collect_statements(&h.body, options, out);
+                }
+                collect_statements(&s.orelse, options, out);
+                collect_statements(&s.finalbody, options, out);
+            }
+            Stmt::With(s) => collect_statements(&s.body, options, out),
+            _ => out.push(stmt),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rustpython_parser::Parse;
+
+    const SOURCE: &str = "try:\n    import fast\nexcept ImportError:\n    def fallback():\n        pass\nif True:\n    class Conditional:\n        pass\ndef top():\n    pass\n";
+
+    fn names(statements: &[&Stmt]) -> Vec<String> {
+        statements
+            .iter()
+            .filter_map(|stmt| match stmt {
+                Stmt::FunctionDef(f) => Some(f.name.to_string()),
+                Stmt::ClassDef(c) => Some(c.name.to_string()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_module_statements_top_level_only_by_default() {
+        let body = ast::Suite::parse(SOURCE, "test.py").unwrap();
+        let statements = module_statements(&body, &ExtractOptions::default());
+        assert_eq!(names(&statements), vec!["top"]);
+    }
+
+    #[test]
+    fn test_module_statements_include_conditional() {
+        let body = ast::Suite::parse(SOURCE, "test.py").unwrap();
+        let options = ExtractOptions {
+            include_conditional: true,
+            ..Default::default()
+        };
+        let statements = module_statements(&body, &options);
+        assert_eq!(names(&statements), vec!["fallback", "Conditional", "top"]);
+    }
+}
@@ -1,9 +1,11 @@
-use crate::output::{ModuleNode, ModuleType, ModulesOutput};
+use super::{extract_classes, extract_enums, extract_functions, ExtractOptions};
+use crate::output::{ModuleNode, ModuleType, ModulesOutput, SymbolCounts};
 use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 
-/// Build a module tree from collected Python files
-pub fn build_module_tree(files: &[PathBuf], base_path: &Path) -> ModulesOutput {
+/// Build a module tree from collected Python files.
+/// With `counts`, every node carries symbol counts extracted with those options.
+pub fn build_module_tree(files: &[PathBuf], base_path: &Path, counts: Option<&ExtractOptions>) -> ModulesOutput {
     let mut output = ModulesOutput::default();
 
     for file in files {
@@ -14,14 +16,50 @@ pub fn build_module_tree(files: &[PathBuf], base_path: &Path) -> ModulesOutput {
             .to_string_lossy()
             .to_string();
 
-        insert_path(&mut output.modules, &rel_path);
+        let file_counts = counts.map(|options| count_symbols(file, options));
+        insert_path(&mut output.modules, &rel_path, file_counts);
     }
 
     output
 }
 
-/// Insert a file path into the module tree
-fn insert_path(tree: &mut BTreeMap<String, ModuleNode>, path: &str) {
+/// Count the top-level symbols in a file (unparseable files count as empty)
+fn count_symbols(file: &Path, options: &ExtractOptions) -> SymbolCounts {
+    SymbolCounts {
+        functions: extract_functions(file, options).map(|f| f.len()).unwrap_or_default(),
+        classes: extract_classes(file, options).map(|c| c.len()).unwrap_or_default(),
+        enums: extract_enums(file, options).map(|e| e.len()).unwrap_or_default(),
+    }
+}
+
+/// Dotted import path of a file relative to a root directory
+/// "root/pkg/sub/mod.py" -> "pkg.sub.mod", "root/pkg/__init__.py" -> "pkg"
+pub fn dotted_module_path(file: &Path, root: &Path) -> String {
+    let rel = file.strip_prefix(root).unwrap_or(file).with_extension("");
+    let mut parts: Vec<String> = rel
+        .components()
+        .filter_map(|c| match c {
+            std::path::Component::Normal(part) => Some(part.to_string_lossy().to_string()),
+            _ => None,
+        })
+        .collect();
+    if parts.last().is_some_and(|last| last == "__init__") {
+        parts.pop();
+    }
+    parts.join(".")
+}
+
+/// Join a module path and a (possibly dotted) symbol name
+pub fn qualify(module: &str, name: &str) -> String {
+    if module.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}.{}", module, name)
+    }
+}
+
+/// Insert a file path into the module tree, adding its counts to every enclosing package
+fn insert_path(tree: &mut BTreeMap<String, ModuleNode>, path: &str, counts: Option<SymbolCounts>) {
     let parts: Vec<&str> = path.split('/').collect();
 
     if parts.is_empty() {
@@ -40,6 +78,7 @@ fn insert_path(tree: &mut BTreeMap<String, ModuleNode>, path: &str) {
                 path_so_far,
                 ModuleNode {
                     node_type: ModuleType::Module,
+                    counts,
                     children: BTreeMap::new(),
                 },
             );
@@ -47,8 +86,12 @@ fn insert_path(tree: &mut BTreeMap<String, ModuleNode>, path: &str) {
             // This is a directory (package)
             let entry = current.entry(path_so_far.clone()).or_insert_with(|| ModuleNode {
                 node_type: ModuleType::Package,
+                counts: counts.map(|_| SymbolCounts::default()),
                 children: BTreeMap::new(),
             });
+            if let (Some(total), Some(file_counts)) = (entry.counts.as_mut(), counts) {
+                *total += file_counts;
+            }
             current = &mut entry.children;
         }
     }
@@ -62,11 +105,41 @@ mod tests {
         PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures")
     }
 
+    #[test]
+    fn test_dotted_module_path_nested_package() {
+        let root = fixtures_dir();
+        let file = root.join("pkg/subpkg/nested.py");
+        assert_eq!(dotted_module_path(&file, &root), "pkg.subpkg.nested");
+    }
+
+    #[test]
+    fn test_dotted_module_path_init() {
+        let root = fixtures_dir();
+        assert_eq!(dotted_module_path(&root.join("pkg/__init__.py"), &root), "pkg");
+        assert_eq!(
+            dotted_module_path(&root.join("pkg/subpkg/__init__.py"), &root),
+            "pkg.subpkg"
+        );
+    }
+
+    #[test]
+    fn test_dotted_module_path_relative_root() {
+        let root = PathBuf::from("./src");
+        let file = PathBuf::from("./src/app/models.py");
+        assert_eq!(dotted_module_path(&file, &root), "app.models");
+    }
+
+    #[test]
+    fn test_qualify() {
+        assert_eq!(qualify("pkg.mod", "MyClass.method"), "pkg.mod.MyClass.method");
+        assert_eq!(qualify("", "func"), "func");
+    }
+
     #[test]
     fn test_build_module_tree_empty() {
         let files: Vec<PathBuf> = vec![];
         let base = PathBuf::from("/base");
-        let result = build_module_tree(&files, &base);
+        let result = build_module_tree(&files, &base, None);
         assert!(result.modules.is_empty());
     }
 
@@ -74,7 +147,7 @@ mod tests {
     fn test_build_module_tree_single_file() {
         let base = PathBuf::from("/base");
         let files = vec![PathBuf::from("/base/test.py")];
-        let result = build_module_tree(&files, &base);
+        let result = build_module_tree(&files, &base, None);
 
         assert_eq!(result.modules.len(), 1);
         assert!(result.modules.contains_key("test.py"));
@@ -91,7 +164,7 @@ mod tests {
             PathBuf::from("/base/pkg/__init__.py"),
             PathBuf::from("/base/pkg/module.py"),
         ];
-        let result = build_module_tree(&files, &base);
+        let result = build_module_tree(&files, &base, None);
 
         // Should have "pkg" as a package
         assert!(result.modules.contains_key("pkg"));
@@ -107,7 +180,7 @@ mod tests {
     fn test_build_module_tree_deeply_nested() {
         let base = PathBuf::from("/base");
         let files = vec![PathBuf::from("/base/a/b/c/module.py")];
-        let result = build_module_tree(&files, &base);
+        let result = build_module_tree(&files, &base, None);
 
         // Should have "a" as top-level
         assert!(result.modules.contains_key("a"));
@@ -139,7 +212,7 @@ mod tests {
             base.join("pkg/__init__.py"),
             base.join("pkg/module.py"),
         ];
-        let result = build_module_tree(&files, &base);
+        let result = build_module_tree(&files, &base, None);
 
         // Should have top-level modules
         assert!(result.modules.contains_key("functions.py"));
@@ -149,10 +222,48 @@ mod tests {
         assert!(result.modules.contains_key("pkg"));
     }
 
+    #[test]
+    fn test_build_module_tree_counts_roll_up() {
+        let base = fixtures_dir();
+        let files = vec![
+            base.join("pkg/__init__.py"),
+            base.join("pkg/module.py"),
+            base.join("pkg/subpkg/__init__.py"),
+            base.join("pkg/subpkg/nested.py"),
+        ];
+        let result = build_module_tree(&files, &base, Some(&ExtractOptions::default()));
+
+        let pkg = &result.modules["pkg"];
+        let subpkg = &pkg.children["pkg/subpkg"];
+        let module = &pkg.children["pkg/module.py"];
+        let nested = &subpkg.children["pkg/subpkg/nested.py"];
+
+        let counts = |functions, classes, enums| {
+            Some(SymbolCounts {
+                functions,
+                classes,
+                enums,
+            })
+        };
+        assert_eq!(module.counts, counts(2, 1, 0));
+        assert_eq!(nested.counts, counts(1, 0, 1));
+        assert_eq!(subpkg.counts, counts(1, 0, 1));
+        assert_eq!(pkg.counts, counts(3, 1, 1));
+    }
+
+    #[test]
+    fn test_build_module_tree_without_counts() {
+        let base = fixtures_dir();
+        let files = vec![base.join("pkg/module.py")];
+        let result = build_module_tree(&files, &base, None);
+        assert!(result.modules["pkg"].counts.is_none());
+        assert!(result.modules["pkg"].children["pkg/module.py"].counts.is_none());
+    }
+
     #[test]
     fn test_insert_path_empty() {
         let mut tree = BTreeMap::new();
-        insert_path(&mut tree, "");
+        insert_path(&mut tree, "", None);
         // Empty string splits into [""], which creates a single entry
         // This is acceptable behavior - empty string becomes a single-segment path
         assert_eq!(tree.len(), 1);
@@ -161,7 +272,7 @@ mod tests {
     #[test]
     fn test_insert_path_single_file() {
         let mut tree = BTreeMap::new();
-        insert_path(&mut tree, "module.py");
+        insert_path(&mut tree, "module.py", None);
 
         assert_eq!(tree.len(), 1);
         let node = tree.get("module.py").unwrap();
@@ -171,7 +282,7 @@ mod tests {
     #[test]
     fn test_insert_path_nested() {
         let mut tree = BTreeMap::new();
-        insert_path(&mut tree, "pkg/subpkg/module.py");
+        insert_path(&mut tree, "pkg/subpkg/module.py", None);
 
         // Should create pkg -> subpkg -> module.py
         assert!(tree.contains_key("pkg"));
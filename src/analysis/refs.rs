@@ -0,0 +1,108 @@
+use super::visit::{stmt_exprs, walk_body, walk_expr};
+use crate::output::CallSite;
+use crate::parser::{expr_to_string, parse_file, ParsedFile};
+use eyre::Result;
+use rustpython_parser::ast::{Expr, Stmt};
+use std::path::Path;
+
+/// Call sites in a file whose callee renders to `name` or ends in `.name`
+/// (`helper(...)`, `self.repo.save(...)` for `save`). This is textual
+/// matching, not name resolution: any `obj.name(...)` counts.
+pub fn find_references(path: &Path, name: &str) -> Result<Vec<CallSite>> {
+    let parsed = parse_file(path)?;
+    let mut sites = Vec::new();
+    scan_body(&parsed.module.body, None, name, &parsed, &mut sites);
+    sites.sort_by_key(|site| site.line);
+    Ok(sites)
+}
+
+/// Collect matching calls in `body`, descending into nested definitions with
+/// their dotted name as the new scope
+fn scan_body(body: &[Stmt], scope: Option<&str>, name: &str, parsed: &ParsedFile, sites: &mut Vec<CallSite>) {
+    walk_body(body, &mut |stmt| {
+        for expr in stmt_exprs(stmt) {
+            walk_expr(expr, &mut |expr| {
+                if let Expr::Call(call) = expr {
+                    let callee = expr_to_string(&call.func);
+                    if calls_name(&callee, name) {
+                        sites.push(CallSite {
+                            line: parsed.offset_to_line(call.range.start().into()),
+                            call: callee,
+                            scope: scope.map(str::to_string),
+                        });
+                    }
+                }
+            });
+        }
+
+        let nested = match stmt {
+            Stmt::FunctionDef(func) => Some((func.name.as_str(), &func.body)),
+            Stmt::AsyncFunctionDef(func) => Some((func.name.as_str(), &func.body)),
+            Stmt::ClassDef(class) => Some((class.name.as_str(), &class.body)),
+            _ => None,
+        };
+        if let Some((child, body)) = nested {
+            let child_scope = match scope {
+                Some(scope) => format!("{}.{}", scope, child),
+                None => child.to_string(),
+            };
+            scan_body(body, Some(&child_scope), name, parsed, sites);
+        }
+    });
+}
+
+/// `helper` calls `helper`; `self.repo.save` calls `save` and `repo.save`
+fn calls_name(callee: &str, name: &str) -> bool {
+    callee == name || callee.strip_suffix(name).is_some_and(|rest| rest.ends_with('.'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn fixtures_dir() -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures")
+    }
+
+    #[test]
+    fn test_find_references_function() {
+        let sites = find_references(&fixtures_dir().join("refs.py"), "helper").unwrap();
+        let found: Vec<(usize, &str, Option<&str>)> = sites
+            .iter()
+            .map(|s| (s.line, s.call.as_str(), s.scope.as_deref()))
+            .collect();
+        // `self.helper_count()` is a different name
+        assert_eq!(
+            found,
+            vec![(6, "helper", Some("process")), (18, "helper", Some("Service.run"))]
+        );
+    }
+
+    #[test]
+    fn test_find_references_method_and_nested_scope() {
+        let path = fixtures_dir().join("refs.py");
+        let sites = find_references(&path, "save").unwrap();
+        assert_eq!(sites.len(), 1);
+        assert_eq!(sites[0].call, "self.repo.save");
+        assert_eq!(find_references(&path, "repo.save").unwrap().len(), 1);
+
+        let sites = find_references(&path, "log").unwrap();
+        assert_eq!(sites[0].scope.as_deref(), Some("Service.run"));
+        assert_eq!(sites[0].line, 17);
+    }
+
+    #[test]
+    fn test_find_references_none() {
+        let sites = find_references(&fixtures_dir().join("refs.py"), "missing").unwrap();
+        assert!(sites.is_empty());
+    }
+
+    #[test]
+    fn test_calls_name() {
+        assert!(calls_name("helper", "helper"));
+        assert!(calls_name("self.helper", "helper"));
+        assert!(!calls_name("self.helper_count", "helper"));
+        assert!(!calls_name("prehelper", "helper"));
+    }
+}
@@ -0,0 +1,99 @@
+use super::deps::parsed_imports;
+use crate::output::{ImportConflict, ImportConflictKind};
+use crate::parser::{parse_file, ParsedFile};
+use eyre::Result;
+use indexmap::IndexMap;
+use rustpython_parser::ast::{Ranged, Stmt};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Names in a file bound by more than one module-level import, or bound by an
+/// import and by a top-level function or class. Imports nested in blocks
+/// (`try`/`except ImportError` fallbacks, `if TYPE_CHECKING:`) are left out.
+pub fn find_import_conflicts(path: &Path) -> Result<Vec<ImportConflict>> {
+    let parsed = parse_file(path)?;
+    Ok(scan_module(&parsed))
+}
+
+fn scan_module(parsed: &ParsedFile) -> Vec<ImportConflict> {
+    let mut imported: IndexMap<String, Vec<usize>> = IndexMap::new();
+    for import in parsed_imports(parsed).into_iter().filter(|import| import.module_level) {
+        for name in import.bindings {
+            imported.entry(name).or_default().push(import.line);
+        }
+    }
+
+    let mut defined: HashMap<&str, Vec<usize>> = HashMap::new();
+    for stmt in &parsed.module.body {
+        let name = match stmt {
+            Stmt::FunctionDef(s) => s.name.as_str(),
+            Stmt::AsyncFunctionDef(s) => s.name.as_str(),
+            Stmt::ClassDef(s) => s.name.as_str(),
+            _ => continue,
+        };
+        let line = parsed.offset_to_line(stmt.range().start().into());
+        defined.entry(name).or_default().push(line);
+    }
+
+    imported
+        .into_iter()
+        .filter_map(|(name, mut lines)| {
+            let kind = match defined.get(name.as_str()) {
+                Some(definitions) => {
+                    lines.extend(definitions);
+                    lines.sort_unstable();
+                    ImportConflictKind::Shadowed
+                }
+                None if lines.len() > 1 => ImportConflictKind::Duplicate,
+                None => return None,
+            };
+            Some(ImportConflict { name, kind, lines })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn fixtures_dir() -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/shadows")
+    }
+
+    fn conflict(name: &str, kind: ImportConflictKind, lines: &[usize]) -> ImportConflict {
+        ImportConflict {
+            name: name.to_string(),
+            kind,
+            lines: lines.to_vec(),
+        }
+    }
+
+    #[test]
+    fn test_find_import_conflicts_duplicate() {
+        let conflicts = find_import_conflicts(&fixtures_dir().join("duplicate_import.py")).unwrap();
+        assert_eq!(conflicts, vec![conflict("os", ImportConflictKind::Duplicate, &[3, 6])]);
+    }
+
+    #[test]
+    fn test_find_import_conflicts_shadowed() {
+        let conflicts = find_import_conflicts(&fixtures_dir().join("shadowed_import.py")).unwrap();
+        assert_eq!(
+            conflicts,
+            vec![conflict("loads", ImportConflictKind::Shadowed, &[3, 10])]
+        );
+    }
+
+    #[test]
+    fn test_aliases_and_fallbacks() {
+        let source = "import os.path\nimport os\nimport numpy as np\nfrom numpy import np\nfrom x import *\nfrom y import *\n\ntry:\n    import json\nexcept ImportError:\n    import simplejson as json\n";
+        let parsed = crate::parser::parse_source(source.to_string(), Path::new("test.py")).unwrap();
+        assert_eq!(
+            scan_module(&parsed),
+            vec![
+                conflict("os", ImportConflictKind::Duplicate, &[1, 2]),
+                conflict("np", ImportConflictKind::Duplicate, &[3, 4]),
+            ]
+        );
+    }
+}
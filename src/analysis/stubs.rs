@@ -0,0 +1,225 @@
+use super::{module_statements, ExtractOptions};
+use crate::parser::{expr_to_string, format_type_params, parse_file, ParsedFile};
+use eyre::Result;
+use rustpython_parser::ast::{self, ArgWithDefault, Arguments, Stmt};
+use std::path::Path;
+
+const INDENT: &str = "    ";
+
+/// Render a `.pyi`-style stub of a file: its imports, top-level functions and
+/// classes, with `...` bodies and defaults. Parameters keep their source order,
+/// annotations and `/`/`*` markers so the stub stays valid Python.
+pub fn generate_stub(path: &Path, options: &ExtractOptions) -> Result<String> {
+    let parsed = parse_file(path)?;
+    let mut imports = Vec::new();
+    let mut definitions = Vec::new();
+
+    for stmt in module_statements(&parsed.module.body, options) {
+        match stmt {
+            Stmt::Import(import) => imports.push(parsed.snippet(import.range).to_string()),
+            Stmt::ImportFrom(import) => imports.push(parsed.snippet(import.range).to_string()),
+            stmt => {
+                let mut lines = Vec::new();
+                stub_definition(stmt, &parsed, options, "", &mut lines);
+                if !lines.is_empty() {
+                    definitions.push(lines.join("\n"));
+                }
+            }
+        }
+    }
+
+    let mut blocks = Vec::new();
+    if !imports.is_empty() {
+        blocks.push(imports.join("\n"));
+    }
+    blocks.extend(definitions);
+    if blocks.is_empty() {
+        return Ok(String::new());
+    }
+    Ok(blocks.join("\n\n") + "\n")
+}
+
+/// Append the stub lines of a function or class definition; other statements
+/// are left out
+fn stub_definition(stmt: &Stmt, parsed: &ParsedFile, options: &ExtractOptions, indent: &str, lines: &mut Vec<String>) {
+    match stmt {
+        Stmt::FunctionDef(func) => {
+            push_decorators(&func.decorator_list, options, indent, lines);
+            lines.push(format!(
+                "{}def {}{}({}){}: ...",
+                indent,
+                func.name,
+                format_type_params(&func.type_params),
+                stub_parameters(&func.args),
+                stub_returns(func.returns.as_deref())
+            ));
+        }
+        Stmt::AsyncFunctionDef(func) => {
+            push_decorators(&func.decorator_list, options, indent, lines);
+            lines.push(format!(
+                "{}async def {}{}({}){}: ...",
+                indent,
+                func.name,
+                format_type_params(&func.type_params),
+                stub_parameters(&func.args),
+                stub_returns(func.returns.as_deref())
+            ));
+        }
+        Stmt::ClassDef(class) => {
+            push_decorators(&class.decorator_list, options, indent, lines);
+            let arguments: Vec<String> = class
+                .bases
+                .iter()
+                .map(expr_to_string)
+                .chain(class.keywords.iter().map(|kw| match &kw.arg {
+                    Some(arg) => format!("{}={}", arg, expr_to_string(&kw.value)),
+                    None => format!("**{}", expr_to_string(&kw.value)),
+                }))
+                .collect();
+            let arguments = if arguments.is_empty() {
+                String::new()
+            } else {
+                format!("({})", arguments.join(", "))
+            };
+            let header = format!(
+                "{}class {}{}{}:",
+                indent,
+                class.name,
+                format_type_params(&class.type_params),
+                arguments
+            );
+
+            let member_indent = format!("{}{}", indent, INDENT);
+            let mut members = Vec::new();
+            for member in &class.body {
+                stub_member(member, parsed, options, &member_indent, &mut members);
+            }
+            if members.is_empty() {
+                lines.push(format!("{} ...", header));
+            } else {
+                lines.push(header);
+                lines.extend(members);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Append the stub lines of a class body statement: methods, nested classes
+/// and class attributes
+fn stub_member(stmt: &Stmt, parsed: &ParsedFile, options: &ExtractOptions, indent: &str, lines: &mut Vec<String>) {
+    match stmt {
+        Stmt::AnnAssign(assign) => {
+            if let ast::Expr::Name(name) = assign.target.as_ref() {
+                let default = if assign.value.is_some() { " = ..." } else { "" };
+                lines.push(format!(
+                    "{}{}: {}{}",
+                    indent,
+                    name.id,
+                    expr_to_string(&assign.annotation),
+                    default
+                ));
+            }
+        }
+        Stmt::Assign(assign) => {
+            for target in &assign.targets {
+                if let ast::Expr::Name(name) = target {
+                    lines.push(format!("{}{} = ...", indent, name.id));
+                }
+            }
+        }
+        stmt => stub_definition(stmt, parsed, options, indent, lines),
+    }
+}
+
+fn push_decorators(decorator_list: &[ast::Expr], options: &ExtractOptions, indent: &str, lines: &mut Vec<String>) {
+    for decorator in decorator_list {
+        let decorator = match decorator {
+            ast::Expr::Call(call) if options.strip_decorator_args => call.func.as_ref(),
+            decorator => decorator,
+        };
+        lines.push(format!("{}@{}", indent, expr_to_string(decorator)));
+    }
+}
+
+fn stub_returns(returns: Option<&ast::Expr>) -> String {
+    returns
+        .map(|r| format!(" -> {}", expr_to_string(r)))
+        .unwrap_or_default()
+}
+
+/// Render parameters in source order: `a, /, b: int = ..., *args, c, **kwargs`
+fn stub_parameters(args: &Arguments) -> String {
+    let mut params: Vec<String> = args.posonlyargs.iter().map(stub_parameter).collect();
+    if !args.posonlyargs.is_empty() {
+        params.push("/".to_string());
+    }
+    params.extend(args.args.iter().map(stub_parameter));
+    match &args.vararg {
+        Some(vararg) => params.push(format!("*{}", annotated(vararg))),
+        // A bare `*` marks the start of keyword-only parameters
+        None if !args.kwonlyargs.is_empty() => params.push("*".to_string()),
+        None => {}
+    }
+    params.extend(args.kwonlyargs.iter().map(stub_parameter));
+    if let Some(kwarg) = &args.kwarg {
+        params.push(format!("**{}", annotated(kwarg)));
+    }
+    params.join(", ")
+}
+
+/// `name: type = ...`, or `name=...` without an annotation
+fn stub_parameter(param: &ArgWithDefault) -> String {
+    match (&param.def.annotation, &param.default) {
+        (Some(_), Some(_)) => format!("{} = ...", annotated(&param.def)),
+        (None, Some(_)) => format!("{}=...", param.def.arg),
+        (_, None) => annotated(&param.def),
+    }
+}
+
+fn annotated(arg: &ast::Arg) -> String {
+    match &arg.annotation {
+        Some(annotation) => format!("{}: {}", arg.arg, expr_to_string(annotation)),
+        None => arg.arg.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn fixtures_dir() -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures")
+    }
+
+    #[test]
+    fn test_generate_stub_matches_golden() {
+        let dir = fixtures_dir().join("stubs");
+        let stub = generate_stub(&dir.join("sample.py"), &ExtractOptions::default()).unwrap();
+        let golden = std::fs::read_to_string(dir.join("sample.pyi")).unwrap();
+        assert_eq!(stub, golden);
+    }
+
+    #[test]
+    fn test_generate_stub_strip_decorator_args() {
+        let path = fixtures_dir().join("routes.py");
+        let stub = generate_stub(&path, &ExtractOptions::default()).unwrap();
+        assert!(stub.contains("@app.route(\"/users/<int:id>\", methods=[\"GET\", \"POST\"])\ndef user"));
+
+        let options = ExtractOptions {
+            strip_decorator_args: true,
+            ..Default::default()
+        };
+        let stub = generate_stub(&path, &options).unwrap();
+        assert!(stub.contains("@app.route\ndef user(id: int) -> str: ..."));
+        assert!(stub.contains("@app.before_request\ndef authenticate() -> None: ..."));
+        assert!(!stub.contains("methods"));
+    }
+
+    #[test]
+    fn test_generate_stub_empty_file() {
+        let stub = generate_stub(&fixtures_dir().join("empty.py"), &ExtractOptions::default()).unwrap();
+        assert_eq!(stub, "");
+    }
+}
@@ -0,0 +1,346 @@
+use rustpython_parser::ast::{self, Expr, Stmt};
+
+/// Visit every statement in `body`, recursing into compound statements.
+/// Nested function and class definitions are visited but not descended into,
+/// since their bodies belong to a different scope.
+pub fn walk_body<'a>(body: &'a [Stmt], visit: &mut impl FnMut(&'a Stmt)) {
+    for stmt in body {
+        visit(stmt);
+        for child in child_bodies(stmt) {
+            walk_body(child, visit);
+        }
+    }
+}
+
+/// The statement lists nested directly inside a compound statement
+pub fn child_bodies(stmt: &Stmt) -> Vec<&[Stmt]> {
+    match stmt {
+        Stmt::For(s) => vec![&s.body, &s.orelse],
+        Stmt::AsyncFor(s) => vec![&s.body, &s.orelse],
+        Stmt::While(s) => vec![&s.body, &s.orelse],
+        Stmt::If(s) => vec![&s.body, &s.orelse],
+        Stmt::With(s) => vec![&s.body],
+        Stmt::AsyncWith(s) => vec![&s.body],
+        Stmt::Match(s) => s.cases.iter().map(|case| case.body.as_slice()).collect(),
+        Stmt::Try(s) => try_bodies(&s.body, &s.handlers, &s.orelse, &s.finalbody),
+        Stmt::TryStar(s) => try_bodies(&s.body, &s.handlers, &s.orelse, &s.finalbody),
+        _ => vec![],
+    }
+}
+
+fn try_bodies<'a>(
+    body: &'a [Stmt],
+    handlers: &'a [ast::ExceptHandler],
+    orelse: &'a [Stmt],
+    finalbody: &'a [Stmt],
+) -> Vec<&'a [Stmt]> {
+    let mut bodies = vec![body];
+    for handler in handlers {
+        let ast::ExceptHandler::ExceptHandler(h) = handler;
+        bodies.push(&h.body);
+    }
+    bodies.push(orelse);
+    bodies.push(finalbody);
+    bodies
+}
+
+/// Expressions owned directly by a statement (not by its nested bodies).
+/// Decorators, annotations and defaults of nested definitions are included
+/// since they are evaluated in the enclosing scope.
+pub fn stmt_exprs(stmt: &Stmt) -> Vec<&Expr> {
+    match stmt {
+        Stmt::FunctionDef(s) => def_exprs(&s.decorator_list, &s.args, s.returns.as_deref()),
+        Stmt::AsyncFunctionDef(s) => def_exprs(&s.decorator_list, &s.args, s.returns.as_deref()),
+        Stmt::ClassDef(s) => s
+            .decorator_list
+            .iter()
+            .chain(s.bases.iter())
+            .chain(s.keywords.iter().map(|kw| &kw.value))
+            .collect(),
+        Stmt::Return(s) => s.value.as_deref().into_iter().collect(),
+        Stmt::Delete(s) => s.targets.iter().collect(),
+        Stmt::Assign(s) => s.targets.iter().chain(std::iter::once(s.value.as_ref())).collect(),
+        Stmt::TypeAlias(s) => vec![&s.name, &s.value],
+        Stmt::AugAssign(s) => vec![&s.target, &s.value],
+        Stmt::AnnAssign(s) => [Some(s.target.as_ref()), Some(s.annotation.as_ref()), s.value.as_deref()]
+            .into_iter()
+            .flatten()
+            .collect(),
+        Stmt::For(s) => vec![&s.target, &s.iter],
+        Stmt::AsyncFor(s) => vec![&s.target, &s.iter],
+        Stmt::While(s) => vec![&s.test],
+        Stmt::If(s) => vec![&s.test],
+        Stmt::With(s) => with_exprs(&s.items),
+        Stmt::AsyncWith(s) => with_exprs(&s.items),
+        Stmt::Match(s) => std::iter::once(s.subject.as_ref())
+            .chain(s.cases.iter().filter_map(|case| case.guard.as_deref()))
+            .collect(),
+        Stmt::Raise(s) => s.exc.as_deref().into_iter().chain(s.cause.as_deref()).collect(),
+        Stmt::Try(s) => handler_exprs(&s.handlers),
+        Stmt::TryStar(s) => handler_exprs(&s.handlers),
+        Stmt::Assert(s) => std::iter::once(s.test.as_ref()).chain(s.msg.as_deref()).collect(),
+        Stmt::Expr(s) => vec![&s.value],
+        _ => vec![],
+    }
+}
+
+fn def_exprs<'a>(decorators: &'a [Expr], args: &'a ast::Arguments, returns: Option<&'a Expr>) -> Vec<&'a Expr> {
+    let defaults = args
+        .posonlyargs
+        .iter()
+        .chain(args.args.iter())
+        .chain(args.kwonlyargs.iter())
+        .filter_map(|arg| arg.default.as_deref());
+    decorators.iter().chain(defaults).chain(returns).collect()
+}
+
+fn with_exprs(items: &[ast::WithItem]) -> Vec<&Expr> {
+    items
+        .iter()
+        .flat_map(|item| std::iter::once(&item.context_expr).chain(item.optional_vars.as_deref()))
+        .collect()
+}
+
+fn handler_exprs(handlers: &[ast::ExceptHandler]) -> Vec<&Expr> {
+    handlers
+        .iter()
+        .filter_map(|handler| {
+            let ast::ExceptHandler::ExceptHandler(h) = handler;
+            h.type_.as_deref()
+        })
+        .collect()
+}
+
+/// Visit `expr` and every sub-expression beneath it
+pub fn walk_expr<'a>(expr: &'a Expr, visit: &mut impl FnMut(&'a Expr)) {
+    visit(expr);
+    for child in child_exprs(expr) {
+        walk_expr(child, visit);
+    }
+}
+
+/// The sub-expressions directly beneath an expression
+fn child_exprs(expr: &Expr) -> Vec<&Expr> {
+    match expr {
+        Expr::BoolOp(e) => e.values.iter().collect(),
+        Expr::NamedExpr(e) => vec![&e.target, &e.value],
+        Expr::BinOp(e) => vec![&e.left, &e.right],
+        Expr::UnaryOp(e) => vec![&e.operand],
+        Expr::Lambda(e) => vec![&e.body],
+        Expr::IfExp(e) => vec![&e.test, &e.body, &e.orelse],
+        Expr::Dict(e) => e.keys.iter().flatten().chain(e.values.iter()).collect(),
+        Expr::Set(e) => e.elts.iter().collect(),
+        Expr::ListComp(e) => comprehension_exprs(Some(&e.elt), None, &e.generators),
+        Expr::SetComp(e) => comprehension_exprs(Some(&e.elt), None, &e.generators),
+        Expr::DictComp(e) => comprehension_exprs(Some(&e.key), Some(&e.value), &e.generators),
+        Expr::GeneratorExp(e) => comprehension_exprs(Some(&e.elt), None, &e.generators),
+        Expr::Await(e) => vec![&e.value],
+        Expr::Yield(e) => e.value.as_deref().into_iter().collect(),
+        Expr::YieldFrom(e) => vec![&e.value],
+        Expr::Compare(e) => std::iter::once(e.left.as_ref()).chain(e.comparators.iter()).collect(),
+        Expr::Call(e) => std::iter::once(e.func.as_ref())
+            .chain(e.args.iter())
+            .chain(e.keywords.iter().map(|kw| &kw.value))
+            .collect(),
+        Expr::FormattedValue(e) => std::iter::once(e.value.as_ref())
+            .chain(e.format_spec.as_deref())
+            .collect(),
+        Expr::JoinedStr(e) => e.values.iter().collect(),
+        Expr::Attribute(e) => vec![&e.value],
+        Expr::Subscript(e) => vec![&e.value, &e.slice],
+        Expr::Starred(e) => vec![&e.value],
+        Expr::List(e) => e.elts.iter().collect(),
+        Expr::Tuple(e) => e.elts.iter().collect(),
+        Expr::Slice(e) => [e.lower.as_deref(), e.upper.as_deref(), e.step.as_deref()]
+            .into_iter()
+            .flatten()
+            .collect(),
+        Expr::Constant(_) | Expr::Name(_) => vec![],
+    }
+}
+
+fn comprehension_exprs<'a>(
+    first: Option<&'a Expr>,
+    second: Option<&'a Expr>,
+    generators: &'a [ast::Comprehension],
+) -> Vec<&'a Expr> {
+    let mut exprs: Vec<&Expr> = first.into_iter().chain(second).collect();
+    for generator in generators {
+        exprs.push(&generator.target);
+        exprs.push(&generator.iter);
+        exprs.extend(generator.ifs.iter());
+    }
+    exprs
+}
+
+/// Visit every expression evaluated by the statements of `body`, including those
+/// in nested compound statements but not inside nested definitions' bodies
+pub fn walk_body_exprs<'a>(body: &'a [Stmt], visit: &mut impl FnMut(&'a Expr)) {
+    walk_body(body, &mut |stmt| {
+        for expr in stmt_exprs(stmt) {
+            walk_expr(expr, visit);
+        }
+    });
+}
+
+/// Whether a function body contains `yield` or `yield from`, making the
+/// function a generator. Nested definitions and lambdas are scopes of their own.
+pub fn is_generator(body: &[Stmt]) -> bool {
+    let mut found = false;
+    walk_body(body, &mut |stmt| {
+        found = found || stmt_exprs(stmt).into_iter().any(yields);
+    });
+    found
+}
+
+/// Whether a function never returns normally: annotated `-> NoReturn` or
+/// `-> Never`, or, as a heuristic, not a generator and running a `while True:`
+/// loop with no `break` out of it and no `return` in it
+pub fn never_returns(returns: Option<&str>, body: &[Stmt]) -> bool {
+    let annotated = returns.is_some_and(|r| matches!(r.rsplit('.').next(), Some("NoReturn" | "Never")));
+    annotated || (!is_generator(body) && body.iter().any(loops_forever))
+}
+
+fn loops_forever(stmt: &Stmt) -> bool {
+    let Stmt::While(s) = stmt else {
+        return false;
+    };
+    let always = match s.test.as_ref() {
+        Expr::Constant(c) => match &c.value {
+            ast::Constant::Bool(b) => *b,
+            ast::Constant::Int(i) => i.to_string() != "0",
+            _ => false,
+        },
+        _ => false,
+    };
+    let mut returns = false;
+    walk_body(&s.body, &mut |stmt| {
+        returns = returns || matches!(stmt, Stmt::Return(_))
+    });
+    always && !returns && !breaks_out(&s.body)
+}
+
+/// Whether a loop body has a `break` for that loop; one in a nested loop ends
+/// the nested loop instead
+fn breaks_out(body: &[Stmt]) -> bool {
+    body.iter().any(|stmt| match stmt {
+        Stmt::Break(_) => true,
+        Stmt::For(s) => breaks_out(&s.orelse),
+        Stmt::AsyncFor(s) => breaks_out(&s.orelse),
+        Stmt::While(s) => breaks_out(&s.orelse),
+        stmt => child_bodies(stmt).into_iter().any(breaks_out),
+    })
+}
+
+fn yields(expr: &Expr) -> bool {
+    match expr {
+        Expr::Yield(_) | Expr::YieldFrom(_) => true,
+        Expr::Lambda(_) => false,
+        expr => child_exprs(expr).into_iter().any(yields),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rustpython_parser::Parse;
+
+    fn parse(source: &str) -> Vec<Stmt> {
+        ast::Suite::parse(source, "test.py").unwrap()
+    }
+
+    #[test]
+    fn test_is_generator() {
+        assert!(is_generator(&parse("for x in y:\n    if x:\n        yield x\n")));
+        assert!(is_generator(&parse("result = yield from inner()\n")));
+        assert!(!is_generator(&parse("return [x for x in y]\n")));
+        // A nested generator or a lambda doesn't make the enclosing function one
+        assert!(!is_generator(&parse("def inner():\n    yield 1\nreturn inner\n")));
+        assert!(!is_generator(&parse("f = lambda: (yield)\n")));
+    }
+
+    #[test]
+    fn test_never_returns() {
+        assert!(never_returns(Some("NoReturn"), &parse("raise SystemExit(1)\n")));
+        assert!(never_returns(Some("typing.Never"), &parse("sys.exit(1)\n")));
+        assert!(never_returns(
+            None,
+            &parse("setup()\nwhile True:\n    if poll():\n        handle()\n")
+        ));
+        assert!(never_returns(
+            None,
+            &parse("while 1:\n    for x in y:\n        break\n")
+        ));
+
+        assert!(!never_returns(Some("None"), &parse("pass\n")));
+        assert!(!never_returns(
+            None,
+            &parse("while True:\n    if done():\n        break\n")
+        ));
+        assert!(!never_returns(
+            None,
+            &parse("while True:\n    try:\n        return get()\n    except E:\n        pass\n")
+        ));
+        assert!(!never_returns(None, &parse("while running:\n    step()\n")));
+        // An endless generator still hands back a generator
+        assert!(!never_returns(None, &parse("while True:\n    yield 1\n")));
+    }
+
+    #[test]
+    fn test_walk_body_recurses_into_compound_statements() {
+        let body = parse("if a:\n    for x in y:\n        pass\nelse:\n    while b:\n        break\n");
+        let mut count = 0;
+        walk_body(&body, &mut |_| count += 1);
+        // if, for, pass, while, break
+        assert_eq!(count, 5);
+    }
+
+    #[test]
+    fn test_walk_body_does_not_enter_nested_definitions() {
+        let body = parse("def f():\n    def g():\n        pass\n    return 1\n");
+        let ast::Stmt::FunctionDef(f) = &body[0] else {
+            panic!("expected function");
+        };
+        let mut count = 0;
+        walk_body(&f.body, &mut |_| count += 1);
+        // def g, return (not g's pass)
+        assert_eq!(count, 2);
+    }
+
+    #[test]
+    fn test_walk_body_try_handlers() {
+        let body = parse("try:\n    a()\nexcept E:\n    b()\nelse:\n    c()\nfinally:\n    d()\n");
+        let mut calls = 0;
+        walk_body_exprs(&body, &mut |expr| {
+            if matches!(expr, Expr::Call(_)) {
+                calls += 1;
+            }
+        });
+        assert_eq!(calls, 4);
+    }
+
+    #[test]
+    fn test_walk_expr_visits_nested_calls() {
+        let body = parse("x = f(g(1), key=h())\n");
+        let mut calls = 0;
+        walk_body_exprs(&body, &mut |expr| {
+            if matches!(expr, Expr::Call(_)) {
+                calls += 1;
+            }
+        });
+        assert_eq!(calls, 3);
+    }
+
+    #[test]
+    fn test_walk_expr_comprehension() {
+        let body = parse("[x for x in xs if p(x)]\n");
+        let mut names = Vec::new();
+        walk_body_exprs(&body, &mut |expr| {
+            if let Expr::Name(name) = expr {
+                names.push(name.id.to_string());
+            }
+        });
+        assert!(names.contains(&"xs".to_string()));
+        assert!(names.contains(&"p".to_string()));
+    }
+}
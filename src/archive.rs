@@ -0,0 +1,117 @@
+use eyre::{Result, WrapErr};
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// Whether a target is a `.zip` or `.whl` archive to read members from
+pub fn is_archive(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("zip") || ext.eq_ignore_ascii_case("whl"))
+}
+
+fn open_zip(archive: &Path) -> Result<zip::ZipArchive<File>> {
+    let file = File::open(archive).wrap_err_with(|| format!("Failed to open archive: {}", archive.display()))?;
+    zip::ZipArchive::new(file).wrap_err_with(|| format!("Not a zip archive: {}", archive.display()))
+}
+
+/// List the members of an archive that `keep` selects, as paths below the
+/// archive itself (`dist/pkg-1.0.whl/pkg/core.py`). Their sources are read
+/// with `read_member`, so members of different archives never collide.
+pub fn list(archive: &Path, keep: impl Fn(&Path) -> bool) -> Result<Vec<PathBuf>> {
+    let mut zip = open_zip(archive)?;
+    let mut paths = Vec::new();
+    for index in 0..zip.len() {
+        let entry = zip.by_index_raw(index)?;
+        // Skips directories and names that would escape the archive (`../`)
+        let Some(path) = entry.enclosed_name().filter(|_| entry.is_file()) else {
+            continue;
+        };
+        if keep(&path) {
+            paths.push(archive.join(path));
+        }
+    }
+    Ok(paths)
+}
+
+/// The archive a path listed by `list` belongs to, and the member path inside it.
+/// `None` for any path that doesn't lie below an archive file.
+fn split(path: &Path) -> Option<(&Path, &Path)> {
+    path.ancestors()
+        .skip(1)
+        .find(|ancestor| is_archive(ancestor) && ancestor.is_file())
+        .and_then(|archive| Some((archive, path.strip_prefix(archive).ok()?)))
+}
+
+/// Source of an archive member listed by `list`, or `None` if `path` isn't one
+pub fn read_member(path: &Path) -> Option<Result<Vec<u8>>> {
+    let (archive, member) = split(path)?;
+    let read = || -> Result<Vec<u8>> {
+        let mut zip = open_zip(archive)?;
+        let index = zip
+            .index_for_path(member)
+            .ok_or_else(|| eyre::eyre!("No member {} in {}", member.display(), archive.display()))?;
+        let mut entry = zip.by_index(index)?;
+        let mut source = Vec::with_capacity(entry.size() as usize);
+        entry
+            .read_to_end(&mut source)
+            .wrap_err_with(|| format!("Failed to read {} from {}", member.display(), archive.display()))?;
+        Ok(source)
+    };
+    Some(read())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use zip::write::SimpleFileOptions;
+
+    fn write_wheel(path: &Path, members: &[(&str, &str)]) {
+        let mut zip = zip::ZipWriter::new(File::create(path).unwrap());
+        for (name, source) in members {
+            zip.start_file(*name, SimpleFileOptions::default()).unwrap();
+            zip.write_all(source.as_bytes()).unwrap();
+        }
+        zip.finish().unwrap();
+    }
+
+    #[test]
+    fn test_list_selected_members() {
+        let dir = tempfile::tempdir().unwrap();
+        let wheel = dir.path().join("pkg-1.0-py3-none-any.whl");
+        write_wheel(
+            &wheel,
+            &[
+                ("archived_pkg/core.py", "def run():\n    pass\n"),
+                ("archived_pkg-1.0.dist-info/METADATA", "Name: pkg\n"),
+            ],
+        );
+
+        assert!(is_archive(&wheel));
+        let paths = list(&wheel, |path| path.extension().is_some_and(|ext| ext == "py")).unwrap();
+        assert_eq!(paths, vec![wheel.join("archived_pkg/core.py")]);
+        assert_eq!(read_member(&paths[0]).unwrap().unwrap(), b"def run():\n    pass\n");
+        assert!(read_member(&wheel.join("archived_pkg/missing.py")).unwrap().is_err());
+        assert!(read_member(&dir.path().join("archived_pkg/core.py")).is_none());
+    }
+
+    #[test]
+    fn test_members_of_two_archives_stay_apart() {
+        let dir = tempfile::tempdir().unwrap();
+        let first = dir.path().join("a-1.0.whl");
+        let second = dir.path().join("b-1.0.whl");
+        write_wheel(&first, &[("pkg/core.py", "def from_a():\n    pass\n")]);
+        write_wheel(&second, &[("pkg/core.py", "def from_b():\n    pass\n")]);
+
+        let mut paths = list(&first, |_| true).unwrap();
+        paths.extend(list(&second, |_| true).unwrap());
+        let sources: Vec<_> = paths.iter().map(|path| read_member(path).unwrap().unwrap()).collect();
+        assert_eq!(
+            sources,
+            vec![
+                b"def from_a():\n    pass\n".to_vec(),
+                b"def from_b():\n    pass\n".to_vec()
+            ]
+        );
+    }
+}
@@ -1,5 +1,8 @@
-use clap::{Parser, Subcommand};
+use crate::output::ClassKind;
+use clap::{Parser, Subcommand, ValueEnum};
+use serde::Deserialize;
 use std::path::PathBuf;
+use std::time::SystemTime;
 
 #[derive(Parser)]
 #[command(
@@ -15,13 +18,286 @@ pub struct Cli {
     #[arg(short = 't', long = "target", default_value = ".", global = true)]
     pub targets: Vec<PathBuf>,
 
+    /// Analyze exactly the files listed in PATH, one per line (`-` for stdin),
+    /// instead of walking the targets
+    #[arg(long, value_name = "PATH", global = true)]
+    pub files_from: Option<PathBuf>,
+
+    /// Print the files that would be analyzed, one per line, and exit without parsing them
+    #[arg(long, global = true)]
+    pub list_targets: bool,
+
+    /// End each `--list-targets` path with a NUL byte instead of a newline, for `xargs -0`
+    #[arg(short = '0', long, requires = "list_targets", global = true)]
+    pub null: bool,
+
     /// Force JSON output (default: YAML, or JSON when not a TTY)
-    #[arg(short, long, global = true)]
+    #[arg(short, long, global = true, conflicts_with = "format")]
     pub json: bool,
 
-    /// Sort symbols alphabetically (default: file order by line)
+    /// Output format (default: YAML, or JSON when not a TTY)
+    #[arg(long, value_enum, global = true)]
+    pub format: Option<OutputFormat>,
+
+    /// Write JSON on a single line instead of pretty-printed
+    #[arg(long, conflicts_with = "no_compact", global = true)]
+    pub compact: bool,
+
+    /// Pretty-print JSON, even when the config file sets `compact`
+    #[arg(long, global = true)]
+    pub no_compact: bool,
+
+    /// Write YAML maps and lists of plain values inline (`{line: 3, arity: 2}`)
+    #[arg(long, global = true)]
+    pub yaml_flow: bool,
+
+    /// List only the signatures of each file, without line numbers, for stable API
+    /// snapshots (function, class, enum, dump)
+    #[arg(long, global = true)]
+    pub signature_only: bool,
+
+    /// Print a `-- N symbols in M files --` footer to stderr (function, class, enum, dump;
+    /// default: when stdout is a terminal)
+    #[arg(long, conflicts_with = "no_summary", global = true)]
+    pub summary: bool,
+
+    /// Don't print the summary footer, even to a terminal
+    #[arg(long, global = true)]
+    pub no_summary: bool,
+
+    /// Show a progress bar on stderr while files are parsed (function, class, enum, untyped, dump;
+    /// default: for large scans when stdout and stderr are terminals). Never drawn when stderr
+    /// isn't a terminal.
+    #[arg(long, conflicts_with = "no_progress", global = true)]
+    pub progress: bool,
+
+    /// Don't show the progress bar, even for large scans
+    #[arg(long, global = true)]
+    pub no_progress: bool,
+
+    /// Color YAML output (never applied to JSON)
+    #[arg(long, value_enum, value_name = "WHEN", default_value_t = ColorChoice::Auto, global = true)]
+    pub color: ColorChoice,
+
+    /// Sort symbols alphabetically (shorthand for --sort-by name)
     #[arg(short, long, global = true)]
     pub alphabetical: bool,
+
+    /// Order symbols within each file by this key (default: line, or score with --fuzzy)
+    #[arg(long, value_enum, global = true)]
+    pub sort_by: Option<SortBy>,
+
+    /// Key symbols by file, or by directory for a coarser overview (function, class, enum, dump)
+    #[arg(long, value_enum, default_value_t = GroupBy::File, global = true)]
+    pub group_by: GroupBy,
+
+    /// Key output by target first, then by file within it (function, class, enum, dump)
+    #[arg(long, global = true)]
+    pub labeled: bool,
+
+    /// Key output by absolute file path, resolving symlinks (falls back to the path as found)
+    #[arg(long, conflicts_with = "labeled", global = true)]
+    pub absolute: bool,
+
+    /// Reverse the sort order (descending)
+    #[arg(short, long, global = true)]
+    pub reverse: bool,
+
+    /// Fail once more than N symbols are collected across all files, before
+    /// filtering (function, class, enum, untyped, dump)
+    #[arg(long, value_name = "N", global = true)]
+    pub max_symbols: Option<usize>,
+
+    /// Keep only the first N symbols of each file, in sort order (function, class, enum, dump)
+    #[arg(long, value_name = "N", value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..), global = true)]
+    pub per_file_limit: Option<usize>,
+
+    /// Don't skip built-in ignored directories (__pycache__, .venv, build, ...) or pyproject.toml excludes
+    #[arg(long, conflicts_with = "ignore", global = true)]
+    pub no_ignore: bool,
+
+    /// Skip the built-in ignored directories and pyproject.toml excludes, even when the config file
+    /// sets `no-ignore`
+    #[arg(long, global = true)]
+    pub ignore: bool,
+
+    /// Include hidden (dot-prefixed) files and directories
+    #[arg(long, conflicts_with = "no_hidden", global = true)]
+    pub hidden: bool,
+
+    /// Leave out hidden files and directories, even when the config file sets `hidden`
+    #[arg(long, global = true)]
+    pub no_hidden: bool,
+
+    /// Skip generated files (*_pb2.py, *_pb2_grpc.py, Django migrations)
+    #[arg(long, conflicts_with = "no_skip_generated", global = true)]
+    pub skip_generated: bool,
+
+    /// Analyze generated files, even when the config file sets `skip-generated`
+    #[arg(long, global = true)]
+    pub no_skip_generated: bool,
+
+    /// Skip files whose name matches this glob (repeatable)
+    #[arg(long = "ignore-file", value_name = "GLOB", global = true)]
+    pub ignore_files: Vec<String>,
+
+    /// Collect files matching this glob even if an ignore rule would skip them: by
+    /// name, or by path relative to the target to reach into an ignored directory (repeatable)
+    #[arg(long = "include", value_name = "GLOB", global = true)]
+    pub includes: Vec<String>,
+
+    /// Also analyze the code cells of Jupyter notebooks (.ipynb)
+    #[arg(long, conflicts_with = "no_notebooks", global = true)]
+    pub notebooks: bool,
+
+    /// Leave out Jupyter notebooks, even when the config file sets `notebooks`
+    #[arg(long, global = true)]
+    pub no_notebooks: bool,
+
+    /// Also extract definitions inside top-level if/try/with blocks
+    #[arg(long, conflicts_with = "no_include_conditional", global = true)]
+    pub include_conditional: bool,
+
+    /// Leave out conditional definitions, even when the config file sets `include-conditional`
+    #[arg(long, global = true)]
+    pub no_include_conditional: bool,
+
+    /// Add each symbol's dotted qualified name (e.g. pkg.module.Class.method)
+    #[arg(long, conflicts_with = "no_qualnames", global = true)]
+    pub qualnames: bool,
+
+    /// Leave out qualified names, even when the config file sets `qualnames`
+    #[arg(long, global = true)]
+    pub no_qualnames: bool,
+
+    /// Key symbols by bare name instead of full signature (function, class, enum, untyped, dump)
+    #[arg(long, global = true)]
+    pub names_only: bool,
+
+    /// Include each function's and class's full source text (bloats output)
+    #[arg(long, global = true)]
+    pub with_source: bool,
+
+    /// Include each function's and method's parameter count
+    #[arg(long, global = true)]
+    pub with_arity: bool,
+
+    /// Keep only symbols matching every PATTERN (default: any)
+    #[arg(long = "all", global = true)]
+    pub match_all: bool,
+
+    /// Match PATTERNs as fuzzy subsequences (`cptl` finds `compute_total`)
+    /// instead of by prefix, then substring
+    #[arg(long, global = true)]
+    pub fuzzy: bool,
+
+    /// Keep only the first N symbols across all files, in sort order (function, class, enum,
+    /// untyped, dump): with --reverse the N last, with --fuzzy the N best matches
+    #[arg(long, value_name = "N", global = true)]
+    pub limit: Option<usize>,
+
+    /// Wrap JSON output in {schema_version, command, targets, results}
+    #[arg(long, global = true)]
+    pub envelope: bool,
+
+    /// Write symbols to a SQLite database (tables `files` and `symbols`) instead of stdout
+    #[arg(long, value_name = "PATH", global = true)]
+    pub sqlite: Option<PathBuf>,
+
+    /// Print diagnostics to stderr: file count, phase timings and skipped files
+    /// (`-vv` also lists files slower than 50ms to parse)
+    #[arg(short, long, action = clap::ArgAction::Count, global = true)]
+    pub verbose: u8,
+
+    /// Fail if any file had to be skipped because of a read or parse error
+    #[arg(long, conflicts_with = "no_strict", global = true)]
+    pub strict: bool,
+
+    /// Don't fail on skipped files, even when the config file sets `strict`
+    #[arg(long, global = true)]
+    pub no_strict: bool,
+
+    /// Number of files to parse in parallel (default: one per CPU)
+    #[arg(long, value_name = "N", global = true)]
+    pub threads: Option<usize>,
+
+    /// Analyze files as of a git revision instead of the working tree;
+    /// targets are relative to the repository root
+    #[arg(long, value_name = "GITREF", global = true)]
+    pub rev: Option<String>,
+
+    /// Only analyze files under target directories modified since a duration
+    /// ago (30m, 24h, 7d, 2w) or a YYYY-MM-DD date
+    #[arg(long, value_name = "WHEN", value_parser = crate::walk::parse_since, conflicts_with = "rev", global = true)]
+    pub since: Option<SystemTime>,
+}
+
+/// Serialization format for results
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum OutputFormat {
+    #[default]
+    Yaml,
+    Json,
+    Toml,
+    /// Graphviz `digraph` (deps only)
+    Dot,
+    /// Classic ctags lines (function, class, enum and dump)
+    Ctags,
+    /// LSP `DocumentSymbol[]` JSON per file (function, class and enum)
+    Lsp,
+    /// `<files>` XML document (function, class, enum, entrypoint, untyped and dump)
+    Xml,
+    /// One JSON object per symbol per line, methods included (function, class, enum and dump)
+    JsonlBySymbol,
+}
+
+/// When to color output
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ColorChoice {
+    /// Color when writing to a terminal
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+/// Key used to order symbols within each file
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SortBy {
+    /// Symbol name (ties broken by signature)
+    Name,
+    /// Definition line (file order)
+    #[default]
+    Line,
+    /// Cyclomatic complexity
+    Complexity,
+    /// Number of source lines spanned
+    Lines,
+    /// Fuzzy match score, best first
+    Score,
+}
+
+/// What symbols are keyed by in the output
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum GroupBy {
+    /// One key per file
+    #[default]
+    File,
+    /// One key per directory, merging the symbols of its files
+    Dir,
+}
+
+/// What classes are keyed by in `class` output
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum ClassKey {
+    /// The full signature, bases included: `class Foo(Base)`
+    #[default]
+    Signature,
+    /// The bare class name, with the signature kept in a `signature` field
+    Name,
 }
 
 /// Visibility filter for functions/methods/fields
@@ -33,6 +309,80 @@ pub enum Visibility {
     Private,
 }
 
+/// Symbol filters shared by the listing commands
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Filters {
+    pub visibility: Visibility,
+    /// Keep only abstract classes
+    pub abstract_only: bool,
+    /// Keep only symbols listed in the file's `__all__`
+    pub exported_only: bool,
+    /// Mark each symbol with whether it is exported
+    pub annotate_exports: bool,
+    /// Add each symbol's dotted qualified name
+    pub qualnames: bool,
+    /// Keep only test functions and test classes' test methods
+    pub tests_only: bool,
+    /// Replace signatures with bare names
+    pub names_only: bool,
+    /// Keep only functions/methods with at least this many parameters
+    pub min_args: Option<usize>,
+    /// Keep only functions/methods with at most this many parameters
+    pub max_args: Option<usize>,
+    /// Keep only functions/methods spanning at least this many lines
+    pub min_lines: Option<usize>,
+    /// Keep only functions/methods spanning at most this many lines
+    pub max_lines: Option<usize>,
+    /// Keep only generators (`Some(true)`) or only non-generators (`Some(false)`)
+    pub generators: Option<bool>,
+    /// Keep only `async def` functions/methods (`Some(true)`) or only plain `def`s (`Some(false)`)
+    pub asynchronous: Option<bool>,
+    /// Keep only decorated functions/methods (`Some(true)`) or only undecorated ones (`Some(false)`)
+    pub decorated: Option<bool>,
+    /// What classes are keyed by
+    pub class_key: ClassKey,
+    /// Keep only functions/methods that never return
+    pub noreturn_only: bool,
+    /// Keep only context-manager functions and classes
+    pub context_managers: bool,
+    /// How patterns are matched against names
+    pub matching: MatchMode,
+    /// Keep only data model classes, reduced to their model fields
+    pub model_only: bool,
+    /// Keep only classes of this structural kind
+    pub kind: Option<ClassKind>,
+    /// Mark methods that override a method of a same-file base class
+    pub overrides: bool,
+    /// Keep only classes without base classes
+    pub roots: bool,
+    /// Keep only classes with at least one base class
+    pub derived: bool,
+    /// Keep only classes with at least this many methods
+    pub min_methods: Option<usize>,
+    /// Keep only classes with at most this many methods
+    pub max_methods: Option<usize>,
+    /// Keep classes left without members by visibility filtering
+    pub keep_empty: bool,
+}
+
+/// How symbol patterns are matched against names
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MatchMode {
+    /// Require every pattern to match instead of any
+    pub all: bool,
+    /// Score names as fuzzy subsequences instead of the prefix/contains cascade
+    pub fuzzy: bool,
+}
+
+impl Cli {
+    pub fn match_mode(&self) -> MatchMode {
+        MatchMode {
+            all: self.match_all,
+            fuzzy: self.fuzzy,
+        }
+    }
+}
+
 #[derive(Subcommand)]
 pub enum Command {
     /// List all functions with signatures and locations
@@ -48,6 +398,66 @@ pub enum Command {
         /// Show only private functions (starting with _)
         #[arg(long, conflicts_with = "public")]
         private: bool,
+
+        /// Show only symbols listed in the file's `__all__`
+        #[arg(long)]
+        exported_only: bool,
+
+        /// Mark each symbol with `exported` (in `__all__`, or public when there is none)
+        #[arg(long)]
+        annotate_exports: bool,
+
+        /// Show only test functions (`test*` names)
+        #[arg(long)]
+        tests_only: bool,
+
+        /// Show only functions with at least N parameters (`self`, `*args` and `**kwargs` count as one each)
+        #[arg(long, value_name = "N")]
+        min_args: Option<usize>,
+
+        /// Show only functions with at most N parameters
+        #[arg(long, value_name = "N")]
+        max_args: Option<usize>,
+
+        /// Show only functions spanning at least N lines (definition through last body line)
+        #[arg(long, value_name = "N")]
+        min_lines: Option<usize>,
+
+        /// Show only functions spanning at most N lines
+        #[arg(long, value_name = "N")]
+        max_lines: Option<usize>,
+
+        /// Show only generators and async generators (functions that `yield`)
+        #[arg(long, conflicts_with = "no_generators")]
+        generators: bool,
+
+        /// Show only functions that are not generators
+        #[arg(long)]
+        no_generators: bool,
+
+        /// Show only `async def` functions
+        #[arg(long = "async", conflicts_with = "sync_only")]
+        async_only: bool,
+
+        /// Show only plain `def` functions
+        #[arg(long = "sync")]
+        sync_only: bool,
+
+        /// Show only functions with at least one decorator
+        #[arg(long, conflicts_with = "undecorated")]
+        decorated: bool,
+
+        /// Show only functions without decorators
+        #[arg(long)]
+        undecorated: bool,
+
+        /// Show only functions that never return: `-> NoReturn`/`-> Never`, or an endless `while True:` loop
+        #[arg(long = "noreturn")]
+        noreturn_only: bool,
+
+        /// Show only `@contextmanager`/`@asynccontextmanager` functions
+        #[arg(long)]
+        context_managers: bool,
     },
 
     /// List all classes with methods and inheritance
@@ -63,6 +473,107 @@ pub enum Command {
         /// Show only private fields/methods (starting with _)
         #[arg(long, conflicts_with = "public")]
         private: bool,
+
+        /// Show only abstract classes (ABC bases or @abstractmethod members), marking abstract methods
+        #[arg(long = "abstract")]
+        abstract_only: bool,
+
+        /// Show only symbols listed in the file's `__all__`
+        #[arg(long)]
+        exported_only: bool,
+
+        /// Mark each symbol with `exported` (in `__all__`, or public when there is none)
+        #[arg(long)]
+        annotate_exports: bool,
+
+        /// Show only test classes (`Test*` names or `TestCase` bases) and their test methods
+        #[arg(long)]
+        tests_only: bool,
+
+        /// Omit a leading `self`/`cls` parameter from method signatures
+        #[arg(long)]
+        no_self: bool,
+
+        /// Show only data model classes (pydantic, dataclass, attrs) with their fields
+        #[arg(long)]
+        model: bool,
+
+        /// Show only classes of a structural kind: Protocol, TypedDict or NamedTuple
+        /// subclasses (and functional `TypedDict(...)`/`NamedTuple(...)` definitions)
+        #[arg(long, value_name = "KIND")]
+        kind: Option<ClassKind>,
+
+        /// Show only methods with at least N parameters (`self`, `*args` and `**kwargs` count as one each)
+        #[arg(long, value_name = "N")]
+        min_args: Option<usize>,
+
+        /// Show only methods with at most N parameters
+        #[arg(long, value_name = "N")]
+        max_args: Option<usize>,
+
+        /// Show only methods spanning at least N lines; classes left without methods are dropped
+        #[arg(long, value_name = "N")]
+        min_lines: Option<usize>,
+
+        /// Show only methods spanning at most N lines; classes left without methods are dropped
+        #[arg(long, value_name = "N")]
+        max_lines: Option<usize>,
+
+        /// Mark methods that override a method of a base class in the same file
+        #[arg(long)]
+        overrides: bool,
+
+        /// Show only root classes: those without base classes (`class Foo`)
+        #[arg(long, conflicts_with = "derived")]
+        roots: bool,
+
+        /// Show only classes with at least one base class
+        #[arg(long)]
+        derived: bool,
+
+        /// Show only classes with at least N methods (counted after --public/--private)
+        #[arg(long, value_name = "N")]
+        min_methods: Option<usize>,
+
+        /// Show only classes with at most N methods (0 for classes without methods)
+        #[arg(long, value_name = "N")]
+        max_methods: Option<usize>,
+
+        /// Keep classes whose members are all filtered out by --public/--private, listed empty
+        #[arg(long)]
+        keep_empty: bool,
+
+        /// Show only `async def` methods; classes left without methods are dropped
+        #[arg(long = "async", conflicts_with = "sync_only")]
+        async_only: bool,
+
+        /// Show only plain `def` methods; classes left without methods are dropped
+        #[arg(long = "sync")]
+        sync_only: bool,
+
+        /// Show only methods with at least one decorator; classes left without methods are dropped
+        #[arg(long, conflicts_with = "undecorated")]
+        decorated: bool,
+
+        /// Show only methods without decorators; classes left without methods are dropped
+        #[arg(long)]
+        undecorated: bool,
+
+        /// Show only methods that never return; classes left without methods are dropped
+        #[arg(long = "noreturn")]
+        noreturn_only: bool,
+
+        /// Show only classes defining `__enter__`/`__exit__` (or `__aenter__`/`__aexit__`)
+        #[arg(long)]
+        context_managers: bool,
+
+        /// Also list classes nested in class bodies, N levels deep (0 = top-level only)
+        #[arg(long, value_name = "N", default_value_t = 0)]
+        class_depth: usize,
+
+        /// Key classes by full signature, or by bare name for a key that doesn't change with the bases
+        #[arg(long, value_enum, value_name = "KEY", default_value_t = ClassKey::Signature)]
+        class_key: ClassKey,
     },
 
     /// List all enum definitions
@@ -70,6 +581,22 @@ pub enum Command {
         /// Patterns to filter by name (prefix match, then contains)
         #[arg(value_name = "PATTERN")]
         patterns: Vec<String>,
+
+        /// Show only public enums (not starting with _)
+        #[arg(long, conflicts_with = "private")]
+        public: bool,
+
+        /// Show only private enums (starting with _)
+        #[arg(long, conflicts_with = "public")]
+        private: bool,
+
+        /// Show only symbols listed in the file's `__all__`
+        #[arg(long)]
+        exported_only: bool,
+
+        /// Mark each symbol with `exported` (in `__all__`, or public when there is none)
+        #[arg(long)]
+        annotate_exports: bool,
     },
 
     /// Show module/package structure
@@ -77,16 +604,196 @@ pub enum Command {
         /// Patterns to filter by name (prefix match, then contains)
         #[arg(value_name = "PATTERN")]
         patterns: Vec<String>,
+
+        /// Show function/class/enum counts per module (packages sum their contents)
+        #[arg(long)]
+        counts: bool,
+
+        /// Render as an indented tree (like the `tree` command) instead of YAML/JSON
+        #[arg(long)]
+        tree: bool,
     },
 
-    /// Comprehensive output (functions, classes, enums)
+    /// List files with an `if __name__ == "__main__":` guard
+    #[command(alias = "entrypoints")]
+    Entrypoint {
+        /// Patterns to filter by module name (prefix match, then contains)
+        #[arg(value_name = "PATTERN")]
+        patterns: Vec<String>,
+    },
+
+    /// Show which project files each file imports (`--format dot` for Graphviz)
+    Deps,
+
+    /// Report type-annotation coverage of parameters and return types per file
+    Coverage,
+
+    /// Count total, blank, comment and code lines per file and in total
+    Loc,
+
+    /// List functions and methods with an unannotated parameter or return type
+    Untyped {
+        /// Patterns to filter by name (prefix match, then contains)
+        #[arg(value_name = "PATTERN")]
+        patterns: Vec<String>,
+
+        /// Show only public functions and methods (not starting with _)
+        #[arg(long)]
+        public: bool,
+    },
+
+    /// Compare function and class signatures of two trees: added, removed and changed per file
+    Diff {
+        /// Tree (or file) before the change
+        #[arg(value_name = "OLD")]
+        old: PathBuf,
+
+        /// Tree (or file) after the change
+        #[arg(value_name = "NEW")]
+        new: PathBuf,
+    },
+
+    /// Find call sites of a function or method: calls to `NAME(...)` or `obj.NAME(...)`, matched by name only
+    Refs {
+        /// Name being called (`save`, or a dotted suffix like `repo.save`)
+        #[arg(value_name = "NAME")]
+        name: String,
+    },
+
+    /// List module-level statements that run at import time: calls, loops, conditionals, ...
+    Effects,
+
+    /// List names imported twice in a file, or imported and then redefined by a function or class
+    Shadows,
+
+    /// List function and class names defined in more than one place
+    Dupes {
+        /// Only consider this kind of definition (default: functions and classes)
+        #[arg(long, value_enum)]
+        kind: Option<DupeKind>,
+    },
+
+    /// Count the decorators used on functions and methods, with where each is used
+    Decorators {
+        /// Patterns to filter by decorator name (prefix match, then contains)
+        #[arg(value_name = "PATTERN")]
+        patterns: Vec<String>,
+    },
+
+    /// Generate `.pyi` stubs: imports, function and class signatures with `...` bodies
+    Stub {
+        /// Write each stub to a mirrored `.pyi` path under this directory instead of stdout
+        #[arg(long, value_name = "DIR")]
+        output_dir: Option<PathBuf>,
+
+        /// Render decorators as just their name, dropping any argument list (`@app.route`)
+        #[arg(long)]
+        strip_decorator_args: bool,
+    },
+
+    /// Comprehensive output (functions, classes, enums, constants)
     Dump {
         /// Patterns to filter by name (prefix match, then contains)
         #[arg(value_name = "PATTERN")]
         patterns: Vec<String>,
+
+        /// Symbol kinds to include, comma-separated (default: all)
+        #[arg(long, value_enum, value_delimiter = ',', value_name = "KINDS")]
+        only: Vec<DumpKind>,
+
+        /// Omit a leading `self`/`cls` parameter from method signatures
+        #[arg(long)]
+        no_self: bool,
     },
 }
 
+/// Symbol kinds merged by `dump`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum DumpKind {
+    Functions,
+    Classes,
+    Enums,
+    Constants,
+}
+
+impl DumpKind {
+    /// Whether a kind is selected by `--only` (an empty selection means everything)
+    pub fn selected(self, only: &[DumpKind]) -> bool {
+        only.is_empty() || only.contains(&self)
+    }
+}
+
+/// Definitions compared by `dupes`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum DupeKind {
+    Function,
+    Class,
+}
+
+impl DupeKind {
+    /// Whether a kind is selected by `--kind` (none means both)
+    pub fn selected(self, kind: Option<DupeKind>) -> bool {
+        kind.is_none_or(|kind| kind == self)
+    }
+}
+
+impl Command {
+    /// The subcommand's name as typed on the command line
+    pub fn name(&self) -> &'static str {
+        match self {
+            Command::Function { .. } => "function",
+            Command::Class { .. } => "class",
+            Command::Enum { .. } => "enum",
+            Command::Module { .. } => "module",
+            Command::Entrypoint { .. } => "entrypoint",
+            Command::Deps => "deps",
+            Command::Coverage => "coverage",
+            Command::Loc => "loc",
+            Command::Untyped { .. } => "untyped",
+            Command::Diff { .. } => "diff",
+            Command::Refs { .. } => "refs",
+            Command::Effects => "effects",
+            Command::Shadows => "shadows",
+            Command::Dupes { .. } => "dupes",
+            Command::Decorators { .. } => "decorators",
+            Command::Stub { .. } => "stub",
+            Command::Dump { .. } => "dump",
+        }
+    }
+}
+
+impl Filters {
+    /// Whether `--min-args` or `--max-args` was given
+    pub fn limits_args(&self) -> bool {
+        self.min_args.is_some() || self.max_args.is_some()
+    }
+
+    /// Whether a parameter count is within the `--min-args`/`--max-args` bounds
+    pub fn args_in_range(&self, params: usize) -> bool {
+        self.min_args.is_none_or(|min| params >= min) && self.max_args.is_none_or(|max| params <= max)
+    }
+
+    /// Whether `--min-lines` or `--max-lines` was given
+    pub fn limits_lines(&self) -> bool {
+        self.min_lines.is_some() || self.max_lines.is_some()
+    }
+
+    /// Whether a line count is within the `--min-lines`/`--max-lines` bounds
+    pub fn lines_in_range(&self, lines: usize) -> bool {
+        self.min_lines.is_none_or(|min| lines >= min) && self.max_lines.is_none_or(|max| lines <= max)
+    }
+
+    /// Whether `--min-methods` or `--max-methods` was given
+    pub fn limits_methods(&self) -> bool {
+        self.min_methods.is_some() || self.max_methods.is_some()
+    }
+
+    /// Whether a method count is within the `--min-methods`/`--max-methods` bounds
+    pub fn methods_in_range(&self, methods: usize) -> bool {
+        self.min_methods.is_none_or(|min| methods >= min) && self.max_methods.is_none_or(|max| methods <= max)
+    }
+}
+
 impl Visibility {
     pub fn from_flags(public: bool, private: bool) -> Self {
         match (public, private) {
@@ -97,10 +804,62 @@ impl Visibility {
     }
 }
 
+impl SortBy {
+    /// The key in effect: `--alphabetical`, then `--sort-by`, then the match
+    /// score with `--fuzzy` or the definition line
+    pub fn from_flags(sort_by: Option<SortBy>, alphabetical: bool, fuzzy: bool) -> Self {
+        match (alphabetical, sort_by) {
+            (true, _) => SortBy::Name,
+            (false, Some(sort_by)) => sort_by,
+            (false, None) if fuzzy => SortBy::Score,
+            (false, None) => SortBy::Line,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_command_name_matches_subcommand() {
+        use clap::CommandFactory;
+        for sub in Cli::command().get_subcommands() {
+            // Placeholder values for required positionals (e.g. `diff OLD NEW`)
+            let required = sub.get_positionals().filter(|arg| arg.is_required_set()).map(|_| "x");
+            let args: Vec<&str> = ["pyr", sub.get_name()].into_iter().chain(required).collect();
+            let cli = Cli::try_parse_from(args).unwrap();
+            assert_eq!(cli.command.name(), sub.get_name());
+        }
+    }
+
+    #[test]
+    fn test_filters_args_in_range() {
+        let filters = Filters {
+            min_args: Some(2),
+            max_args: Some(4),
+            ..Default::default()
+        };
+        assert!(filters.limits_args());
+        assert!(!filters.args_in_range(1));
+        assert!(filters.args_in_range(2));
+        assert!(filters.args_in_range(4));
+        assert!(!filters.args_in_range(5));
+        assert!(Filters::default().args_in_range(0));
+    }
+
+    #[test]
+    fn test_filters_methods_in_range() {
+        let filters = Filters {
+            max_methods: Some(0),
+            ..Default::default()
+        };
+        assert!(filters.limits_methods());
+        assert!(filters.methods_in_range(0));
+        assert!(!filters.methods_in_range(1));
+        assert!(!Filters::default().limits_methods());
+    }
+
     #[test]
     fn test_visibility_default() {
         let vis = Visibility::default();
@@ -150,4 +909,53 @@ mod tests {
         assert_eq!(format!("{:?}", Visibility::Public), "Public");
         assert_eq!(format!("{:?}", Visibility::Private), "Private");
     }
+
+    #[test]
+    fn test_sort_by_default_is_line() {
+        assert_eq!(SortBy::default(), SortBy::Line);
+    }
+
+    #[test]
+    fn test_sort_by_alphabetical_overrides() {
+        assert_eq!(SortBy::from_flags(Some(SortBy::Complexity), true, false), SortBy::Name);
+        assert_eq!(SortBy::from_flags(Some(SortBy::Lines), false, true), SortBy::Lines);
+        assert_eq!(SortBy::from_flags(None, false, false), SortBy::Line);
+        assert_eq!(SortBy::from_flags(None, false, true), SortBy::Score);
+    }
+
+    #[test]
+    fn test_dump_kind_selected() {
+        assert!(DumpKind::Classes.selected(&[]));
+        assert!(DumpKind::Enums.selected(&[DumpKind::Functions, DumpKind::Enums]));
+        assert!(!DumpKind::Classes.selected(&[DumpKind::Functions]));
+    }
+
+    #[test]
+    fn test_dump_only_parses_comma_separated() {
+        let cli = Cli::parse_from(["pyr", "dump", "--only", "functions,enums"]);
+        let Command::Dump { only, .. } = cli.command else {
+            panic!("expected dump");
+        };
+        assert_eq!(only, vec![DumpKind::Functions, DumpKind::Enums]);
+    }
+
+    #[test]
+    fn test_sort_by_parses_from_cli() {
+        let cli = Cli::parse_from(["pyr", "function", "--sort-by", "complexity", "--reverse"]);
+        assert_eq!(cli.sort_by, Some(SortBy::Complexity));
+        assert!(cli.reverse);
+    }
+
+    #[test]
+    fn test_format_parses_from_cli() {
+        let cli = Cli::parse_from(["pyr", "function", "--format", "yaml"]);
+        assert_eq!(cli.format, Some(OutputFormat::Yaml));
+        assert!(!cli.json);
+
+        let cli = Cli::parse_from(["pyr", "--json", "function"]);
+        assert!(cli.json && cli.format.is_none());
+
+        // `--json` is `--format json`, so it can't be combined with another format
+        assert!(Cli::try_parse_from(["pyr", "--json", "--format", "yaml", "function"]).is_err());
+    }
 }
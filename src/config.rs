@@ -0,0 +1,274 @@
+use crate::cli::{Cli, ColorChoice, OutputFormat, SortBy};
+use clap::parser::ValueSource;
+use clap::ArgMatches;
+use eyre::{Result, WrapErr};
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+/// Name of the config file looked up from the current directory upwards
+pub const CONFIG_FILE: &str = ".pyr.toml";
+
+/// Project file whose exclude settings are honored at a target's root
+pub const PYPROJECT_FILE: &str = "pyproject.toml";
+
+/// Default options from `.pyr.toml`. Keys are named like the CLI flags they
+/// stand in for; a flag given on the command line always wins, and a switch
+/// turned on here is turned off by its `--no-` flag (`--ignore` for `no-ignore`).
+#[derive(Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Config {
+    pub format: Option<OutputFormat>,
+    pub color: Option<ColorChoice>,
+    pub compact: bool,
+    pub sort_by: Option<SortBy>,
+    pub no_ignore: bool,
+    pub hidden: bool,
+    pub skip_generated: bool,
+    /// File name globs to skip, like `--ignore-file`
+    pub ignore: Vec<String>,
+    /// Globs collected over ignore rules, like `--include`
+    pub include: Vec<String>,
+    pub notebooks: bool,
+    pub include_conditional: bool,
+    pub qualnames: bool,
+    pub strict: bool,
+    pub threads: Option<usize>,
+}
+
+impl Config {
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path).wrap_err_with(|| format!("Failed to read {}", path.display()))?;
+        toml::from_str(&content).wrap_err_with(|| format!("Invalid config file: {}", path.display()))
+    }
+
+    /// Load the nearest config file in `dir` or one of its ancestors, if any
+    pub fn discover(dir: &Path) -> Result<Option<Self>> {
+        find_config(dir).map(|path| Self::load(&path)).transpose()
+    }
+
+    /// Fill in options not given on the command line. `matches` are the
+    /// parsed arguments `cli` was built from.
+    pub fn apply(self, cli: &mut Cli, matches: &ArgMatches) {
+        // `--json` is a format too, so it also overrides a configured one
+        if cli.format.is_none() && !cli.json {
+            cli.format = self.format;
+        }
+        if let Some(color) = self.color.filter(|_| !from_command_line(matches, "color")) {
+            cli.color = color;
+        }
+        if cli.sort_by.is_none() {
+            cli.sort_by = self.sort_by;
+        }
+        if cli.ignore_files.is_empty() {
+            cli.ignore_files = self.ignore;
+        }
+        if cli.includes.is_empty() {
+            cli.includes = self.include;
+        }
+        cli.threads = cli.threads.or(self.threads);
+        // A configured switch is turned off again by its `--no-` flag
+        cli.compact |= self.compact && !cli.no_compact;
+        cli.no_ignore |= self.no_ignore && !cli.ignore;
+        cli.hidden |= self.hidden && !cli.no_hidden;
+        cli.skip_generated |= self.skip_generated && !cli.no_skip_generated;
+        cli.notebooks |= self.notebooks && !cli.no_notebooks;
+        cli.include_conditional |= self.include_conditional && !cli.no_include_conditional;
+        cli.qualnames |= self.qualnames && !cli.no_qualnames;
+        cli.strict |= self.strict && !cli.no_strict;
+    }
+}
+
+/// Path of the nearest `.pyr.toml` in `dir` or one of its ancestors
+pub fn find_config(dir: &Path) -> Option<PathBuf> {
+    dir.ancestors()
+        .map(|ancestor| ancestor.join(CONFIG_FILE))
+        .find(|path| path.is_file())
+}
+
+/// The parts of `pyproject.toml` pyr reads; everything else is ignored
+#[derive(Debug, Default, Deserialize)]
+struct PyProject {
+    #[serde(default)]
+    tool: Tools,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct Tools {
+    pyr: Option<ExcludeSettings>,
+    ruff: Option<ExcludeSettings>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+struct ExcludeSettings {
+    exclude: Vec<String>,
+    extend_exclude: Vec<String>,
+}
+
+/// Exclude globs declared in the `pyproject.toml` of `dir`, if it has one:
+/// `exclude` and `extend-exclude` of `[tool.pyr]`, or of `[tool.ruff]` when
+/// there is no `[tool.pyr]` section
+pub fn pyproject_excludes(dir: &Path) -> Result<Vec<String>> {
+    let path = dir.join(PYPROJECT_FILE);
+    if !path.is_file() {
+        return Ok(Vec::new());
+    }
+    let content = std::fs::read_to_string(&path).wrap_err_with(|| format!("Failed to read {}", path.display()))?;
+    let pyproject: PyProject = toml::from_str(&content).wrap_err_with(|| format!("Invalid {}", path.display()))?;
+    let settings = pyproject.tool.pyr.or(pyproject.tool.ruff).unwrap_or_default();
+    Ok(settings.exclude.into_iter().chain(settings.extend_exclude).collect())
+}
+
+/// Whether an argument was given explicitly rather than left at its default
+fn from_command_line(matches: &ArgMatches, id: &str) -> bool {
+    matches.value_source(id) == Some(ValueSource::CommandLine)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::{CommandFactory, FromArgMatches};
+    use tempfile::TempDir;
+
+    fn parse(args: &[&str], config: Config) -> Cli {
+        let matches = Cli::command().try_get_matches_from(args).unwrap();
+        let mut cli = Cli::from_arg_matches(&matches).unwrap();
+        config.apply(&mut cli, &matches);
+        cli
+    }
+
+    fn config_dir(content: &str) -> TempDir {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join(CONFIG_FILE), content).unwrap();
+        std::fs::create_dir_all(dir.path().join("pkg/sub")).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_discover_walks_up_to_config() {
+        let dir = config_dir("format = \"json\"\nignore = [\"conftest.py\"]\ninclude = [\"build/api.py\"]\n");
+        let config = Config::discover(&dir.path().join("pkg/sub")).unwrap().unwrap();
+        assert_eq!(config.format, Some(OutputFormat::Json));
+        assert_eq!(config.ignore, vec!["conftest.py"]);
+        assert_eq!(config.include, vec!["build/api.py"]);
+    }
+
+    #[test]
+    fn test_discover_without_config() {
+        let dir = TempDir::new().unwrap();
+        assert_eq!(Config::discover(dir.path()).unwrap(), None);
+    }
+
+    #[test]
+    fn test_invalid_config_is_an_error() {
+        let dir = config_dir("formatt = \"json\"\n");
+        let err = Config::discover(dir.path()).unwrap_err();
+        assert!(err.to_string().starts_with("Invalid config file"));
+    }
+
+    fn pyproject_dir(content: &str) -> TempDir {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join(PYPROJECT_FILE), content).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_pyproject_excludes_from_ruff() {
+        let dir = pyproject_dir(
+            "[project]\nname = \"app\"\n\n[tool.ruff]\nexclude = [\"legacy\"]\nextend-exclude = [\"*_pb2.py\"]\nline-length = 100\n",
+        );
+        assert_eq!(pyproject_excludes(dir.path()).unwrap(), vec!["legacy", "*_pb2.py"]);
+    }
+
+    #[test]
+    fn test_pyproject_excludes_prefer_tool_pyr() {
+        let dir = pyproject_dir("[tool.pyr]\nexclude = [\"vendor\"]\n\n[tool.ruff]\nexclude = [\"legacy\"]\n");
+        assert_eq!(pyproject_excludes(dir.path()).unwrap(), vec!["vendor"]);
+    }
+
+    #[test]
+    fn test_pyproject_excludes_without_settings() {
+        let dir = pyproject_dir("[project]\nname = \"app\"\n");
+        assert!(pyproject_excludes(dir.path()).unwrap().is_empty());
+        assert!(pyproject_excludes(TempDir::new().unwrap().path()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_config_sets_defaults() {
+        let dir = config_dir("format = \"json\"\ncolor = \"never\"\nsort-by = \"name\"\nhidden = true\n");
+        let config = Config::discover(dir.path()).unwrap().unwrap();
+        let cli = parse(&["pyr", "function"], config);
+        assert_eq!(cli.format, Some(OutputFormat::Json));
+        assert_eq!(cli.color, ColorChoice::Never);
+        assert_eq!(cli.sort_by, Some(SortBy::Name));
+        assert!(cli.hidden);
+    }
+
+    #[test]
+    fn test_command_line_overrides_config() {
+        let config = || Config {
+            format: Some(OutputFormat::Json),
+            color: Some(ColorChoice::Never),
+            ignore: vec!["conftest.py".to_string()],
+            ..Default::default()
+        };
+        let cli = parse(
+            &[
+                "pyr",
+                "function",
+                "--format",
+                "toml",
+                "--color",
+                "auto",
+                "--ignore-file",
+                "x.py",
+            ],
+            config(),
+        );
+        assert_eq!(cli.format, Some(OutputFormat::Toml));
+        assert_eq!(cli.color, ColorChoice::Auto);
+        assert_eq!(cli.ignore_files, vec!["x.py"]);
+
+        let cli = parse(&["pyr", "--json", "function"], config());
+        assert_eq!(cli.format, None);
+    }
+
+    #[test]
+    fn test_no_flags_override_config_switches() {
+        let config = || Config {
+            compact: true,
+            no_ignore: true,
+            hidden: true,
+            skip_generated: true,
+            notebooks: true,
+            include_conditional: true,
+            qualnames: true,
+            strict: true,
+            ..Default::default()
+        };
+        let cli = parse(&["pyr", "function", "--no-hidden", "--ignore", "--no-strict"], config());
+        assert!(!cli.hidden);
+        assert!(!cli.no_ignore);
+        assert!(!cli.strict);
+        // Switches without a `--no-` flag on the command line keep the config value
+        assert!(cli.compact && cli.skip_generated && cli.notebooks && cli.include_conditional && cli.qualnames);
+
+        let cli = parse(
+            &[
+                "pyr",
+                "function",
+                "--no-compact",
+                "--no-skip-generated",
+                "--no-notebooks",
+                "--no-include-conditional",
+                "--no-qualnames",
+            ],
+            config(),
+        );
+        assert!(!cli.compact && !cli.skip_generated && !cli.notebooks && !cli.include_conditional && !cli.qualnames);
+
+        assert!(Cli::command()
+            .try_get_matches_from(["pyr", "function", "--hidden", "--no-hidden"])
+            .is_err());
+    }
+}
@@ -0,0 +1,200 @@
+use eyre::{Result, WrapErr};
+use std::path::{Component, Path, PathBuf};
+use std::process::Command;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
+
+/// Revision set with `--rev`; sources are read from it instead of the working tree
+static REVISION: OnceLock<Revision> = OnceLock::new();
+
+/// Set while `in_working_tree` runs
+static WORKING_TREE: AtomicBool = AtomicBool::new(false);
+
+/// A git commit whose files are analyzed instead of the working tree. Paths
+/// are relative to the repository root.
+#[derive(Debug)]
+pub struct Revision {
+    /// Full commit hash the ref resolved to
+    commit: String,
+    root: PathBuf,
+}
+
+impl Revision {
+    /// Resolve `rev` to a commit of the repository containing `dir`
+    pub fn open(dir: &Path, rev: &str) -> Result<Self> {
+        let root = git(dir, &["rev-parse", "--show-toplevel"]).wrap_err("Not inside a git repository")?;
+        let root = PathBuf::from(root.trim_end());
+        let commit = git(
+            &root,
+            &["rev-parse", "--verify", "--quiet", &format!("{}^{{commit}}", rev)],
+        )
+        .wrap_err_with(|| format!("Unknown git revision: {}", rev))?;
+        Ok(Self {
+            commit: commit.trim_end().to_string(),
+            root,
+        })
+    }
+
+    /// Files under `target` at this revision, or `None` if it has no such path
+    pub fn list_files(&self, target: &Path) -> Result<Option<Vec<PathBuf>>> {
+        let target = target.to_string_lossy();
+        let listing = git(
+            &self.root,
+            &["ls-tree", "-r", "-z", "--name-only", &self.commit, "--", &target],
+        )?;
+        let files: Vec<PathBuf> = listing
+            .split('\0')
+            .filter(|p| !p.is_empty())
+            .map(PathBuf::from)
+            .collect();
+        Ok((!files.is_empty()).then_some(files))
+    }
+
+    /// Contents of a file at this revision
+    pub fn read(&self, path: &Path) -> Result<String> {
+        git(&self.root, &["cat-file", "blob", &self.object(path)])
+            .wrap_err_with(|| format!("Failed to read {} at {}", path.display(), short(&self.commit)))
+    }
+
+    /// Object type of a path at this revision (`blob`, `tree`), if it exists
+    fn object_type(&self, path: &Path) -> Option<String> {
+        git(&self.root, &["cat-file", "-t", &self.object(path)])
+            .ok()
+            .map(|kind| kind.trim_end().to_string())
+    }
+
+    /// `<commit>:<path>` naming a path in this revision's tree
+    fn object(&self, path: &Path) -> String {
+        let path: Vec<_> = path
+            .components()
+            .filter(|c| *c != Component::CurDir)
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect();
+        format!("{}:{}", self.commit, path.join("/"))
+    }
+}
+
+/// Whether `path` is a directory, at the `--rev` revision if one is set
+pub fn is_dir(path: &Path) -> bool {
+    match revision() {
+        Some(revision) => revision.object_type(path).as_deref() == Some("tree"),
+        None => path.is_dir(),
+    }
+}
+
+/// Whether `path` is a file, at the `--rev` revision if one is set
+pub fn is_file(path: &Path) -> bool {
+    match revision() {
+        Some(revision) => revision.object_type(path).as_deref() == Some("blob"),
+        None => path.is_file(),
+    }
+}
+
+/// Read sources from `revision` for the rest of the run
+pub fn use_revision(revision: Revision) {
+    // Set once from main, before any file is read
+    let _ = REVISION.set(revision);
+}
+
+/// The revision given with `--rev`, if any and not set aside by `in_working_tree`
+pub fn revision() -> Option<&'static Revision> {
+    REVISION.get().filter(|_| !WORKING_TREE.load(Ordering::Relaxed))
+}
+
+/// Run `f` reading from the working tree even when `--rev` is set, e.g. for
+/// the NEW side of `diff`
+pub fn in_working_tree<T>(f: impl FnOnce() -> T) -> T {
+    WORKING_TREE.store(true, Ordering::Relaxed);
+    let result = f();
+    WORKING_TREE.store(false, Ordering::Relaxed);
+    result
+}
+
+/// Run a git command in `dir` and return its stdout
+fn git(dir: &Path, args: &[&str]) -> Result<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .wrap_err("Failed to run git")?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(eyre::eyre!("git {} failed: {}", args[0], stderr.trim()));
+    }
+    String::from_utf8(output.stdout).wrap_err("git output is not valid UTF-8")
+}
+
+fn short(commit: &str) -> &str {
+    &commit[..commit.len().min(12)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    /// A repository with `app.py` defining `old` in the first commit and
+    /// `new` in the second, plus a `lib/` package added in the second
+    fn two_commit_repo() -> TempDir {
+        let dir = TempDir::new().unwrap();
+        let run = |args: &[&str]| {
+            let status = Command::new("git")
+                .arg("-C")
+                .arg(dir.path())
+                .args(["-c", "user.name=pyr", "-c", "user.email=pyr@example.com"])
+                .args(args)
+                .output()
+                .unwrap()
+                .status;
+            assert!(status.success(), "git {:?} failed", args);
+        };
+        run(&["init", "-q"]);
+        std::fs::write(dir.path().join("app.py"), "def old():\n    pass\n").unwrap();
+        run(&["add", "."]);
+        run(&["commit", "-q", "-m", "first"]);
+        std::fs::write(dir.path().join("app.py"), "def new():\n    pass\n").unwrap();
+        std::fs::create_dir(dir.path().join("lib")).unwrap();
+        std::fs::write(dir.path().join("lib/util.py"), "X = 1\n").unwrap();
+        run(&["add", "."]);
+        run(&["commit", "-q", "-m", "second"]);
+        dir
+    }
+
+    #[test]
+    fn test_read_file_at_revision() {
+        let dir = two_commit_repo();
+        let first = Revision::open(dir.path(), "HEAD~1").unwrap();
+        assert_eq!(first.read(Path::new("app.py")).unwrap(), "def old():\n    pass\n");
+        let head = Revision::open(dir.path(), "HEAD").unwrap();
+        assert_eq!(head.read(Path::new("app.py")).unwrap(), "def new():\n    pass\n");
+        assert!(head.read(Path::new("missing.py")).is_err());
+        assert_eq!(head.object_type(Path::new("./lib")).as_deref(), Some("tree"));
+        assert_eq!(head.object_type(Path::new(".")).as_deref(), Some("tree"));
+        assert_eq!(first.object_type(Path::new("lib")), None);
+    }
+
+    #[test]
+    fn test_list_files_at_revision() {
+        let dir = two_commit_repo();
+        let first = Revision::open(dir.path(), "HEAD~1").unwrap();
+        assert_eq!(
+            first.list_files(Path::new(".")).unwrap(),
+            Some(vec![PathBuf::from("app.py")])
+        );
+        assert_eq!(first.list_files(Path::new("lib")).unwrap(), None);
+
+        let head = Revision::open(dir.path(), "HEAD").unwrap();
+        assert_eq!(
+            head.list_files(Path::new("lib")).unwrap(),
+            Some(vec![PathBuf::from("lib/util.py")])
+        );
+    }
+
+    #[test]
+    fn test_unknown_revision() {
+        let dir = two_commit_repo();
+        let err = Revision::open(dir.path(), "no-such-branch").unwrap_err();
+        assert_eq!(err.to_string(), "Unknown git revision: no-such-branch");
+    }
+}
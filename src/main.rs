@@ -1,335 +1,2316 @@
-use clap::Parser;
-use eyre::Result;
+use clap::{CommandFactory, FromArgMatches};
+use eyre::{Result, WrapErr};
+use indexmap::IndexMap;
+use indicatif::ProgressBar;
 use rayon::prelude::*;
-use std::collections::BTreeMap;
-use std::path::PathBuf;
-use std::sync::Mutex;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::io::{IsTerminal, Write};
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 mod analysis;
+mod archive;
 mod cli;
+mod config;
+mod git;
 mod output;
 mod parser;
 mod pattern;
+mod sort;
 mod walk;
 
-use cli::{Cli, Command, Visibility};
-use output::{output, should_use_json, ClassInfo, ClassMap, ClassesOutput, FilesOutput};
+use analysis::ExtractOptions;
+use cli::{ClassKey, Cli, Command, DumpKind, DupeKind, Filters, GroupBy, MatchMode, OutputFormat, SortBy, Visibility};
+use output::{
+    output, resolve_format, should_show_summary, should_use_color, summary_footer, AnnotationCoverage, ClassKind,
+    ClassMap, ClassSignatures, ClassSignaturesOutput, ClassesOutput, DocumentSymbol, Entry, EntryMap, FilesOutput,
+    LabeledOutput, LspOutput, Meta, OutputStyle, RunInfo, SignatureChange, SignatureDiff, SignaturesOutput, SymbolKind,
+    Tag, TagKind,
+};
 use pattern::{extract_class_name, extract_function_name, filter_classes_output, filter_files_output};
+use sort::SortOrder;
+use walk::WalkOptions;
 
 fn main() -> Result<()> {
-    let cli = Cli::parse();
-    let use_json = should_use_json(cli.json);
-    let targets = &cli.targets;
+    let matches = Cli::command().get_matches();
+    let mut cli = Cli::from_arg_matches(&matches).unwrap_or_else(|err| err.exit());
+    if let Some(config) = config::Config::discover(&std::env::current_dir()?)? {
+        config.apply(&mut cli, &matches);
+    }
+    if let Some(threads) = cli.threads {
+        rayon::ThreadPoolBuilder::new().num_threads(threads).build_global()?;
+    }
+    if let Some(rev) = &cli.rev {
+        git::use_revision(git::Revision::open(&std::env::current_dir()?, rev)?);
+    }
+    let format = resolve_format(cli.format, cli.json);
+    let style = OutputStyle {
+        format,
+        color: should_use_color(cli.color, format),
+        compact: cli.compact,
+        envelope: cli.envelope.then_some(RunInfo {
+            command: cli.command.name(),
+            targets: &cli.targets,
+        }),
+        sqlite: cli.sqlite.as_deref(),
+        group_by: cli.group_by,
+        labeled: cli.labeled,
+        absolute: cli.absolute,
+        yaml_flow: cli.yaml_flow,
+        per_file_limit: cli.per_file_limit,
+        summary: should_show_summary(cli.summary, cli.no_summary),
+        signature_only: cli.signature_only,
+    };
+    if style.group_by == GroupBy::Dir
+        && (style.sqlite.is_some()
+            || matches!(
+                format,
+                OutputFormat::Ctags | OutputFormat::Lsp | OutputFormat::JsonlBySymbol
+            ))
+    {
+        // Tags and symbol locations need the file each symbol is in
+        return Err(eyre::eyre!(
+            "--group-by dir can't be combined with --sqlite, --format ctags, --format lsp or --format jsonl-by-symbol"
+        ));
+    }
+    if style.labeled
+        && (style.sqlite.is_some()
+            || matches!(
+                format,
+                OutputFormat::Ctags | OutputFormat::Lsp | OutputFormat::Xml | OutputFormat::JsonlBySymbol
+            ))
+    {
+        return Err(eyre::eyre!(
+            "--labeled can't be combined with --sqlite, --format ctags, --format lsp, --format xml or --format jsonl-by-symbol"
+        ));
+    }
+    let mut source = Source {
+        targets: &cli.targets,
+        walk: WalkOptions {
+            no_ignore: cli.no_ignore,
+            hidden: cli.hidden,
+            skip_generated: cli.skip_generated,
+            ignore_files: cli.ignore_files.clone(),
+            includes: cli.includes.clone(),
+            notebooks: cli.notebooks,
+            since: cli.since,
+        },
+        extract: ExtractOptions {
+            include_conditional: cli.include_conditional,
+            no_self: matches!(
+                cli.command,
+                Command::Class { no_self: true, .. } | Command::Dump { no_self: true, .. }
+            ),
+            with_source: cli.with_source,
+            with_arity: cli.with_arity,
+            models: matches!(cli.command, Command::Class { model: true, .. }),
+            functional_classes: matches!(cli.command, Command::Class { kind: Some(_), .. }),
+            class_depth: match cli.command {
+                Command::Class { class_depth, .. } => class_depth,
+                _ => 0,
+            },
+            strip_decorator_args: matches!(
+                cli.command,
+                Command::Stub {
+                    strip_decorator_args: true,
+                    ..
+                }
+            ),
+        },
+        files_from: None,
+        budget: SymbolBudget::new(cli.max_symbols),
+        progress: (cli.progress || cli.no_progress).then_some(cli.progress),
+        skipped: Skipped::default(),
+        collected: Mutex::default(),
+        timings: Timings::default(),
+    };
+    if let Some(list) = &cli.files_from {
+        source.files_from = Some(read_file_list(list, &source.walk)?);
+    }
+    if cli.list_targets {
+        list_targets(&source, cli.null)?;
+        return report_skipped(source.skipped.into_inner(), cli.verbose, cli.strict);
+    }
+    let order = SortOrder::new(
+        SortBy::from_flags(cli.sort_by, cli.alphabetical, cli.fuzzy),
+        cli.reverse,
+    )
+    .with_limit(cli.limit);
 
-    match &cli.command {
+    let result = match &cli.command {
         Command::Function {
             patterns,
             public,
             private,
+            exported_only,
+            annotate_exports,
+            tests_only,
+            min_args,
+            max_args,
+            min_lines,
+            max_lines,
+            generators,
+            no_generators,
+            async_only,
+            sync_only,
+            decorated,
+            undecorated,
+            noreturn_only,
+            context_managers,
         } => {
-            let visibility = Visibility::from_flags(*public, *private);
-            run_functions(targets, patterns, visibility, cli.alphabetical, use_json)
+            let filters = Filters {
+                visibility: Visibility::from_flags(*public, *private),
+                exported_only: *exported_only,
+                annotate_exports: *annotate_exports,
+                qualnames: cli.qualnames,
+                names_only: cli.names_only,
+                matching: cli.match_mode(),
+                tests_only: *tests_only,
+                min_args: *min_args,
+                max_args: *max_args,
+                min_lines: *min_lines,
+                max_lines: *max_lines,
+                generators: (*generators || *no_generators).then_some(*generators),
+                asynchronous: (*async_only || *sync_only).then_some(*async_only),
+                decorated: (*decorated || *undecorated).then_some(*decorated),
+                noreturn_only: *noreturn_only,
+                context_managers: *context_managers,
+                ..Default::default()
+            };
+            run_functions(&source, patterns, &filters, order, style)
         }
         Command::Class {
             patterns,
             public,
             private,
+            abstract_only,
+            exported_only,
+            annotate_exports,
+            tests_only,
+            model,
+            kind,
+            min_args,
+            max_args,
+            min_lines,
+            max_lines,
+            overrides,
+            roots,
+            derived,
+            min_methods,
+            max_methods,
+            keep_empty,
+            async_only,
+            sync_only,
+            decorated,
+            undecorated,
+            class_key,
+            noreturn_only,
+            context_managers,
+            ..
         } => {
-            let visibility = Visibility::from_flags(*public, *private);
-            run_classes(targets, patterns, visibility, cli.alphabetical, use_json)
+            let filters = Filters {
+                visibility: Visibility::from_flags(*public, *private),
+                abstract_only: *abstract_only,
+                exported_only: *exported_only,
+                annotate_exports: *annotate_exports,
+                qualnames: cli.qualnames,
+                names_only: cli.names_only,
+                matching: cli.match_mode(),
+                tests_only: *tests_only,
+                model_only: *model,
+                kind: *kind,
+                min_args: *min_args,
+                max_args: *max_args,
+                min_lines: *min_lines,
+                max_lines: *max_lines,
+                overrides: *overrides,
+                roots: *roots,
+                derived: *derived,
+                min_methods: *min_methods,
+                max_methods: *max_methods,
+                keep_empty: *keep_empty,
+                generators: None,
+                asynchronous: (*async_only || *sync_only).then_some(*async_only),
+                decorated: (*decorated || *undecorated).then_some(*decorated),
+                class_key: *class_key,
+                noreturn_only: *noreturn_only,
+                context_managers: *context_managers,
+            };
+            run_classes(&source, patterns, &filters, order, style)
+        }
+        Command::Enum {
+            patterns,
+            public,
+            private,
+            exported_only,
+            annotate_exports,
+        } => {
+            let filters = Filters {
+                visibility: Visibility::from_flags(*public, *private),
+                exported_only: *exported_only,
+                annotate_exports: *annotate_exports,
+                qualnames: cli.qualnames,
+                names_only: cli.names_only,
+                matching: cli.match_mode(),
+                ..Default::default()
+            };
+            run_enums(&source, patterns, &filters, order, style)
+        }
+        Command::Module { patterns, counts, tree } => run_modules(&source, patterns, *counts, *tree, style),
+        Command::Entrypoint { patterns } => run_entrypoints(&source, patterns, style),
+        Command::Deps => run_deps(&source, style),
+        Command::Coverage => run_coverage(&source, style),
+        Command::Loc => run_loc(&source, style),
+        Command::Untyped { patterns, public } => {
+            let filters = Filters {
+                visibility: Visibility::from_flags(*public, false),
+                names_only: cli.names_only,
+                matching: cli.match_mode(),
+                ..Default::default()
+            };
+            run_untyped(&source, patterns, &filters, order, style)
+        }
+        Command::Diff { old, new } => {
+            let old = source.retarget(std::slice::from_ref(old));
+            let new = source.retarget(std::slice::from_ref(new));
+            let result = run_diff(&old, &new, style);
+            source.skipped.absorb(old.skipped);
+            source.skipped.absorb(new.skipped);
+            result
+        }
+        Command::Refs { name } => run_refs(&source, name, style),
+        Command::Effects => run_effects(&source, style),
+        Command::Shadows => run_shadows(&source, style),
+        Command::Dupes { kind } => run_dupes(&source, *kind, style),
+        Command::Decorators { patterns } => run_decorators(&source, patterns, cli.match_mode(), style),
+        Command::Stub { output_dir, .. } => run_stub(&source, output_dir.as_deref()),
+        Command::Dump { patterns, only, .. } => {
+            let filters = Filters {
+                qualnames: cli.qualnames,
+                names_only: cli.names_only,
+                matching: cli.match_mode(),
+                ..Default::default()
+            };
+            run_dump(&source, patterns, only, &filters, order, style)
+        }
+    };
+    result?;
+    source.timings.lap("serialize");
+    for line in source.timings.report(cli.verbose) {
+        eprintln!("{}", line);
+    }
+    report_skipped(source.skipped.into_inner(), cli.verbose, cli.strict)
+}
+
+/// Print the files a run would analyze, one per line (`--list-targets`), or
+/// NUL-terminated with `null` (`-0`)
+fn list_targets(source: &Source, null: bool) -> Result<()> {
+    let terminator = if null { '\0' } else { '\n' };
+    let stdout = std::io::stdout();
+    let mut handle = stdout.lock();
+    for file in source.collect_files()? {
+        write!(handle, "{}{}", file.display(), terminator)?;
+    }
+    Ok(())
+}
+
+/// Read the `--files-from` list from a file, or from stdin for `-`. Entries
+/// that aren't Python files are left out with a warning.
+fn read_file_list(path: &Path, options: &WalkOptions) -> Result<Vec<PathBuf>> {
+    let list = if path == Path::new("-") {
+        std::io::read_to_string(std::io::stdin()).wrap_err("Failed to read the file list from stdin")?
+    } else {
+        std::fs::read_to_string(path).wrap_err_with(|| format!("Failed to read file list: {}", path.display()))?
+    };
+    let (files, others) = walk::parse_file_list(&list, options);
+    for other in others {
+        eprintln!("skipping {}: not a Python file", other.display());
+    }
+    Ok(files)
+}
+
+/// Print how many files were skipped (and, with `--verbose`, which and why).
+/// Skipping is only an error with `--strict`.
+fn report_skipped(skipped: BTreeMap<PathBuf, String>, verbose: u8, strict: bool) -> Result<()> {
+    if skipped.is_empty() {
+        return Ok(());
+    }
+    let summary = format!(
+        "{} {} skipped due to errors",
+        skipped.len(),
+        if skipped.len() == 1 { "file" } else { "files" }
+    );
+    eprintln!("{}", summary);
+    if verbose > 0 {
+        for (path, error) in &skipped {
+            eprintln!("  {}: {}", path.display(), error);
+        }
+    }
+    if strict {
+        return Err(eyre::eyre!("{} (--strict)", summary));
+    }
+    Ok(())
+}
+
+/// What to analyze: the targets plus options for discovering and reading them
+struct Source<'a> {
+    targets: &'a [PathBuf],
+    walk: WalkOptions,
+    extract: ExtractOptions,
+    /// Files listed with `--files-from`, analyzed instead of walking the targets
+    files_from: Option<Vec<PathBuf>>,
+    budget: SymbolBudget,
+    /// Draw a progress bar (`Some(true)`), never (`Some(false)`), or only for large scans in a terminal
+    progress: Option<bool>,
+    /// Files that couldn't be walked, read or parsed
+    skipped: Skipped,
+    /// Files collected for analysis, so `--sqlite` can refresh those left without symbols
+    collected: Mutex<Vec<PathBuf>>,
+    timings: Timings,
+}
+
+/// Files left out of the results because of an error, with the error message.
+/// Filled in from parallel workers, so it is behind a lock.
+#[derive(Debug, Default)]
+struct Skipped(Mutex<BTreeMap<PathBuf, String>>);
+
+impl Skipped {
+    /// Record a file as skipped; the first error for a path is kept
+    fn record(&self, path: &Path, error: String) {
+        self.0.lock().unwrap().entry(path.to_path_buf()).or_insert(error);
+    }
+
+    /// The value of a per-file result, recording the file as skipped on error
+    fn ok<T>(&self, path: &Path, result: Result<T>) -> Option<T> {
+        result.map_err(|err| self.record(path, err.to_string())).ok()
+    }
+
+    fn absorb(&self, other: Skipped) {
+        for (path, error) in other.into_inner() {
+            self.record(&path, error);
+        }
+    }
+
+    fn into_inner(self) -> BTreeMap<PathBuf, String> {
+        self.0.into_inner().unwrap()
+    }
+}
+
+/// Running count of the symbols collected from all files, checked against
+/// `--max-symbols` by the parallel workers
+#[derive(Debug, Default)]
+struct SymbolBudget {
+    limit: Option<usize>,
+    collected: AtomicUsize,
+}
+
+impl SymbolBudget {
+    fn new(limit: Option<usize>) -> Self {
+        SymbolBudget {
+            limit,
+            ..Default::default()
+        }
+    }
+
+    /// Count the symbols of one file; false once the total is over the limit
+    fn take(&self, symbols: usize) -> bool {
+        let total = self.collected.fetch_add(symbols, Ordering::Relaxed) + symbols;
+        self.limit.is_none_or(|limit| total <= limit)
+    }
+
+    fn exhausted(&self) -> bool {
+        self.limit
+            .is_some_and(|limit| self.collected.load(Ordering::Relaxed) > limit)
+    }
+
+    /// Fail the run if more symbols were found than the limit allows
+    fn check(&self) -> Result<()> {
+        match self.limit {
+            Some(limit) if self.exhausted() => Err(eyre::eyre!(
+                "More than {} symbols found (--max-symbols); narrow the targets or raise the limit",
+                limit
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Scans of at least this many files get a progress bar in a terminal
+const PROGRESS_THRESHOLD: usize = 1000;
+
+/// Parsing a single file for longer than this is logged with `-vv`
+const SLOW_FILE: Duration = Duration::from_millis(50);
+
+/// Where a run's time goes, reported with `--verbose`. Phases are timed as
+/// laps, each running from the end of the previous one; a phase lapped more
+/// than once (e.g. both trees of a `diff`) adds up. Clones share the same state.
+#[derive(Debug, Clone)]
+struct Timings(Arc<Mutex<TimingState>>);
+
+#[derive(Debug)]
+struct TimingState {
+    started: Instant,
+    last_lap: Instant,
+    files: usize,
+    phases: IndexMap<&'static str, Duration>,
+    slow_files: Vec<(PathBuf, Duration)>,
+}
+
+impl Default for Timings {
+    fn default() -> Self {
+        let now = Instant::now();
+        Timings(Arc::new(Mutex::new(TimingState {
+            started: now,
+            last_lap: now,
+            files: 0,
+            phases: IndexMap::new(),
+            slow_files: Vec::new(),
+        })))
+    }
+}
+
+impl Timings {
+    /// Charge the time since the previous lap to `phase`
+    fn lap(&self, phase: &'static str) {
+        let mut state = self.0.lock().unwrap();
+        let now = Instant::now();
+        let elapsed = now - state.last_lap;
+        *state.phases.entry(phase).or_default() += elapsed;
+        state.last_lap = now;
+    }
+
+    fn discovered(&self, files: usize) {
+        self.0.lock().unwrap().files += files;
+    }
+
+    /// Run the processing of one file, noting it if it is slow
+    fn file<T>(&self, path: &Path, process: impl FnOnce() -> T) -> T {
+        let start = Instant::now();
+        let result = process();
+        let elapsed = start.elapsed();
+        if elapsed > SLOW_FILE {
+            self.0.lock().unwrap().slow_files.push((path.to_path_buf(), elapsed));
+        }
+        result
+    }
+
+    /// Diagnostic lines for the given `--verbose` level: file count and phase
+    /// timings from `-v`, slow files (slowest first) from `-vv`
+    fn report(&self, verbose: u8) -> Vec<String> {
+        if verbose == 0 {
+            return Vec::new();
+        }
+        let state = self.0.lock().unwrap();
+        let phases: Vec<String> = state
+            .phases
+            .iter()
+            .map(|(phase, elapsed)| format!("{} {:.1?}", phase, elapsed))
+            .collect();
+        let mut lines = vec![
+            format!(
+                "{} {} discovered",
+                state.files,
+                if state.files == 1 { "file" } else { "files" }
+            ),
+            format!("{} (total {:.1?})", phases.join(", "), state.started.elapsed()),
+        ];
+        if verbose > 1 {
+            let mut slow = state.slow_files.clone();
+            slow.sort_by_key(|(_, elapsed)| std::cmp::Reverse(*elapsed));
+            for (path, elapsed) in slow {
+                lines.push(format!("slow file: {} ({:.1?})", path.display(), elapsed));
+            }
+        }
+        lines
+    }
+}
+
+impl<'a> Source<'a> {
+    /// Source with default walk and extraction options
+    #[cfg(test)]
+    fn new(targets: &'a [PathBuf]) -> Self {
+        Source {
+            targets,
+            walk: WalkOptions::default(),
+            extract: ExtractOptions::default(),
+            files_from: None,
+            budget: SymbolBudget::default(),
+            progress: Some(false),
+            skipped: Skipped::default(),
+            collected: Mutex::default(),
+            timings: Timings::default(),
+        }
+    }
+
+    /// The same walk and extraction options applied to other targets
+    fn retarget<'b>(&self, targets: &'b [PathBuf]) -> Source<'b> {
+        Source {
+            targets,
+            walk: self.walk.clone(),
+            extract: self.extract,
+            files_from: None,
+            budget: SymbolBudget::new(self.budget.limit),
+            progress: self.progress,
+            skipped: Skipped::default(),
+            collected: Mutex::default(),
+            timings: self.timings.clone(),
+        }
+    }
+
+    /// Collect the Python files to analyze: those listed with `--files-from`,
+    /// or a walk of the targets. Entries that can't be read are recorded as
+    /// skipped rather than failing the walk.
+    fn collect_files(&self) -> Result<Vec<PathBuf>> {
+        let files = match (&self.files_from, git::revision()) {
+            (Some(files), _) => files.clone(),
+            (None, Some(revision)) => walk::collect_revision_files(revision, self.targets, &self.walk)?,
+            (None, None) => walk::collect_python_files_skipping(self.targets, &self.walk, &mut |path, error| {
+                self.skipped.record(path, error)
+            })?,
+        };
+        self.timings.lap("walk");
+        self.timings.discovered(files.len());
+        self.collected.lock().unwrap().extend(files.iter().cloned());
+        Ok(files)
+    }
+
+    /// Paths of the collected files that were analyzed without error, keyed
+    /// as in the results
+    fn scanned_paths(&self, absolute: bool) -> Vec<String> {
+        let skipped = self.skipped.0.lock().unwrap();
+        self.collected
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|path| !skipped.contains_key(*path))
+            .map(|path| path.to_string_lossy().to_string())
+            .map(|path| if absolute { absolute_path(&path) } else { path })
+            .collect()
+    }
+
+    /// A progress bar on stderr over `len` files, hidden unless `--progress`
+    /// was given or the scan is large and runs in a terminal. Never drawn when
+    /// stderr isn't a terminal, so piped and redirected runs stay clean.
+    fn progress_bar(&self, len: usize) -> ProgressBar {
+        let show = match self.progress {
+            Some(progress) => progress,
+            None => len >= PROGRESS_THRESHOLD && std::io::stdout().is_terminal(),
+        };
+        if show && std::io::stderr().is_terminal() {
+            ProgressBar::new(len as u64)
+        } else {
+            ProgressBar::hidden()
+        }
+    }
+
+    /// Parse and extract files in parallel, timing each file
+    fn process_files<F>(&self, files: &[PathBuf], processor: F) -> Result<BTreeMap<String, EntryMap>>
+    where
+        F: Fn(&Path) -> Option<EntryMap> + Sync,
+    {
+        let bar = self.progress_bar(files.len());
+        let collected = process_files_parallel(files, &self.budget, |path| {
+            let result = self.timings.file(path, || processor(path));
+            bar.inc(1);
+            result
+        });
+        bar.finish_and_clear();
+        self.timings.lap("parse");
+        self.budget.check()?;
+        Ok(collected)
+    }
+
+    /// Like `process_files`, for classes
+    fn process_classes<F>(&self, files: &[PathBuf], processor: F) -> Result<BTreeMap<String, ClassMap>>
+    where
+        F: Fn(&Path) -> Option<ClassMap> + Sync,
+    {
+        let bar = self.progress_bar(files.len());
+        let collected = process_classes_parallel(files, &self.budget, |path| {
+            let result = self.timings.file(path, || processor(path));
+            bar.inc(1);
+            result
+        });
+        bar.finish_and_clear();
+        self.timings.lap("parse");
+        self.budget.check()?;
+        Ok(collected)
+    }
+
+    /// Analyze every file in parallel, timing each one and recording the files
+    /// that fail as skipped; results are keyed by file path
+    fn collect_per_file<T, F>(&self, analyze: F) -> Result<BTreeMap<String, T>>
+    where
+        T: Send,
+        F: Fn(&Path) -> Result<T> + Sync,
+    {
+        let files = self.collect_files()?;
+        let collected: Mutex<BTreeMap<String, T>> = Mutex::new(BTreeMap::new());
+
+        files.par_iter().for_each(|path| {
+            let result = self.timings.file(path, || analyze(path));
+            if let Some(value) = self.skipped.ok(path, result) {
+                let key = path.to_string_lossy().to_string();
+                collected.lock().unwrap().insert(key, value);
+            }
+        });
+        self.timings.lap("parse");
+
+        Ok(collected.into_inner().unwrap())
+    }
+
+    /// Directory paths are reported relative to: the first target, or its
+    /// parent directory when it is a file (current dir without targets)
+    fn base_path(&self) -> PathBuf {
+        self.targets
+            .first()
+            .map(|p| {
+                if git::is_dir(p) {
+                    p.clone()
+                } else {
+                    p.parent().map(|p| p.to_path_buf()).unwrap_or_default()
+                }
+            })
+            .unwrap_or_else(|| PathBuf::from("."))
+    }
+
+    /// Split per-file results by the target each file was found under, keyed
+    /// by the target as given; file paths become relative to their target.
+    /// `wrap` builds each target's output from its files.
+    fn label_roots<V, T>(
+        &self,
+        files: BTreeMap<String, V>,
+        wrap: impl Fn(BTreeMap<String, V>) -> T,
+    ) -> LabeledOutput<T> {
+        let mut roots: BTreeMap<String, BTreeMap<String, V>> = BTreeMap::new();
+        for (path, value) in files {
+            let file = Path::new(&path);
+            let root = self.targets.iter().find(|target| file.starts_with(target));
+            let (label, relative) = match root {
+                Some(root) => {
+                    let relative = file.strip_prefix(root).unwrap_or(file);
+                    // A file target is keyed by its own name
+                    let relative = match relative.as_os_str().is_empty() {
+                        true => file.file_name().map(Path::new).unwrap_or(file),
+                        false => relative,
+                    };
+                    (
+                        root.to_string_lossy().to_string(),
+                        relative.to_string_lossy().to_string(),
+                    )
+                }
+                None => (".".to_string(), path.clone()),
+            };
+            roots.entry(label).or_default().insert(relative, value);
+        }
+        LabeledOutput {
+            roots: roots.into_iter().map(|(label, files)| (label, wrap(files))).collect(),
         }
-        Command::Enum { patterns } => run_enums(targets, patterns, cli.alphabetical, use_json),
-        Command::Module { patterns } => run_modules(targets, patterns, use_json),
-        Command::Dump { patterns } => run_dump(targets, patterns, cli.alphabetical, use_json),
+    }
+
+    /// Dotted module path of a file, relative to the target it was found under
+    /// (a file target is relative to its own directory)
+    fn module_path(&self, file: &Path) -> String {
+        let root = self
+            .targets
+            .iter()
+            .find(|target| git::is_dir(target) && file.starts_with(target))
+            .cloned()
+            .or_else(|| file.parent().map(Path::to_path_buf))
+            .unwrap_or_default();
+        analysis::dotted_module_path(file, &root)
     }
 }
 
 /// Compute functions output (testable without I/O)
-fn compute_functions(targets: &[PathBuf], patterns: &[String], visibility: Visibility) -> Result<FilesOutput> {
-    let files = walk::collect_python_files(targets)?;
-    let collected = process_files_parallel(&files, |path| {
-        let functions = analysis::extract_functions(path).ok()?;
+fn compute_functions(source: &Source, patterns: &[String], filters: &Filters) -> Result<FilesOutput> {
+    let files = source.collect_files()?;
+    let collected = source.process_files(&files, |path| {
+        let mut functions = source
+            .skipped
+            .ok(path, analysis::extract_functions(path, &source.extract))?;
+        apply_exports(&mut functions, path, filters, extract_function_name, |e| &mut e.meta);
+        if filters.qualnames {
+            set_qualnames(&mut functions, &source.module_path(path), extract_function_name);
+        }
         if functions.is_empty() {
             None
         } else {
             Some(functions)
         }
-    });
-    let filtered = filter_files_output(collected, patterns, extract_function_name);
-    let filtered = filter_by_visibility(filtered, visibility);
+    })?;
+    let filtered = filter_files_output(collected, patterns, extract_function_name, filters.matching);
+    let filtered = filter_by_visibility(filtered, filters.visibility, extract_function_name);
+    let filtered = if filters.tests_only { filter_test_functions(filtered) } else { filtered };
+    let filtered = if filters.limits_args() {
+        filter_functions_by_args(filtered, filters)
+    } else {
+        filtered
+    };
+    let filtered = if filters.limits_lines() {
+        filter_functions_by_lines(filtered, filters)
+    } else {
+        filtered
+    };
+    let filtered = match filters.generators {
+        Some(generators) => filter_generators(filtered, generators),
+        None => filtered,
+    };
+    let filtered = match filters.asynchronous {
+        Some(asynchronous) => filter_async_functions(filtered, asynchronous),
+        None => filtered,
+    };
+    let filtered = match filters.decorated {
+        Some(decorated) => filter_decorated_functions(filtered, decorated),
+        None => filtered,
+    };
+    let filtered = if filters.noreturn_only {
+        filter_noreturn_functions(filtered)
+    } else {
+        filtered
+    };
+    let filtered = if filters.context_managers {
+        filter_context_manager_functions(filtered)
+    } else {
+        filtered
+    };
+    let filtered = if filters.names_only {
+        files_to_names(filtered, extract_function_name)
+    } else {
+        filtered
+    };
     Ok(FilesOutput { files: filtered })
 }
 
 fn run_functions(
-    targets: &[PathBuf],
+    source: &Source,
     patterns: &[String],
-    visibility: Visibility,
-    _alphabetical: bool,
-    use_json: bool,
+    filters: &Filters,
+    order: SortOrder,
+    style: OutputStyle,
 ) -> Result<()> {
-    let result = compute_functions(targets, patterns, visibility)?;
-    output(&result, use_json)
+    let mut result = compute_functions(source, patterns, filters)?;
+    if style.absolute {
+        result.files = absolute_paths(result.files);
+    }
+    if style.group_by == GroupBy::Dir {
+        result.files = group_by_dir(result.files);
+    }
+    sort::sort_files(&mut result.files, order);
+    let mut notes = limit_per_file(&mut result.files, style.per_file_limit);
+    notes.extend(style.summary.then(|| summary_footer(&result.files, style.group_by)));
+    source.timings.lap("filter");
+    let written = if let Some(db) = style.sqlite {
+        output::write_sqlite(
+            db,
+            &source.scanned_paths(style.absolute),
+            &file_tags(&result.files, classify_function_signature),
+        )
+    } else {
+        match style.format {
+            OutputFormat::Ctags => output::output_ctags(&file_tags(&result.files, classify_function_signature)),
+            OutputFormat::JsonlBySymbol => output::output_jsonl(&file_tags(&result.files, classify_function_signature)),
+            OutputFormat::Lsp => {
+                let symbols = file_symbols(&result.files, SymbolKind::Function, extract_function_name);
+                output::output_lsp(&symbols, style.compact)
+            }
+            OutputFormat::Xml => output::output_xml(&result.files),
+            _ if style.signature_only => output_files(
+                source,
+                files_to_signatures(result.files),
+                |files| SignaturesOutput { files },
+                style,
+                patterns,
+            ),
+            _ if style.labeled => output(
+                &source.label_roots(result.files, |files| FilesOutput { files }),
+                style,
+                patterns,
+            ),
+            _ => output(&result, style, patterns),
+        }
+    };
+    print_notes(&notes);
+    written
 }
 
 /// Compute classes output (testable without I/O)
-fn compute_classes(targets: &[PathBuf], patterns: &[String], visibility: Visibility) -> Result<ClassesOutput> {
-    let files = walk::collect_python_files(targets)?;
-    let collected = process_classes_parallel(&files, |path| {
-        let classes = analysis::extract_classes(path).ok()?;
+fn compute_classes(source: &Source, patterns: &[String], filters: &Filters) -> Result<ClassesOutput> {
+    let files = source.collect_files()?;
+    let collected = source.process_classes(&files, |path| {
+        let mut classes = source
+            .skipped
+            .ok(path, analysis::extract_classes(path, &source.extract))?;
+        if filters.overrides {
+            // Before pattern filtering, so bases that don't match still resolve
+            analysis::mark_overrides(&mut classes);
+        }
+        apply_exports(&mut classes, path, filters, extract_class_name, |c| &mut c.meta);
+        if filters.qualnames {
+            set_class_qualnames(&mut classes, &source.module_path(path));
+        }
         if classes.is_empty() {
             None
         } else {
             Some(classes)
         }
-    });
-    let filtered = filter_classes_output(collected, patterns);
-    let filtered = filter_classes_by_visibility(filtered, visibility);
+    })?;
+    let filtered = filter_classes_output(collected, patterns, filters.matching);
+    let filtered = if filters.roots || filters.derived {
+        filter_classes_by_bases(filtered, filters.derived)
+    } else {
+        filtered
+    };
+    // Classify before visibility filtering so private abstract methods still count
+    let filtered = if filters.abstract_only { filter_abstract_classes(filtered) } else { filtered };
+    let filtered = filter_classes_by_visibility(filtered, filters.visibility, filters.keep_empty);
+    let filtered = if filters.limits_methods() {
+        filter_classes_by_method_count(filtered, filters)
+    } else {
+        filtered
+    };
+    let filtered = if filters.tests_only { filter_test_classes(filtered) } else { filtered };
+    let filtered = if filters.model_only { filter_model_classes(filtered) } else { filtered };
+    let filtered = match filters.kind {
+        Some(kind) => filter_classes_by_kind(filtered, kind),
+        None => filtered,
+    };
+    let filtered = if filters.limits_args() {
+        filter_methods_by_args(filtered, filters)
+    } else {
+        filtered
+    };
+    let filtered = if filters.limits_lines() {
+        filter_methods_by_lines(filtered, filters)
+    } else {
+        filtered
+    };
+    let filtered = match filters.asynchronous {
+        Some(asynchronous) => filter_async_methods(filtered, asynchronous),
+        None => filtered,
+    };
+    let filtered = match filters.decorated {
+        Some(decorated) => filter_decorated_methods(filtered, decorated),
+        None => filtered,
+    };
+    let filtered = if filters.noreturn_only { filter_noreturn_methods(filtered) } else { filtered };
+    let filtered = if filters.context_managers {
+        filter_context_manager_classes(filtered)
+    } else {
+        filtered
+    };
+    let filtered = match filters.class_key {
+        ClassKey::Name => files_keyed_by_class_name(filtered),
+        ClassKey::Signature => filtered,
+    };
+    let filtered = if filters.names_only { classes_to_names(filtered) } else { filtered };
     Ok(ClassesOutput { files: filtered })
 }
 
 fn run_classes(
-    targets: &[PathBuf],
+    source: &Source,
     patterns: &[String],
-    visibility: Visibility,
-    _alphabetical: bool,
-    use_json: bool,
+    filters: &Filters,
+    order: SortOrder,
+    style: OutputStyle,
 ) -> Result<()> {
-    let result = compute_classes(targets, patterns, visibility)?;
-    output(&result, use_json)
+    let mut result = compute_classes(source, patterns, filters)?;
+    if style.absolute {
+        result.files = absolute_paths(result.files);
+    }
+    if style.group_by == GroupBy::Dir {
+        result.files = group_by_dir(result.files);
+    }
+    sort::sort_class_files(&mut result.files, order);
+    let mut notes = limit_per_file(&mut result.files, style.per_file_limit);
+    notes.extend(style.summary.then(|| summary_footer(&result.files, style.group_by)));
+    source.timings.lap("filter");
+    let written = if let Some(db) = style.sqlite {
+        output::write_sqlite(db, &source.scanned_paths(style.absolute), &class_tags(&result.files))
+    } else {
+        match style.format {
+            OutputFormat::Ctags => output::output_ctags(&class_tags(&result.files)),
+            OutputFormat::JsonlBySymbol => output::output_jsonl(&class_tags(&result.files)),
+            OutputFormat::Lsp => output::output_lsp(&class_symbols(&result.files), style.compact),
+            OutputFormat::Xml => output::output_classes_xml(&result.files),
+            _ if style.signature_only => output_files(
+                source,
+                result
+                    .files
+                    .into_iter()
+                    .map(|(path, classes)| (path, classes_to_signatures(classes)))
+                    .collect(),
+                |files| ClassSignaturesOutput { files },
+                style,
+                patterns,
+            ),
+            _ if style.labeled => output(
+                &source.label_roots(result.files, |files| ClassesOutput { files }),
+                style,
+                patterns,
+            ),
+            _ => output(&result, style, patterns),
+        }
+    };
+    print_notes(&notes);
+    written
 }
 
 /// Compute enums output (testable without I/O)
-fn compute_enums(targets: &[PathBuf], patterns: &[String]) -> Result<FilesOutput> {
-    let files = walk::collect_python_files(targets)?;
-    let collected = process_files_parallel(&files, |path| {
-        let enums = analysis::extract_enums(path).ok()?;
+fn compute_enums(source: &Source, patterns: &[String], filters: &Filters) -> Result<FilesOutput> {
+    let files = source.collect_files()?;
+    let collected = source.process_files(&files, |path| {
+        let mut enums = source
+            .skipped
+            .ok(path, analysis::extract_enums(path, &source.extract))?;
+        apply_exports(&mut enums, path, filters, extract_class_name, |e| &mut e.meta);
+        if filters.qualnames {
+            set_qualnames(&mut enums, &source.module_path(path), extract_class_name);
+        }
         if enums.is_empty() {
             None
         } else {
             Some(enums)
         }
-    });
-    let filtered = filter_files_output(collected, patterns, extract_class_name);
+    })?;
+    let filtered = filter_files_output(collected, patterns, extract_class_name, filters.matching);
+    let filtered = filter_by_visibility(filtered, filters.visibility, extract_class_name);
+    let filtered = if filters.names_only {
+        files_to_names(filtered, extract_class_name)
+    } else {
+        filtered
+    };
     Ok(FilesOutput { files: filtered })
 }
 
-fn run_enums(targets: &[PathBuf], patterns: &[String], _alphabetical: bool, use_json: bool) -> Result<()> {
-    let result = compute_enums(targets, patterns)?;
-    output(&result, use_json)
+fn run_enums(
+    source: &Source,
+    patterns: &[String],
+    filters: &Filters,
+    order: SortOrder,
+    style: OutputStyle,
+) -> Result<()> {
+    let mut result = compute_enums(source, patterns, filters)?;
+    if style.absolute {
+        result.files = absolute_paths(result.files);
+    }
+    if style.group_by == GroupBy::Dir {
+        result.files = group_by_dir(result.files);
+    }
+    sort::sort_files(&mut result.files, order);
+    let mut notes = limit_per_file(&mut result.files, style.per_file_limit);
+    notes.extend(style.summary.then(|| summary_footer(&result.files, style.group_by)));
+    source.timings.lap("filter");
+    let written = if let Some(db) = style.sqlite {
+        output::write_sqlite(
+            db,
+            &source.scanned_paths(style.absolute),
+            &file_tags(&result.files, classify_enum_signature),
+        )
+    } else {
+        match style.format {
+            OutputFormat::Ctags => output::output_ctags(&file_tags(&result.files, classify_enum_signature)),
+            OutputFormat::JsonlBySymbol => output::output_jsonl(&file_tags(&result.files, classify_enum_signature)),
+            OutputFormat::Lsp => {
+                let symbols = file_symbols(&result.files, SymbolKind::Enum, extract_class_name);
+                output::output_lsp(&symbols, style.compact)
+            }
+            OutputFormat::Xml => output::output_xml(&result.files),
+            _ if style.signature_only => output_files(
+                source,
+                files_to_signatures(result.files),
+                |files| SignaturesOutput { files },
+                style,
+                patterns,
+            ),
+            _ if style.labeled => output(
+                &source.label_roots(result.files, |files| FilesOutput { files }),
+                style,
+                patterns,
+            ),
+            _ => output(&result, style, patterns),
+        }
+    };
+    print_notes(&notes);
+    written
 }
 
 /// Compute modules output (testable without I/O)
-fn compute_modules(targets: &[PathBuf], patterns: &[String]) -> Result<output::ModulesOutput> {
-    let files = walk::collect_python_files(targets)?;
-
-    // Use the first target as base path, or current dir
-    let base_path = targets
-        .first()
-        .map(|p| {
-            if p.is_dir() {
-                p.clone()
-            } else {
-                p.parent().map(|p| p.to_path_buf()).unwrap_or_default()
-            }
-        })
-        .unwrap_or_else(|| PathBuf::from("."));
-
-    let result = analysis::build_module_tree(&files, &base_path);
+fn compute_modules(source: &Source, patterns: &[String], counts: bool) -> Result<output::ModulesOutput> {
+    let files = source.collect_files()?;
+    let counts = counts.then_some(&source.extract);
+    let result = analysis::build_module_tree(&files, &source.base_path(), counts);
+    source.timings.lap("parse");
     Ok(pattern::filter_modules_output(result, patterns))
 }
 
-fn run_modules(targets: &[PathBuf], patterns: &[String], use_json: bool) -> Result<()> {
-    let result = compute_modules(targets, patterns)?;
-    output(&result, use_json)
+fn run_modules(source: &Source, patterns: &[String], counts: bool, tree: bool, style: OutputStyle) -> Result<()> {
+    let result = compute_modules(source, patterns, counts)?;
+    source.timings.lap("filter");
+    if tree {
+        output::output_tree(&result)
+    } else {
+        output(&result, style, &[])
+    }
 }
 
-/// Compute dump output (testable without I/O)
-fn compute_dump(targets: &[PathBuf], patterns: &[String]) -> Result<FilesOutput> {
-    let files = walk::collect_python_files(targets)?;
-    let collected = process_files_parallel(&files, |path| {
-        let mut all_entries = BTreeMap::new();
-
-        if let Ok(functions) = analysis::extract_functions(path) {
-            all_entries.extend(functions);
-        }
-        // Flatten classes: prefix method signatures with class name
-        if let Ok(classes) = analysis::extract_classes(path) {
-            for (class_sig, class_info) in classes {
-                // Extract class name from signature (e.g., "class Foo" -> "Foo")
-                let class_name = class_sig
-                    .strip_prefix("class ")
-                    .and_then(|s| s.split('(').next())
-                    .unwrap_or(&class_sig);
-                for (method_sig, line) in class_info.methods {
-                    let full_sig = format!("{}.{}", class_name, method_sig);
-                    all_entries.insert(full_sig, line);
-                }
-            }
-        }
-        if let Ok(enums) = analysis::extract_enums(path) {
-            all_entries.extend(enums);
-        }
-
-        if all_entries.is_empty() {
+/// Compute entrypoints output (testable without I/O)
+fn compute_entrypoints(source: &Source, patterns: &[String]) -> Result<FilesOutput> {
+    let files = source.collect_files()?;
+    let collected = source.process_files(&files, |path| {
+        let entrypoints = source.skipped.ok(path, analysis::extract_entrypoints(path))?;
+        if entrypoints.is_empty() {
             None
         } else {
-            Some(all_entries)
+            Some(entrypoints)
         }
-    });
-    let filtered = filter_files_output(collected, patterns, pattern::extract_dump_name);
+    })?;
+    let filtered = pattern::filter_files_by_module(collected, patterns);
     Ok(FilesOutput { files: filtered })
 }
 
-fn run_dump(targets: &[PathBuf], patterns: &[String], _alphabetical: bool, use_json: bool) -> Result<()> {
-    let result = compute_dump(targets, patterns)?;
-    output(&result, use_json)
+/// Compute deps output (testable without I/O)
+fn compute_deps(source: &Source) -> Result<output::DepsOutput> {
+    let files = source.collect_files()?;
+    let graph = analysis::build_dependency_graph(&files, &source.base_path(), &|path, err| {
+        source.skipped.record(path, err.to_string())
+    });
+    source.timings.lap("parse");
+    Ok(graph)
 }
 
-/// Process files in parallel and collect results (flat structure)
-fn process_files_parallel<F>(files: &[PathBuf], processor: F) -> BTreeMap<String, BTreeMap<String, usize>>
-where
-    F: Fn(&std::path::Path) -> Option<BTreeMap<String, usize>> + Sync,
-{
-    let results: Mutex<BTreeMap<String, BTreeMap<String, usize>>> = Mutex::new(BTreeMap::new());
+fn run_deps(source: &Source, style: OutputStyle) -> Result<()> {
+    let result = compute_deps(source)?;
+    source.timings.lap("filter");
+    if style.format == OutputFormat::Dot {
+        output::output_dot(&result)
+    } else {
+        output(&result, style, &[])
+    }
+}
 
-    files.par_iter().for_each(|path| {
-        if let Some(content) = processor(path) {
-            let key = path.to_string_lossy().to_string();
-            results.lock().unwrap().insert(key, content);
-        }
-    });
+/// Compute coverage output (testable without I/O)
+fn compute_coverage(source: &Source) -> Result<output::CoverageOutput> {
+    let mut files = source.collect_per_file(|path| analysis::annotation_coverage(path, &source.extract))?;
+    // Files without any functions have nothing to report
+    files.retain(|_, coverage| coverage.returns.total > 0);
 
-    results.into_inner().unwrap()
+    let mut total = AnnotationCoverage::default();
+    for coverage in files.values() {
+        total += *coverage;
+    }
+    Ok(output::CoverageOutput { files, total })
 }
 
-/// Process files in parallel and collect results (nested structure for classes)
-fn process_classes_parallel<F>(files: &[PathBuf], processor: F) -> BTreeMap<String, ClassMap>
-where
-    F: Fn(&std::path::Path) -> Option<ClassMap> + Sync,
-{
-    let results: Mutex<BTreeMap<String, ClassMap>> = Mutex::new(BTreeMap::new());
+fn run_coverage(source: &Source, style: OutputStyle) -> Result<()> {
+    let mut result = compute_coverage(source)?;
+    if style.absolute {
+        result.files = absolute_paths(result.files);
+    }
+    source.timings.lap("filter");
+    output(&result, style, &[])
+}
 
-    files.par_iter().for_each(|path| {
-        if let Some(content) = processor(path) {
-            let key = path.to_string_lossy().to_string();
-            results.lock().unwrap().insert(key, content);
-        }
-    });
+/// Compute loc output (testable without I/O)
+fn compute_loc(source: &Source) -> Result<output::LocOutput> {
+    let files = source.collect_per_file(analysis::count_lines)?;
 
-    results.into_inner().unwrap()
+    let mut total = output::LineCounts::default();
+    for counts in files.values() {
+        total += *counts;
+    }
+    Ok(output::LocOutput { files, total })
 }
 
-/// Check if a name matches the visibility filter
-fn matches_visibility(name: &str, visibility: Visibility) -> bool {
-    match visibility {
-        Visibility::All => true,
-        Visibility::Public => !name.starts_with('_'),
-        Visibility::Private => name.starts_with('_'),
+fn run_loc(source: &Source, style: OutputStyle) -> Result<()> {
+    let mut result = compute_loc(source)?;
+    if style.absolute {
+        result.files = absolute_paths(result.files);
     }
+    source.timings.lap("filter");
+    output(&result, style, &[])
 }
 
-/// Filter files output by visibility (for functions)
-fn filter_by_visibility(
-    files: BTreeMap<String, BTreeMap<String, usize>>,
-    visibility: Visibility,
-) -> BTreeMap<String, BTreeMap<String, usize>> {
-    if visibility == Visibility::All {
-        return files;
-    }
+/// Compute diff output (testable without I/O): functions, classes and methods
+/// are matched by file and qualified name, then their signatures compared
+fn compute_diff(old: &Source, new: &Source) -> Result<output::DiffOutput> {
+    let before = signature_index(old)?;
+    // With `--rev`, only OLD is read at the revision
+    let after = git::in_working_tree(|| signature_index(new))?;
+    let paths: BTreeSet<&String> = before.keys().chain(after.keys()).collect();
 
-    files
-        .into_iter()
-        .filter_map(|(file_path, entries)| {
-            let filtered: BTreeMap<String, usize> = entries
-                .into_iter()
-                .filter(|(sig, _)| {
-                    let name = extract_function_name(sig);
-                    matches_visibility(name, visibility)
-                })
-                .collect();
+    let empty = IndexMap::new();
+    let mut files = BTreeMap::new();
+    for path in paths {
+        let old_sigs = before.get(path).unwrap_or(&empty);
+        let new_sigs = after.get(path).unwrap_or(&empty);
+        let mut diff = SignatureDiff::default();
 
-            if filtered.is_empty() {
-                None
-            } else {
-                Some((file_path, filtered))
+        for (name, new_sig) in new_sigs {
+            match old_sigs.get(name) {
+                None => diff.added.push(new_sig.clone()),
+                Some(old_sig) if old_sig != new_sig => {
+                    diff.changed.insert(
+                        name.clone(),
+                        SignatureChange {
+                            old: old_sig.clone(),
+                            new: new_sig.clone(),
+                        },
+                    );
+                }
+                Some(_) => {}
             }
-        })
-        .collect()
-}
+        }
+        for (name, old_sig) in old_sigs {
+            if !new_sigs.contains_key(name) {
+                diff.removed.push(old_sig.clone());
+            }
+        }
 
-/// Filter classes output by visibility (filters fields and methods within each class)
-fn filter_classes_by_visibility(
-    files: BTreeMap<String, ClassMap>,
-    visibility: Visibility,
-) -> BTreeMap<String, ClassMap> {
-    if visibility == Visibility::All {
-        return files;
+        if !diff.is_empty() {
+            files.insert(path.clone(), diff);
+        }
     }
+    Ok(output::DiffOutput { files })
+}
 
-    files
-        .into_iter()
-        .filter_map(|(file_path, classes)| {
-            let filtered_classes: ClassMap = classes
-                .into_iter()
-                .map(|(class_sig, class_info)| {
-                    let filtered_fields: BTreeMap<String, usize> = class_info
-                        .fields
-                        .into_iter()
-                        .filter(|(field_sig, _)| {
-                            // Extract field name from signature (e.g., "field_name: Type" -> "field_name")
-                            let name = field_sig.split(':').next().unwrap_or(field_sig).trim();
-                            matches_visibility(name, visibility)
-                        })
-                        .collect();
-
-                    let filtered_methods: BTreeMap<String, usize> = class_info
-                        .methods
-                        .into_iter()
-                        .filter(|(method_sig, _)| {
-                            let name = extract_function_name(method_sig);
-                            matches_visibility(name, visibility)
-                        })
-                        .collect();
-
-                    (
-                        class_sig,
-                        ClassInfo {
-                            fields: filtered_fields,
-                            methods: filtered_methods,
-                        },
-                    )
-                })
-                .filter(|(_, class_info)| {
-                    // Keep class if it has any fields or methods after filtering
-                    !class_info.fields.is_empty() || !class_info.methods.is_empty()
-                })
-                .collect();
+/// Signatures of a tree by file (relative to the tree) and qualified name
+/// within the file (`func`, `Class`, `Class.method`). Methods are rendered
+/// like `dump` (`Class.def method(...)`); a redefined name keeps its first signature.
+fn signature_index(source: &Source) -> Result<BTreeMap<String, IndexMap<String, String>>> {
+    let base = source.base_path();
+    let relative = |file: &str| {
+        Path::new(file)
+            .strip_prefix(&base)
+            .map(|p| p.to_string_lossy().to_string())
+            .unwrap_or_else(|_| file.to_string())
+    };
+    let mut index: BTreeMap<String, IndexMap<String, String>> = BTreeMap::new();
 
-            if filtered_classes.is_empty() {
-                None
-            } else {
-                Some((file_path, filtered_classes))
+    for (file, functions) in compute_functions(source, &[], &Filters::default())?.files {
+        let signatures = index.entry(relative(&file)).or_default();
+        for sig in functions.into_keys() {
+            signatures.entry(extract_function_name(&sig).to_string()).or_insert(sig);
+        }
+    }
+    for (file, classes) in compute_classes(source, &[], &Filters::default())?.files {
+        let signatures = index.entry(relative(&file)).or_default();
+        for (class_sig, class_info) in classes {
+            let class_name = extract_class_name(&class_sig).to_string();
+            signatures.entry(class_name.clone()).or_insert(class_sig);
+            for method_sig in class_info.methods.into_keys() {
+                let name = format!("{}.{}", class_name, extract_function_name(&method_sig));
+                signatures
+                    .entry(name)
+                    .or_insert_with(|| format!("{}.{}", class_name, method_sig));
             }
-        })
-        .collect()
+        }
+    }
+
+    Ok(index)
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+fn run_diff(old: &Source, new: &Source, style: OutputStyle) -> Result<()> {
+    let result = compute_diff(old, new)?;
+    new.timings.lap("filter");
+    output(&result, style, &[])
+}
 
-    fn fixtures_dir() -> PathBuf {
-        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures")
-    }
+/// Compute refs output (testable without I/O)
+fn compute_refs(source: &Source, name: &str) -> Result<output::RefsOutput> {
+    let mut files = source.collect_per_file(|path| analysis::find_references(path, name))?;
+    files.retain(|_, sites| !sites.is_empty());
+    Ok(output::RefsOutput { files })
+}
 
-    #[test]
-    fn test_matches_visibility_all() {
-        assert!(matches_visibility("public_func", Visibility::All));
-        assert!(matches_visibility("_private_func", Visibility::All));
-        assert!(matches_visibility("__dunder__", Visibility::All));
+fn run_refs(source: &Source, name: &str, style: OutputStyle) -> Result<()> {
+    let mut result = compute_refs(source, name)?;
+    if style.absolute {
+        result.files = absolute_paths(result.files);
     }
+    source.timings.lap("filter");
+    output(&result, style, &[])
+}
 
-    #[test]
+/// Compute effects output (testable without I/O)
+fn compute_effects(source: &Source) -> Result<output::EffectsOutput> {
+    let mut files = source.collect_per_file(analysis::find_effects)?;
+    files.retain(|_, effects| !effects.is_empty());
+    Ok(output::EffectsOutput { files })
+}
+
+fn run_effects(source: &Source, style: OutputStyle) -> Result<()> {
+    let mut result = compute_effects(source)?;
+    if style.absolute {
+        result.files = absolute_paths(result.files);
+    }
+    source.timings.lap("filter");
+    output(&result, style, &[])
+}
+
+/// Compute shadows output (testable without I/O)
+fn compute_shadows(source: &Source) -> Result<output::ShadowsOutput> {
+    let mut files = source.collect_per_file(analysis::find_import_conflicts)?;
+    files.retain(|_, conflicts| !conflicts.is_empty());
+    Ok(output::ShadowsOutput { files })
+}
+
+fn run_shadows(source: &Source, style: OutputStyle) -> Result<()> {
+    let mut result = compute_shadows(source)?;
+    if style.absolute {
+        result.files = absolute_paths(result.files);
+    }
+    source.timings.lap("filter");
+    output(&result, style, &[])
+}
+
+/// Compute dupes output (testable without I/O): top-level functions and
+/// classes grouped by name, keeping names defined more than once. `@overload`
+/// declarations are left out since they are meant to be redefined.
+fn compute_dupes(source: &Source, kind: Option<DupeKind>) -> Result<output::DupesOutput> {
+    let files = source.collect_files()?;
+    let collected: Mutex<Vec<(String, output::Definition)>> = Mutex::new(Vec::new());
+
+    files.par_iter().for_each(|path| {
+        let file = path.to_string_lossy().to_string();
+        let definition = |line, kind: TagKind| output::Definition {
+            file: file.clone(),
+            line,
+            kind: kind.name(),
+        };
+        let mut definitions = Vec::new();
+        source.timings.file(path, || {
+            if DupeKind::Function.selected(kind) {
+                if let Some(functions) = source
+                    .skipped
+                    .ok(path, analysis::extract_functions(path, &source.extract))
+                {
+                    for (sig, entry) in functions {
+                        if !entry.decorators.iter().any(|d| is_overload(d)) {
+                            let name = extract_function_name(&sig).to_string();
+                            definitions.push((name, definition(entry.line, TagKind::Function)));
+                        }
+                    }
+                }
+            }
+            if DupeKind::Class.selected(kind) {
+                if let Some(classes) = source
+                    .skipped
+                    .ok(path, analysis::extract_classes(path, &source.extract))
+                {
+                    for (sig, info) in classes {
+                        let name = extract_class_name(&sig).to_string();
+                        definitions.push((name, definition(info.line, TagKind::Class)));
+                    }
+                }
+            }
+        });
+        collected.lock().unwrap().extend(definitions);
+    });
+    source.timings.lap("parse");
+
+    let mut names: BTreeMap<String, Vec<output::Definition>> = BTreeMap::new();
+    for (name, definition) in collected.into_inner().unwrap() {
+        names.entry(name).or_default().push(definition);
+    }
+    names.retain(|_, definitions| definitions.len() > 1);
+    for definitions in names.values_mut() {
+        definitions.sort_by(|a, b| (&a.file, a.line).cmp(&(&b.file, b.line)));
+    }
+    Ok(output::DupesOutput { names })
+}
+
+fn is_overload(decorator: &str) -> bool {
+    decorator == "overload" || decorator.ends_with(".overload")
+}
+
+fn run_dupes(source: &Source, kind: Option<DupeKind>, style: OutputStyle) -> Result<()> {
+    let mut result = compute_dupes(source, kind)?;
+    if style.absolute {
+        for definition in result.names.values_mut().flatten() {
+            definition.file = absolute_path(&definition.file);
+        }
+    }
+    source.timings.lap("filter");
+    output(&result, style, &[])
+}
+
+/// Compute decorators output (testable without I/O): the decorators on
+/// top-level functions and on methods, keyed by name without any call
+/// arguments (`@app.route("/")` counts as `app.route`)
+fn compute_decorators(source: &Source, patterns: &[String], mode: MatchMode) -> Result<output::DecoratorsOutput> {
+    let files = source.collect_files()?;
+    let collected: Mutex<Vec<(String, output::DecoratorUse)>> = Mutex::new(Vec::new());
+
+    files.par_iter().for_each(|path| {
+        let file = path.to_string_lossy().to_string();
+        let mut uses = Vec::new();
+        let mut record = |decorators: &[String], line, name: String| {
+            for decorator in decorators {
+                let decorated = output::DecoratorUse {
+                    file: file.clone(),
+                    line,
+                    name: name.clone(),
+                };
+                uses.push((decorator_name(decorator).to_string(), decorated));
+            }
+        };
+        source.timings.file(path, || {
+            if let Some(functions) = source
+                .skipped
+                .ok(path, analysis::extract_functions(path, &source.extract))
+            {
+                for (sig, entry) in &functions {
+                    record(&entry.decorators, entry.line, extract_function_name(sig).to_string());
+                }
+            }
+            if let Some(classes) = source
+                .skipped
+                .ok(path, analysis::extract_classes(path, &source.extract))
+            {
+                for (class_sig, info) in &classes {
+                    let class_name = extract_class_name(class_sig);
+                    for (sig, entry) in &info.methods {
+                        let name = format!("{}.{}", class_name, extract_function_name(sig));
+                        record(&entry.decorators, entry.line, name);
+                    }
+                }
+            }
+        });
+        collected.lock().unwrap().extend(uses);
+    });
+    source.timings.lap("parse");
+
+    let mut decorators: BTreeMap<String, output::DecoratorUses> = BTreeMap::new();
+    for (decorator, decorated) in collected.into_inner().unwrap() {
+        decorators.entry(decorator).or_default().uses.push(decorated);
+    }
+    for entry in decorators.values_mut() {
+        entry.uses.sort_by(|a, b| (&a.file, a.line).cmp(&(&b.file, b.line)));
+        entry.count = entry.uses.len();
+        entry.files = entry.uses.iter().map(|u| &u.file).collect::<BTreeSet<_>>().len();
+    }
+    Ok(output::DecoratorsOutput {
+        decorators: pattern::filter_names(decorators, patterns, mode),
+    })
+}
+
+/// A rendered decorator without its call arguments: `app.route("/")` -> `app.route`
+fn decorator_name(decorator: &str) -> &str {
+    decorator.split('(').next().unwrap_or(decorator)
+}
+
+fn run_decorators(source: &Source, patterns: &[String], mode: MatchMode, style: OutputStyle) -> Result<()> {
+    let mut result = compute_decorators(source, patterns, mode)?;
+    if style.absolute {
+        for decorated in result.decorators.values_mut().flat_map(|entry| &mut entry.uses) {
+            decorated.file = absolute_path(&decorated.file);
+        }
+    }
+    source.timings.lap("filter");
+    output(&result, style, &[])
+}
+
+/// Compute a stub per file (testable without I/O)
+fn compute_stubs(source: &Source) -> Result<BTreeMap<PathBuf, String>> {
+    let files = source.collect_files()?;
+    let collected: Mutex<BTreeMap<PathBuf, String>> = Mutex::new(BTreeMap::new());
+
+    files.par_iter().for_each(|path| {
+        let stub = source
+            .timings
+            .file(path, || analysis::generate_stub(path, &source.extract));
+        if let Some(stub) = source.skipped.ok(path, stub) {
+            collected.lock().unwrap().insert(path.clone(), stub);
+        }
+    });
+    source.timings.lap("parse");
+
+    Ok(collected.into_inner().unwrap())
+}
+
+/// Where a file's stub goes under `output_dir`: its path relative to the
+/// target it was found under (a file target by its own name), with a `.pyi`
+/// extension. Paths that would leave `output_dir` are an error.
+fn stub_path(source: &Source, file: &Path, output_dir: &Path) -> Result<PathBuf> {
+    let relative = match source.targets.iter().find(|target| file.starts_with(target)) {
+        Some(target) => match file.strip_prefix(target) {
+            Ok(relative) if !relative.as_os_str().is_empty() => relative,
+            _ => file.file_name().map(Path::new).unwrap_or(file),
+        },
+        None => file,
+    };
+    let mut dest = output_dir.to_path_buf();
+    for component in relative.components() {
+        match component {
+            Component::Normal(part) => dest.push(part),
+            Component::CurDir | Component::RootDir | Component::Prefix(_) => {}
+            Component::ParentDir => {
+                return Err(eyre::eyre!(
+                    "Stub for {} would be written outside {}",
+                    file.display(),
+                    output_dir.display()
+                ))
+            }
+        }
+    }
+    Ok(dest.with_extension("pyi"))
+}
+
+fn run_stub(source: &Source, output_dir: Option<&Path>) -> Result<()> {
+    let stubs = compute_stubs(source)?;
+    source.timings.lap("filter");
+
+    if let Some(dir) = output_dir {
+        let mut written = HashMap::new();
+        for (file, stub) in &stubs {
+            let dest = stub_path(source, file, dir)?;
+            if let Some(other) = written.insert(dest.clone(), file) {
+                return Err(eyre::eyre!(
+                    "Stubs for {} and {} would both be written to {}",
+                    other.display(),
+                    file.display(),
+                    dest.display()
+                ));
+            }
+            if let Some(parent) = dest.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(&dest, stub)?;
+        }
+        return Ok(());
+    }
+    // Several files go to stdout one after another, each under a `# <path>` header
+    let stdout = std::io::stdout();
+    let mut handle = stdout.lock();
+    let single = stubs.len() == 1;
+    for (i, (file, stub)) in stubs.iter().filter(|(_, stub)| single || !stub.is_empty()).enumerate() {
+        if !single {
+            if i > 0 {
+                writeln!(handle)?;
+            }
+            writeln!(handle, "# {}", file.display())?;
+        }
+        handle.write_all(stub.as_bytes())?;
+    }
+    Ok(())
+}
+
+/// Compute untyped output (testable without I/O)
+fn compute_untyped(source: &Source, patterns: &[String], filters: &Filters) -> Result<FilesOutput> {
+    let files = source.collect_files()?;
+    let collected = source.process_files(&files, |path| {
+        let untyped = source
+            .skipped
+            .ok(path, analysis::extract_untyped(path, &source.extract))?;
+        if untyped.is_empty() {
+            None
+        } else {
+            Some(untyped)
+        }
+    })?;
+    let filtered = filter_files_output(collected, patterns, pattern::extract_dump_name, filters.matching);
+    let filtered = filter_by_visibility(filtered, filters.visibility, pattern::extract_dump_name);
+    let filtered = if filters.names_only {
+        files_to_names(filtered, pattern::extract_dump_name)
+    } else {
+        filtered
+    };
+    Ok(FilesOutput { files: filtered })
+}
+
+fn run_untyped(
+    source: &Source,
+    patterns: &[String],
+    filters: &Filters,
+    order: SortOrder,
+    style: OutputStyle,
+) -> Result<()> {
+    let mut result = compute_untyped(source, patterns, filters)?;
+    if style.absolute {
+        result.files = absolute_paths(result.files);
+    }
+    sort::sort_files(&mut result.files, order);
+    source.timings.lap("filter");
+    if style.format == OutputFormat::Xml {
+        return output::output_xml(&result.files);
+    }
+    output(&result, style, patterns)
+}
+
+fn run_entrypoints(source: &Source, patterns: &[String], style: OutputStyle) -> Result<()> {
+    let mut result = compute_entrypoints(source, patterns)?;
+    if style.absolute {
+        result.files = absolute_paths(result.files);
+    }
+    source.timings.lap("filter");
+    if style.format == OutputFormat::Xml {
+        return output::output_xml(&result.files);
+    }
+    output(&result, style, &[])
+}
+
+/// Compute dump output (testable without I/O)
+fn compute_dump(source: &Source, patterns: &[String], only: &[DumpKind], filters: &Filters) -> Result<FilesOutput> {
+    let files = source.collect_files()?;
+    let collected = source.process_files(&files, |path| {
+        let mut all_entries = EntryMap::new();
+        let module = if filters.qualnames { source.module_path(path) } else { String::new() };
+
+        if DumpKind::Functions.selected(only) {
+            if let Some(mut functions) = source
+                .skipped
+                .ok(path, analysis::extract_functions(path, &source.extract))
+            {
+                if filters.qualnames {
+                    set_qualnames(&mut functions, &module, extract_function_name);
+                }
+                all_entries.extend(functions);
+            }
+        }
+        // Flatten classes: prefix method signatures with class name
+        if DumpKind::Classes.selected(only) {
+            if let Some(classes) = source
+                .skipped
+                .ok(path, analysis::extract_classes(path, &source.extract))
+            {
+                for (class_sig, class_info) in classes {
+                    let class_name = extract_class_name(&class_sig);
+                    for (method_sig, mut entry) in class_info.methods {
+                        if filters.qualnames {
+                            let name = format!("{}.{}", class_name, extract_function_name(&method_sig));
+                            entry.meta.qualname = Some(analysis::qualify(&module, &name));
+                        }
+                        let full_sig = format!("{}.{}", class_name, method_sig);
+                        all_entries.insert(full_sig, entry);
+                    }
+                }
+            }
+        }
+        if DumpKind::Enums.selected(only) {
+            if let Some(mut enums) = source.skipped.ok(path, analysis::extract_enums(path, &source.extract)) {
+                if filters.qualnames {
+                    set_qualnames(&mut enums, &module, extract_class_name);
+                }
+                all_entries.extend(enums);
+            }
+        }
+        if DumpKind::Constants.selected(only) {
+            if let Some(mut constants) = source
+                .skipped
+                .ok(path, analysis::extract_constants(path, &source.extract))
+            {
+                if filters.qualnames {
+                    set_qualnames(&mut constants, &module, pattern::extract_constant_name);
+                }
+                all_entries.extend(constants);
+            }
+        }
+
+        if all_entries.is_empty() {
+            None
+        } else {
+            Some(all_entries)
+        }
+    })?;
+    let filtered = filter_files_output(collected, patterns, pattern::extract_dump_name, filters.matching);
+    let filtered = if filters.names_only {
+        files_to_names(filtered, pattern::extract_dump_name)
+    } else {
+        filtered
+    };
+    Ok(FilesOutput { files: filtered })
+}
+
+fn run_dump(
+    source: &Source,
+    patterns: &[String],
+    only: &[DumpKind],
+    filters: &Filters,
+    order: SortOrder,
+    style: OutputStyle,
+) -> Result<()> {
+    let mut result = compute_dump(source, patterns, only, filters)?;
+    if style.absolute {
+        result.files = absolute_paths(result.files);
+    }
+    if style.group_by == GroupBy::Dir {
+        result.files = group_by_dir(result.files);
+    }
+    sort::sort_files(&mut result.files, order);
+    let mut notes = limit_per_file(&mut result.files, style.per_file_limit);
+    notes.extend(style.summary.then(|| summary_footer(&result.files, style.group_by)));
+    source.timings.lap("filter");
+    let written = if let Some(db) = style.sqlite {
+        output::write_sqlite(
+            db,
+            &source.scanned_paths(style.absolute),
+            &file_tags(&result.files, classify_dump_signature),
+        )
+    } else {
+        match style.format {
+            OutputFormat::Ctags => output::output_ctags(&file_tags(&result.files, classify_dump_signature)),
+            OutputFormat::JsonlBySymbol => output::output_jsonl(&file_tags(&result.files, classify_dump_signature)),
+            OutputFormat::Xml => output::output_xml(&result.files),
+            _ if style.signature_only => output_files(
+                source,
+                files_to_signatures(result.files),
+                |files| SignaturesOutput { files },
+                style,
+                patterns,
+            ),
+            _ if style.labeled => output(
+                &source.label_roots(result.files, |files| FilesOutput { files }),
+                style,
+                patterns,
+            ),
+            _ => output(&result, style, patterns),
+        }
+    };
+    print_notes(&notes);
+    written
+}
+
+/// Write per-file signatures (`--signature-only`), split by target with `--labeled`
+fn output_files<V, T: serde::Serialize>(
+    source: &Source,
+    files: BTreeMap<String, V>,
+    wrap: impl Fn(BTreeMap<String, V>) -> T,
+    style: OutputStyle,
+    patterns: &[String],
+) -> Result<()> {
+    if style.labeled {
+        output(&source.label_roots(files, wrap), style, patterns)
+    } else {
+        output(&wrap(files), style, patterns)
+    }
+}
+
+/// Flatten `function`, `enum` or `dump` output into tags. `classify` maps a
+/// signature to its kind, owning class and name.
+fn file_tags(files: &BTreeMap<String, EntryMap>, classify: fn(&str) -> (TagKind, Option<&str>, &str)) -> Vec<Tag> {
+    let mut tags = Vec::new();
+    for (file, entries) in files {
+        for (sig, entry) in entries {
+            let (kind, scope, name) = classify(sig);
+            // `dump` prefixes methods with their class: "Class.def method(...)"
+            let signature = scope.map_or(sig.as_str(), |class| &sig[class.len() + 1..]);
+            tags.push(Tag {
+                name: name.to_string(),
+                file: file.clone(),
+                line: entry.line,
+                kind,
+                scope: scope.map(str::to_string),
+                signature: signature.to_string(),
+            });
+        }
+    }
+    tags
+}
+
+/// Flatten `class` output into tags: each class and its methods
+fn class_tags(files: &BTreeMap<String, ClassMap>) -> Vec<Tag> {
+    let mut tags = Vec::new();
+    for (file, classes) in files {
+        for (class_sig, class_info) in classes {
+            let class_name = extract_class_name(class_sig);
+            tags.push(Tag {
+                name: class_name.to_string(),
+                file: file.clone(),
+                line: class_info.line,
+                kind: TagKind::Class,
+                scope: None,
+                signature: class_info.signature.clone().unwrap_or_else(|| class_sig.clone()),
+            });
+            for (method_sig, entry) in &class_info.methods {
+                tags.push(Tag {
+                    name: extract_function_name(method_sig).to_string(),
+                    file: file.clone(),
+                    line: entry.line,
+                    kind: TagKind::Method,
+                    scope: Some(class_name.to_string()),
+                    signature: method_sig.clone(),
+                });
+            }
+        }
+    }
+    tags
+}
+
+/// LSP document symbols of `function` or `enum` output
+fn file_symbols(files: &BTreeMap<String, EntryMap>, kind: SymbolKind, name_extractor: fn(&str) -> &str) -> LspOutput {
+    files
+        .iter()
+        .map(|(file, entries)| {
+            let symbols = entries
+                .iter()
+                .map(|(sig, entry)| DocumentSymbol::new(name_extractor(sig), sig, kind, entry.line, entry.end_line))
+                .collect();
+            (file.clone(), symbols)
+        })
+        .collect()
+}
+
+/// LSP document symbols of `class` output, with methods as children
+fn class_symbols(files: &BTreeMap<String, ClassMap>) -> LspOutput {
+    files
+        .iter()
+        .map(|(file, classes)| {
+            let symbols = classes
+                .iter()
+                .map(|(class_sig, class_info)| {
+                    let methods = class_info.methods.iter().map(|(sig, entry)| {
+                        let name = extract_function_name(sig);
+                        DocumentSymbol::new(name, sig, SymbolKind::Method, entry.line, entry.end_line)
+                    });
+                    let name = extract_class_name(class_sig);
+                    DocumentSymbol {
+                        children: methods.collect(),
+                        ..DocumentSymbol::new(name, class_sig, SymbolKind::Class, class_info.line, class_info.end_line)
+                    }
+                })
+                .collect();
+            (file.clone(), symbols)
+        })
+        .collect()
+}
+
+fn classify_function_signature(sig: &str) -> (TagKind, Option<&str>, &str) {
+    (TagKind::Function, None, extract_function_name(sig))
+}
+
+fn classify_enum_signature(sig: &str) -> (TagKind, Option<&str>, &str) {
+    (TagKind::Enum, None, extract_class_name(sig))
+}
+
+/// Kind, owning class and name of a `dump` signature. Classes only appear in
+/// `dump` through their methods, so a `class` signature is an enum.
+fn classify_dump_signature(sig: &str) -> (TagKind, Option<&str>, &str) {
+    let is_function = |s: &str| s.starts_with("def ") || s.starts_with("async def ");
+    if is_function(sig) {
+        return (TagKind::Function, None, extract_function_name(sig));
+    }
+    if sig.starts_with("class ") {
+        return (TagKind::Enum, None, extract_class_name(sig));
+    }
+    match sig.split_once('.') {
+        Some((class, method)) if is_function(method) => (TagKind::Method, Some(class), extract_function_name(method)),
+        _ => (TagKind::Variable, None, pattern::extract_constant_name(sig)),
+    }
+}
+
+/// Re-key per-file symbols by the directory of each file (`.` for files in
+/// the current directory), merging the symbols of a directory's files in
+/// path order. A signature found in several files of one directory is kept
+/// for each of them, keyed as `<file name>::<signature>`.
+fn group_by_dir<V>(files: BTreeMap<String, IndexMap<String, V>>) -> BTreeMap<String, IndexMap<String, V>> {
+    let dir_of = |path: &str| match Path::new(path).parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir.to_string_lossy().to_string(),
+        _ => ".".to_string(),
+    };
+
+    let mut counts: HashMap<(String, &str), usize> = HashMap::new();
+    for (path, symbols) in &files {
+        for signature in symbols.keys() {
+            *counts.entry((dir_of(path), signature)).or_default() += 1;
+        }
+    }
+    let shared: HashSet<(String, String)> = counts
+        .into_iter()
+        .filter(|&(_, count)| count > 1)
+        .map(|((dir, signature), _)| (dir, signature.to_string()))
+        .collect();
+
+    let mut grouped: BTreeMap<String, IndexMap<String, V>> = BTreeMap::new();
+    for (path, symbols) in files {
+        let dir = dir_of(&path);
+        let name = Path::new(&path)
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string();
+        for (signature, value) in symbols {
+            let key = if shared.contains(&(dir.clone(), signature.clone())) {
+                format!("{}::{}", name, signature)
+            } else {
+                signature
+            };
+            grouped.entry(dir.clone()).or_default().insert(key, value);
+        }
+    }
+    grouped
+}
+
+/// Re-key per-file results by absolute path (`--absolute`)
+fn absolute_paths<V>(files: BTreeMap<String, V>) -> BTreeMap<String, V> {
+    files
+        .into_iter()
+        .map(|(path, value)| (absolute_path(&path), value))
+        .collect()
+}
+
+/// The canonical absolute form of a path, or the path as given when it can't
+/// be resolved (e.g. a file read from a `--rev` revision)
+fn absolute_path(path: &str) -> String {
+    std::fs::canonicalize(path)
+        .map(|path| path.to_string_lossy().to_string())
+        .unwrap_or_else(|_| path.to_string())
+}
+
+/// Keep the first `limit` symbols of each file (`--per-file-limit`), in their
+/// sorted order. Returns a note for each file that was cut short.
+fn limit_per_file<V>(files: &mut BTreeMap<String, IndexMap<String, V>>, limit: Option<usize>) -> Vec<String> {
+    let Some(limit) = limit else {
+        return Vec::new();
+    };
+    let mut truncated = Vec::new();
+    for (path, symbols) in files.iter_mut() {
+        if symbols.len() > limit {
+            truncated.push(format!(
+                "{}: showing the first {} of {} symbols (--per-file-limit)",
+                path,
+                limit,
+                symbols.len()
+            ));
+            symbols.truncate(limit);
+        }
+    }
+    truncated
+}
+
+/// Print notes on the results (cut-short files, the summary footer) to
+/// stderr, after the output they apply to
+fn print_notes(notes: &[String]) {
+    for note in notes {
+        eprintln!("{}", note);
+    }
+}
+
+/// Process files in parallel and collect results (flat structure)
+fn process_files_parallel<F>(files: &[PathBuf], budget: &SymbolBudget, processor: F) -> BTreeMap<String, EntryMap>
+where
+    F: Fn(&std::path::Path) -> Option<EntryMap> + Sync,
+{
+    let results: Mutex<BTreeMap<String, EntryMap>> = Mutex::new(BTreeMap::new());
+
+    files.par_iter().for_each(|path| {
+        // Over the limit the run fails anyway, so stop reading files
+        if budget.exhausted() {
+            return;
+        }
+        if let Some(content) = processor(path) {
+            if budget.take(content.len()) {
+                let key = path.to_string_lossy().to_string();
+                results.lock().unwrap().insert(key, content);
+            }
+        }
+    });
+
+    results.into_inner().unwrap()
+}
+
+/// Process files in parallel and collect results (nested structure for classes)
+fn process_classes_parallel<F>(files: &[PathBuf], budget: &SymbolBudget, processor: F) -> BTreeMap<String, ClassMap>
+where
+    F: Fn(&std::path::Path) -> Option<ClassMap> + Sync,
+{
+    let results: Mutex<BTreeMap<String, ClassMap>> = Mutex::new(BTreeMap::new());
+
+    files.par_iter().for_each(|path| {
+        if budget.exhausted() {
+            return;
+        }
+        if let Some(content) = processor(path) {
+            // A class counts once, plus one for each of its fields and methods
+            let symbols: usize = content
+                .values()
+                .map(|info| 1 + info.fields.len() + info.methods.len())
+                .sum();
+            if budget.take(symbols) {
+                let key = path.to_string_lossy().to_string();
+                results.lock().unwrap().insert(key, content);
+            }
+        }
+    });
+
+    results.into_inner().unwrap()
+}
+
+/// Apply the `__all__`-based options to a file's symbols:
+/// `--exported-only` keeps only names listed in `__all__` (files without it export nothing),
+/// `--annotate-exports` marks every symbol with whether it is exported
+fn apply_exports<V>(
+    symbols: &mut IndexMap<String, V>,
+    path: &Path,
+    filters: &Filters,
+    name_extractor: fn(&str) -> &str,
+    meta: fn(&mut V) -> &mut Meta,
+) {
+    if !filters.exported_only && !filters.annotate_exports {
+        return;
+    }
+    let exports = analysis::extract_exports(path).ok().flatten();
+
+    if filters.exported_only {
+        symbols.retain(|sig, _| exports.as_ref().is_some_and(|e| e.contains(name_extractor(sig))));
+    }
+    if filters.annotate_exports {
+        for (sig, value) in symbols.iter_mut() {
+            meta(value).exported = Some(analysis::is_exported(name_extractor(sig), exports.as_ref()));
+        }
+    }
+}
+
+/// Set each symbol's `qualname` to its module path plus name
+fn set_qualnames(entries: &mut EntryMap, module: &str, name_extractor: fn(&str) -> &str) {
+    for (sig, entry) in entries.iter_mut() {
+        entry.meta.qualname = Some(analysis::qualify(module, name_extractor(sig)));
+    }
+}
+
+/// Set `qualname` on each class and on its methods (`module.Class.method`)
+fn set_class_qualnames(classes: &mut ClassMap, module: &str) {
+    for (class_sig, class_info) in classes.iter_mut() {
+        let class_qualname = analysis::qualify(module, extract_class_name(class_sig));
+        for (method_sig, entry) in class_info.methods.iter_mut() {
+            entry.meta.qualname = Some(format!("{}.{}", class_qualname, extract_function_name(method_sig)));
+        }
+        class_info.meta.qualname = Some(class_qualname);
+    }
+}
+
+/// Re-key symbols by bare name, keeping the first symbol when names collide
+/// (e.g. a function redefined under `if`/`else`)
+fn to_names<M, V>(symbols: M, name_extractor: fn(&str) -> &str) -> M
+where
+    M: IntoIterator<Item = (String, V)> + FromIterator<(String, V)>,
+{
+    let mut seen = HashSet::new();
+    symbols
+        .into_iter()
+        .filter_map(|(sig, value)| {
+            let name = name_extractor(&sig).to_string();
+            seen.insert(name.clone()).then_some((name, value))
+        })
+        .collect()
+}
+
+/// Re-key every file's symbols by bare name (`--names-only`)
+fn files_to_names(files: BTreeMap<String, EntryMap>, name_extractor: fn(&str) -> &str) -> BTreeMap<String, EntryMap> {
+    files
+        .into_iter()
+        .map(|(path, entries)| (path, to_names(entries, name_extractor)))
+        .collect()
+}
+
+/// Re-key classes, their fields and their methods by bare name (`--names-only`)
+fn classes_to_names(files: BTreeMap<String, ClassMap>) -> BTreeMap<String, ClassMap> {
+    files
+        .into_iter()
+        .map(|(path, classes)| {
+            let classes = classes
+                .into_iter()
+                .map(|(sig, mut info)| {
+                    info.fields = to_names(info.fields, pattern::extract_constant_name);
+                    info.methods = to_names(info.methods, extract_function_name);
+                    (sig, info)
+                })
+                .collect();
+            (path, to_names(classes, extract_class_name))
+        })
+        .collect()
+}
+
+/// Drop the lines from `function`, `enum` or `dump` output, keeping each
+/// file's signatures in order (`--signature-only`)
+fn files_to_signatures(files: BTreeMap<String, EntryMap>) -> BTreeMap<String, Vec<String>> {
+    files
+        .into_iter()
+        .map(|(path, entries)| (path, entries.into_keys().collect()))
+        .collect()
+}
+
+/// Drop the lines from classes and their members, nested classes included
+fn classes_to_signatures(classes: ClassMap) -> IndexMap<String, ClassSignatures> {
+    classes
+        .into_iter()
+        .map(|(sig, info)| {
+            let signatures = ClassSignatures {
+                fields: info.fields.into_keys().collect(),
+                methods: info.methods.into_keys().collect(),
+                classes: classes_to_signatures(info.classes),
+            };
+            (sig, signatures)
+        })
+        .collect()
+}
+
+/// Key classes by bare name, nested classes included, keeping each signature
+/// in its `signature` field (`--class-key name`)
+fn files_keyed_by_class_name(files: BTreeMap<String, ClassMap>) -> BTreeMap<String, ClassMap> {
+    files
+        .into_iter()
+        .map(|(path, classes)| (path, key_by_class_name(classes)))
+        .collect()
+}
+
+fn key_by_class_name(classes: ClassMap) -> ClassMap {
+    let classes: ClassMap = classes
+        .into_iter()
+        .map(|(sig, mut info)| {
+            info.classes = key_by_class_name(info.classes);
+            info.signature = Some(sig.clone());
+            (sig, info)
+        })
+        .collect();
+    to_names(classes, extract_class_name)
+}
+
+/// Check if a name matches the visibility filter
+fn matches_visibility(name: &str, visibility: Visibility) -> bool {
+    match visibility {
+        Visibility::All => true,
+        Visibility::Public => !name.starts_with('_'),
+        Visibility::Private => name.starts_with('_'),
+    }
+}
+
+/// Apply `keep` to the symbols of each file, dropping files left without any
+fn retain_nonempty<V>(
+    files: BTreeMap<String, IndexMap<String, V>>,
+    mut keep: impl FnMut(&String, &mut V) -> bool,
+) -> BTreeMap<String, IndexMap<String, V>> {
+    files
+        .into_iter()
+        .filter_map(|(file_path, mut symbols)| {
+            symbols.retain(&mut keep);
+            (!symbols.is_empty()).then_some((file_path, symbols))
+        })
+        .collect()
+}
+
+/// Apply `keep` to the methods of each class, dropping classes left without
+/// methods and files left without classes
+fn retain_methods(
+    files: BTreeMap<String, ClassMap>,
+    mut keep: impl FnMut(&String, &mut Entry) -> bool,
+) -> BTreeMap<String, ClassMap> {
+    retain_nonempty(files, |_, class_info| {
+        class_info.methods.retain(&mut keep);
+        !class_info.methods.is_empty()
+    })
+}
+
+/// Filter files output by visibility, matching on the name pulled from each signature
+fn filter_by_visibility(
+    files: BTreeMap<String, EntryMap>,
+    visibility: Visibility,
+    name_extractor: fn(&str) -> &str,
+) -> BTreeMap<String, EntryMap> {
+    if visibility == Visibility::All {
+        return files;
+    }
+    retain_nonempty(files, |sig, _| matches_visibility(name_extractor(sig), visibility))
+}
+
+/// Filter classes output by visibility (filters fields and methods within each class)
+fn filter_classes_by_visibility(
+    files: BTreeMap<String, ClassMap>,
+    visibility: Visibility,
+    keep_empty: bool,
+) -> BTreeMap<String, ClassMap> {
+    if visibility == Visibility::All {
+        return files;
+    }
+
+    retain_nonempty(files, |_, class_info| {
+        class_info.fields.retain(|field_sig, _| {
+            // Extract field name from signature (e.g., "field_name: Type" -> "field_name")
+            let name = field_sig.split(':').next().unwrap_or(field_sig).trim();
+            matches_visibility(name, visibility)
+        });
+        class_info
+            .methods
+            .retain(|method_sig, _| matches_visibility(extract_function_name(method_sig), visibility));
+
+        // Keep class if it has any fields, methods or nested classes after filtering
+        keep_empty || !class_info.fields.is_empty() || !class_info.methods.is_empty() || !class_info.classes.is_empty()
+    })
+}
+
+/// Keep only abstract classes, marking them and their abstract methods
+fn filter_abstract_classes(files: BTreeMap<String, ClassMap>) -> BTreeMap<String, ClassMap> {
+    retain_nonempty(files, |_, class_info| {
+        if !analysis::is_abstract_class(class_info) {
+            return false;
+        }
+        class_info.meta.is_abstract = true;
+        for entry in class_info.methods.values_mut() {
+            entry.meta.is_abstract = analysis::is_abstract_method(entry);
+        }
+        true
+    })
+}
+
+/// Keep only test functions
+fn filter_test_functions(files: BTreeMap<String, EntryMap>) -> BTreeMap<String, EntryMap> {
+    retain_nonempty(files, |sig, _| analysis::is_test_function(extract_function_name(sig)))
+}
+
+/// Keep only test classes, reduced to their test methods
+fn filter_test_classes(files: BTreeMap<String, ClassMap>) -> BTreeMap<String, ClassMap> {
+    retain_nonempty(files, |class_sig, class_info| {
+        if !analysis::is_test_class(extract_class_name(class_sig), class_info) {
+            return false;
+        }
+        class_info.fields.clear();
+        class_info
+            .methods
+            .retain(|sig, _| analysis::is_test_function(extract_function_name(sig)));
+        !class_info.methods.is_empty()
+    })
+}
+
+/// Keep only data model classes, reduced to their model fields
+fn filter_model_classes(files: BTreeMap<String, ClassMap>) -> BTreeMap<String, ClassMap> {
+    retain_nonempty(files, |_, class_info| {
+        class_info.fields.retain(|_, entry| entry.model_field);
+        class_info.methods.clear();
+        class_info.meta.model.is_some()
+    })
+}
+
+/// Keep only classes of one structural kind (`--kind`) and mark them with it
+fn filter_classes_by_kind(files: BTreeMap<String, ClassMap>, kind: ClassKind) -> BTreeMap<String, ClassMap> {
+    retain_nonempty(files, |_, class_info| {
+        class_info.meta.kind = class_info.kind;
+        class_info.kind == Some(kind)
+    })
+}
+
+/// Keep only functions whose parameter count is within `--min-args`/`--max-args`
+fn filter_functions_by_args(files: BTreeMap<String, EntryMap>, filters: &Filters) -> BTreeMap<String, EntryMap> {
+    retain_nonempty(files, |_, entry| filters.args_in_range(entry.params))
+}
+
+/// Keep only functions whose line count is within `--min-lines`/`--max-lines`
+fn filter_functions_by_lines(files: BTreeMap<String, EntryMap>, filters: &Filters) -> BTreeMap<String, EntryMap> {
+    retain_nonempty(files, |_, entry| filters.lines_in_range(entry.lines()))
+}
+
+/// Keep only generator functions, or only the other functions
+fn filter_generators(files: BTreeMap<String, EntryMap>, generators: bool) -> BTreeMap<String, EntryMap> {
+    retain_nonempty(files, |_, entry| entry.generator == generators)
+}
+
+fn is_async(signature: &str) -> bool {
+    signature.starts_with("async def ")
+}
+
+/// Keep only `async def` functions, or with `asynchronous` false only plain `def`s
+fn filter_async_functions(files: BTreeMap<String, EntryMap>, asynchronous: bool) -> BTreeMap<String, EntryMap> {
+    retain_nonempty(files, |sig, _| is_async(sig) == asynchronous)
+}
+
+/// Keep only `async def` methods (or only plain `def`s), dropping classes left
+/// without methods
+fn filter_async_methods(files: BTreeMap<String, ClassMap>, asynchronous: bool) -> BTreeMap<String, ClassMap> {
+    retain_methods(files, |sig, _| is_async(sig) == asynchronous)
+}
+
+/// Keep only functions with at least one decorator, or with `decorated` false
+/// only those without
+fn filter_decorated_functions(files: BTreeMap<String, EntryMap>, decorated: bool) -> BTreeMap<String, EntryMap> {
+    retain_nonempty(files, |_, entry| entry.decorators.is_empty() != decorated)
+}
+
+/// Keep only decorated (or only undecorated) methods, dropping classes left
+/// without methods
+fn filter_decorated_methods(files: BTreeMap<String, ClassMap>, decorated: bool) -> BTreeMap<String, ClassMap> {
+    retain_methods(files, |_, entry| entry.decorators.is_empty() != decorated)
+}
+
+/// Keep only functions that never return (`--noreturn`), marking them
+fn filter_noreturn_functions(files: BTreeMap<String, EntryMap>) -> BTreeMap<String, EntryMap> {
+    retain_nonempty(files, |_, entry| {
+        entry.meta.noreturn = entry.noreturn;
+        entry.noreturn
+    })
+}
+
+/// Keep only methods that never return, marking them and dropping classes left
+/// without methods
+fn filter_noreturn_methods(files: BTreeMap<String, ClassMap>) -> BTreeMap<String, ClassMap> {
+    retain_methods(files, |_, entry| {
+        entry.meta.noreturn = entry.noreturn;
+        entry.noreturn
+    })
+}
+
+/// Keep only `@contextmanager`/`@asynccontextmanager` functions, marking them
+fn filter_context_manager_functions(files: BTreeMap<String, EntryMap>) -> BTreeMap<String, EntryMap> {
+    retain_nonempty(files, |_, entry| {
+        entry.meta.context_manager = entry.context_manager;
+        entry.context_manager
+    })
+}
+
+/// Keep only classes that implement the context-manager protocol, marking them
+/// and their `@contextmanager` methods
+fn filter_context_manager_classes(files: BTreeMap<String, ClassMap>) -> BTreeMap<String, ClassMap> {
+    retain_nonempty(files, |_, class_info| {
+        class_info.meta.context_manager = class_info.context_manager;
+        for entry in class_info.methods.values_mut() {
+            entry.meta.context_manager = entry.context_manager;
+        }
+        class_info.context_manager
+    })
+}
+
+/// Keep root classes (no bases), or with `derived` only classes that have bases
+fn filter_classes_by_bases(files: BTreeMap<String, ClassMap>, derived: bool) -> BTreeMap<String, ClassMap> {
+    retain_nonempty(files, |_, class_info| class_info.bases.is_empty() != derived)
+}
+
+/// Keep classes whose method count is within the `--min-methods`/`--max-methods` bounds
+fn filter_classes_by_method_count(files: BTreeMap<String, ClassMap>, filters: &Filters) -> BTreeMap<String, ClassMap> {
+    retain_nonempty(files, |_, class_info| {
+        filters.methods_in_range(class_info.methods.len())
+    })
+}
+
+/// Keep only methods whose parameter count is within `--min-args`/`--max-args`,
+/// dropping classes left without methods
+fn filter_methods_by_args(files: BTreeMap<String, ClassMap>, filters: &Filters) -> BTreeMap<String, ClassMap> {
+    retain_methods(files, |_, entry| filters.args_in_range(entry.params))
+}
+
+/// Keep only methods whose line count is within `--min-lines`/`--max-lines`,
+/// dropping classes left without methods
+fn filter_methods_by_lines(files: BTreeMap<String, ClassMap>, filters: &Filters) -> BTreeMap<String, ClassMap> {
+    retain_methods(files, |_, entry| filters.lines_in_range(entry.lines()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::MatchMode;
+    use crate::output::{ClassInfo, MemberMap};
+
+    fn fixtures_dir() -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures")
+    }
+
+    #[test]
+    fn test_matches_visibility_all() {
+        assert!(matches_visibility("public_func", Visibility::All));
+        assert!(matches_visibility("_private_func", Visibility::All));
+        assert!(matches_visibility("__dunder__", Visibility::All));
+    }
+
+    #[test]
     fn test_matches_visibility_public() {
         assert!(matches_visibility("public_func", Visibility::Public));
         assert!(!matches_visibility("_private_func", Visibility::Public));
@@ -337,469 +2318,1624 @@ mod tests {
     }
 
     #[test]
-    fn test_matches_visibility_private() {
-        assert!(!matches_visibility("public_func", Visibility::Private));
-        assert!(matches_visibility("_private_func", Visibility::Private));
-        assert!(matches_visibility("__dunder__", Visibility::Private));
+    fn test_matches_visibility_private() {
+        assert!(!matches_visibility("public_func", Visibility::Private));
+        assert!(matches_visibility("_private_func", Visibility::Private));
+        assert!(matches_visibility("__dunder__", Visibility::Private));
+    }
+
+    #[test]
+    fn test_filter_by_visibility_all() {
+        let mut files = BTreeMap::new();
+        let mut entries = EntryMap::new();
+        entries.insert("def public_func()".to_string(), 1.into());
+        entries.insert("def _private_func()".to_string(), 2.into());
+        files.insert("test.py".to_string(), entries);
+
+        let result = filter_by_visibility(files, Visibility::All, extract_function_name);
+        let entries = result.get("test.py").unwrap();
+        assert_eq!(entries.len(), 2);
+    }
+
+    #[test]
+    fn test_filter_by_visibility_public() {
+        let mut files = BTreeMap::new();
+        let mut entries = EntryMap::new();
+        entries.insert("def public_func()".to_string(), 1.into());
+        entries.insert("def _private_func()".to_string(), 2.into());
+        files.insert("test.py".to_string(), entries);
+
+        let result = filter_by_visibility(files, Visibility::Public, extract_function_name);
+        let entries = result.get("test.py").unwrap();
+        assert_eq!(entries.len(), 1);
+        assert!(entries.contains_key("def public_func()"));
+    }
+
+    #[test]
+    fn test_filter_by_visibility_private() {
+        let mut files = BTreeMap::new();
+        let mut entries = EntryMap::new();
+        entries.insert("def public_func()".to_string(), 1.into());
+        entries.insert("def _private_func()".to_string(), 2.into());
+        files.insert("test.py".to_string(), entries);
+
+        let result = filter_by_visibility(files, Visibility::Private, extract_function_name);
+        let entries = result.get("test.py").unwrap();
+        assert_eq!(entries.len(), 1);
+        assert!(entries.contains_key("def _private_func()"));
+    }
+
+    #[test]
+    fn test_filter_by_visibility_removes_empty_files() {
+        let mut files = BTreeMap::new();
+        let mut entries = EntryMap::new();
+        entries.insert("def _private_func()".to_string(), 1.into());
+        files.insert("test.py".to_string(), entries);
+
+        let result = filter_by_visibility(files, Visibility::Public, extract_function_name);
+        assert!(result.is_empty()); // File removed because no public functions
+    }
+
+    #[test]
+    fn test_filter_classes_by_visibility_all() {
+        let mut files: BTreeMap<String, ClassMap> = BTreeMap::new();
+        let mut classes = ClassMap::new();
+
+        let mut fields = MemberMap::new();
+        fields.insert("name: str".to_string(), 1.into());
+        fields.insert("_private: int".to_string(), 2.into());
+
+        let mut methods = MemberMap::new();
+        methods.insert("def public()".to_string(), 3.into());
+        methods.insert("def _private()".to_string(), 4.into());
+
+        classes.insert(
+            "class Test".to_string(),
+            ClassInfo {
+                fields,
+                methods,
+                ..Default::default()
+            },
+        );
+        files.insert("test.py".to_string(), classes);
+
+        let result = filter_classes_by_visibility(files, Visibility::All, false);
+        let classes = result.get("test.py").unwrap();
+        let class_info = classes.get("class Test").unwrap();
+        assert_eq!(class_info.fields.len(), 2);
+        assert_eq!(class_info.methods.len(), 2);
+    }
+
+    #[test]
+    fn test_filter_classes_by_visibility_public() {
+        let mut files: BTreeMap<String, ClassMap> = BTreeMap::new();
+        let mut classes = ClassMap::new();
+
+        let mut fields = MemberMap::new();
+        fields.insert("name: str".to_string(), 1.into());
+        fields.insert("_private: int".to_string(), 2.into());
+
+        let mut methods = MemberMap::new();
+        methods.insert("def public()".to_string(), 3.into());
+        methods.insert("def _private()".to_string(), 4.into());
+
+        classes.insert(
+            "class Test".to_string(),
+            ClassInfo {
+                fields,
+                methods,
+                ..Default::default()
+            },
+        );
+        files.insert("test.py".to_string(), classes);
+
+        let result = filter_classes_by_visibility(files, Visibility::Public, false);
+        let classes = result.get("test.py").unwrap();
+        let class_info = classes.get("class Test").unwrap();
+        assert_eq!(class_info.fields.len(), 1);
+        assert_eq!(class_info.methods.len(), 1);
+        assert!(class_info.fields.contains_key("name: str"));
+        assert!(class_info.methods.contains_key("def public()"));
+    }
+
+    #[test]
+    fn test_filter_classes_by_visibility_private() {
+        let mut files: BTreeMap<String, ClassMap> = BTreeMap::new();
+        let mut classes = ClassMap::new();
+
+        let mut fields = MemberMap::new();
+        fields.insert("name: str".to_string(), 1.into());
+        fields.insert("_private: int".to_string(), 2.into());
+
+        let mut methods = MemberMap::new();
+        methods.insert("def public()".to_string(), 3.into());
+        methods.insert("def _private()".to_string(), 4.into());
+
+        classes.insert(
+            "class Test".to_string(),
+            ClassInfo {
+                fields,
+                methods,
+                ..Default::default()
+            },
+        );
+        files.insert("test.py".to_string(), classes);
+
+        let result = filter_classes_by_visibility(files, Visibility::Private, false);
+        let classes = result.get("test.py").unwrap();
+        let class_info = classes.get("class Test").unwrap();
+        assert_eq!(class_info.fields.len(), 1);
+        assert_eq!(class_info.methods.len(), 1);
+        assert!(class_info.fields.contains_key("_private: int"));
+        assert!(class_info.methods.contains_key("def _private()"));
+    }
+
+    #[test]
+    fn test_filter_classes_removes_empty_classes() {
+        let mut files: BTreeMap<String, ClassMap> = BTreeMap::new();
+        let mut classes = ClassMap::new();
+
+        let mut fields = MemberMap::new();
+        fields.insert("_private: int".to_string(), 1.into());
+
+        let mut methods = MemberMap::new();
+        methods.insert("def _private()".to_string(), 2.into());
+
+        classes.insert(
+            "class Test".to_string(),
+            ClassInfo {
+                fields,
+                methods,
+                ..Default::default()
+            },
+        );
+        files.insert("test.py".to_string(), classes);
+
+        let result = filter_classes_by_visibility(files.clone(), Visibility::Public, false);
+        assert!(result.is_empty()); // File and class removed because no public members
+
+        let result = filter_classes_by_visibility(files, Visibility::Public, true);
+        let class_info = &result["test.py"]["class Test"];
+        assert!(class_info.fields.is_empty());
+        assert!(class_info.methods.is_empty());
+    }
+
+    #[test]
+    fn test_process_files_parallel_empty() {
+        let files: Vec<PathBuf> = vec![];
+        let result = process_files_parallel(&files, &SymbolBudget::default(), |_| None);
+        assert!(result.is_empty());
+    }
+
+    #[test]
+    fn test_process_files_parallel_with_files() {
+        let files = vec![fixtures_dir().join("functions.py"), fixtures_dir().join("classes.py")];
+        let result = process_files_parallel(&files, &SymbolBudget::default(), |path| {
+            let functions = analysis::extract_functions(path, &ExtractOptions::default()).ok()?;
+            if functions.is_empty() {
+                None
+            } else {
+                Some(functions)
+            }
+        });
+
+        // functions.py should have functions, classes.py should have none at top level
+        assert!(!result.is_empty());
+    }
+
+    #[test]
+    fn test_max_symbols_aborts_collection() {
+        let targets = vec![fixtures_dir()];
+        let source = Source {
+            budget: SymbolBudget::new(Some(3)),
+            ..Source::new(&targets)
+        };
+        let err = compute_functions(&source, &[], &Filters::default()).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "More than 3 symbols found (--max-symbols); narrow the targets or raise the limit"
+        );
+
+        let source = Source {
+            budget: SymbolBudget::new(Some(3)),
+            ..Source::new(&targets)
+        };
+        assert!(compute_classes(&source, &[], &Filters::default()).is_err());
+
+        // A limit the files stay within changes nothing
+        let targets = vec![fixtures_dir().join("receivers.py")];
+        let source = Source {
+            budget: SymbolBudget::new(Some(100)),
+            ..Source::new(&targets)
+        };
+        let limited = compute_classes(&source, &[], &Filters::default()).unwrap();
+        let unlimited = compute_classes(&Source::new(&targets), &[], &Filters::default()).unwrap();
+        assert_eq!(
+            serde_json::to_value(&limited).unwrap(),
+            serde_json::to_value(&unlimited).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_process_classes_parallel_empty() {
+        let files: Vec<PathBuf> = vec![];
+        let result = process_classes_parallel(&files, &SymbolBudget::default(), |_| None);
+        assert!(result.is_empty());
+    }
+
+    #[test]
+    fn test_process_classes_parallel_with_files() {
+        let files = vec![fixtures_dir().join("classes.py")];
+        let result = process_classes_parallel(&files, &SymbolBudget::default(), |path| {
+            let classes = analysis::extract_classes(path, &ExtractOptions::default()).ok()?;
+            if classes.is_empty() {
+                None
+            } else {
+                Some(classes)
+            }
+        });
+
+        assert!(!result.is_empty());
+    }
+
+    // Integration tests that exercise the run_* functions indirectly
+    #[test]
+    fn test_integration_extract_functions_and_filter() {
+        let targets = vec![fixtures_dir().join("functions.py")];
+        let files = walk::collect_python_files(&targets, &WalkOptions::default()).unwrap();
+        let collected = process_files_parallel(&files, &SymbolBudget::default(), |path| {
+            let functions = analysis::extract_functions(path, &ExtractOptions::default()).ok()?;
+            if functions.is_empty() {
+                None
+            } else {
+                Some(functions)
+            }
+        });
+        let filtered = filter_files_output(
+            collected,
+            &["simple".to_string()],
+            extract_function_name,
+            MatchMode::default(),
+        );
+        let filtered = filter_by_visibility(filtered, Visibility::All, extract_function_name);
+
+        assert!(!filtered.is_empty());
+        let has_simple = filtered
+            .values()
+            .any(|entries| entries.keys().any(|k| k.contains("simple_function")));
+        assert!(has_simple);
+    }
+
+    #[test]
+    fn test_integration_extract_classes_and_filter() {
+        let targets = vec![fixtures_dir().join("classes.py")];
+        let files = walk::collect_python_files(&targets, &WalkOptions::default()).unwrap();
+        let collected = process_classes_parallel(&files, &SymbolBudget::default(), |path| {
+            let classes = analysis::extract_classes(path, &ExtractOptions::default()).ok()?;
+            if classes.is_empty() {
+                None
+            } else {
+                Some(classes)
+            }
+        });
+        let filtered = filter_classes_output(collected, &["Class".to_string()], MatchMode::default());
+        let filtered = filter_classes_by_visibility(filtered, Visibility::All, false);
+
+        assert!(!filtered.is_empty());
+    }
+
+    #[test]
+    fn test_integration_extract_enums_and_filter() {
+        let targets = vec![fixtures_dir().join("enums.py")];
+        let files = walk::collect_python_files(&targets, &WalkOptions::default()).unwrap();
+        let collected = process_files_parallel(&files, &SymbolBudget::default(), |path| {
+            let enums = analysis::extract_enums(path, &ExtractOptions::default()).ok()?;
+            if enums.is_empty() {
+                None
+            } else {
+                Some(enums)
+            }
+        });
+        let filtered = filter_files_output(
+            collected,
+            &["Color".to_string()],
+            extract_class_name,
+            MatchMode::default(),
+        );
+
+        assert!(!filtered.is_empty());
+    }
+
+    #[test]
+    fn test_integration_build_module_tree() {
+        let targets = vec![fixtures_dir().join("pkg")];
+        let files = walk::collect_python_files(&targets, &WalkOptions::default()).unwrap();
+
+        let base_path = &targets[0];
+        let result = analysis::build_module_tree(&files, base_path, None);
+        let filtered = pattern::filter_modules_output(result, &[]);
+
+        assert!(!filtered.modules.is_empty());
+    }
+
+    #[test]
+    fn test_integration_mixed_file_dump_style() {
+        let targets = vec![fixtures_dir().join("mixed.py")];
+        let files = walk::collect_python_files(&targets, &WalkOptions::default()).unwrap();
+        let collected = process_files_parallel(&files, &SymbolBudget::default(), |path| {
+            let mut all_entries = EntryMap::new();
+
+            if let Ok(functions) = analysis::extract_functions(path, &ExtractOptions::default()) {
+                all_entries.extend(functions);
+            }
+            if let Ok(classes) = analysis::extract_classes(path, &ExtractOptions::default()) {
+                for (class_sig, class_info) in classes {
+                    let class_name = extract_class_name(&class_sig);
+                    for (method_sig, line) in class_info.methods {
+                        let full_sig = format!("{}.{}", class_name, method_sig);
+                        all_entries.insert(full_sig, line);
+                    }
+                }
+            }
+            if let Ok(enums) = analysis::extract_enums(path, &ExtractOptions::default()) {
+                all_entries.extend(enums);
+            }
+
+            if all_entries.is_empty() {
+                None
+            } else {
+                Some(all_entries)
+            }
+        });
+
+        assert!(!collected.is_empty());
+        let filtered = filter_files_output(
+            collected,
+            &["helper".to_string()],
+            pattern::extract_dump_name,
+            MatchMode::default(),
+        );
+        assert!(!filtered.is_empty());
+    }
+
+    #[test]
+    fn test_integration_visibility_filtering_public() {
+        let targets = vec![fixtures_dir().join("functions.py")];
+        let files = walk::collect_python_files(&targets, &WalkOptions::default()).unwrap();
+        let collected = process_files_parallel(&files, &SymbolBudget::default(), |path| {
+            let functions = analysis::extract_functions(path, &ExtractOptions::default()).ok()?;
+            if functions.is_empty() {
+                None
+            } else {
+                Some(functions)
+            }
+        });
+        let filtered = filter_files_output(collected, &[], extract_function_name, MatchMode::default());
+        let public_only = filter_by_visibility(filtered.clone(), Visibility::Public, extract_function_name);
+        let private_only = filter_by_visibility(filtered, Visibility::Private, extract_function_name);
+
+        let public_count: usize = public_only.values().map(|e| e.len()).sum();
+        let private_count: usize = private_only.values().map(|e| e.len()).sum();
+
+        assert!(public_count > 0);
+        assert!(private_count > 0);
+    }
+
+    #[test]
+    fn test_integration_class_visibility_filtering() {
+        let targets = vec![fixtures_dir().join("classes.py")];
+        let files = walk::collect_python_files(&targets, &WalkOptions::default()).unwrap();
+        let collected = process_classes_parallel(&files, &SymbolBudget::default(), |path| {
+            let classes = analysis::extract_classes(path, &ExtractOptions::default()).ok()?;
+            if classes.is_empty() {
+                None
+            } else {
+                Some(classes)
+            }
+        });
+        let filtered = filter_classes_output(collected, &[], MatchMode::default());
+        let public_only = filter_classes_by_visibility(filtered.clone(), Visibility::Public, false);
+        let private_only = filter_classes_by_visibility(filtered, Visibility::Private, false);
+
+        let has_public = public_only.values().any(|classes| {
+            classes
+                .values()
+                .any(|info| !info.fields.is_empty() || !info.methods.is_empty())
+        });
+        let has_private = private_only.values().any(|classes| {
+            classes
+                .values()
+                .any(|info| !info.fields.is_empty() || !info.methods.is_empty())
+        });
+
+        assert!(has_public);
+        assert!(has_private);
+    }
+
+    #[test]
+    fn test_integration_modules_with_base_path_file() {
+        let targets = vec![fixtures_dir().join("functions.py")];
+        let files = walk::collect_python_files(&targets, &WalkOptions::default()).unwrap();
+
+        let base_path = targets
+            .first()
+            .map(|p| {
+                if p.is_dir() {
+                    p.clone()
+                } else {
+                    p.parent().map(|p| p.to_path_buf()).unwrap_or_default()
+                }
+            })
+            .unwrap_or_else(|| PathBuf::from("."));
+
+        let result = analysis::build_module_tree(&files, &base_path, None);
+        assert!(!result.modules.is_empty());
+    }
+
+    // Tests for compute_* functions
+    #[test]
+    fn test_compute_functions() {
+        let targets = vec![fixtures_dir().join("functions.py")];
+        let result = compute_functions(&Source::new(&targets), &[], &Filters::default()).unwrap();
+        assert!(!result.files.is_empty());
+    }
+
+    #[test]
+    fn test_compute_functions_with_pattern() {
+        let targets = vec![fixtures_dir().join("functions.py")];
+        let result = compute_functions(&Source::new(&targets), &["simple".to_string()], &Filters::default()).unwrap();
+        assert!(!result.files.is_empty());
+        let has_simple = result.files.values().any(|e| e.keys().any(|k| k.contains("simple")));
+        assert!(has_simple);
+    }
+
+    #[test]
+    fn test_compute_functions_generators() {
+        let targets = vec![fixtures_dir().join("generators.py")];
+        let names = |generators| {
+            let filters = Filters {
+                generators,
+                names_only: true,
+                ..Default::default()
+            };
+            let result = compute_functions(&Source::new(&targets), &[], &filters).unwrap();
+            result
+                .files
+                .into_values()
+                .flat_map(|e| e.into_keys())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(names(Some(true)), vec!["count_up", "ticks", "delegate"]);
+        // `factory` only returns a generator function
+        assert_eq!(names(Some(false)), vec!["plain", "factory"]);
+        assert_eq!(names(None).len(), 5);
+    }
+
+    #[test]
+    fn test_compute_decorated_filters() {
+        let targets = vec![fixtures_dir().join("decorated.py")];
+        let filters = |decorated| Filters {
+            decorated,
+            names_only: true,
+            ..Default::default()
+        };
+        let functions = |decorated| {
+            let result = compute_functions(&Source::new(&targets), &[], &filters(decorated)).unwrap();
+            result
+                .files
+                .into_values()
+                .flat_map(|e| e.into_keys())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(functions(Some(true)), vec!["dashboard"]);
+        assert_eq!(functions(Some(false)), vec!["login_required", "health"]);
+
+        let methods = |decorated| {
+            let result = compute_classes(&Source::new(&targets), &[], &filters(decorated)).unwrap();
+            result
+                .files
+                .into_values()
+                .flat_map(|classes| classes.into_iter())
+                .map(|(class, info)| (class, info.methods.into_keys().collect::<Vec<_>>()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            methods(Some(true)),
+            vec![("Views".to_string(), vec!["index".to_string()])]
+        );
+        assert_eq!(
+            methods(Some(false)),
+            vec![
+                ("Views".to_string(), vec!["detail".to_string()]),
+                ("Plain".to_string(), vec!["render".to_string()]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_compute_async_filters() {
+        let targets = vec![fixtures_dir().join("async_mix.py")];
+        let filters = |asynchronous| Filters {
+            asynchronous,
+            names_only: true,
+            ..Default::default()
+        };
+        let functions = |asynchronous| {
+            let result = compute_functions(&Source::new(&targets), &[], &filters(asynchronous)).unwrap();
+            result
+                .files
+                .into_values()
+                .flat_map(|e| e.into_keys())
+                .collect::<Vec<_>>()
+        };
+        // An async generator is still a coroutine definition
+        assert_eq!(functions(Some(true)), vec!["fetch", "stream"]);
+        assert_eq!(functions(Some(false)), vec!["parse"]);
+
+        let result = compute_classes(&Source::new(&targets), &[], &filters(Some(true))).unwrap();
+        let classes = &result.files[&targets[0].to_string_lossy().to_string()];
+        assert_eq!(classes.keys().collect::<Vec<_>>(), vec!["Client"]);
+        assert_eq!(classes["Client"].methods.keys().collect::<Vec<_>>(), vec!["get"]);
+
+        let result = compute_classes(&Source::new(&targets), &[], &filters(Some(false))).unwrap();
+        let classes = &result.files[&targets[0].to_string_lossy().to_string()];
+        assert_eq!(classes["Client"].methods.keys().collect::<Vec<_>>(), vec!["close"]);
+        assert!(classes.contains_key("Config"));
     }
 
     #[test]
-    fn test_filter_by_visibility_all() {
-        let mut files = BTreeMap::new();
-        let mut entries = BTreeMap::new();
-        entries.insert("def public_func()".to_string(), 1);
-        entries.insert("def _private_func()".to_string(), 2);
-        files.insert("test.py".to_string(), entries);
+    fn test_compute_functions_visibility_public() {
+        let targets = vec![fixtures_dir().join("functions.py")];
+        let result = compute_functions(
+            &Source::new(&targets),
+            &[],
+            &Filters {
+                visibility: Visibility::Public,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        // Should have only public functions
+        for entries in result.files.values() {
+            for sig in entries.keys() {
+                let name = extract_function_name(sig);
+                assert!(!name.starts_with('_'), "Should not have private function: {}", name);
+            }
+        }
+    }
 
-        let result = filter_by_visibility(files, Visibility::All);
-        let entries = result.get("test.py").unwrap();
-        assert_eq!(entries.len(), 2);
+    #[test]
+    fn test_compute_functions_visibility_private() {
+        let targets = vec![fixtures_dir().join("functions.py")];
+        let result = compute_functions(
+            &Source::new(&targets),
+            &[],
+            &Filters {
+                visibility: Visibility::Private,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        // Should have only private functions
+        for entries in result.files.values() {
+            for sig in entries.keys() {
+                let name = extract_function_name(sig);
+                assert!(name.starts_with('_'), "Should only have private functions: {}", name);
+            }
+        }
     }
 
     #[test]
-    fn test_filter_by_visibility_public() {
-        let mut files = BTreeMap::new();
-        let mut entries = BTreeMap::new();
-        entries.insert("def public_func()".to_string(), 1);
-        entries.insert("def _private_func()".to_string(), 2);
-        files.insert("test.py".to_string(), entries);
+    fn test_compute_classes() {
+        let targets = vec![fixtures_dir().join("classes.py")];
+        let result = compute_classes(&Source::new(&targets), &[], &Filters::default()).unwrap();
+        assert!(!result.files.is_empty());
+    }
 
-        let result = filter_by_visibility(files, Visibility::Public);
-        let entries = result.get("test.py").unwrap();
-        assert_eq!(entries.len(), 1);
-        assert!(entries.contains_key("def public_func()"));
+    #[test]
+    fn test_compute_classes_with_pattern() {
+        let targets = vec![fixtures_dir().join("classes.py")];
+        let result = compute_classes(&Source::new(&targets), &["Simple".to_string()], &Filters::default()).unwrap();
+        assert!(!result.files.is_empty());
     }
 
     #[test]
-    fn test_filter_by_visibility_private() {
-        let mut files = BTreeMap::new();
-        let mut entries = BTreeMap::new();
-        entries.insert("def public_func()".to_string(), 1);
-        entries.insert("def _private_func()".to_string(), 2);
-        files.insert("test.py".to_string(), entries);
+    fn test_compute_classes_visibility_public() {
+        let targets = vec![fixtures_dir().join("classes.py")];
+        let result = compute_classes(
+            &Source::new(&targets),
+            &[],
+            &Filters {
+                visibility: Visibility::Public,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        // Check that private fields/methods are filtered
+        for classes in result.files.values() {
+            for class_info in classes.values() {
+                for field in class_info.fields.keys() {
+                    let name = field.split(':').next().unwrap_or(field).trim();
+                    assert!(!name.starts_with('_'), "Should not have private field: {}", name);
+                }
+                for method in class_info.methods.keys() {
+                    let name = extract_function_name(method);
+                    assert!(!name.starts_with('_'), "Should not have private method: {}", name);
+                }
+            }
+        }
+    }
 
-        let result = filter_by_visibility(files, Visibility::Private);
-        let entries = result.get("test.py").unwrap();
-        assert_eq!(entries.len(), 1);
-        assert!(entries.contains_key("def _private_func()"));
+    #[test]
+    fn test_compute_exported_only() {
+        let targets = vec![fixtures_dir().join("exports.py")];
+        let filters = Filters {
+            exported_only: true,
+            ..Default::default()
+        };
+        let source = Source::new(&targets);
+
+        let functions = compute_functions(&source, &[], &filters).unwrap();
+        let names: Vec<_> = functions.files.values().flat_map(|e| e.keys()).collect();
+        assert_eq!(names, vec!["def connect(url: str) -> Client"]);
+
+        let classes = compute_classes(&source, &[], &filters).unwrap();
+        let names: Vec<_> = classes.files.values().flat_map(|c| c.keys()).collect();
+        assert_eq!(names, vec!["class Client"]);
+
+        let enums = compute_enums(&source, &[], &filters).unwrap();
+        let names: Vec<_> = enums.files.values().flat_map(|e| e.keys()).collect();
+        assert_eq!(names, vec!["class Mode(Enum)"]);
     }
 
     #[test]
-    fn test_filter_by_visibility_removes_empty_files() {
-        let mut files = BTreeMap::new();
-        let mut entries = BTreeMap::new();
-        entries.insert("def _private_func()".to_string(), 1);
-        files.insert("test.py".to_string(), entries);
+    fn test_compute_exported_only_without_dunder_all() {
+        let targets = vec![fixtures_dir().join("functions.py")];
+        let filters = Filters {
+            exported_only: true,
+            ..Default::default()
+        };
+        let result = compute_functions(&Source::new(&targets), &[], &filters).unwrap();
+        assert!(result.files.is_empty());
+    }
 
-        let result = filter_by_visibility(files, Visibility::Public);
-        assert!(result.is_empty()); // File removed because no public functions
+    #[test]
+    fn test_compute_annotate_exports_with_dunder_all() {
+        let targets = vec![fixtures_dir().join("exports.py")];
+        let filters = Filters {
+            annotate_exports: true,
+            ..Default::default()
+        };
+        let result = compute_functions(&Source::new(&targets), &[], &filters).unwrap();
+        let json = serde_json::to_value(&result).unwrap();
+        let file = json["files"].as_object().unwrap().values().next().unwrap();
+        assert_eq!(file["def connect(url: str) -> Client"]["exported"], true);
+        assert_eq!(file["def disconnect(client: Client) -> None"]["exported"], false);
+
+        let classes = compute_classes(&Source::new(&targets), &[], &filters).unwrap();
+        let classes = classes.files.values().next().unwrap();
+        assert_eq!(classes["class Client"].meta.exported, Some(true));
+        assert_eq!(classes["class Helper"].meta.exported, Some(false));
     }
 
     #[test]
-    fn test_filter_classes_by_visibility_all() {
-        let mut files: BTreeMap<String, ClassMap> = BTreeMap::new();
-        let mut classes = BTreeMap::new();
+    fn test_compute_annotate_exports_without_dunder_all() {
+        let targets = vec![fixtures_dir().join("functions.py")];
+        let filters = Filters {
+            annotate_exports: true,
+            ..Default::default()
+        };
+        let result = compute_functions(&Source::new(&targets), &[], &filters).unwrap();
+        let entries = result.files.values().next().unwrap();
+        assert_eq!(entries["def simple_function()"].meta.exported, Some(true));
+        assert_eq!(entries["def _private_function() -> None"].meta.exported, Some(false));
+
+        // Without the flag, entries stay bare line numbers
+        let plain = compute_functions(&Source::new(&targets), &[], &Filters::default()).unwrap();
+        assert!(!serde_json::to_string(&plain).unwrap().contains("exported"));
+    }
 
-        let mut fields = BTreeMap::new();
-        fields.insert("name: str".to_string(), 1);
-        fields.insert("_private: int".to_string(), 2);
+    /// A temp tree: app/__init__.py (def setup) and app/models/user.py (class User, def load)
+    fn qualname_tree() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        let models = dir.path().join("app/models");
+        std::fs::create_dir_all(&models).unwrap();
+        std::fs::write(dir.path().join("app/__init__.py"), "def setup():\n    pass\n").unwrap();
+        std::fs::write(
+            models.join("user.py"),
+            "class User:\n    def save(self):\n        pass\n\ndef load():\n    pass\n",
+        )
+        .unwrap();
+        dir
+    }
 
-        let mut methods = BTreeMap::new();
-        methods.insert("def public()".to_string(), 3);
-        methods.insert("def _private()".to_string(), 4);
+    #[test]
+    fn test_compute_qualnames() {
+        let dir = qualname_tree();
+        let targets = vec![dir.path().to_path_buf()];
+        let filters = Filters {
+            qualnames: true,
+            ..Default::default()
+        };
 
-        classes.insert("class Test".to_string(), ClassInfo { fields, methods });
-        files.insert("test.py".to_string(), classes);
+        let functions = compute_functions(&Source::new(&targets), &[], &filters).unwrap();
+        let qualnames: Vec<_> = functions
+            .files
+            .values()
+            .flat_map(|e| e.values())
+            .filter_map(|e| e.meta.qualname.clone())
+            .collect();
+        assert_eq!(qualnames, vec!["app.setup", "app.models.user.load"]);
 
-        let result = filter_classes_by_visibility(files, Visibility::All);
-        let classes = result.get("test.py").unwrap();
-        let class_info = classes.get("class Test").unwrap();
-        assert_eq!(class_info.fields.len(), 2);
-        assert_eq!(class_info.methods.len(), 2);
+        let classes = compute_classes(&Source::new(&targets), &[], &filters).unwrap();
+        let user = classes.files.values().next().unwrap()["class User"].clone();
+        assert_eq!(user.meta.qualname.as_deref(), Some("app.models.user.User"));
+        assert_eq!(
+            user.methods["def save(self)"].meta.qualname.as_deref(),
+            Some("app.models.user.User.save")
+        );
+
+        let dump = compute_dump(&Source::new(&targets), &[], &[], &filters).unwrap();
+        let json = serde_json::to_string(&dump).unwrap();
+        assert!(json.contains("\"qualname\":\"app.models.user.User.save\""));
     }
 
     #[test]
-    fn test_filter_classes_by_visibility_public() {
-        let mut files: BTreeMap<String, ClassMap> = BTreeMap::new();
-        let mut classes = BTreeMap::new();
+    fn test_compute_qualnames_file_target() {
+        let dir = qualname_tree();
+        let targets = vec![dir.path().join("app/models/user.py")];
+        let filters = Filters {
+            qualnames: true,
+            ..Default::default()
+        };
+        let functions = compute_functions(&Source::new(&targets), &[], &filters).unwrap();
+        let entry = &functions.files.values().next().unwrap()["def load()"];
+        assert_eq!(entry.meta.qualname.as_deref(), Some("user.load"));
+    }
 
-        let mut fields = BTreeMap::new();
-        fields.insert("name: str".to_string(), 1);
-        fields.insert("_private: int".to_string(), 2);
+    #[test]
+    fn test_compute_names_only() {
+        let targets = vec![fixtures_dir().join("receivers.py"), fixtures_dir().join("enums.py")];
+        let filters = Filters {
+            names_only: true,
+            ..Default::default()
+        };
 
-        let mut methods = BTreeMap::new();
-        methods.insert("def public()".to_string(), 3);
-        methods.insert("def _private()".to_string(), 4);
+        let classes = compute_classes(&Source::new(&targets), &[], &filters).unwrap();
+        let account = classes.files.values().find_map(|c| c.get("Account")).unwrap();
+        assert!(account.methods.contains_key("deposit"));
+        assert!(account.methods.contains_key("open"));
 
-        classes.insert("class Test".to_string(), ClassInfo { fields, methods });
-        files.insert("test.py".to_string(), classes);
+        let enums = compute_enums(&Source::new(&targets), &[], &filters).unwrap();
+        assert!(enums
+            .files
+            .values()
+            .flat_map(|e| e.keys())
+            .all(|k| !k.starts_with("class ")));
 
-        let result = filter_classes_by_visibility(files, Visibility::Public);
-        let classes = result.get("test.py").unwrap();
-        let class_info = classes.get("class Test").unwrap();
-        assert_eq!(class_info.fields.len(), 1);
-        assert_eq!(class_info.methods.len(), 1);
-        assert!(class_info.fields.contains_key("name: str"));
-        assert!(class_info.methods.contains_key("def public()"));
+        let dump = compute_dump(&Source::new(&targets), &[], &[], &filters).unwrap();
+        assert!(dump.files.values().any(|e| e.contains_key("deposit")));
     }
 
     #[test]
-    fn test_filter_classes_by_visibility_private() {
-        let mut files: BTreeMap<String, ClassMap> = BTreeMap::new();
-        let mut classes = BTreeMap::new();
+    fn test_compute_names_only_with_arity() {
+        let targets = vec![fixtures_dir().join("receivers.py")];
+        let mut source = Source::new(&targets);
+        source.extract.with_arity = true;
+        let filters = Filters {
+            names_only: true,
+            ..Default::default()
+        };
 
-        let mut fields = BTreeMap::new();
-        fields.insert("name: str".to_string(), 1);
-        fields.insert("_private: int".to_string(), 2);
+        let classes = compute_classes(&source, &[], &filters).unwrap();
+        let account = classes.files.values().find_map(|c| c.get("Account")).unwrap();
+        let deposit = &account.methods["deposit"];
+        // `self` and `amount`
+        assert_eq!(deposit.meta.arity, Some(2));
+    }
 
-        let mut methods = BTreeMap::new();
-        methods.insert("def public()".to_string(), 3);
-        methods.insert("def _private()".to_string(), 4);
+    #[test]
+    fn test_to_names() {
+        let mut entries = EntryMap::new();
+        entries.insert("def foo(x: int) -> int".to_string(), Entry::new(1));
+        entries.insert("def bar()".to_string(), Entry::new(3));
+        entries.insert("def foo(x: str) -> str".to_string(), Entry::new(6));
 
-        classes.insert("class Test".to_string(), ClassInfo { fields, methods });
-        files.insert("test.py".to_string(), classes);
+        let named = to_names(entries, extract_function_name);
+        let keys: Vec<_> = named.keys().map(String::as_str).collect();
+        assert_eq!(keys, vec!["foo", "bar"]);
+        assert_eq!(named["foo"].line, 1);
+    }
 
-        let result = filter_classes_by_visibility(files, Visibility::Private);
-        let classes = result.get("test.py").unwrap();
-        let class_info = classes.get("class Test").unwrap();
-        assert_eq!(class_info.fields.len(), 1);
-        assert_eq!(class_info.methods.len(), 1);
-        assert!(class_info.fields.contains_key("_private: int"));
-        assert!(class_info.methods.contains_key("def _private()"));
+    #[test]
+    fn test_compute_coverage() {
+        let targets = vec![fixtures_dir().join("coverage.py"), fixtures_dir().join("receivers.py")];
+        let result = compute_coverage(&Source::new(&targets)).unwrap();
+        assert_eq!(result.files.len(), 2);
+
+        // coverage.py: params 4/9, returns 3/6; receivers.py: params 3/6, returns 4/4
+        assert_eq!((result.total.params.annotated, result.total.params.total), (7, 15));
+        assert_eq!(result.total.params.percent(), 46.7);
+        assert_eq!((result.total.returns.annotated, result.total.returns.total), (7, 10));
+        assert_eq!(result.total.returns.percent(), 70.0);
     }
 
     #[test]
-    fn test_filter_classes_removes_empty_classes() {
-        let mut files: BTreeMap<String, ClassMap> = BTreeMap::new();
-        let mut classes = BTreeMap::new();
+    fn test_compute_loc_totals_files() {
+        let targets = vec![fixtures_dir().join("loc.py"), fixtures_dir().join("crlf.py")];
+        let result = compute_loc(&Source::new(&targets)).unwrap();
+        assert_eq!(result.files.len(), 2);
+        // loc.py: 19 lines (5 blank, 4 comment, 10 code); crlf.py: 17 lines (6 blank, 11 code)
+        let total = result.total;
+        assert_eq!((total.lines, total.blank, total.comment, total.code), (36, 11, 4, 21));
+    }
+
+    #[test]
+    fn test_compute_coverage_skips_files_without_functions() {
+        let targets = vec![fixtures_dir().join("enums.py")];
+        let result = compute_coverage(&Source::new(&targets)).unwrap();
+        assert!(result.files.is_empty());
+        assert_eq!(result.total.params.percent(), 100.0);
+    }
 
-        let mut fields = BTreeMap::new();
-        fields.insert("_private: int".to_string(), 1);
+    #[test]
+    fn test_compute_refs() {
+        let targets = vec![fixtures_dir().join("refs.py"), fixtures_dir().join("functions.py")];
+        let result = compute_refs(&Source::new(&targets), "helper").unwrap();
+        // functions.py never calls `helper`, so it is left out
+        assert_eq!(result.files.len(), 1);
+        let sites = result.files.values().next().unwrap();
+        let lines: Vec<usize> = sites.iter().map(|s| s.line).collect();
+        assert_eq!(lines, vec![6, 18]);
 
-        let mut methods = BTreeMap::new();
-        methods.insert("def _private()".to_string(), 2);
+        let yaml = serde_yaml::to_string(&result).unwrap();
+        assert!(yaml.contains("call: helper\n    scope: Service.run"));
+    }
 
-        classes.insert("class Test".to_string(), ClassInfo { fields, methods });
-        files.insert("test.py".to_string(), classes);
+    #[test]
+    fn test_compute_dupes() {
+        let targets = vec![fixtures_dir().join("dupes")];
+        let result = compute_dupes(&Source::new(&targets), None).unwrap();
+        // `only_in_*` are unique and `parse`'s overloads don't count
+        assert_eq!(result.names.keys().collect::<Vec<_>>(), vec!["Settings", "load"]);
 
-        let result = filter_classes_by_visibility(files, Visibility::Public);
-        assert!(result.is_empty()); // File and class removed because no public members
+        let load = &result.names["load"];
+        assert!(load[0].file.ends_with("dupes/a.py") && load[0].line == 4);
+        assert!(load[1].file.ends_with("dupes/b.py") && load[1].line == 1);
+        assert_eq!(result.names["Settings"][1].kind, "class");
     }
 
     #[test]
-    fn test_process_files_parallel_empty() {
-        let files: Vec<PathBuf> = vec![];
-        let result = process_files_parallel(&files, |_| None);
-        assert!(result.is_empty());
+    fn test_compute_dupes_by_kind() {
+        let targets = vec![fixtures_dir().join("dupes")];
+        let functions = compute_dupes(&Source::new(&targets), Some(DupeKind::Function)).unwrap();
+        assert_eq!(functions.names.keys().collect::<Vec<_>>(), vec!["load"]);
+        let classes = compute_dupes(&Source::new(&targets), Some(DupeKind::Class)).unwrap();
+        assert_eq!(classes.names.keys().collect::<Vec<_>>(), vec!["Settings"]);
     }
 
     #[test]
-    fn test_process_files_parallel_with_files() {
-        let files = vec![fixtures_dir().join("functions.py"), fixtures_dir().join("classes.py")];
-        let result = process_files_parallel(&files, |path| {
-            let functions = analysis::extract_functions(path).ok()?;
-            if functions.is_empty() {
-                None
-            } else {
-                Some(functions)
-            }
-        });
+    fn test_compute_dupes_none() {
+        let targets = vec![fixtures_dir().join("dupes/a.py")];
+        let result = compute_dupes(&Source::new(&targets), None).unwrap();
+        assert!(result.names.is_empty());
+    }
+
+    #[test]
+    fn test_compute_decorators() {
+        let targets = vec![fixtures_dir().join("decorators")];
+        let result = compute_decorators(&Source::new(&targets), &[], MatchMode::default()).unwrap();
+        assert_eq!(
+            result.decorators.keys().collect::<Vec<_>>(),
+            vec!["app.route", "cache", "property", "staticmethod"]
+        );
+
+        let cache = &result.decorators["cache"];
+        assert_eq!((cache.count, cache.files), (2, 2));
+        let uses: Vec<(&str, usize)> = cache.uses.iter().map(|u| (u.name.as_str(), u.line)).collect();
+        assert_eq!(uses, vec![("Order.rates", 12), ("about", 14)]);
+        assert!(cache.uses[0].file.ends_with("decorators/models.py"));
+
+        let route = &result.decorators["app.route"];
+        assert_eq!((route.count, route.files), (2, 1));
+        assert_eq!(route.uses[0].line, 8);
+
+        let result = compute_decorators(&Source::new(&targets), &["route".to_string()], MatchMode::default()).unwrap();
+        assert_eq!(result.decorators.keys().collect::<Vec<_>>(), vec!["app.route"]);
+    }
+
+    #[test]
+    fn test_group_by_dir_merges_files() {
+        let targets = vec![fixtures_dir().join("dupes")];
+        let result = compute_functions(&Source::new(&targets), &[], &Filters::default()).unwrap();
+        assert_eq!(result.files.len(), 2);
+
+        let grouped = group_by_dir(result.files);
+        let dir = fixtures_dir().join("dupes").to_string_lossy().to_string();
+        assert_eq!(grouped.keys().collect::<Vec<_>>(), vec![&dir]);
+        let functions = &grouped[&dir];
+        assert!(functions.contains_key("def only_in_a()") && functions.contains_key("def only_in_b()"));
+        assert_eq!(functions["def load(path)"].line, 4);
+        assert_eq!(functions["def load(url, timeout)"].line, 1);
+    }
+
+    #[test]
+    fn test_label_roots_splits_by_target() {
+        let dupes = fixtures_dir().join("dupes");
+        let stubs = fixtures_dir().join("stubs");
+        let functions = fixtures_dir().join("functions.py");
+        let targets = vec![dupes.clone(), stubs.clone(), functions.clone()];
+        let source = Source::new(&targets);
+        let result = compute_functions(&source, &[], &Filters::default()).unwrap();
+
+        let labeled = source.label_roots(result.files, |files| FilesOutput { files });
+        let label = |path: &PathBuf| path.to_string_lossy().to_string();
+        assert_eq!(
+            labeled.roots.keys().collect::<Vec<_>>(),
+            vec![&label(&dupes), &label(&functions), &label(&stubs)]
+        );
+        let files = |root: &PathBuf| labeled.roots[&label(root)].files.keys().cloned().collect::<Vec<_>>();
+        assert_eq!(files(&dupes), vec!["a.py", "b.py"]);
+        assert_eq!(files(&stubs), vec!["sample.py"]);
+        assert_eq!(files(&functions), vec!["functions.py"]);
+        assert!(labeled.roots[&label(&dupes)].files["a.py"].contains_key("def only_in_a()"));
+    }
+
+    #[test]
+    fn test_absolute_paths() {
+        // Tests run from the crate root, so this relative target resolves
+        let targets = vec![PathBuf::from("tests/fixtures/functions.py")];
+        let result = compute_functions(&Source::new(&targets), &[], &Filters::default()).unwrap();
+        assert_eq!(
+            result.files.keys().collect::<Vec<_>>(),
+            vec!["tests/fixtures/functions.py"]
+        );
+
+        let files = absolute_paths(result.files);
+        let key = files.keys().next().unwrap();
+        assert!(Path::new(key).is_absolute(), "{}", key);
+        assert_eq!(
+            Path::new(key),
+            std::fs::canonicalize(fixtures_dir().join("functions.py")).unwrap()
+        );
+
+        // Paths that can't be resolved are kept as they are
+        assert_eq!(absolute_path("no/such/file.py"), "no/such/file.py");
+    }
+
+    #[test]
+    fn test_limit_per_file() {
+        let targets = vec![fixtures_dir()];
+        let mut result = compute_functions(&Source::new(&targets), &[], &Filters::default()).unwrap();
+        sort::sort_files(&mut result.files, SortOrder::new(SortBy::Name, false));
+        let expected: BTreeMap<String, Vec<String>> = result
+            .files
+            .iter()
+            .map(|(path, functions)| (path.clone(), functions.keys().take(2).cloned().collect()))
+            .collect();
+        let cut = result.files.values().filter(|functions| functions.len() > 2).count();
+        assert!(cut > 0);
+
+        let truncated = limit_per_file(&mut result.files, Some(2));
+        assert_eq!(truncated.len(), cut);
+        assert!(truncated[0].ends_with("symbols (--per-file-limit)"), "{}", truncated[0]);
+        for (path, functions) in &result.files {
+            assert!(functions.len() <= 2, "{} has {} functions", path, functions.len());
+            // The first symbols in sort order are the ones kept
+            assert_eq!(functions.keys().cloned().collect::<Vec<_>>(), expected[path]);
+        }
+
+        assert!(limit_per_file(&mut result.files, None).is_empty());
+    }
+
+    #[test]
+    fn test_group_by_dir_keys() {
+        let mut files: BTreeMap<String, EntryMap> = BTreeMap::new();
+        for path in ["top.py", "pkg/a.py", "pkg/sub/b.py"] {
+            files
+                .entry(path.to_string())
+                .or_default()
+                .insert("def f()".to_string(), 1.into());
+        }
+        let grouped = group_by_dir(files);
+        assert_eq!(grouped.keys().collect::<Vec<_>>(), vec![".", "pkg", "pkg/sub"]);
+    }
+
+    #[test]
+    fn test_group_by_dir_keeps_shared_signatures() {
+        let mut files: BTreeMap<String, EntryMap> = BTreeMap::new();
+        for (path, signature, line) in [
+            ("pkg/a.py", "def main()", 3),
+            ("pkg/a.py", "def only_a()", 7),
+            ("pkg/b.py", "def main()", 5),
+        ] {
+            files
+                .entry(path.to_string())
+                .or_default()
+                .insert(signature.to_string(), Entry::new(line));
+        }
+        let grouped = group_by_dir(files);
+        let lines: Vec<_> = grouped["pkg"]
+            .iter()
+            .map(|(sig, entry)| (sig.as_str(), entry.line))
+            .collect();
+        assert_eq!(
+            lines,
+            vec![("a.py::def main()", 3), ("def only_a()", 7), ("b.py::def main()", 5)]
+        );
+    }
+
+    #[test]
+    fn test_compute_untyped() {
+        let targets = vec![fixtures_dir().join("coverage.py")];
+        let result = compute_untyped(&Source::new(&targets), &[], &Filters::default()).unwrap();
+        let untyped = result.files.values().next().unwrap();
+        assert_eq!(untyped.len(), 4);
+        assert!(!untyped.keys().any(|k| k.starts_with("def typed")));
+    }
+
+    #[test]
+    fn test_compute_untyped_public() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("api.py"),
+            "def run(x):\n    pass\n\ndef _helper(x):\n    pass\n\nclass Api:\n    def _load(self, x):\n        pass\n",
+        )
+        .unwrap();
+        let targets = vec![dir.path().to_path_buf()];
+        let filters = Filters {
+            visibility: Visibility::Public,
+            ..Default::default()
+        };
+        let result = compute_untyped(&Source::new(&targets), &[], &filters).unwrap();
+        let keys: Vec<_> = result.files.values().flat_map(|e| e.keys()).collect();
+        assert_eq!(keys, vec!["def run(x)"]);
+    }
+
+    #[test]
+    fn test_compute_functions_args_filters() {
+        let targets = vec![fixtures_dir().join("args.py")];
+        let max_three = Filters {
+            max_args: Some(3),
+            ..Default::default()
+        };
+        let result = compute_functions(&Source::new(&targets), &[], &max_three).unwrap();
+        let names: Vec<_> = result
+            .files
+            .values()
+            .flat_map(|e| e.keys())
+            .map(|k| extract_function_name(k))
+            .collect();
+        assert_eq!(names, vec!["two"]);
+
+        let min_four = Filters {
+            min_args: Some(4),
+            ..Default::default()
+        };
+        let result = compute_functions(&Source::new(&targets), &[], &min_four).unwrap();
+        let names: Vec<_> = result
+            .files
+            .values()
+            .flat_map(|e| e.keys())
+            .map(|k| extract_function_name(k))
+            .collect();
+        assert_eq!(names, vec!["five"]);
+
+        // Composes with name patterns
+        let result = compute_functions(&Source::new(&targets), &["tw".to_string()], &min_four).unwrap();
+        assert!(result.files.is_empty());
+    }
+
+    #[test]
+    fn test_compute_classes_args_filters() {
+        let targets = vec![fixtures_dir().join("args.py")];
+        let filters = Filters {
+            min_args: Some(4),
+            ..Default::default()
+        };
+        let result = compute_classes(&Source::new(&targets), &[], &filters).unwrap();
+        let methods = &result.files.values().next().unwrap()["class Builder"].methods;
+        let names: Vec<_> = methods.keys().map(|k| extract_function_name(k)).collect();
+        assert_eq!(names, vec!["configure"]);
+
+        let filters = Filters {
+            max_args: Some(0),
+            ..Default::default()
+        };
+        let result = compute_classes(&Source::new(&targets), &[], &filters).unwrap();
+        assert!(result.files.is_empty());
+    }
+
+    #[test]
+    fn test_compute_lines_filters() {
+        let targets = vec![fixtures_dir().join("sizes.py")];
+        let min_twenty = Filters {
+            min_lines: Some(20),
+            ..Default::default()
+        };
+        let result = compute_functions(&Source::new(&targets), &[], &min_twenty).unwrap();
+        let names: Vec<_> = result
+            .files
+            .values()
+            .flat_map(|e| e.keys())
+            .map(|k| extract_function_name(k))
+            .collect();
+        assert_eq!(names, vec!["large"]);
+
+        let min_ten = Filters {
+            min_lines: Some(10),
+            ..Default::default()
+        };
+        let result = compute_classes(&Source::new(&targets), &[], &min_ten).unwrap();
+        let methods = &result.files.values().next().unwrap()["class Report"].methods;
+        let names: Vec<_> = methods.keys().map(|k| extract_function_name(k)).collect();
+        assert_eq!(names, vec!["render"]);
+
+        let max_two = Filters {
+            max_lines: Some(2),
+            ..Default::default()
+        };
+        let result = compute_functions(&Source::new(&targets), &[], &max_two).unwrap();
+        let names: Vec<_> = result
+            .files
+            .values()
+            .flat_map(|e| e.keys())
+            .map(|k| extract_function_name(k))
+            .collect();
+        assert_eq!(names, vec!["small"]);
+
+        // Composes with name patterns
+        let result = compute_functions(&Source::new(&targets), &["small".to_string()], &min_twenty).unwrap();
+        assert!(result.files.is_empty());
+    }
+
+    #[test]
+    fn test_compute_classes_roots_and_derived() {
+        let targets = vec![fixtures_dir().join("overrides.py"), fixtures_dir().join("generics.py")];
+        let classes = |filters: Filters| {
+            let result = compute_classes(&Source::new(&targets), &[], &filters).unwrap();
+            let names: Vec<String> = result
+                .files
+                .values()
+                .flat_map(|classes| classes.keys().map(|k| extract_class_name(k).to_string()))
+                .collect();
+            names
+        };
+
+        let roots = Filters {
+            roots: true,
+            ..Default::default()
+        };
+        // Type parameters aren't bases
+        assert_eq!(classes(roots), vec!["Stack", "Shape"]);
+
+        let derived = Filters {
+            derived: true,
+            ..Default::default()
+        };
+        assert_eq!(classes(derived), vec!["Registry", "Rectangle", "Square", "Widget"]);
+    }
+
+    #[test]
+    fn test_compute_classes_method_count_filters() {
+        let targets = vec![fixtures_dir().join("method_counts.py")];
+        let classes = |filters: Filters, patterns: &[&str]| {
+            let patterns: Vec<String> = patterns.iter().map(|p| p.to_string()).collect();
+            let result = compute_classes(&Source::new(&targets), &patterns, &filters).unwrap();
+            let names: Vec<String> = result
+                .files
+                .values()
+                .flat_map(|classes| classes.keys().map(|k| extract_class_name(k).to_string()))
+                .collect();
+            names
+        };
+
+        let min_two = Filters {
+            min_methods: Some(2),
+            ..Default::default()
+        };
+        // Single's one method isn't enough
+        assert_eq!(classes(min_two, &[]), vec!["Service"]);
+        assert!(classes(min_two, &["Single"]).is_empty());
+
+        let empty = Filters {
+            max_methods: Some(0),
+            ..Default::default()
+        };
+        assert_eq!(classes(empty, &[]), vec!["Empty"]);
 
-        // functions.py should have functions, classes.py should have none at top level
-        assert!(result.len() >= 1);
+        // Counted after visibility filtering: Service has two public methods
+        let public_three = Filters {
+            visibility: Visibility::Public,
+            min_methods: Some(3),
+            ..Default::default()
+        };
+        assert!(classes(public_three, &[]).is_empty());
     }
 
     #[test]
-    fn test_process_classes_parallel_empty() {
-        let files: Vec<PathBuf> = vec![];
-        let result = process_classes_parallel(&files, |_| None);
-        assert!(result.is_empty());
+    fn test_compute_classes_model_only() {
+        let targets = vec![fixtures_dir().join("models.py")];
+        let mut source = Source::new(&targets);
+        source.extract.models = true;
+        let filters = Filters {
+            model_only: true,
+            ..Default::default()
+        };
+        let result = compute_classes(&source, &[], &filters).unwrap();
+        let classes = result.files.values().next().unwrap();
+
+        let names: Vec<_> = classes.keys().map(|k| extract_class_name(k)).collect();
+        assert_eq!(names, vec!["User", "Point", "Config"]);
+        let user = &classes["class User(BaseModel)"];
+        assert!(user.methods.is_empty());
+        assert!(!user.fields.keys().any(|k| k.starts_with("registry")));
+
+        let yaml = serde_yaml::to_string(&result).unwrap();
+        assert!(yaml.contains("model: pydantic"));
+        assert!(yaml.contains("model: dataclass"));
     }
 
     #[test]
-    fn test_process_classes_parallel_with_files() {
-        let files = vec![fixtures_dir().join("classes.py")];
-        let result = process_classes_parallel(&files, |path| {
-            let classes = analysis::extract_classes(path).ok()?;
-            if classes.is_empty() {
-                None
-            } else {
-                Some(classes)
-            }
-        });
+    fn test_compute_classes_by_kind() {
+        let targets = vec![fixtures_dir().join("structural_types.py")];
+        let mut source = Source::new(&targets);
+        source.extract.functional_classes = true;
+        let names = |kind| {
+            let filters = Filters {
+                kind: Some(kind),
+                ..Default::default()
+            };
+            let result = compute_classes(&source, &[], &filters).unwrap();
+            result
+                .files
+                .values()
+                .flat_map(|classes| classes.keys().map(|k| extract_class_name(k).to_string()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(names(ClassKind::Protocol), vec!["Closeable", "SupportsRead"]);
+        assert_eq!(names(ClassKind::TypedDict), vec!["Movie", "Album"]);
+        assert_eq!(names(ClassKind::NamedTuple), vec!["Point", "Pair", "Size", "Triple"]);
 
-        assert!(!result.is_empty());
+        let movie = |filters: &Filters| {
+            let result = compute_classes(&Source::new(&targets), &["Movie".to_string()], filters).unwrap();
+            serde_yaml::to_string(&result).unwrap()
+        };
+        assert!(!movie(&Filters::default()).contains("kind:"));
+        let filters = Filters {
+            kind: Some(ClassKind::TypedDict),
+            ..Default::default()
+        };
+        let yaml = movie(&filters);
+        assert!(yaml.contains("kind: typeddict"), "{}", yaml);
     }
 
-    // Integration tests that exercise the run_* functions indirectly
     #[test]
-    fn test_integration_extract_functions_and_filter() {
-        let targets = vec![fixtures_dir().join("functions.py")];
-        let files = walk::collect_python_files(&targets).unwrap();
-        let collected = process_files_parallel(&files, |path| {
-            let functions = analysis::extract_functions(path).ok()?;
-            if functions.is_empty() {
-                None
-            } else {
-                Some(functions)
-            }
-        });
-        let filtered = filter_files_output(collected, &["simple".to_string()], extract_function_name);
-        let filtered = filter_by_visibility(filtered, Visibility::All);
+    fn test_compute_classes_overrides() {
+        let targets = vec![fixtures_dir().join("overrides.py")];
+        let filters = Filters {
+            overrides: true,
+            ..Default::default()
+        };
+        // The base class is filtered out but still resolves
+        let result = compute_classes(&Source::new(&targets), &["Rectangle".to_string()], &filters).unwrap();
+        let classes = result.files.values().next().unwrap();
+        let rectangle = &classes["class Rectangle(Shape)"];
+        assert_eq!(
+            rectangle.methods["def area(self) -> float"].meta.overrides.as_deref(),
+            Some("Shape")
+        );
+        assert!(!classes.contains_key("class Shape"));
 
-        assert!(!filtered.is_empty());
-        let has_simple = filtered
+        let yaml = serde_yaml::to_string(&result).unwrap();
+        assert!(yaml.contains("overrides: Shape"));
+    }
+
+    #[test]
+    fn test_compute_classes_no_overrides_by_default() {
+        let targets = vec![fixtures_dir().join("overrides.py")];
+        let result = compute_classes(&Source::new(&targets), &[], &Filters::default()).unwrap();
+        let classes = result.files.values().next().unwrap();
+        assert!(classes
             .values()
-            .any(|entries| entries.keys().any(|k| k.contains("simple_function")));
-        assert!(has_simple);
+            .flat_map(|c| c.methods.values())
+            .all(|e| e.meta.overrides.is_none()));
     }
 
     #[test]
-    fn test_integration_extract_classes_and_filter() {
-        let targets = vec![fixtures_dir().join("classes.py")];
-        let files = walk::collect_python_files(&targets).unwrap();
-        let collected = process_classes_parallel(&files, |path| {
-            let classes = analysis::extract_classes(path).ok()?;
-            if classes.is_empty() {
-                None
-            } else {
-                Some(classes)
-            }
-        });
-        let filtered = filter_classes_output(collected, &["Class".to_string()]);
-        let filtered = filter_classes_by_visibility(filtered, Visibility::All);
+    fn test_compute_diff() {
+        let old = vec![fixtures_dir().join("diff/old")];
+        let new = vec![fixtures_dir().join("diff/new")];
+        let result = compute_diff(&Source::new(&old), &Source::new(&new)).unwrap();
 
-        assert!(!filtered.is_empty());
+        // Files are matched by their path within each tree
+        let diff = &result.files["api.py"];
+        assert_eq!(diff.added, vec!["def retry(times: int) -> None"]);
+        assert!(diff.removed.is_empty());
+        assert_eq!(diff.changed.len(), 1);
+        assert_eq!(diff.changed["parse"].old, "def parse(data)");
+        assert_eq!(diff.changed["parse"].new, "def parse(data: bytes) -> dict");
     }
 
     #[test]
-    fn test_integration_extract_enums_and_filter() {
-        let targets = vec![fixtures_dir().join("enums.py")];
-        let files = walk::collect_python_files(&targets).unwrap();
-        let collected = process_files_parallel(&files, |path| {
-            let enums = analysis::extract_enums(path).ok()?;
-            if enums.is_empty() {
-                None
-            } else {
-                Some(enums)
-            }
-        });
-        let filtered = filter_files_output(collected, &["Color".to_string()], extract_class_name);
+    fn test_compute_diff_reversed_and_identical() {
+        let old = vec![fixtures_dir().join("diff/old")];
+        let new = vec![fixtures_dir().join("diff/new")];
+        let result = compute_diff(&Source::new(&new), &Source::new(&old)).unwrap();
+        let diff = &result.files["api.py"];
+        assert_eq!(diff.removed, vec!["def retry(times: int) -> None"]);
+        assert!(diff.added.is_empty());
 
-        assert!(!filtered.is_empty());
+        let result = compute_diff(&Source::new(&old), &Source::new(&old)).unwrap();
+        assert!(result.files.is_empty());
     }
 
     #[test]
-    fn test_integration_build_module_tree() {
-        let targets = vec![fixtures_dir().join("pkg")];
-        let files = walk::collect_python_files(&targets).unwrap();
+    fn test_signature_index_qualified_names() {
+        let targets = vec![fixtures_dir().join("diff/old")];
+        let index = signature_index(&Source::new(&targets)).unwrap();
+        let names: Vec<&str> = index["api.py"].keys().map(String::as_str).collect();
+        assert_eq!(names, vec!["fetch", "parse", "Client", "Client.get"]);
+        assert_eq!(
+            index["api.py"]["Client.get"],
+            "Client.def get(self, path: str) -> bytes"
+        );
+    }
 
-        let base_path = &targets[0];
-        let result = analysis::build_module_tree(&files, base_path);
-        let filtered = pattern::filter_modules_output(result, &[]);
+    #[test]
+    fn test_compute_stubs() {
+        let targets = vec![fixtures_dir().join("stubs")];
+        let source = Source::new(&targets);
+        let stubs = compute_stubs(&source).unwrap();
+        let file = fixtures_dir().join("stubs/sample.py");
+        assert!(stubs[&file].contains("class Store[T](dict[str, T], metaclass=type):"));
 
-        assert!(!filtered.modules.is_empty());
+        // Mirrored under the output directory, relative to the target
+        let dest = stub_path(&source, &file, Path::new("out")).unwrap();
+        assert_eq!(dest, PathBuf::from("out/sample.pyi"));
     }
 
     #[test]
-    fn test_integration_mixed_file_dump_style() {
-        let targets = vec![fixtures_dir().join("mixed.py")];
-        let files = walk::collect_python_files(&targets).unwrap();
-        let collected = process_files_parallel(&files, |path| {
-            let mut all_entries = BTreeMap::new();
-
-            if let Ok(functions) = analysis::extract_functions(path) {
-                all_entries.extend(functions);
-            }
-            if let Ok(classes) = analysis::extract_classes(path) {
-                for (class_sig, class_info) in classes {
-                    let class_name = class_sig
-                        .strip_prefix("class ")
-                        .and_then(|s| s.split('(').next())
-                        .unwrap_or(&class_sig);
-                    for (method_sig, line) in class_info.methods {
-                        let full_sig = format!("{}.{}", class_name, method_sig);
-                        all_entries.insert(full_sig, line);
-                    }
-                }
-            }
-            if let Ok(enums) = analysis::extract_enums(path) {
-                all_entries.extend(enums);
-            }
+    fn test_stub_path_relative_to_each_target() {
+        let dupes = fixtures_dir().join("dupes");
+        let stubs = fixtures_dir().join("stubs");
+        let targets = vec![dupes.clone(), stubs.clone()];
+        let source = Source::new(&targets);
+        let out = Path::new("out");
+        assert_eq!(
+            stub_path(&source, &dupes.join("a.py"), out).unwrap(),
+            PathBuf::from("out/a.pyi")
+        );
+        assert_eq!(
+            stub_path(&source, &stubs.join("sample.py"), out).unwrap(),
+            PathBuf::from("out/sample.pyi")
+        );
+        // Listed files outside every target stay below the output directory
+        assert_eq!(
+            stub_path(&source, Path::new("/elsewhere/pkg/mod.py"), out).unwrap(),
+            PathBuf::from("out/elsewhere/pkg/mod.pyi")
+        );
+        assert!(stub_path(&source, Path::new("../pkg/mod.py"), out).is_err());
+    }
 
-            if all_entries.is_empty() {
-                None
-            } else {
-                Some(all_entries)
-            }
-        });
+    #[test]
+    fn test_classify_dump_signature() {
+        assert_eq!(
+            classify_dump_signature("async def fetch(url: str) -> bytes"),
+            (TagKind::Function, None, "fetch")
+        );
+        assert_eq!(
+            classify_dump_signature("Svc.def run(self, job: a.Job)"),
+            (TagKind::Method, Some("Svc"), "run")
+        );
+        assert_eq!(
+            classify_dump_signature("class Color(Enum)"),
+            (TagKind::Enum, None, "Color")
+        );
+        assert_eq!(
+            classify_dump_signature("TIMEOUT: float = 2.5"),
+            (TagKind::Variable, None, "TIMEOUT")
+        );
+    }
 
-        assert!(!collected.is_empty());
-        let filtered = filter_files_output(collected, &["helper".to_string()], pattern::extract_dump_name);
-        assert!(!filtered.is_empty());
+    #[test]
+    fn test_class_tags() {
+        let targets = vec![fixtures_dir().join("overrides.py")];
+        let result = compute_classes(&Source::new(&targets), &["Rectangle".to_string()], &Filters::default()).unwrap();
+        let tags = class_tags(&result.files);
+        let summary: Vec<_> = tags
+            .iter()
+            .map(|t| (t.name.as_str(), t.line, t.kind, t.scope.as_deref()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("Rectangle", 12, TagKind::Class, None),
+                ("area", 13, TagKind::Method, Some("Rectangle")),
+                ("corners", 16, TagKind::Method, Some("Rectangle")),
+            ]
+        );
     }
 
     #[test]
-    fn test_integration_visibility_filtering_public() {
-        let targets = vec![fixtures_dir().join("functions.py")];
-        let files = walk::collect_python_files(&targets).unwrap();
-        let collected = process_files_parallel(&files, |path| {
-            let functions = analysis::extract_functions(path).ok()?;
-            if functions.is_empty() {
-                None
-            } else {
-                Some(functions)
-            }
-        });
-        let filtered = filter_files_output(collected, &[], extract_function_name);
-        let public_only = filter_by_visibility(filtered.clone(), Visibility::Public);
-        let private_only = filter_by_visibility(filtered, Visibility::Private);
+    fn test_class_symbols_lsp_shape() {
+        let targets = vec![fixtures_dir().join("overrides.py")];
+        let result = compute_classes(&Source::new(&targets), &["Rectangle".to_string()], &Filters::default()).unwrap();
+        let json = serde_json::to_value(class_symbols(&result.files)).unwrap();
+        let rectangle = &json.as_object().unwrap().values().next().unwrap()[0];
 
-        let public_count: usize = public_only.values().map(|e| e.len()).sum();
-        let private_count: usize = private_only.values().map(|e| e.len()).sum();
+        // SymbolKind.Class = 5, SymbolKind.Method = 6; lines are zero-based
+        assert_eq!(rectangle["name"], "Rectangle");
+        assert_eq!(rectangle["kind"], 5);
+        assert_eq!(
+            rectangle["range"]["start"],
+            serde_json::json!({"line": 11, "character": 0})
+        );
+        assert_eq!(
+            rectangle["range"]["end"],
+            serde_json::json!({"line": 17, "character": 0})
+        );
+        assert_eq!(rectangle["selectionRange"]["end"]["line"], 12);
+        let area = &rectangle["children"][0];
+        assert_eq!(area["name"], "area");
+        assert_eq!(area["detail"], "def area(self) -> float");
+        assert_eq!(area["kind"], 6);
+        assert!(area.get("children").is_none());
+    }
 
-        assert!(public_count > 0);
-        assert!(private_count > 0);
+    #[test]
+    fn test_file_symbols_lsp_kinds() {
+        let targets = vec![fixtures_dir().join("enums.py")];
+        let result = compute_enums(&Source::new(&targets), &[], &Filters::default()).unwrap();
+        let symbols = file_symbols(&result.files, SymbolKind::Enum, extract_class_name);
+        let json = serde_json::to_value(&symbols).unwrap();
+        let first = &json.as_object().unwrap().values().next().unwrap()[0];
+        // SymbolKind.Enum = 10
+        assert_eq!(first["kind"], 10);
+        assert_eq!(serde_json::to_value(SymbolKind::Function).unwrap(), 12);
     }
 
     #[test]
-    fn test_integration_class_visibility_filtering() {
-        let targets = vec![fixtures_dir().join("classes.py")];
-        let files = walk::collect_python_files(&targets).unwrap();
-        let collected = process_classes_parallel(&files, |path| {
-            let classes = analysis::extract_classes(path).ok()?;
-            if classes.is_empty() {
-                None
-            } else {
-                Some(classes)
-            }
-        });
-        let filtered = filter_classes_output(collected, &[]);
-        let public_only = filter_classes_by_visibility(filtered.clone(), Visibility::Public);
-        let private_only = filter_classes_by_visibility(filtered, Visibility::Private);
+    fn test_class_tags_to_sqlite() {
+        let targets = vec![fixtures_dir().join("overrides.py")];
+        let source = Source::new(&targets);
+        let result = compute_classes(&source, &[], &Filters::default()).unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let db = dir.path().join("pyr.db");
+        output::write_sqlite(&db, &source.scanned_paths(false), &class_tags(&result.files)).unwrap();
 
-        let has_public = public_only.values().any(|classes| {
-            classes
-                .values()
-                .any(|info| !info.fields.is_empty() || !info.methods.is_empty())
-        });
-        let has_private = private_only.values().any(|classes| {
-            classes
-                .values()
-                .any(|info| !info.fields.is_empty() || !info.methods.is_empty())
-        });
+        let conn = rusqlite::Connection::open(&db).unwrap();
+        let mut stmt = conn
+            .prepare(
+                "SELECT parent FROM symbols WHERE kind = 'method'
+                 GROUP BY file_id, parent HAVING COUNT(*) > 1 ORDER BY parent",
+            )
+            .unwrap();
+        let classes: Vec<String> = stmt
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(classes, vec!["Rectangle", "Shape", "Square"]);
 
-        assert!(has_public);
-        assert!(has_private);
+        let signature: String = conn
+            .query_row("SELECT signature FROM symbols WHERE name = 'Square'", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(signature, "class Square(Rectangle)");
+    }
+
+    /// A directory with one valid file, one that isn't UTF-8 and one that doesn't parse
+    fn mixed_dir() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("good.py"), "def ok():\n    pass\n").unwrap();
+        std::fs::write(dir.path().join("broken.py"), "def f(:\n").unwrap();
+        std::fs::write(dir.path().join("binary.py"), b"x = '\xff\xfe'\n").unwrap();
+        dir
     }
 
     #[test]
-    fn test_integration_modules_with_base_path_file() {
-        let targets = vec![fixtures_dir().join("functions.py")];
-        let files = walk::collect_python_files(&targets).unwrap();
+    fn test_compute_skips_unreadable_files() {
+        let dir = mixed_dir();
+        let targets = vec![dir.path().to_path_buf()];
+        let source = Source::new(&targets);
+        let result = compute_functions(&source, &[], &Filters::default()).unwrap();
 
-        let base_path = targets
-            .first()
-            .map(|p| {
-                if p.is_dir() {
-                    p.clone()
-                } else {
-                    p.parent().map(|p| p.to_path_buf()).unwrap_or_default()
-                }
-            })
-            .unwrap_or_else(|| PathBuf::from("."));
+        // Valid results still appear
+        assert_eq!(result.files.len(), 1);
+        assert!(result.files.keys().next().unwrap().ends_with("good.py"));
 
-        let result = analysis::build_module_tree(&files, &base_path);
-        assert!(!result.modules.is_empty());
+        let skipped = source.skipped.into_inner();
+        let names: Vec<_> = skipped.keys().map(|p| p.file_name().unwrap().to_owned()).collect();
+        assert_eq!(names, vec!["binary.py", "broken.py"]);
+        assert!(skipped[&dir.path().join("broken.py")].contains("invalid syntax"));
     }
 
-    // Tests for compute_* functions
     #[test]
-    fn test_compute_functions() {
-        let targets = vec![fixtures_dir().join("functions.py")];
-        let result = compute_functions(&targets, &[], Visibility::All).unwrap();
-        assert!(!result.files.is_empty());
+    fn test_compute_dump_records_each_skipped_file_once() {
+        let dir = mixed_dir();
+        let targets = vec![dir.path().to_path_buf()];
+        let source = Source::new(&targets);
+        // Every extractor fails on the same files
+        compute_dump(&source, &[], &[], &Filters::default()).unwrap();
+        assert_eq!(source.skipped.into_inner().len(), 2);
     }
 
     #[test]
-    fn test_compute_functions_with_pattern() {
-        let targets = vec![fixtures_dir().join("functions.py")];
-        let result = compute_functions(&targets, &["simple".to_string()], Visibility::All).unwrap();
-        assert!(!result.files.is_empty());
-        let has_simple = result.files.values().any(|e| e.keys().any(|k| k.contains("simple")));
-        assert!(has_simple);
+    fn test_report_skipped_fails_only_when_strict() {
+        assert!(report_skipped(BTreeMap::new(), 0, true).is_ok());
+        let skipped = BTreeMap::from([(PathBuf::from("bad.py"), "invalid syntax".to_string())]);
+        assert!(report_skipped(skipped.clone(), 1, false).is_ok());
+        let err = report_skipped(skipped, 0, true).unwrap_err();
+        assert!(err.to_string().contains("1 file skipped due to errors"));
     }
 
     #[test]
-    fn test_compute_functions_visibility_public() {
+    fn test_timings_report_by_verbosity() {
         let targets = vec![fixtures_dir().join("functions.py")];
-        let result = compute_functions(&targets, &[], Visibility::Public).unwrap();
-        // Should have only public functions
-        for entries in result.files.values() {
-            for sig in entries.keys() {
-                let name = extract_function_name(sig);
-                assert!(!name.starts_with('_'), "Should not have private function: {}", name);
-            }
-        }
+        let source = Source::new(&targets);
+        compute_functions(&source, &[], &Filters::default()).unwrap();
+        source.timings.lap("filter");
+        source
+            .timings
+            .0
+            .lock()
+            .unwrap()
+            .slow_files
+            .push((PathBuf::from("big.py"), Duration::from_millis(80)));
+
+        assert!(source.timings.report(0).is_empty());
+        let report = source.timings.report(1);
+        assert_eq!(report.len(), 2);
+        assert_eq!(report[0], "1 file discovered");
+        assert!(report[1].starts_with("walk "));
+        assert!(report[1].contains(", parse ") && report[1].contains(", filter "));
+        let report = source.timings.report(2);
+        assert!(report[2].starts_with("slow file: big.py ("));
     }
 
     #[test]
-    fn test_compute_functions_visibility_private() {
+    fn test_retargeted_timings_add_up() {
         let targets = vec![fixtures_dir().join("functions.py")];
-        let result = compute_functions(&targets, &[], Visibility::Private).unwrap();
-        // Should have only private functions
-        for entries in result.files.values() {
-            for sig in entries.keys() {
-                let name = extract_function_name(sig);
-                assert!(name.starts_with('_'), "Should only have private functions: {}", name);
-            }
-        }
+        let source = Source::new(&targets);
+        let other = source.retarget(&targets);
+        source.collect_files().unwrap();
+        other.collect_files().unwrap();
+        assert_eq!(source.timings.report(1)[0], "2 files discovered");
     }
 
     #[test]
-    fn test_compute_classes() {
-        let targets = vec![fixtures_dir().join("classes.py")];
-        let result = compute_classes(&targets, &[], Visibility::All).unwrap();
+    fn test_compute_enums() {
+        let targets = vec![fixtures_dir().join("enums.py")];
+        let result = compute_enums(&Source::new(&targets), &[], &Filters::default()).unwrap();
         assert!(!result.files.is_empty());
     }
 
     #[test]
-    fn test_compute_classes_with_pattern() {
-        let targets = vec![fixtures_dir().join("classes.py")];
-        let result = compute_classes(&targets, &["Simple".to_string()], Visibility::All).unwrap();
-        assert!(!result.files.is_empty());
-    }
+    fn test_compute_enums_visibility() {
+        let targets = vec![fixtures_dir().join("enums.py")];
+        let source = Source::new(&targets);
+        let filters_for = |visibility| Filters {
+            visibility,
+            ..Default::default()
+        };
 
-    #[test]
-    fn test_compute_classes_visibility_public() {
-        let targets = vec![fixtures_dir().join("classes.py")];
-        let result = compute_classes(&targets, &[], Visibility::Public).unwrap();
-        // Check that private fields/methods are filtered
-        for classes in result.files.values() {
-            for class_info in classes.values() {
-                for field in class_info.fields.keys() {
-                    let name = field.split(':').next().unwrap_or(field).trim();
-                    assert!(!name.starts_with('_'), "Should not have private field: {}", name);
-                }
-                for method in class_info.methods.keys() {
-                    let name = extract_function_name(method);
-                    assert!(!name.starts_with('_'), "Should not have private method: {}", name);
-                }
-            }
-        }
-    }
+        let public = compute_enums(&source, &[], &filters_for(Visibility::Public)).unwrap();
+        let names: Vec<_> = public.files.values().flat_map(|e| e.keys()).collect();
+        assert!(names.contains(&&"class Color(Enum)".to_string()));
+        assert!(names.iter().all(|sig| !extract_class_name(sig).starts_with('_')));
 
-    #[test]
-    fn test_compute_enums() {
-        let targets = vec![fixtures_dir().join("enums.py")];
-        let result = compute_enums(&targets, &[]).unwrap();
-        assert!(!result.files.is_empty());
+        let private = compute_enums(&source, &[], &filters_for(Visibility::Private)).unwrap();
+        let names: Vec<_> = private.files.values().flat_map(|e| e.keys()).collect();
+        assert_eq!(names, vec!["class _InternalStatus(Enum)"]);
     }
 
     #[test]
     fn test_compute_enums_with_pattern() {
         let targets = vec![fixtures_dir().join("enums.py")];
-        let result = compute_enums(&targets, &["Color".to_string()]).unwrap();
+        let result = compute_enums(&Source::new(&targets), &["Color".to_string()], &Filters::default()).unwrap();
         assert!(!result.files.is_empty());
         let has_color = result.files.values().any(|e| e.keys().any(|k| k.contains("Color")));
         assert!(has_color);
@@ -808,44 +3944,185 @@ mod tests {
     #[test]
     fn test_compute_modules() {
         let targets = vec![fixtures_dir().join("pkg")];
-        let result = compute_modules(&targets, &[]).unwrap();
+        let result = compute_modules(&Source::new(&targets), &[], false).unwrap();
         assert!(!result.modules.is_empty());
     }
 
     #[test]
     fn test_compute_modules_with_pattern() {
         let targets = vec![fixtures_dir().join("pkg")];
-        let result = compute_modules(&targets, &["module".to_string()]).unwrap();
+        let result = compute_modules(&Source::new(&targets), &["module".to_string()], false).unwrap();
         // Should filter modules by pattern
         assert!(!result.modules.is_empty());
     }
 
+    #[test]
+    fn test_compute_modules_counts_serialized_only_when_requested() {
+        let targets = vec![fixtures_dir().join("pkg")];
+        let with_counts = serde_json::to_value(compute_modules(&Source::new(&targets), &[], true).unwrap()).unwrap();
+        assert_eq!(with_counts["modules"]["module.py"]["functions"], 2);
+        assert_eq!(with_counts["modules"]["subpkg"]["enums"], 1);
+
+        let plain = serde_json::to_string(&compute_modules(&Source::new(&targets), &[], false).unwrap()).unwrap();
+        assert!(!plain.contains("functions"));
+    }
+
     #[test]
     fn test_compute_modules_file_target() {
         // When target is a file, use parent as base path
         let targets = vec![fixtures_dir().join("functions.py")];
-        let result = compute_modules(&targets, &[]).unwrap();
+        let result = compute_modules(&Source::new(&targets), &[], false).unwrap();
         assert!(!result.modules.is_empty());
     }
 
+    #[test]
+    fn test_compute_entrypoints_lists_only_guarded_files() {
+        let targets = vec![fixtures_dir()];
+        let result = compute_entrypoints(&Source::new(&targets), &[]).unwrap();
+        assert_eq!(result.files.len(), 1);
+        let (path, entries) = result.files.iter().next().unwrap();
+        assert!(path.ends_with("entrypoint.py"));
+        assert_eq!(entries["__main__"].line, 9);
+    }
+
+    #[test]
+    fn test_compute_entrypoints_with_pattern() {
+        let targets = vec![fixtures_dir()];
+        let result = compute_entrypoints(&Source::new(&targets), &["nomatch".to_string()]).unwrap();
+        assert!(result.files.is_empty());
+    }
+
+    #[test]
+    fn test_compute_functions_tests_only() {
+        let targets = vec![fixtures_dir().join("test_sample.py")];
+        let filters = Filters {
+            tests_only: true,
+            ..Default::default()
+        };
+        let result = compute_functions(&Source::new(&targets), &[], &filters).unwrap();
+        let keys: Vec<_> = result.files.values().flat_map(|e| e.keys()).collect();
+        assert_eq!(keys, vec!["def test_foo()"]);
+    }
+
+    #[test]
+    fn test_compute_classes_tests_only() {
+        let targets = vec![fixtures_dir().join("test_sample.py")];
+        let filters = Filters {
+            tests_only: true,
+            ..Default::default()
+        };
+        let result = compute_classes(&Source::new(&targets), &[], &filters).unwrap();
+        let classes = result.files.values().next().unwrap();
+        assert_eq!(
+            classes.keys().collect::<Vec<_>>(),
+            vec!["class TestBar", "class ArithmeticCase(unittest.TestCase)"]
+        );
+
+        let bar = &classes["class TestBar"];
+        assert!(bar.fields.is_empty());
+        assert_eq!(
+            bar.methods.keys().collect::<Vec<_>>(),
+            vec!["def test_value(self)", "def test_helper(self)"]
+        );
+    }
+
+    #[test]
+    fn test_compute_functions_notebook() {
+        let targets = vec![fixtures_dir().join("notebook.ipynb")];
+        let source = Source {
+            walk: WalkOptions {
+                notebooks: true,
+                ..Default::default()
+            },
+            ..Source::new(&targets)
+        };
+        let result = compute_functions(&source, &[], &Filters::default()).unwrap();
+        let entries = result.files.values().next().unwrap();
+        assert_eq!(entries["def area(radius: float) -> float"].line, 3);
+
+        // Without --notebooks the file isn't collected at all
+        let result = compute_functions(&Source::new(&targets), &[], &Filters::default()).unwrap();
+        assert!(result.files.is_empty());
+    }
+
+    #[test]
+    fn test_compute_deps() {
+        let targets = vec![fixtures_dir().join("deps")];
+        let result = compute_deps(&Source::new(&targets)).unwrap();
+        let a = fixtures_dir().join("deps/a.py").to_string_lossy().to_string();
+        let b = fixtures_dir().join("deps/b.py").to_string_lossy().to_string();
+        assert!(result.deps[&a].contains(&b));
+        assert!(result.external[&a].contains("json"));
+    }
+
     #[test]
     fn test_compute_dump() {
         let targets = vec![fixtures_dir().join("mixed.py")];
-        let result = compute_dump(&targets, &[]).unwrap();
+        let result = compute_dump(&Source::new(&targets), &[], &[], &Filters::default()).unwrap();
         assert!(!result.files.is_empty());
     }
 
+    #[test]
+    fn test_compute_dump_only_functions() {
+        let targets = vec![fixtures_dir().join("mixed.py")];
+        let result = compute_dump(&Source::new(&targets), &[], &[DumpKind::Functions], &Filters::default()).unwrap();
+        let keys: Vec<_> = result.files.values().flat_map(|e| e.keys()).collect();
+        assert!(!keys.is_empty());
+        // No class-method ("Class.def ...") or enum ("class ...") entries
+        assert!(keys
+            .iter()
+            .all(|k| k.starts_with("def ") || k.starts_with("async def ")));
+    }
+
+    #[test]
+    fn test_compute_dump_only_classes_and_enums() {
+        let targets = vec![fixtures_dir().join("mixed.py")];
+        let only = [DumpKind::Classes, DumpKind::Enums];
+        let result = compute_dump(&Source::new(&targets), &[], &only, &Filters::default()).unwrap();
+        let keys: Vec<_> = result.files.values().flat_map(|e| e.keys()).collect();
+        assert!(keys.iter().any(|k| k.contains(".def ")));
+        assert!(keys
+            .iter()
+            .all(|k| !k.starts_with("def ") && !k.starts_with("async def ")));
+    }
+
+    #[test]
+    fn test_compute_dump_includes_constants() {
+        let targets = vec![fixtures_dir().join("constants.py")];
+        let result = compute_dump(&Source::new(&targets), &[], &[], &Filters::default()).unwrap();
+        let entries = result.files.values().next().unwrap();
+        assert_eq!(entries["MAX_RETRIES = 3"].line, 4);
+        assert!(entries.contains_key("DEFAULT_TIMEOUT: float = 2.5"));
+        assert!(entries.contains_key("def connect(retries: int) -> None"));
+
+        let filtered = compute_dump(
+            &Source::new(&targets),
+            &["DEFAULT".to_string()],
+            &[],
+            &Filters::default(),
+        )
+        .unwrap();
+        let keys: Vec<_> = filtered.files.values().flat_map(|e| e.keys()).collect();
+        assert_eq!(keys, vec!["DEFAULT_TIMEOUT: float = 2.5"]);
+    }
+
     #[test]
     fn test_compute_dump_with_pattern() {
         let targets = vec![fixtures_dir().join("mixed.py")];
-        let result = compute_dump(&targets, &["helper".to_string()]).unwrap();
+        let result = compute_dump(
+            &Source::new(&targets),
+            &["helper".to_string()],
+            &[],
+            &Filters::default(),
+        )
+        .unwrap();
         assert!(!result.files.is_empty());
     }
 
     #[test]
     fn test_compute_dump_includes_methods() {
         let targets = vec![fixtures_dir().join("mixed.py")];
-        let result = compute_dump(&targets, &[]).unwrap();
+        let result = compute_dump(&Source::new(&targets), &[], &[], &Filters::default()).unwrap();
         // Should include methods with class prefix
         let has_method = result
             .files
@@ -858,7 +4135,7 @@ mod tests {
     fn test_compute_functions_empty_dir() {
         let temp_dir = tempfile::tempdir().unwrap();
         let targets = vec![temp_dir.path().to_path_buf()];
-        let result = compute_functions(&targets, &[], Visibility::All).unwrap();
+        let result = compute_functions(&Source::new(&targets), &[], &Filters::default()).unwrap();
         assert!(result.files.is_empty());
     }
 
@@ -866,7 +4143,7 @@ mod tests {
     fn test_compute_classes_empty_dir() {
         let temp_dir = tempfile::tempdir().unwrap();
         let targets = vec![temp_dir.path().to_path_buf()];
-        let result = compute_classes(&targets, &[], Visibility::All).unwrap();
+        let result = compute_classes(&Source::new(&targets), &[], &Filters::default()).unwrap();
         assert!(result.files.is_empty());
     }
 
@@ -874,7 +4151,7 @@ mod tests {
     fn test_compute_enums_empty_dir() {
         let temp_dir = tempfile::tempdir().unwrap();
         let targets = vec![temp_dir.path().to_path_buf()];
-        let result = compute_enums(&targets, &[]).unwrap();
+        let result = compute_enums(&Source::new(&targets), &[], &Filters::default()).unwrap();
         assert!(result.files.is_empty());
     }
 
@@ -882,7 +4159,7 @@ mod tests {
     fn test_compute_modules_empty_dir() {
         let temp_dir = tempfile::tempdir().unwrap();
         let targets = vec![temp_dir.path().to_path_buf()];
-        let result = compute_modules(&targets, &[]).unwrap();
+        let result = compute_modules(&Source::new(&targets), &[], false).unwrap();
         assert!(result.modules.is_empty());
     }
 
@@ -890,7 +4167,7 @@ mod tests {
     fn test_compute_dump_empty_dir() {
         let temp_dir = tempfile::tempdir().unwrap();
         let targets = vec![temp_dir.path().to_path_buf()];
-        let result = compute_dump(&targets, &[]).unwrap();
+        let result = compute_dump(&Source::new(&targets), &[], &[], &Filters::default()).unwrap();
         assert!(result.files.is_empty());
     }
 
@@ -899,7 +4176,7 @@ mod tests {
         // Test the default path case
         let empty: Vec<PathBuf> = vec![];
         // This should use PathBuf::from(".")
-        let files = walk::collect_python_files(&empty);
+        let files = walk::collect_python_files(&empty, &WalkOptions::default());
         assert!(files.is_err() || files.unwrap().is_empty());
     }
 
@@ -907,7 +4184,7 @@ mod tests {
     fn test_compute_dump_class_without_prefix() {
         // Test the case where class_sig doesn't start with "class "
         let targets = vec![fixtures_dir().join("mixed.py")];
-        let result = compute_dump(&targets, &[]).unwrap();
+        let result = compute_dump(&Source::new(&targets), &[], &[], &Filters::default()).unwrap();
 
         // All entries should have been processed
         assert!(!result.files.is_empty());
@@ -916,7 +4193,7 @@ mod tests {
     #[test]
     fn test_compute_functions_multiple_files() {
         let targets = vec![fixtures_dir()];
-        let result = compute_functions(&targets, &[], Visibility::All).unwrap();
+        let result = compute_functions(&Source::new(&targets), &[], &Filters::default()).unwrap();
         // Should have functions from multiple files
         assert!(result.files.len() >= 2);
     }
@@ -924,9 +4201,9 @@ mod tests {
     #[test]
     fn test_compute_classes_multiple_files() {
         let targets = vec![fixtures_dir()];
-        let result = compute_classes(&targets, &[], Visibility::All).unwrap();
+        let result = compute_classes(&Source::new(&targets), &[], &Filters::default()).unwrap();
         // Should have classes from multiple files
-        assert!(result.files.len() >= 1);
+        assert!(!result.files.is_empty());
     }
 
     #[test]
@@ -946,11 +4223,63 @@ mod tests {
     #[test]
     fn test_class_info_structure() {
         // Test ClassInfo directly
-        let info = ClassInfo {
-            fields: BTreeMap::new(),
-            methods: BTreeMap::new(),
-        };
+        let info = ClassInfo::default();
         assert!(info.fields.is_empty());
         assert!(info.methods.is_empty());
     }
+
+    #[test]
+    fn test_compute_classes_abstract_only() {
+        let targets = vec![fixtures_dir().join("abstract.py")];
+        let result = compute_classes(
+            &Source::new(&targets),
+            &[],
+            &Filters {
+                abstract_only: true,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let classes = result.files.values().next().unwrap();
+
+        assert!(classes.contains_key("class Shape(ABC)"));
+        assert!(classes.contains_key("class Repository"));
+        assert!(classes.contains_key("class Plugin"));
+        assert!(!classes.contains_key("class Concrete"));
+        assert!(classes.values().all(|info| info.meta.is_abstract));
+
+        let shape = &classes["class Shape(ABC)"];
+        assert!(shape.methods["def area(self) -> float"].meta.is_abstract);
+        assert!(!shape.methods["def describe(self) -> str"].meta.is_abstract);
+    }
+
+    #[test]
+    fn test_compute_classes_abstract_marker_serialized() {
+        let targets = vec![fixtures_dir().join("abstract.py")];
+        let result = compute_classes(
+            &Source::new(&targets),
+            &["Shape".to_string()],
+            &Filters {
+                abstract_only: true,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let json = serde_json::to_value(&result).unwrap();
+        let shape = json["files"].as_object().unwrap().values().next().unwrap()["class Shape(ABC)"].clone();
+
+        assert_eq!(shape["abstract"], true);
+        assert_eq!(shape["methods"]["def area(self) -> float"]["abstract"], true);
+        // Concrete methods keep the bare line number
+        assert!(shape["methods"]["def describe(self) -> str"].is_u64());
+    }
+
+    #[test]
+    fn test_compute_classes_without_abstract_flag_has_no_marker() {
+        let targets = vec![fixtures_dir().join("abstract.py")];
+        let result = compute_classes(&Source::new(&targets), &[], &Filters::default()).unwrap();
+        let json = serde_json::to_string(&result).unwrap();
+        assert!(!json.contains("\"abstract\":"));
+        assert!(result.files.values().next().unwrap().contains_key("class Concrete"));
+    }
 }
@@ -1,64 +1,1007 @@
+use super::{
+    ClassInfo, ClassMap, DepsOutput, EntryMap, Envelope, LspOutput, MemberMap, ModuleNode, ModuleType, ModulesOutput,
+    RunInfo, Tag,
+};
+use crate::cli::{ColorChoice, GroupBy, OutputFormat};
 use eyre::Result;
+use indexmap::IndexMap;
+use owo_colors::OwoColorize;
 use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt::Write as _;
 use std::io::{self, IsTerminal, Write};
+use std::path::Path;
 
-/// Determines output format based on flags and TTY detection
-pub fn should_use_json(json_flag: bool) -> bool {
-    json_flag || !io::stdout().is_terminal()
+/// Determines output format: `--format` if given, else JSON for `--json` (an
+/// alias for `--format json`), else YAML for a TTY and JSON otherwise
+pub fn resolve_format(format: Option<OutputFormat>, json_flag: bool) -> OutputFormat {
+    format_for_stdout(format, json_flag, io::stdout().is_terminal())
 }
 
-/// Outputs serializable data as YAML or JSON
-pub fn output<T: Serialize>(data: &T, use_json: bool) -> Result<()> {
+/// `resolve_format` for a stdout that is or isn't a terminal
+fn format_for_stdout(format: Option<OutputFormat>, json_flag: bool, is_terminal: bool) -> OutputFormat {
+    match format {
+        Some(format) => format,
+        None if json_flag || !is_terminal => OutputFormat::Json,
+        None => OutputFormat::Yaml,
+    }
+}
+
+/// How results are written to stdout
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct OutputStyle<'a> {
+    pub format: OutputFormat,
+    /// Highlight YAML output with ANSI colors
+    pub color: bool,
+    /// Write JSON on a single line instead of pretty-printed
+    pub compact: bool,
+    /// Wrap JSON output in a versioned envelope (`--envelope`); other formats stay flat
+    pub envelope: Option<RunInfo<'a>>,
+    /// Write symbols to this SQLite database instead of stdout (`--sqlite`)
+    pub sqlite: Option<&'a Path>,
+    /// Key symbols by file or by directory (`--group-by`)
+    pub group_by: GroupBy,
+    /// Key output by target before file (`--labeled`)
+    pub labeled: bool,
+    /// Key output by canonical absolute file path (`--absolute`)
+    pub absolute: bool,
+    /// Write YAML maps and sequences of scalars inline (`--yaml-flow`)
+    pub yaml_flow: bool,
+    /// Keep at most this many symbols per file (`--per-file-limit`)
+    pub per_file_limit: Option<usize>,
+    /// Follow the output with a count footer on stderr (`--summary`)
+    pub summary: bool,
+    /// List signatures without their lines (`--signature-only`)
+    pub signature_only: bool,
+}
+
+/// Determines whether to color output: only YAML is colored, per `--color`
+pub fn should_use_color(choice: ColorChoice, format: OutputFormat) -> bool {
+    match choice {
+        _ if format != OutputFormat::Yaml => false,
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+        ColorChoice::Auto => io::stdout().is_terminal(),
+    }
+}
+
+/// Whether to print the summary footer: as `--summary`/`--no-summary` say,
+/// else only when stdout is a terminal
+pub fn should_show_summary(summary: bool, no_summary: bool) -> bool {
+    summary || (!no_summary && io::stdout().is_terminal())
+}
+
+/// The `-- N symbols in M files --` footer for per-file (or, with
+/// `--group-by dir`, per-directory) results
+pub fn summary_footer<V>(files: &BTreeMap<String, IndexMap<String, V>>, group_by: GroupBy) -> String {
+    let symbols: usize = files.values().map(IndexMap::len).sum();
+    let unit = match (group_by, files.len()) {
+        (GroupBy::File, 1) => "file",
+        (GroupBy::File, _) => "files",
+        (GroupBy::Dir, 1) => "directory",
+        (GroupBy::Dir, _) => "directories",
+    };
+    format!(
+        "-- {} {} in {} {} --",
+        symbols,
+        if symbols == 1 { "symbol" } else { "symbols" },
+        files.len(),
+        unit
+    )
+}
+
+/// Outputs serializable data as YAML, JSON, or TOML, bolding `highlight`
+/// pattern matches in symbol names when coloring
+pub fn output<T: Serialize>(data: &T, style: OutputStyle, highlight: &[String]) -> Result<()> {
+    if style.sqlite.is_some() {
+        return Err(eyre::eyre!(
+            "--sqlite is only supported by the function, class, enum and dump commands"
+        ));
+    }
     let stdout = io::stdout();
     let mut handle = stdout.lock();
+    output_to_writer(data, style, highlight, &mut handle)
+}
 
-    if use_json {
-        serde_json::to_writer_pretty(&mut handle, data)?;
-        writeln!(handle)?;
+fn write_json<T: Serialize, W: Write>(data: &T, compact: bool, writer: &mut W) -> Result<()> {
+    if compact {
+        serde_json::to_writer(writer, data)?;
     } else {
-        serde_yaml::to_writer(&mut handle, data)?;
+        serde_json::to_writer_pretty(writer, data)?;
+    }
+    Ok(())
+}
+
+/// Write output to any writer
+fn output_to_writer<T: Serialize, W: Write>(
+    data: &T,
+    style: OutputStyle,
+    highlight: &[String],
+    writer: &mut W,
+) -> Result<()> {
+    match style.format {
+        OutputFormat::Json => {
+            match style.envelope {
+                Some(run) => write_json(&Envelope::new(run, data), style.compact, writer)?,
+                None => write_json(data, style.compact, writer)?,
+            }
+            writeln!(writer)?;
+        }
+        OutputFormat::Yaml if style.color || style.yaml_flow => {
+            let yaml = if style.yaml_flow {
+                flow_yaml(&serde_yaml::to_value(data)?)
+            } else {
+                serde_yaml::to_string(data)?
+            };
+            if style.color {
+                writer.write_all(colorize_yaml(&yaml, highlight).as_bytes())?;
+            } else {
+                writer.write_all(yaml.as_bytes())?;
+            }
+        }
+        OutputFormat::Yaml => serde_yaml::to_writer(&mut *writer, data)?,
+        // Signature keys are quoted; nested maps become [files."path"."class ..."] tables
+        OutputFormat::Toml => writer.write_all(toml::to_string(data)?.as_bytes())?,
+        OutputFormat::Dot => return Err(eyre::eyre!("--format dot is only supported by the deps command")),
+        OutputFormat::Lsp => {
+            return Err(eyre::eyre!(
+                "--format lsp is only supported by the function, class and enum commands"
+            ))
+        }
+        OutputFormat::Ctags => {
+            return Err(eyre::eyre!(
+                "--format ctags is only supported by the function, class, enum and dump commands"
+            ))
+        }
+        OutputFormat::Xml => {
+            return Err(eyre::eyre!(
+                "--format xml is only supported by the function, class, enum, entrypoint, untyped and dump commands"
+            ))
+        }
+        OutputFormat::JsonlBySymbol => {
+            return Err(eyre::eyre!(
+                "--format jsonl-by-symbol is only supported by the function, class, enum and dump commands"
+            ))
+        }
     }
 
     Ok(())
 }
 
-/// Write output to a custom writer (for testing)
-#[cfg(test)]
-fn output_to_writer<T: Serialize, W: Write>(data: &T, use_json: bool, writer: &mut W) -> Result<()> {
-    if use_json {
-        serde_json::to_writer_pretty(&mut *writer, data)?;
-        writeln!(writer)?;
+/// Render YAML in block style down to the leaves, then in flow style:
+/// maps and sequences holding only scalars are written inline, as
+/// `'def run(self)': {line: 3, abstract: true}` or `[a, b]`
+fn flow_yaml(value: &serde_yaml::Value) -> String {
+    let mut out = String::new();
+    if is_flow_leaf(value) {
+        out.push_str(&flow_node(value));
+        out.push('\n');
     } else {
-        serde_yaml::to_writer(&mut *writer, data)?;
+        write_block(value, 0, &mut out);
+    }
+    out
+}
+
+/// A scalar, or a map or sequence of scalars (empty ones included)
+fn is_flow_leaf(value: &serde_yaml::Value) -> bool {
+    use serde_yaml::Value;
+    let scalar = |v: &Value| !matches!(v, Value::Mapping(_) | Value::Sequence(_));
+    match value {
+        Value::Mapping(map) => map.values().all(scalar),
+        Value::Sequence(seq) => seq.iter().all(scalar),
+        _ => true,
+    }
+}
+
+/// Write a map or sequence that has nested collections as indented block lines
+fn write_block(value: &serde_yaml::Value, indent: usize, out: &mut String) {
+    use serde_yaml::Value;
+    let pad = " ".repeat(indent);
+    match value {
+        Value::Mapping(map) => {
+            for (key, child) in map {
+                let _ = write!(out, "{}{}:", pad, block_scalar(key));
+                if is_flow_leaf(child) {
+                    let _ = writeln!(out, " {}", flow_node(child));
+                } else {
+                    out.push('\n');
+                    // Like serde_yaml, sequences are not indented under their key
+                    let child_indent = if matches!(child, Value::Sequence(_)) { indent } else { indent + 2 };
+                    write_block(child, child_indent, out);
+                }
+            }
+        }
+        Value::Sequence(seq) => {
+            for item in seq {
+                if is_flow_leaf(item) {
+                    let _ = writeln!(out, "{}- {}", pad, flow_node(item));
+                } else {
+                    // The item's first line goes after the dash
+                    let mut nested = String::new();
+                    write_block(item, indent + 2, &mut nested);
+                    let _ = write!(out, "{}- {}", pad, &nested[indent + 2..]);
+                }
+            }
+        }
+        _ => {
+            let _ = writeln!(out, "{}{}", pad, block_scalar(value));
+        }
+    }
+}
+
+/// A scalar, map or sequence of scalars on one line
+fn flow_node(value: &serde_yaml::Value) -> String {
+    use serde_yaml::Value;
+    match value {
+        Value::Mapping(map) => {
+            let entries: Vec<String> = map
+                .iter()
+                .map(|(key, value)| format!("{}: {}", flow_scalar(key), flow_scalar(value)))
+                .collect();
+            format!("{{{}}}", entries.join(", "))
+        }
+        Value::Sequence(seq) => {
+            let items: Vec<String> = seq.iter().map(flow_scalar).collect();
+            format!("[{}]", items.join(", "))
+        }
+        _ => flow_scalar(value),
+    }
+}
+
+/// A scalar as serde_yaml writes it in block style; multi-line strings
+/// (block literals) are double-quoted instead
+fn block_scalar(value: &serde_yaml::Value) -> String {
+    let rendered = serde_yaml::to_string(value).unwrap_or_default();
+    let rendered = rendered.trim_end_matches('\n');
+    match value {
+        serde_yaml::Value::String(s) if rendered.contains('\n') => double_quoted(s),
+        _ => rendered.to_string(),
+    }
+}
+
+/// A scalar inside `{...}` or `[...]`, where plain strings can't contain
+/// flow indicators such as the commas of a signature
+fn flow_scalar(value: &serde_yaml::Value) -> String {
+    let rendered = block_scalar(value);
+    match value {
+        serde_yaml::Value::String(s) if !rendered.starts_with(['\'', '"']) && s.contains([',', '[', ']', '{', '}']) => {
+            double_quoted(s)
+        }
+        _ => rendered,
+    }
+}
+
+/// A YAML double-quoted string; JSON string escapes are valid YAML
+fn double_quoted(s: &str) -> String {
+    serde_json::to_string(s).unwrap_or_default()
+}
+
+/// Keywords that start a rendered signature
+const SIGNATURE_KEYWORDS: &[&str] = &["async def ", "def ", "class "];
+
+/// Color signature keywords and bold pattern matches in each line of YAML
+fn colorize_yaml(yaml: &str, highlight: &[String]) -> String {
+    yaml.lines()
+        .map(|line| format!("{}\n", colorize_line(line, highlight)))
+        .collect()
+}
+
+fn colorize_line(line: &str, highlight: &[String]) -> String {
+    let body = line.trim_start();
+    let indent = &line[..line.len() - body.len()];
+    // Keys containing `:` or other special characters are quoted
+    let quote_len = usize::from(body.starts_with(['\'', '"']));
+    let (quote, key) = body.split_at(quote_len);
+
+    let Some((owner, keywords, name, rest)) = split_signature(key) else {
+        return line.to_string();
+    };
+
+    let keywords: Vec<String> = keywords.split_whitespace().map(|k| k.magenta().to_string()).collect();
+    format!(
+        "{}{}{}{} {}{}",
+        indent,
+        quote,
+        owner,
+        keywords.join(" "),
+        highlight_match(name, highlight),
+        rest
+    )
+}
+
+/// Split a signature into owner prefix (`Class.` in dump), keywords, name, and
+/// the rest: "Svc.async def run(self)" -> ("Svc.", "async def ", "run", "(self)")
+fn split_signature(text: &str) -> Option<(&str, &str, &str, &str)> {
+    let starts_with_keyword = |s: &str| SIGNATURE_KEYWORDS.iter().find(|k| s.starts_with(*k));
+    let owner_len = match text.split_once('.') {
+        Some((owner, after))
+            if !owner.is_empty()
+                && owner.chars().all(|c| c.is_alphanumeric() || c == '_')
+                && starts_with_keyword(after).is_some() =>
+        {
+            owner.len() + 1
+        }
+        _ => 0,
+    };
+    let keyword = starts_with_keyword(&text[owner_len..])?;
+    let name_start = owner_len + keyword.len();
+    let name_len = text[name_start..]
+        .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .unwrap_or(text.len() - name_start);
+    let name_end = name_start + name_len;
+
+    Some((
+        &text[..owner_len],
+        &text[owner_len..name_start],
+        &text[name_start..name_end],
+        &text[name_end..],
+    ))
+}
+
+/// Bold the first (case-insensitive) occurrence of any pattern in a name
+fn highlight_match(name: &str, patterns: &[String]) -> String {
+    let lower = name.to_ascii_lowercase();
+    let found = patterns
+        .iter()
+        .filter(|pattern| !pattern.is_empty())
+        .find_map(|pattern| {
+            lower
+                .find(&pattern.to_ascii_lowercase())
+                .map(|start| (start, pattern.len()))
+        });
+
+    match found {
+        Some((start, len)) => format!(
+            "{}{}{}",
+            &name[..start],
+            (&name[start..start + len]).bold(),
+            &name[start + len..]
+        ),
+        None => name.to_string(),
+    }
+}
+
+/// Outputs the import graph as Graphviz DOT
+pub fn output_dot(deps: &DepsOutput) -> Result<()> {
+    let stdout = io::stdout();
+    let mut handle = stdout.lock();
+    handle.write_all(deps_to_dot(deps).as_bytes())?;
+    Ok(())
+}
+
+/// Outputs LSP document symbols as JSON, one array per file
+pub fn output_lsp(files: &LspOutput, compact: bool) -> Result<()> {
+    let stdout = io::stdout();
+    let mut handle = stdout.lock();
+    write_json(files, compact, &mut handle)?;
+    writeln!(handle)?;
+    Ok(())
+}
+
+/// Outputs symbols as a ctags file
+pub fn output_ctags(tags: &[Tag]) -> Result<()> {
+    let stdout = io::stdout();
+    let mut handle = stdout.lock();
+    handle.write_all(tags_to_ctags(tags).as_bytes())?;
+    Ok(())
+}
+
+/// Outputs symbols as JSON Lines, one object per symbol
+pub fn output_jsonl(tags: &[Tag]) -> Result<()> {
+    let stdout = io::stdout();
+    let mut handle = stdout.lock();
+    handle.write_all(tags_to_jsonl(tags)?.as_bytes())?;
+    Ok(())
+}
+
+/// A symbol as written by `--format jsonl-by-symbol`
+#[derive(Serialize)]
+struct SymbolLine<'a> {
+    file: &'a str,
+    kind: &'static str,
+    name: &'a str,
+    signature: &'a str,
+    line: usize,
+    /// Class a method belongs to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    parent: Option<&'a str>,
+}
+
+/// Render tags as JSON Lines: one compact `{file, kind, name, signature, line}`
+/// object per line, in output order
+pub fn tags_to_jsonl(tags: &[Tag]) -> Result<String> {
+    let mut out = String::new();
+    for tag in tags {
+        let symbol = SymbolLine {
+            file: &tag.file,
+            kind: tag.kind.name(),
+            name: &tag.name,
+            signature: &tag.signature,
+            line: tag.line,
+            parent: tag.scope.as_deref(),
+        };
+        out.push_str(&serde_json::to_string(&symbol)?);
+        out.push('\n');
     }
+    Ok(out)
+}
+
+/// Render tags as a sorted ctags file, one `name<TAB>file<TAB>address;"<TAB>kind`
+/// line per symbol (plus `class:<Class>` for methods). The address is a search
+/// pattern for the symbol's source line, or the line number when the line
+/// can't be read back (e.g. notebooks).
+pub fn tags_to_ctags(tags: &[Tag]) -> String {
+    let mut sorted: Vec<&Tag> = tags.iter().collect();
+    sorted.sort_by(|a, b| (&a.name, &a.file, a.line).cmp(&(&b.name, &b.file, b.line)));
+
+    let mut sources: HashMap<&str, Option<String>> = HashMap::new();
+    let mut out =
+        String::from("!_TAG_FILE_FORMAT\t2\t/extended format/\n!_TAG_FILE_SORTED\t1\t/0=unsorted, 1=sorted/\n");
+    for tag in sorted {
+        let source = sources.entry(&tag.file).or_insert_with(|| {
+            let is_notebook = Path::new(&tag.file).extension().is_some_and(|ext| ext == "ipynb");
+            if is_notebook {
+                None
+            } else {
+                crate::parser::read_source(Path::new(&tag.file)).ok()
+            }
+        });
+        let address = match source
+            .as_deref()
+            .and_then(|s| s.lines().nth(tag.line.saturating_sub(1)))
+        {
+            Some(line) => format!("/^{}$/", line.replace('\\', "\\\\").replace('/', "\\/")),
+            None => tag.line.to_string(),
+        };
+        let _ = write!(out, "{}\t{}\t{};\"\t{}", tag.name, tag.file, address, tag.kind.letter());
+        if let Some(scope) = &tag.scope {
+            let _ = write!(out, "\tclass:{}", scope);
+        }
+        out.push('\n');
+    }
+    out
+}
+
+const XML_HEADER: &str = "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n";
+
+/// Outputs a file-to-symbols listing as XML
+pub fn output_xml(files: &BTreeMap<String, EntryMap>) -> Result<()> {
+    let stdout = io::stdout();
+    let mut handle = stdout.lock();
+    handle.write_all(files_to_xml(files).as_bytes())?;
+    Ok(())
+}
 
+/// Outputs classes as XML
+pub fn output_classes_xml(files: &BTreeMap<String, ClassMap>) -> Result<()> {
+    let stdout = io::stdout();
+    let mut handle = stdout.lock();
+    handle.write_all(classes_to_xml(files).as_bytes())?;
     Ok(())
 }
 
+/// Render symbols as `<files><file path=".."><symbol line="10">def foo()</symbol></file></files>`
+pub fn files_to_xml(files: &BTreeMap<String, EntryMap>) -> String {
+    let mut out = String::from(XML_HEADER);
+    out.push_str("<files>\n");
+    for (path, symbols) in files {
+        let _ = writeln!(out, "  <file path=\"{}\">", xml_escape(path));
+        for (signature, entry) in symbols {
+            xml_element(&mut out, 4, "symbol", entry.line, signature);
+        }
+        out.push_str("  </file>\n");
+    }
+    out.push_str("</files>\n");
+    out
+}
+
+/// Render classes as `<class line=".." signature="..">` elements holding their
+/// `<field>`s and `<method>`s
+pub fn classes_to_xml(files: &BTreeMap<String, ClassMap>) -> String {
+    let mut out = String::from(XML_HEADER);
+    out.push_str("<files>\n");
+    for (path, classes) in files {
+        let _ = writeln!(out, "  <file path=\"{}\">", xml_escape(path));
+        for (signature, info) in classes {
+            xml_class(&mut out, 4, signature, info);
+        }
+        out.push_str("  </file>\n");
+    }
+    out.push_str("</files>\n");
+    out
+}
+
+/// A `<class>` element with its members and nested classes, or a
+/// self-closing one when it has none
+fn xml_class(out: &mut String, indent: usize, signature: &str, info: &ClassInfo) {
+    let _ = write!(
+        out,
+        "{:indent$}<class line=\"{}\" signature=\"{}\"",
+        "",
+        info.line,
+        xml_escape(signature),
+        indent = indent
+    );
+    if info.fields.is_empty() && info.methods.is_empty() && info.classes.is_empty() {
+        out.push_str("/>\n");
+        return;
+    }
+    out.push_str(">\n");
+    xml_members(out, indent + 2, "field", &info.fields);
+    xml_members(out, indent + 2, "method", &info.methods);
+    for (signature, nested) in &info.classes {
+        xml_class(out, indent + 2, signature, nested);
+    }
+    let _ = writeln!(out, "{:indent$}</class>", "", indent = indent);
+}
+
+fn xml_members(out: &mut String, indent: usize, tag: &str, members: &MemberMap) {
+    for (signature, entry) in members {
+        xml_element(out, indent, tag, entry.line, signature);
+    }
+}
+
+fn xml_element(out: &mut String, indent: usize, tag: &str, line: usize, text: &str) {
+    let _ = writeln!(
+        out,
+        "{:indent$}<{tag} line=\"{line}\">{}</{tag}>",
+        "",
+        xml_escape(text),
+        indent = indent,
+        tag = tag,
+        line = line
+    );
+}
+
+/// Escape the characters with special meaning in XML text and attribute values
+fn xml_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Outputs the module tree with box-drawing connectors
+pub fn output_tree(modules: &ModulesOutput) -> Result<()> {
+    let stdout = io::stdout();
+    let mut handle = stdout.lock();
+    handle.write_all(modules_to_tree(modules).as_bytes())?;
+    Ok(())
+}
+
+/// Render the module tree like the `tree` command. Packages get a trailing `/`;
+/// children are already ordered by path.
+pub fn modules_to_tree(modules: &ModulesOutput) -> String {
+    let mut tree = String::from(".\n");
+    render_tree_level(&modules.modules, "", &mut tree);
+    tree
+}
+
+fn render_tree_level(nodes: &BTreeMap<String, ModuleNode>, prefix: &str, tree: &mut String) {
+    for (i, (path, node)) in nodes.iter().enumerate() {
+        let last = i == nodes.len() - 1;
+        let (connector, indent) = if last { ("└── ", "    ") } else { ("├── ", "│   ") };
+        let name = path.rsplit('/').next().unwrap_or(path);
+        let slash = if node.node_type == ModuleType::Package { "/" } else { "" };
+        let _ = write!(tree, "{}{}{}{}", prefix, connector, name, slash);
+        if let Some(counts) = &node.counts {
+            let _ = write!(
+                tree,
+                " ({} functions, {} classes, {} enums)",
+                counts.functions, counts.classes, counts.enums
+            );
+        }
+        tree.push('\n');
+        render_tree_level(&node.children, &format!("{}{}", prefix, indent), tree);
+    }
+}
+
+/// Render the import graph as a `digraph`: one node per file, one edge per import
+pub fn deps_to_dot(deps: &DepsOutput) -> String {
+    let edges: BTreeSet<(&str, &str)> = deps
+        .deps
+        .iter()
+        .flat_map(|(from, targets)| targets.iter().map(move |to| (from.as_str(), to.as_str())))
+        .collect();
+    let nodes: BTreeSet<&str> = edges.iter().flat_map(|&(from, to)| [from, to]).collect();
+
+    let mut dot = String::from("digraph deps {\n");
+    for node in nodes {
+        let _ = writeln!(dot, "  {};", dot_quote(node));
+    }
+    for (from, to) in edges {
+        // Imports only seen by type checkers don't run, so draw them dashed
+        let typing_only = deps.type_checking.get(from).is_some_and(|deps| deps.contains(to));
+        let style = if typing_only { " [style=dashed]" } else { "" };
+        let _ = writeln!(dot, "  {} -> {}{};", dot_quote(from), dot_quote(to), style);
+    }
+    dot.push_str("}\n");
+    dot
+}
+
+/// Quote a DOT identifier, escaping embedded quotes and backslashes
+fn dot_quote(id: &str) -> String {
+    format!("\"{}\"", id.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::collections::BTreeMap;
+    use crate::output::{ClassInfo, ClassMap, ClassesOutput, Entry, FilesOutput, MemberMap, Meta, TagKind};
+    use std::path::PathBuf;
 
-    #[derive(Serialize)]
-    struct TestData {
-        name: String,
-        value: i32,
+    const JSON: OutputStyle = OutputStyle {
+        format: OutputFormat::Json,
+        color: false,
+        compact: false,
+        envelope: None,
+        sqlite: None,
+        group_by: GroupBy::File,
+        labeled: false,
+        absolute: false,
+        yaml_flow: false,
+        per_file_limit: None,
+        summary: false,
+        signature_only: false,
+    };
+    const YAML: OutputStyle = OutputStyle {
+        format: OutputFormat::Yaml,
+        color: false,
+        compact: false,
+        envelope: None,
+        sqlite: None,
+        group_by: GroupBy::File,
+        labeled: false,
+        absolute: false,
+        yaml_flow: false,
+        per_file_limit: None,
+        summary: false,
+        signature_only: false,
+    };
+    const COLOR: OutputStyle = OutputStyle {
+        format: OutputFormat::Yaml,
+        color: true,
+        compact: false,
+        envelope: None,
+        sqlite: None,
+        group_by: GroupBy::File,
+        labeled: false,
+        absolute: false,
+        yaml_flow: false,
+        per_file_limit: None,
+        summary: false,
+        signature_only: false,
+    };
+    const TOML: OutputStyle = OutputStyle {
+        format: OutputFormat::Toml,
+        color: false,
+        compact: false,
+        envelope: None,
+        sqlite: None,
+        group_by: GroupBy::File,
+        labeled: false,
+        absolute: false,
+        yaml_flow: false,
+        per_file_limit: None,
+        summary: false,
+        signature_only: false,
+    };
+
+    fn signatures() -> BTreeMap<String, i32> {
+        let mut data = BTreeMap::new();
+        data.insert("def load_config(path: str) -> dict".to_string(), 3);
+        data.insert("Loader.async def load(self)".to_string(), 9);
+        data.insert("class ConfigError(Exception)".to_string(), 12);
+        data
+    }
+
+    fn render(style: OutputStyle, highlight: &[String]) -> String {
+        let mut buffer = Vec::new();
+        output_to_writer(&signatures(), style, highlight, &mut buffer).unwrap();
+        String::from_utf8(buffer).unwrap()
     }
 
     #[test]
-    fn test_should_use_json_when_flag_true() {
-        assert!(should_use_json(true));
+    fn test_color_never_has_no_ansi_escapes() {
+        let output = render(YAML, &["config".to_string()]);
+        assert!(!output.contains('\x1b'));
+        assert!(output.contains("def load_config(path: str) -> dict"));
     }
 
     #[test]
-    fn test_should_use_json_when_flag_false_and_not_tty() {
-        // In test context, stdout is typically not a TTY
-        // So this should return true (use JSON when not a TTY)
-        let result = should_use_json(false);
-        // Result depends on whether we're running in a TTY or not
-        // In CI/tests, typically not a TTY, so this would be true
-        assert!(result || !result); // Always passes, but exercises the code
+    fn test_color_never_applied_to_json() {
+        let style = OutputStyle { color: true, ..JSON };
+        assert!(!render(style, &["config".to_string()]).contains('\x1b'));
+        assert!(!should_use_color(ColorChoice::Always, OutputFormat::Json));
+        assert!(!should_use_color(ColorChoice::Always, OutputFormat::Toml));
+        assert!(should_use_color(ColorChoice::Always, OutputFormat::Yaml));
+        assert!(!should_use_color(ColorChoice::Never, OutputFormat::Yaml));
+    }
+
+    #[test]
+    fn test_color_highlights_keywords_and_matches() {
+        let output = render(COLOR, &["config".to_string()]);
+        assert!(output.contains(&format!("{} load_{}(path", "def".magenta(), "config".bold())));
+        assert!(output.contains(&format!("Loader.{} {} load(self)", "async".magenta(), "def".magenta())));
+        assert!(output.contains(&format!("{} {}Error(Exception)", "class".magenta(), "Config".bold())));
+        // Plain text is unchanged once escapes are stripped
+        assert!(!render(COLOR, &[]).contains(&"config".bold().to_string()));
+    }
+
+    #[test]
+    fn test_resolve_format() {
+        assert_eq!(resolve_format(Some(OutputFormat::Toml), false), OutputFormat::Toml);
+        assert_eq!(resolve_format(None, true), OutputFormat::Json);
+    }
+
+    #[test]
+    fn test_format_precedence() {
+        // `--format` wins over the terminal check, either way
+        assert_eq!(
+            format_for_stdout(Some(OutputFormat::Yaml), false, false),
+            OutputFormat::Yaml
+        );
+        assert_eq!(
+            format_for_stdout(Some(OutputFormat::Xml), false, true),
+            OutputFormat::Xml
+        );
+        // `--json` forces JSON even on a terminal
+        assert_eq!(format_for_stdout(None, true, true), OutputFormat::Json);
+        // Otherwise YAML for a terminal, JSON when piped
+        assert_eq!(format_for_stdout(None, false, true), OutputFormat::Yaml);
+        assert_eq!(format_for_stdout(None, false, false), OutputFormat::Json);
+    }
+
+    #[test]
+    fn test_summary_footer() {
+        let mut files: BTreeMap<String, EntryMap> = BTreeMap::new();
+        files
+            .entry("a.py".to_string())
+            .or_default()
+            .insert("def f()".to_string(), Entry::new(1));
+        assert_eq!(summary_footer(&files, GroupBy::File), "-- 1 symbol in 1 file --");
+
+        let b = files.entry("b.py".to_string()).or_default();
+        b.insert("def g()".to_string(), Entry::new(1));
+        b.insert("def h()".to_string(), Entry::new(3));
+        assert_eq!(summary_footer(&files, GroupBy::File), "-- 3 symbols in 2 files --");
+        assert_eq!(summary_footer(&files, GroupBy::Dir), "-- 3 symbols in 2 directories --");
+        assert!(should_show_summary(true, false));
+    }
+
+    /// Emit TOML and parse it back, comparing against the JSON form of the same data
+    fn assert_toml_round_trip<T: Serialize>(data: &T) -> String {
+        let mut buffer = Vec::new();
+        output_to_writer(data, TOML, &[], &mut buffer).unwrap();
+        let text = String::from_utf8(buffer).unwrap();
+        let parsed: serde_json::Value = toml::from_str(&text).unwrap();
+        assert_eq!(parsed, serde_json::to_value(data).unwrap());
+        text
+    }
+
+    #[test]
+    fn test_toml_round_trip_files() {
+        let mut entries = EntryMap::new();
+        entries.insert("def load(path: str) -> dict".to_string(), Entry::new(3));
+        let abstract_entry = Entry {
+            meta: Meta {
+                is_abstract: true,
+                ..Default::default()
+            },
+            ..Entry::new(7)
+        };
+        entries.insert("async def fetch(url: str) -> bytes".to_string(), abstract_entry);
+        let mut files = BTreeMap::new();
+        files.insert("src/app.py".to_string(), entries);
+
+        let text = assert_toml_round_trip(&FilesOutput { files });
+        assert!(text.contains(r#""def load(path: str) -> dict" = 3"#));
+    }
+
+    #[test]
+    fn test_toml_round_trip_classes() {
+        let mut fields = MemberMap::new();
+        fields.insert("name: str".to_string(), Entry::new(2));
+        let mut methods = MemberMap::new();
+        methods.insert("def greet(self) -> str".to_string(), Entry::new(4));
+        let mut classes = ClassMap::new();
+        classes.insert(
+            "class User(Base)".to_string(),
+            ClassInfo {
+                fields,
+                methods,
+                ..Default::default()
+            },
+        );
+        classes.insert("class Empty".to_string(), ClassInfo::default());
+        let mut files = BTreeMap::new();
+        files.insert("src/models.py".to_string(), classes);
+
+        let text = assert_toml_round_trip(&ClassesOutput { files });
+        assert!(text.contains(r#"[files."src/models.py"."class User(Base)".methods]"#));
+    }
+
+    #[test]
+    fn test_toml_round_trip_modules_and_deps() {
+        let base = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/pkg");
+        let files = crate::walk::collect_python_files(std::slice::from_ref(&base), &Default::default()).unwrap();
+        assert_toml_round_trip(&crate::analysis::build_module_tree(&files, &base, None));
+        assert_toml_round_trip(&graph(&[("a.py", "b.py")]));
+    }
+
+    #[test]
+    fn test_dot_format_rejected_for_plain_output() {
+        let style = OutputStyle {
+            format: OutputFormat::Dot,
+            ..JSON
+        };
+        assert!(output_to_writer(&signatures(), style, &[], &mut Vec::new()).is_err());
+    }
+
+    fn tag(name: &str, file: &str, line: usize, kind: TagKind, scope: Option<&str>) -> Tag {
+        Tag {
+            name: name.to_string(),
+            file: file.to_string(),
+            line,
+            kind,
+            scope: scope.map(str::to_string),
+            signature: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_tags_to_jsonl() {
+        let tags = vec![
+            tag("Svc", "svc.py", 1, TagKind::Class, None),
+            tag("run", "svc.py", 2, TagKind::Method, Some("Svc")),
+        ];
+        assert_eq!(
+            tags_to_jsonl(&tags).unwrap(),
+            "{\"file\":\"svc.py\",\"kind\":\"class\",\"name\":\"Svc\",\"signature\":\"\",\"line\":1}\n\
+             {\"file\":\"svc.py\",\"kind\":\"method\",\"name\":\"run\",\"signature\":\"\",\"line\":2,\"parent\":\"Svc\"}\n"
+        );
+    }
+
+    #[test]
+    fn test_tags_to_ctags_function_line() {
+        let file = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/functions.py");
+        let file = file.to_string_lossy();
+        let ctags = tags_to_ctags(&[tag("function_with_types", &file, 9, TagKind::Function, None)]);
+        let expected = format!(
+            "function_with_types\t{}\t/^def function_with_types(x: int, y: str) -> bool:$/;\"\tf\n",
+            file
+        );
+        assert!(ctags.starts_with("!_TAG_FILE_FORMAT\t2\t"));
+        assert!(ctags.ends_with(&expected), "{}", ctags);
+    }
+
+    #[test]
+    fn test_tags_to_ctags_sorted_with_scope() {
+        let ctags = tags_to_ctags(&[
+            tag("zeta", "missing.py", 3, TagKind::Method, Some("Svc")),
+            tag("Alpha", "missing.py", 1, TagKind::Class, None),
+            tag("beta", "nb.ipynb", 7, TagKind::Function, None),
+        ]);
+        let lines: Vec<&str> = ctags.lines().filter(|l| !l.starts_with("!_")).collect();
+        // Byte order, as ctags requires; unreadable sources fall back to line numbers
+        assert_eq!(
+            lines,
+            vec![
+                "Alpha\tmissing.py\t1;\"\tc",
+                "beta\tnb.ipynb\t7;\"\tf",
+                "zeta\tmissing.py\t3;\"\tm\tclass:Svc",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_files_to_xml_escapes_signatures() {
+        let mut symbols = EntryMap::new();
+        symbols.insert(r#"def f(x: Literal["<", "&"]) -> A"#.to_string(), Entry::new(3));
+        symbols.insert("def g(s='it''s') -> B".to_string(), Entry::new(7));
+        let files = BTreeMap::from([("pkg/a&b.py".to_string(), symbols)]);
+
+        assert_eq!(
+            files_to_xml(&files),
+            concat!(
+                "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+                "<files>\n",
+                "  <file path=\"pkg/a&amp;b.py\">\n",
+                "    <symbol line=\"3\">def f(x: Literal[&quot;&lt;&quot;, &quot;&amp;&quot;]) -&gt; A</symbol>\n",
+                "    <symbol line=\"7\">def g(s=&apos;it&apos;&apos;s&apos;) -&gt; B</symbol>\n",
+                "  </file>\n",
+                "</files>\n",
+            )
+        );
+    }
+
+    #[test]
+    fn test_classes_to_xml_nests_members() {
+        let service = ClassInfo {
+            fields: MemberMap::from([("items: list[int]".to_string(), Entry::new(2))]),
+            methods: MemberMap::from([("def run(self) -> dict[str, int]".to_string(), Entry::new(4))]),
+            line: 1,
+            ..Default::default()
+        };
+        let empty = ClassInfo {
+            line: 8,
+            ..Default::default()
+        };
+        let classes: ClassMap = [
+            ("class Service(Base[T])".to_string(), service),
+            ("class Empty".to_string(), empty),
+        ]
+        .into_iter()
+        .collect();
+        let files = BTreeMap::from([("a.py".to_string(), classes)]);
+
+        let xml = classes_to_xml(&files);
+        let body: Vec<&str> = xml.lines().skip(2).collect();
+        assert_eq!(
+            body,
+            vec![
+                "  <file path=\"a.py\">",
+                "    <class line=\"1\" signature=\"class Service(Base[T])\">",
+                "      <field line=\"2\">items: list[int]</field>",
+                "      <method line=\"4\">def run(self) -&gt; dict[str, int]</method>",
+                "    </class>",
+                "    <class line=\"8\" signature=\"class Empty\"/>",
+                "  </file>",
+                "</files>",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_xml_format_rejected_for_plain_output() {
+        let style = OutputStyle {
+            format: OutputFormat::Xml,
+            ..JSON
+        };
+        assert!(output_to_writer(&signatures(), style, &[], &mut Vec::new()).is_err());
+    }
+
+    #[test]
+    fn test_lsp_format_rejected_for_plain_output() {
+        let style = OutputStyle {
+            format: OutputFormat::Lsp,
+            ..JSON
+        };
+        assert!(output_to_writer(&signatures(), style, &[], &mut Vec::new()).is_err());
+    }
+
+    #[test]
+    fn test_ctags_format_rejected_for_plain_output() {
+        let style = OutputStyle {
+            format: OutputFormat::Ctags,
+            ..JSON
+        };
+        assert!(output_to_writer(&signatures(), style, &[], &mut Vec::new()).is_err());
+    }
+
+    #[test]
+    fn test_split_signature() {
+        assert_eq!(
+            split_signature("Svc.async def run(self)"),
+            Some(("Svc.", "async def ", "run", "(self)"))
+        );
+        assert_eq!(split_signature("class Point"), Some(("", "class ", "Point", "")));
+        assert_eq!(split_signature("MAX_RETRIES = 3"), None);
+        assert_eq!(split_signature("files"), None);
+    }
+
+    #[derive(Serialize)]
+    struct TestData {
+        name: String,
+        value: i32,
     }
 
     #[test]
@@ -69,13 +1012,71 @@ mod tests {
         };
 
         let mut buffer = Vec::new();
-        output_to_writer(&data, true, &mut buffer).unwrap();
+        output_to_writer(&data, JSON, &[], &mut buffer).unwrap();
 
         let output = String::from_utf8(buffer).unwrap();
         assert!(output.contains("\"name\": \"test\""));
         assert!(output.contains("\"value\": 42"));
     }
 
+    #[test]
+    fn test_output_to_writer_json_envelope() {
+        let targets = vec![PathBuf::from("src")];
+        let style = OutputStyle {
+            envelope: Some(RunInfo {
+                command: "function",
+                targets: &targets,
+            }),
+            ..JSON
+        };
+        let mut files = BTreeMap::new();
+        files.insert(
+            "src/app.py".to_string(),
+            EntryMap::from([("def main()".to_string(), Entry::new(3))]),
+        );
+        let data = FilesOutput { files };
+
+        let mut buffer = Vec::new();
+        output_to_writer(&data, style, &[], &mut buffer).unwrap();
+        let value: serde_json::Value = serde_json::from_slice(&buffer).unwrap();
+
+        assert_eq!(value["schema_version"], crate::output::types::SCHEMA_VERSION);
+        assert_eq!(value["command"], "function");
+        assert_eq!(value["targets"], serde_json::json!(["src"]));
+        assert_eq!(value["results"], serde_json::to_value(&data).unwrap());
+    }
+
+    #[test]
+    fn test_output_to_writer_compact_json() {
+        let style = OutputStyle { compact: true, ..JSON };
+        let output = render(style, &[]);
+        let body = output.strip_suffix('\n').unwrap();
+
+        assert!(!body.contains('\n'));
+        assert!(!body.contains("  "));
+        let parsed: serde_json::Value = serde_json::from_str(body).unwrap();
+        assert_eq!(parsed, serde_json::to_value(signatures()).unwrap());
+    }
+
+    #[test]
+    fn test_compact_not_applied_to_yaml() {
+        let style = OutputStyle { compact: true, ..YAML };
+        assert_eq!(render(style, &[]), render(YAML, &[]));
+    }
+
+    #[test]
+    fn test_envelope_not_applied_to_yaml() {
+        let targets = vec![PathBuf::from("src")];
+        let style = OutputStyle {
+            envelope: Some(RunInfo {
+                command: "function",
+                targets: &targets,
+            }),
+            ..YAML
+        };
+        assert_eq!(render(style, &[]), render(YAML, &[]));
+    }
+
     #[test]
     fn test_output_to_writer_yaml() {
         let data = TestData {
@@ -84,13 +1085,79 @@ mod tests {
         };
 
         let mut buffer = Vec::new();
-        output_to_writer(&data, false, &mut buffer).unwrap();
+        output_to_writer(&data, YAML, &[], &mut buffer).unwrap();
 
         let output = String::from_utf8(buffer).unwrap();
         assert!(output.contains("name: test"));
         assert!(output.contains("value: 42"));
     }
 
+    #[test]
+    fn test_yaml_flow_inlines_leaf_maps() {
+        let method = Entry {
+            meta: Meta {
+                is_abstract: true,
+                ..Default::default()
+            },
+            ..Entry::new(7)
+        };
+        let service = ClassInfo {
+            fields: MemberMap::from([("name: str".to_string(), Entry::new(3))]),
+            methods: MemberMap::from([
+                ("def run(self, force: bool)".to_string(), method),
+                ("def stop(self)".to_string(), Entry::new(12)),
+            ]),
+            ..Default::default()
+        };
+        let mut files = BTreeMap::new();
+        files.insert(
+            "app/service.py".to_string(),
+            ClassMap::from([("class Service(Base)".to_string(), service)]),
+        );
+        let data = ClassesOutput { files };
+
+        let mut buffer = Vec::new();
+        let style = OutputStyle {
+            yaml_flow: true,
+            ..YAML
+        };
+        output_to_writer(&data, style, &[], &mut buffer).unwrap();
+        let output = String::from_utf8(buffer).unwrap();
+
+        assert_eq!(
+            output,
+            "files:\n  app/service.py:\n    class Service(Base):\n      fields: {'name: str': 3}\n      methods:\n        \
+             'def run(self, force: bool)': {line: 7, abstract: true}\n        def stop(self): 12\n"
+        );
+        let parsed: serde_yaml::Value = serde_yaml::from_str(&output).unwrap();
+        assert_eq!(parsed, serde_yaml::to_value(&data).unwrap());
+    }
+
+    #[test]
+    fn test_yaml_flow_sequences() {
+        let data = serde_json::json!({
+            "modules": {"app": {"imports": ["os", "sys"], "edges": [{"from": "a", "to": "b"}]}},
+            "empty": {},
+            "calls": ["def f(a, b)", "g"],
+            "note": "line one\nline two",
+        });
+        let mut buffer = Vec::new();
+        let style = OutputStyle {
+            yaml_flow: true,
+            ..YAML
+        };
+        output_to_writer(&data, style, &[], &mut buffer).unwrap();
+        let output = String::from_utf8(buffer).unwrap();
+
+        assert!(output.contains("imports: [os, sys]"), "{}", output);
+        assert!(output.contains("- {from: a, to: b}"), "{}", output);
+        assert!(output.contains("empty: {}"), "{}", output);
+        // Commas inside a flow sequence must be quoted
+        assert!(output.contains(r#"calls: ["def f(a, b)", g]"#), "{}", output);
+        let parsed: serde_yaml::Value = serde_yaml::from_str(&output).unwrap();
+        assert_eq!(parsed, serde_yaml::to_value(&data).unwrap());
+    }
+
     #[test]
     fn test_output_to_writer_btreemap_json() {
         let mut data: BTreeMap<String, i32> = BTreeMap::new();
@@ -98,7 +1165,7 @@ mod tests {
         data.insert("bar".to_string(), 2);
 
         let mut buffer = Vec::new();
-        output_to_writer(&data, true, &mut buffer).unwrap();
+        output_to_writer(&data, JSON, &[], &mut buffer).unwrap();
 
         let output = String::from_utf8(buffer).unwrap();
         assert!(output.contains("\"foo\": 1"));
@@ -112,7 +1179,7 @@ mod tests {
         data.insert("bar".to_string(), 2);
 
         let mut buffer = Vec::new();
-        output_to_writer(&data, false, &mut buffer).unwrap();
+        output_to_writer(&data, YAML, &[], &mut buffer).unwrap();
 
         let output = String::from_utf8(buffer).unwrap();
         assert!(output.contains("foo: 1"));
@@ -124,18 +1191,109 @@ mod tests {
         let data: BTreeMap<String, i32> = BTreeMap::new();
 
         let mut buffer = Vec::new();
-        output_to_writer(&data, true, &mut buffer).unwrap();
+        output_to_writer(&data, JSON, &[], &mut buffer).unwrap();
 
         let output = String::from_utf8(buffer).unwrap();
         assert!(output.contains("{}"));
     }
 
+    fn graph(edges: &[(&str, &str)]) -> DepsOutput {
+        let mut deps = DepsOutput::default();
+        for (from, to) in edges {
+            deps.deps.entry(from.to_string()).or_default().insert(to.to_string());
+        }
+        deps
+    }
+
+    #[test]
+    fn test_modules_to_tree_pkg_fixture() {
+        let base = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/pkg");
+        let files = crate::walk::collect_python_files(std::slice::from_ref(&base), &Default::default()).unwrap();
+        let modules = crate::analysis::build_module_tree(&files, &base, None);
+
+        let expected = "\
+.
+├── __init__.py
+├── module.py
+└── subpkg/
+    ├── __init__.py
+    └── nested.py
+";
+        assert_eq!(modules_to_tree(&modules), expected);
+    }
+
+    #[test]
+    fn test_modules_to_tree_deep_nesting_and_counts() {
+        let base = PathBuf::from("/base");
+        let files = vec![
+            PathBuf::from("/base/a/b/c/deep.py"),
+            PathBuf::from("/base/a/side.py"),
+            PathBuf::from("/base/top.py"),
+        ];
+        let mut modules = crate::analysis::build_module_tree(&files, &base, None);
+        modules.modules.get_mut("top.py").unwrap().counts = Some(crate::output::SymbolCounts {
+            functions: 2,
+            classes: 1,
+            enums: 0,
+        });
+
+        let expected = "\
+.
+├── a/
+│   ├── b/
+│   │   └── c/
+│   │       └── deep.py
+│   └── side.py
+└── top.py (2 functions, 1 classes, 0 enums)
+";
+        assert_eq!(modules_to_tree(&modules), expected);
+    }
+
+    #[test]
+    fn test_deps_to_dot_edges() {
+        let dot = deps_to_dot(&graph(&[("a", "b"), ("a", "c"), ("b", "c")]));
+        assert!(dot.starts_with("digraph deps {\n"));
+        assert!(dot.contains("  \"a\" -> \"b\";\n"));
+        assert!(dot.contains("  \"b\" -> \"c\";\n"));
+        assert!(dot.contains("  \"c\";\n"));
+        assert!(dot.ends_with("}\n"));
+    }
+
+    #[test]
+    fn test_deps_to_dot_dedupes_edges() {
+        let dot = deps_to_dot(&graph(&[("a", "b"), ("a", "b")]));
+        assert_eq!(dot.matches("\"a\" -> \"b\"").count(), 1);
+    }
+
+    #[test]
+    fn test_deps_to_dot_quotes_labels() {
+        let dot = deps_to_dot(&graph(&[("dir\\we\"ird.py", "b.py")]));
+        assert!(dot.contains(r#""dir\\we\"ird.py" -> "b.py""#));
+    }
+
+    #[test]
+    fn test_deps_to_dot_dashes_type_checking_edges() {
+        let mut deps = graph(&[("a", "b"), ("a", "c")]);
+        deps.type_checking
+            .entry("a".to_string())
+            .or_default()
+            .insert("c".to_string());
+        let dot = deps_to_dot(&deps);
+        assert!(dot.contains("  \"a\" -> \"b\";\n"));
+        assert!(dot.contains("  \"a\" -> \"c\" [style=dashed];\n"));
+    }
+
+    #[test]
+    fn test_deps_to_dot_empty() {
+        assert_eq!(deps_to_dot(&DepsOutput::default()), "digraph deps {\n}\n");
+    }
+
     #[test]
     fn test_output_to_writer_empty_map_yaml() {
         let data: BTreeMap<String, i32> = BTreeMap::new();
 
         let mut buffer = Vec::new();
-        output_to_writer(&data, false, &mut buffer).unwrap();
+        output_to_writer(&data, YAML, &[], &mut buffer).unwrap();
 
         let output = String::from_utf8(buffer).unwrap();
         assert!(output.contains("{}"));
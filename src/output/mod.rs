@@ -1,5 +1,17 @@
 pub mod format;
+pub mod sqlite;
 pub mod types;
 
-pub use format::{output, should_use_json};
-pub use types::{ClassInfo, ClassMap, ClassesOutput, FilesOutput, ModuleNode, ModuleType, ModulesOutput};
+pub use format::{
+    output, output_classes_xml, output_ctags, output_dot, output_jsonl, output_lsp, output_tree, output_xml,
+    resolve_format, should_show_summary, should_use_color, summary_footer, OutputStyle,
+};
+pub use sqlite::write_sqlite;
+pub use types::{
+    AnnotationCoverage, CallSite, ClassInfo, ClassKind, ClassMap, ClassSignatures, ClassSignaturesOutput,
+    ClassesOutput, CoverageOutput, DecoratorUse, DecoratorUses, DecoratorsOutput, Definition, DepsOutput, DiffOutput,
+    DocumentSymbol, DupesOutput, Effect, EffectKind, EffectsOutput, Entry, EntryMap, Envelope, FilesOutput,
+    ImportConflict, ImportConflictKind, LabeledOutput, LineCounts, LocOutput, LspOutput, MemberMap, Meta, ModelKind,
+    ModuleNode, ModuleType, ModulesOutput, PropertyAccessors, RefsOutput, RunInfo, ShadowsOutput, SignatureChange,
+    SignatureDiff, SignaturesOutput, SymbolCounts, SymbolKind, Tag, TagKind,
+};
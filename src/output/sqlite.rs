@@ -0,0 +1,176 @@
+use super::Tag;
+use eyre::Result;
+use rusqlite::{params, Connection};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS files (
+    id INTEGER PRIMARY KEY,
+    path TEXT NOT NULL UNIQUE
+);
+CREATE TABLE IF NOT EXISTS symbols (
+    id INTEGER PRIMARY KEY,
+    file_id INTEGER NOT NULL REFERENCES files(id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    signature TEXT NOT NULL,
+    line INTEGER NOT NULL,
+    parent TEXT
+);
+CREATE INDEX IF NOT EXISTS symbols_file_id ON symbols(file_id);
+CREATE INDEX IF NOT EXISTS symbols_name ON symbols(name);
+";
+
+/// Write symbols to a SQLite database, creating the schema if absent. The
+/// previous symbols of every scanned file are replaced, so repeated runs
+/// upsert by path and a file whose symbols are all gone is left with none.
+pub fn write_sqlite(path: &Path, scanned: &[String], tags: &[Tag]) -> Result<()> {
+    let mut conn = Connection::open(path)?;
+    conn.execute_batch(SCHEMA)?;
+
+    let mut by_file: BTreeMap<&str, Vec<&Tag>> = scanned.iter().map(|file| (file.as_str(), Vec::new())).collect();
+    for tag in tags {
+        by_file.entry(&tag.file).or_default().push(tag);
+    }
+
+    let tx = conn.transaction()?;
+    {
+        let mut upsert_file = tx.prepare(
+            "INSERT INTO files (path) VALUES (?1)
+             ON CONFLICT(path) DO UPDATE SET path = excluded.path
+             RETURNING id",
+        )?;
+        let mut clear_symbols = tx.prepare("DELETE FROM symbols WHERE file_id = ?1")?;
+        let mut insert_symbol = tx.prepare(
+            "INSERT INTO symbols (file_id, name, kind, signature, line, parent)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        )?;
+
+        for (file, tags) in by_file {
+            let file_id: i64 = upsert_file.query_row([file], |row| row.get(0))?;
+            clear_symbols.execute([file_id])?;
+            for tag in tags {
+                insert_symbol.execute(params![
+                    file_id,
+                    tag.name,
+                    tag.kind.name(),
+                    tag.signature,
+                    tag.line,
+                    tag.scope
+                ])?;
+            }
+        }
+    }
+    tx.commit()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::TagKind;
+
+    fn tag(name: &str, file: &str, kind: TagKind, scope: Option<&str>) -> Tag {
+        Tag {
+            name: name.to_string(),
+            file: file.to_string(),
+            line: 1,
+            kind,
+            scope: scope.map(str::to_string),
+            signature: format!("def {}()", name),
+        }
+    }
+
+    fn symbols(conn: &Connection) -> Vec<(String, String, String, Option<String>)> {
+        let mut stmt = conn
+            .prepare(
+                "SELECT files.path, symbols.name, symbols.kind, symbols.parent
+                 FROM symbols JOIN files ON files.id = symbols.file_id
+                 ORDER BY symbols.id",
+            )
+            .unwrap();
+        stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_write_sqlite_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = dir.path().join("pyr.db");
+        write_sqlite(
+            &db,
+            &[],
+            &[
+                tag("Svc", "a.py", TagKind::Class, None),
+                tag("run", "a.py", TagKind::Method, Some("Svc")),
+                tag("helper", "b.py", TagKind::Function, None),
+            ],
+        )
+        .unwrap();
+
+        let conn = Connection::open(&db).unwrap();
+        assert_eq!(
+            symbols(&conn),
+            vec![
+                ("a.py".into(), "Svc".into(), "class".into(), None),
+                ("a.py".into(), "run".into(), "method".into(), Some("Svc".into())),
+                ("b.py".into(), "helper".into(), "function".into(), None),
+            ]
+        );
+        let signature: String = conn
+            .query_row("SELECT signature FROM symbols WHERE name = 'helper'", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(signature, "def helper()");
+    }
+
+    #[test]
+    fn test_write_sqlite_upserts_by_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = dir.path().join("pyr.db");
+        write_sqlite(
+            &db,
+            &[],
+            &[
+                tag("old", "a.py", TagKind::Function, None),
+                tag("kept", "b.py", TagKind::Function, None),
+            ],
+        )
+        .unwrap();
+        write_sqlite(&db, &[], &[tag("new", "a.py", TagKind::Function, None)]).unwrap();
+
+        let conn = Connection::open(&db).unwrap();
+        let names: Vec<String> = symbols(&conn).into_iter().map(|(_, name, _, _)| name).collect();
+        // a.py's symbols are replaced; b.py, absent from the second run, is left alone
+        assert_eq!(names, vec!["kept", "new"]);
+        let files: i64 = conn
+            .query_row("SELECT COUNT(*) FROM files", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(files, 2);
+    }
+
+    #[test]
+    fn test_write_sqlite_clears_scanned_files_without_symbols() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = dir.path().join("pyr.db");
+        write_sqlite(
+            &db,
+            &[],
+            &[
+                tag("removed", "a.py", TagKind::Function, None),
+                tag("kept", "b.py", TagKind::Function, None),
+            ],
+        )
+        .unwrap();
+        // a.py was scanned again but no longer defines anything
+        write_sqlite(&db, &["a.py".to_string()], &[]).unwrap();
+
+        let conn = Connection::open(&db).unwrap();
+        let names: Vec<String> = symbols(&conn).into_iter().map(|(_, name, _, _)| name).collect();
+        assert_eq!(names, vec!["kept"]);
+    }
+}
@@ -1,5 +1,199 @@
-use serde::Serialize;
-use std::collections::BTreeMap;
+use clap::ValueEnum;
+use indexmap::IndexMap;
+use serde::{Serialize, Serializer};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::PathBuf;
+
+/// Version of the `--envelope` JSON layout; bump when a results structure changes
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// The run that produced a result, reported in the `--envelope` wrapper
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RunInfo<'a> {
+    pub command: &'a str,
+    pub targets: &'a [PathBuf],
+}
+
+/// JSON wrapper that lets consumers detect format changes (`--envelope`)
+/// Format:
+///   schema_version: 1
+///   command: <subcommand>
+///   targets: [<path>, ...]
+///   results: <the command's usual output>
+#[derive(Debug, Serialize)]
+pub struct Envelope<'a, T> {
+    pub schema_version: u32,
+    pub command: &'a str,
+    pub targets: &'a [PathBuf],
+    pub results: &'a T,
+}
+
+impl<'a, T> Envelope<'a, T> {
+    pub fn new(run: RunInfo<'a>, results: &'a T) -> Self {
+        Envelope {
+            schema_version: SCHEMA_VERSION,
+            command: run.command,
+            targets: run.targets,
+            results,
+        }
+    }
+}
+
+/// Optional annotations attached to a symbol by command flags or grouping.
+/// Every field is skipped when unset, so an empty `Meta` adds nothing to the output.
+#[derive(Debug, Serialize, Default, Clone, PartialEq, Eq)]
+pub struct Meta {
+    #[serde(rename = "abstract", skip_serializing_if = "std::ops::Not::not")]
+    pub is_abstract: bool,
+    /// Never returns normally (`--noreturn`)
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub noreturn: bool,
+    /// A context manager (`--context-managers`)
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub context_manager: bool,
+    /// Whether the symbol is part of the module's public surface (`--annotate-exports`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exported: Option<bool>,
+    /// Dotted import path of the symbol, e.g. `pkg.module.Class.method` (`--qualnames`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub qualname: Option<String>,
+    /// Accessor lines of a `@property`, grouped under its getter
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub property: Option<PropertyAccessors>,
+    /// Number of parameters, `self`/`*args`/`**kwargs` included (`--with-arity`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub arity: Option<usize>,
+    /// Full source text of the definition (`--with-source`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    /// Data model library a class is declared with (`--model`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<ModelKind>,
+    /// Structural type a class declares (`--kind`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kind: Option<ClassKind>,
+    /// Base class (in the same file) whose method this one shadows (`--overrides`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub overrides: Option<String>,
+    /// How well the name matched the patterns, higher is better (`--fuzzy`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub score: Option<u32>,
+}
+
+/// Libraries whose classes declare their fields as annotated class attributes
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ModelKind {
+    /// Subclass of pydantic `BaseModel`/`BaseSettings`
+    Pydantic,
+    /// `@dataclass`
+    Dataclass,
+    /// `@attr.s` / `@attrs.define`
+    Attrs,
+}
+
+/// Structural types that read differently from ordinary classes
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum ClassKind {
+    /// Subclass of `typing.Protocol`
+    Protocol,
+    /// `TypedDict` subclass or functional `TypedDict(...)` call
+    #[value(name = "typeddict")]
+    TypedDict,
+    /// `NamedTuple` subclass or functional `NamedTuple(...)` call
+    #[value(name = "namedtuple")]
+    NamedTuple,
+}
+
+/// Lines of a property's getter and, when defined, its setter and deleter
+#[derive(Debug, Serialize, Default, Clone, PartialEq, Eq)]
+pub struct PropertyAccessors {
+    pub get: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub set: Option<usize>,
+    #[serde(rename = "del", skip_serializing_if = "Option::is_none")]
+    pub delete: Option<usize>,
+}
+
+impl Meta {
+    pub fn is_empty(&self) -> bool {
+        *self == Meta::default()
+    }
+}
+
+/// A single symbol: its line number plus facts gathered during extraction.
+/// Serializes as a bare line number unless an annotation is set in `meta`,
+/// so the default output stays `<signature>: lineno`.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub line: usize,
+    /// Last line of the definition (inclusive)
+    pub end_line: usize,
+    /// Cyclomatic complexity of the body (0 for non-function symbols)
+    pub complexity: usize,
+    /// Number of parameters, `self`/`*args`/`**kwargs` included (0 for non-function symbols)
+    pub params: usize,
+    /// A field of a data model class, as opposed to a plain class variable
+    pub model_field: bool,
+    /// A function whose body yields: a generator, or an async generator
+    pub generator: bool,
+    /// A function that never returns normally: `-> NoReturn`/`-> Never`, or an endless `while True:`
+    pub noreturn: bool,
+    /// A `@contextmanager`/`@asynccontextmanager` function
+    pub context_manager: bool,
+    /// Decorator expressions as written, without the leading `@`
+    pub decorators: Vec<String>,
+    pub meta: Meta,
+}
+
+impl Entry {
+    pub fn new(line: usize) -> Self {
+        Entry {
+            line,
+            end_line: line,
+            ..Default::default()
+        }
+    }
+
+    /// Number of source lines spanned by the definition
+    pub fn lines(&self) -> usize {
+        self.end_line.saturating_sub(self.line) + 1
+    }
+}
+
+impl From<usize> for Entry {
+    fn from(line: usize) -> Self {
+        Entry::new(line)
+    }
+}
+
+impl Serialize for Entry {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        #[derive(Serialize)]
+        struct Detailed<'a> {
+            line: usize,
+            #[serde(flatten)]
+            meta: &'a Meta,
+        }
+
+        if self.meta.is_empty() {
+            serializer.serialize_u64(self.line as u64)
+        } else {
+            Detailed {
+                line: self.line,
+                meta: &self.meta,
+            }
+            .serialize(serializer)
+        }
+    }
+}
+
+/// Type alias for symbol map: signature -> Entry, kept in output order
+pub type EntryMap = IndexMap<String, Entry>;
+
+/// Type alias for class member map: signature -> Entry, kept in source order
+pub type MemberMap = IndexMap<String, Entry>;
 
 /// Top-level output for functions/enums commands
 /// Format:
@@ -8,26 +202,63 @@ use std::collections::BTreeMap;
 ///       <signature>: lineno
 #[derive(Debug, Serialize, Default)]
 pub struct FilesOutput {
-    pub files: BTreeMap<String, BTreeMap<String, usize>>,
+    pub files: BTreeMap<String, EntryMap>,
 }
 
 /// Information about a single class
 #[derive(Debug, Serialize, Default, Clone)]
 pub struct ClassInfo {
-    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
-    pub fields: BTreeMap<String, usize>,
-    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
-    pub methods: BTreeMap<String, usize>,
+    /// Full class signature, when classes are keyed by name (`--class-key name`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+    #[serde(flatten)]
+    pub meta: Meta,
+    #[serde(skip_serializing_if = "IndexMap::is_empty")]
+    pub fields: MemberMap,
+    #[serde(skip_serializing_if = "IndexMap::is_empty")]
+    pub methods: MemberMap,
+    /// Classes nested in the body, down to `--class-depth` levels
+    #[serde(skip_serializing_if = "IndexMap::is_empty")]
+    pub classes: ClassMap,
+    /// First and last line of the class definition
+    #[serde(skip)]
+    pub line: usize,
+    #[serde(skip)]
+    pub end_line: usize,
+    /// Rendered base class expressions
+    #[serde(skip)]
+    pub bases: Vec<String>,
+    /// Rendered `metaclass=` keyword, if any
+    #[serde(skip)]
+    pub metaclass: Option<String>,
+    /// Defines `__enter__`/`__exit__` (or `__aenter__`/`__aexit__`)
+    #[serde(skip)]
+    pub context_manager: bool,
+    /// Structural type declared by the bases (`Protocol`, `TypedDict` or `NamedTuple`)
+    #[serde(skip)]
+    pub kind: Option<ClassKind>,
 }
 
-/// Type alias for class map: class_signature -> ClassInfo
-pub type ClassMap = BTreeMap<String, ClassInfo>;
+/// Type alias for class map: class_signature -> ClassInfo, kept in output order
+pub type ClassMap = IndexMap<String, ClassInfo>;
+
+/// Output of a listing command split by target with `--labeled`
+/// Format:
+///   roots:
+///     <target>:
+///       files:
+///         <filepath relative to target>: ...
+#[derive(Debug, Serialize)]
+pub struct LabeledOutput<T> {
+    pub roots: BTreeMap<String, T>,
+}
 
 /// Top-level output for classes command
 /// Format:
 ///   files:
 ///     <filepath>:
-///       <class_signature>:
+///       <class_signature>:        (<class_name> with `--class-key name`)
+///         signature: <class_signature>  (only with `--class-key name`)
 ///         fields:
 ///           <field_name>: lineno
 ///         methods:
@@ -37,22 +268,511 @@ pub struct ClassesOutput {
     pub files: BTreeMap<String, ClassMap>,
 }
 
+/// `function`, `enum` or `dump` output with `--signature-only`
+/// Format:
+///   files:
+///     <filepath>:
+///       - <signature>
+#[derive(Debug, Serialize, Default)]
+pub struct SignaturesOutput {
+    pub files: BTreeMap<String, Vec<String>>,
+}
+
+/// `class` output with `--signature-only`
+/// Format:
+///   files:
+///     <filepath>:
+///       <class_signature>:
+///         fields:
+///           - <field>
+///         methods:
+///           - <method_signature>
+#[derive(Debug, Serialize, Default)]
+pub struct ClassSignaturesOutput {
+    pub files: BTreeMap<String, IndexMap<String, ClassSignatures>>,
+}
+
+/// Member signatures of a single class, without lines
+#[derive(Debug, Serialize, Default)]
+pub struct ClassSignatures {
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub methods: Vec<String>,
+    #[serde(skip_serializing_if = "IndexMap::is_empty")]
+    pub classes: IndexMap<String, ClassSignatures>,
+}
+
 /// Top-level output for modules command
 #[derive(Debug, Serialize, Default)]
 pub struct ModulesOutput {
     pub modules: BTreeMap<String, ModuleNode>,
 }
 
+/// Top-level output for deps command
+/// Format:
+///   deps:
+///     <filepath>:
+///       - <imported filepath>
+///   external:
+///     <filepath>:
+///       - <module as written>
+///   type_checking:
+///     <filepath>:
+///       - <imported filepath or module only imported under `if TYPE_CHECKING:`>
+#[derive(Debug, Serialize, Default)]
+pub struct DepsOutput {
+    pub deps: BTreeMap<String, BTreeSet<String>>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub external: BTreeMap<String, BTreeSet<String>>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub type_checking: BTreeMap<String, BTreeSet<String>>,
+}
+
 /// A node in the module tree
 #[derive(Debug, Serialize)]
 pub struct ModuleNode {
     #[serde(rename = "type")]
     pub node_type: ModuleType,
 
+    /// Symbol counts (only with `--counts`); packages sum their descendants
+    #[serde(flatten, skip_serializing_if = "Option::is_none")]
+    pub counts: Option<SymbolCounts>,
+
     #[serde(skip_serializing_if = "BTreeMap::is_empty")]
     pub children: BTreeMap<String, ModuleNode>,
 }
 
+/// Number of top-level functions, classes and enums in a module
+#[derive(Debug, Serialize, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SymbolCounts {
+    pub functions: usize,
+    pub classes: usize,
+    pub enums: usize,
+}
+
+impl std::ops::AddAssign for SymbolCounts {
+    fn add_assign(&mut self, other: Self) {
+        self.functions += other.functions;
+        self.classes += other.classes;
+        self.enums += other.enums;
+    }
+}
+
+/// Top-level output for coverage command
+/// Format:
+///   files:
+///     <filepath>:
+///       params: {annotated, total, percent}
+///       returns: {annotated, total, percent}
+///   total:
+///     params: ...
+///     returns: ...
+#[derive(Debug, Serialize, Default)]
+pub struct CoverageOutput {
+    pub files: BTreeMap<String, AnnotationCoverage>,
+    pub total: AnnotationCoverage,
+}
+
+/// Top-level output for loc command
+/// Format:
+///   files:
+///     <filepath>:
+///       lines: ...
+///       blank: ...
+///       comment: ...
+///       code: ...
+///   total: ...
+#[derive(Debug, Serialize, Default)]
+pub struct LocOutput {
+    pub files: BTreeMap<String, LineCounts>,
+    pub total: LineCounts,
+}
+
+/// A symbol flattened for `--format ctags` and `--sqlite`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tag {
+    pub name: String,
+    pub file: String,
+    pub line: usize,
+    pub kind: TagKind,
+    /// Class a method belongs to
+    pub scope: Option<String>,
+    /// Rendered signature, without the class prefix for methods
+    pub signature: String,
+}
+
+/// Kind of a ctags entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TagKind {
+    Function,
+    Class,
+    Method,
+    Enum,
+    Variable,
+}
+
+impl TagKind {
+    /// Single-letter kind written in the tags file
+    pub fn letter(self) -> char {
+        match self {
+            TagKind::Function => 'f',
+            TagKind::Class => 'c',
+            TagKind::Method => 'm',
+            TagKind::Enum => 'g',
+            TagKind::Variable => 'v',
+        }
+    }
+
+    /// Kind name stored by `--sqlite`
+    pub fn name(self) -> &'static str {
+        match self {
+            TagKind::Function => "function",
+            TagKind::Class => "class",
+            TagKind::Method => "method",
+            TagKind::Enum => "enum",
+            TagKind::Variable => "variable",
+        }
+    }
+}
+
+/// An LSP `DocumentSymbol` (`--format lsp`). Lines are zero-based; a range
+/// runs from the start of its first line to the start of the line after its last.
+#[derive(Debug, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentSymbol {
+    pub name: String,
+    /// The rendered signature
+    pub detail: String,
+    pub kind: SymbolKind,
+    pub range: LspRange,
+    /// The symbol's first line
+    pub selection_range: LspRange,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<DocumentSymbol>,
+}
+
+impl DocumentSymbol {
+    /// Symbol spanning the one-based, inclusive lines `line..=end_line`
+    pub fn new(name: &str, detail: &str, kind: SymbolKind, line: usize, end_line: usize) -> Self {
+        let start = line.saturating_sub(1);
+        DocumentSymbol {
+            name: name.to_string(),
+            detail: detail.to_string(),
+            kind,
+            range: LspRange::lines(start, end_line.max(line)),
+            selection_range: LspRange::lines(start, start + 1),
+            children: Vec::new(),
+        }
+    }
+}
+
+/// LSP `SymbolKind` values of the symbols pyr reports
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum SymbolKind {
+    Class = 5,
+    Method = 6,
+    Enum = 10,
+    Function = 12,
+}
+
+impl Serialize for SymbolKind {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u8(*self as u8)
+    }
+}
+
+#[derive(Debug, Serialize, PartialEq, Eq)]
+pub struct LspRange {
+    pub start: LspPosition,
+    pub end: LspPosition,
+}
+
+impl LspRange {
+    /// From the start of zero-based line `start` to the start of line `end`
+    fn lines(start: usize, end: usize) -> Self {
+        LspRange {
+            start: LspPosition {
+                line: start,
+                character: 0,
+            },
+            end: LspPosition {
+                line: end,
+                character: 0,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Serialize, PartialEq, Eq)]
+pub struct LspPosition {
+    pub line: usize,
+    pub character: usize,
+}
+
+/// Top-level output for `--format lsp`: each file's symbols, keyed by path
+pub type LspOutput = BTreeMap<String, Vec<DocumentSymbol>>;
+
+/// Top-level output for diff command
+/// Format:
+///   files:
+///     <filepath relative to its tree>:
+///       added: [<signature>, ...]
+///       removed: [<signature>, ...]
+///       changed:
+///         <qualified name>: {old, new}
+#[derive(Debug, Serialize, Default)]
+pub struct DiffOutput {
+    pub files: BTreeMap<String, SignatureDiff>,
+}
+
+/// Signature changes of one file. Methods are listed as `Class.def method(...)`.
+#[derive(Debug, Serialize, Default, PartialEq, Eq)]
+pub struct SignatureDiff {
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub added: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub removed: Vec<String>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub changed: BTreeMap<String, SignatureChange>,
+}
+
+impl SignatureDiff {
+    pub fn is_empty(&self) -> bool {
+        *self == SignatureDiff::default()
+    }
+}
+
+/// A symbol present on both sides whose signature differs
+#[derive(Debug, Serialize, PartialEq, Eq)]
+pub struct SignatureChange {
+    pub old: String,
+    pub new: String,
+}
+
+/// Top-level output for refs command
+/// Format:
+///   files:
+///     <filepath>:
+///       - line: <n>
+///         call: <rendered callee>
+///         scope: <enclosing function or class, omitted at module level>
+#[derive(Debug, Serialize, Default)]
+pub struct RefsOutput {
+    pub files: BTreeMap<String, Vec<CallSite>>,
+}
+
+/// A call whose callee matches the name looked up
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+pub struct CallSite {
+    pub line: usize,
+    pub call: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+}
+
+/// Top-level output for shadows command
+/// Format:
+///   files:
+///     <filepath>:
+///       - name: <bound name>
+///         kind: <duplicate|shadowed>
+///         lines: [<n>, ...]
+#[derive(Debug, Serialize, Default)]
+pub struct ShadowsOutput {
+    pub files: BTreeMap<String, Vec<ImportConflict>>,
+}
+
+/// A name bound more than once at module level, with every line binding it
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+pub struct ImportConflict {
+    pub name: String,
+    pub kind: ImportConflictKind,
+    pub lines: Vec<usize>,
+}
+
+/// How an imported name is bound again
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportConflictKind {
+    /// Imported more than once
+    Duplicate,
+    /// Imported and also defined as a top-level function or class
+    Shadowed,
+}
+
+/// Top-level output for effects command
+/// Format:
+///   files:
+///     <filepath>:
+///       - line: <n>
+///         kind: <call|loop|conditional|try|with|other>
+///         code: <first line of the statement>
+#[derive(Debug, Serialize, Default)]
+pub struct EffectsOutput {
+    pub files: BTreeMap<String, Vec<Effect>>,
+}
+
+/// A module-level statement that does work when the module is imported
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+pub struct Effect {
+    pub line: usize,
+    pub kind: EffectKind,
+    pub code: String,
+}
+
+/// What a module-level statement does at import time
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum EffectKind {
+    /// A call, bare or as the value of an assignment
+    Call,
+    /// `for` or `while`
+    Loop,
+    /// `if` or `match`
+    Conditional,
+    Try,
+    With,
+    /// Anything else that runs: `raise`, `assert`, `del`, bare expressions, ...
+    Other,
+}
+
+/// Top-level output for dupes command
+/// Format:
+///   names:
+///     <name>:
+///       - file: <filepath>
+///         line: <n>
+///         kind: function | class
+#[derive(Debug, Serialize, Default)]
+pub struct DupesOutput {
+    pub names: BTreeMap<String, Vec<Definition>>,
+}
+
+/// Where a top-level function or class is defined
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+pub struct Definition {
+    pub file: String,
+    pub line: usize,
+    pub kind: &'static str,
+}
+
+/// Top-level output for decorators command
+/// Format:
+///   decorators:
+///     <decorator>:
+///       count: <n>
+///       files: <n>
+///       uses:
+///         - file: <filepath>
+///           line: <n>
+///           name: <function or Class.method>
+#[derive(Debug, Serialize, Default)]
+pub struct DecoratorsOutput {
+    pub decorators: BTreeMap<String, DecoratorUses>,
+}
+
+/// Where a decorator is used, and how often
+#[derive(Debug, Serialize, Default, Clone, PartialEq, Eq)]
+pub struct DecoratorUses {
+    pub count: usize,
+    /// Number of distinct files
+    pub files: usize,
+    pub uses: Vec<DecoratorUse>,
+}
+
+/// A function or method carrying a decorator
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+pub struct DecoratorUse {
+    pub file: String,
+    /// Line of the decorated `def`
+    pub line: usize,
+    pub name: String,
+}
+
+/// Physical line counts of a file: every line is blank, comment or code
+#[derive(Debug, Serialize, Default, Clone, Copy, PartialEq, Eq)]
+pub struct LineCounts {
+    pub lines: usize,
+    pub blank: usize,
+    pub comment: usize,
+    pub code: usize,
+}
+
+impl std::ops::AddAssign for LineCounts {
+    fn add_assign(&mut self, other: Self) {
+        self.lines += other.lines;
+        self.blank += other.blank;
+        self.comment += other.comment;
+        self.code += other.code;
+    }
+}
+
+/// How many parameters and return types are annotated
+#[derive(Debug, Serialize, Default, Clone, Copy, PartialEq, Eq)]
+pub struct AnnotationCoverage {
+    pub params: Ratio,
+    pub returns: Ratio,
+}
+
+impl std::ops::AddAssign for AnnotationCoverage {
+    fn add_assign(&mut self, other: Self) {
+        self.params += other.params;
+        self.returns += other.returns;
+    }
+}
+
+/// Annotated items out of a total; serialized with its percentage
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Ratio {
+    pub annotated: usize,
+    pub total: usize,
+}
+
+impl Ratio {
+    /// Count one item
+    pub fn record(&mut self, annotated: bool) {
+        self.total += 1;
+        if annotated {
+            self.annotated += 1;
+        }
+    }
+
+    /// Percentage annotated, to one decimal place (100 when there is nothing to annotate)
+    pub fn percent(&self) -> f64 {
+        if self.total == 0 {
+            return 100.0;
+        }
+        (self.annotated as f64 * 1000.0 / self.total as f64).round() / 10.0
+    }
+}
+
+impl std::ops::AddAssign for Ratio {
+    fn add_assign(&mut self, other: Self) {
+        self.annotated += other.annotated;
+        self.total += other.total;
+    }
+}
+
+impl Serialize for Ratio {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        #[derive(Serialize)]
+        struct Detailed {
+            annotated: usize,
+            total: usize,
+            percent: f64,
+        }
+
+        Detailed {
+            annotated: self.annotated,
+            total: self.total,
+            percent: self.percent(),
+        }
+        .serialize(serializer)
+    }
+}
+
 #[derive(Debug, Serialize, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
 pub enum ModuleType {
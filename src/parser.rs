@@ -1,4 +1,5 @@
-use eyre::Result;
+use crate::{archive, git};
+use eyre::{Result, WrapErr};
 use rustpython_parser::{ast, Parse};
 use std::collections::BTreeMap;
 use std::fs;
@@ -11,29 +12,125 @@ pub struct ParsedFile {
 }
 
 impl ParsedFile {
-    /// Convert a byte offset to a 1-based line number
+    /// Convert a byte offset to a 1-based line number. Lines end at `\n`,
+    /// `\r\n` or a lone `\r`, as they do for the parser and in editors.
     pub fn offset_to_line(&self, offset: u32) -> usize {
-        let offset = offset as usize;
-        self.source[..offset.min(self.source.len())].matches('\n').count() + 1
+        let source = self.source.as_bytes();
+        let offset = (offset as usize).min(source.len());
+        let breaks = source[..offset]
+            .iter()
+            .enumerate()
+            .filter(|&(i, &byte)| byte == b'\n' || (byte == b'\r' && source.get(i + 1) != Some(&b'\n')))
+            .count();
+        breaks + 1
+    }
+
+    /// Source text covered by a node's range. Offsets are clamped to the source
+    /// and moved back to the nearest char boundary, so this never panics.
+    pub fn snippet(&self, range: ast::text_size::TextRange) -> &str {
+        let floor = |offset: usize| {
+            let mut offset = offset.min(self.source.len());
+            while !self.source.is_char_boundary(offset) {
+                offset -= 1;
+            }
+            offset
+        };
+        let start = floor(range.start().into());
+        let end = floor(range.end().into()).max(start);
+        &self.source[start..end]
     }
 }
 
-/// Parse a Python file and return the AST module with source
+/// Byte order mark some Windows editors write at the start of UTF-8 files
+const BOM: char = '\u{feff}';
+
+/// Leading bytes searched for a NUL to tell binary files from source
+const BINARY_SNIFF_LEN: usize = 8 * 1024;
+
+/// Parse a Python file (or the code cells of a `.ipynb` notebook) and return the AST module with source
 pub fn parse_file(path: &Path) -> Result<ParsedFile> {
-    let source = fs::read_to_string(path)?;
+    parse_source(read_source(path)?, path)
+}
+
+/// Contents of a file: a member of a `.zip`/`.whl` target, or a file from the
+/// working tree or the `--rev` revision. Binary files (a NUL byte near the
+/// start) are an error rather than source.
+pub fn read_source(path: &Path) -> Result<String> {
+    let source = match (archive::read_member(path), git::revision()) {
+        (Some(source), _) => source?,
+        (None, Some(revision)) => revision.read(path)?.into_bytes(),
+        (None, None) => fs::read(path)?,
+    };
+    if is_binary(&source) {
+        return Err(eyre::eyre!(
+            "binary file (NUL byte in the first {} KB)",
+            BINARY_SNIFF_LEN / 1024
+        ));
+    }
+    String::from_utf8(source).wrap_err("file is not valid UTF-8")
+}
+
+/// Whether the start of a file holds a NUL byte, which source never does
+fn is_binary(content: &[u8]) -> bool {
+    content[..content.len().min(BINARY_SNIFF_LEN)].contains(&0)
+}
+
+/// Parse in-memory source as if it were read from `path`, which decides
+/// whether it is a notebook and names the file in parse errors. A leading
+/// UTF-8 BOM is dropped, so offsets start at the first character.
+pub fn parse_source(mut source: String, path: &Path) -> Result<ParsedFile> {
+    if source.starts_with(BOM) {
+        source.drain(..BOM.len_utf8());
+    }
+    if path.extension().is_some_and(|ext| ext == "ipynb") {
+        source = notebook_source(&source)?;
+    }
     let module = ast::ModModule::parse(&source, path.to_string_lossy().as_ref())?;
     Ok(ParsedFile { module, source })
 }
 
+/// Concatenate a notebook's code cells into one Python source.
+/// IPython magics (`%...`) and shell escapes (`!...`) are commented out so the
+/// cell still parses; line numbers refer to the concatenated source.
+pub fn notebook_source(json: &str) -> Result<String> {
+    let notebook: serde_json::Value = serde_json::from_str(json)?;
+    let cells = notebook["cells"]
+        .as_array()
+        .ok_or_else(|| eyre::eyre!("Notebook has no cells array"))?;
+
+    let mut source = String::new();
+    for cell in cells.iter().filter(|cell| cell["cell_type"] == "code") {
+        // Cell source is either a single string or a list of lines
+        let text = match &cell["source"] {
+            serde_json::Value::String(text) => text.clone(),
+            serde_json::Value::Array(lines) => lines.iter().filter_map(|line| line.as_str()).collect(),
+            _ => continue,
+        };
+        for line in text.lines() {
+            let trimmed = line.trim_start();
+            if trimmed.starts_with('%') || trimmed.starts_with('!') {
+                source.push_str("# ");
+            }
+            source.push_str(line);
+            source.push('\n');
+        }
+    }
+    Ok(source)
+}
+
 /// Extract parameters as a map of name -> type
 pub fn extract_params(args: &ast::Arguments) -> BTreeMap<String, String> {
     let mut params = BTreeMap::new();
 
-    // Regular positional-or-keyword args
-    for arg_with_default in args.args.iter() {
+    // Positional-only and regular positional-or-keyword args
+    for arg_with_default in args.posonlyargs.iter().chain(&args.args) {
         let arg = &arg_with_default.def;
         let name = arg.arg.to_string();
-        let type_str = arg.annotation.as_ref().map(|a| expr_to_string(a)).unwrap_or_default();
+        let type_str = arg
+            .annotation
+            .as_ref()
+            .map(|a| annotation_to_string(a))
+            .unwrap_or_default();
         if !type_str.is_empty() {
             params.insert(name, type_str);
         } else {
@@ -47,7 +144,7 @@ pub fn extract_params(args: &ast::Arguments) -> BTreeMap<String, String> {
         let type_str = vararg
             .annotation
             .as_ref()
-            .map(|a| expr_to_string(a))
+            .map(|a| annotation_to_string(a))
             .unwrap_or_default();
         if !type_str.is_empty() {
             params.insert(name, type_str);
@@ -60,7 +157,11 @@ pub fn extract_params(args: &ast::Arguments) -> BTreeMap<String, String> {
     for arg_with_default in args.kwonlyargs.iter() {
         let arg = &arg_with_default.def;
         let name = arg.arg.to_string();
-        let type_str = arg.annotation.as_ref().map(|a| expr_to_string(a)).unwrap_or_default();
+        let type_str = arg
+            .annotation
+            .as_ref()
+            .map(|a| annotation_to_string(a))
+            .unwrap_or_default();
         if !type_str.is_empty() {
             params.insert(name, type_str);
         } else {
@@ -71,7 +172,11 @@ pub fn extract_params(args: &ast::Arguments) -> BTreeMap<String, String> {
     // **kwargs
     if let Some(kwarg) = &args.kwarg {
         let name = format!("**{}", kwarg.arg);
-        let type_str = kwarg.annotation.as_ref().map(|a| expr_to_string(a)).unwrap_or_default();
+        let type_str = kwarg
+            .annotation
+            .as_ref()
+            .map(|a| annotation_to_string(a))
+            .unwrap_or_default();
         if !type_str.is_empty() {
             params.insert(name, type_str);
         } else {
@@ -82,9 +187,158 @@ pub fn extract_params(args: &ast::Arguments) -> BTreeMap<String, String> {
     params
 }
 
+/// Render parameters for a signature as `name: type` (bare `name` when
+/// unannotated) in source order: positional-only parameters, a `/` marker, the
+/// other positional parameters, `*args`, keyword-only parameters and `**kwargs`.
+/// `skip` leaves out a parameter by name, such as a dropped `self`.
+pub fn format_params(args: &ast::Arguments, skip: Option<&str>) -> String {
+    let render = |prefix: &str, arg: &ast::Arg| match &arg.annotation {
+        Some(annotation) => format!("{}{}: {}", prefix, arg.arg, annotation_to_string(annotation)),
+        None => format!("{}{}", prefix, arg.arg),
+    };
+    let named = |params: &[ast::ArgWithDefault]| -> Vec<String> {
+        params
+            .iter()
+            .filter(|param| Some(param.def.arg.as_str()) != skip)
+            .map(|param| render("", &param.def))
+            .collect()
+    };
+
+    let mut rendered = named(&args.posonlyargs);
+    if !rendered.is_empty() {
+        rendered.push("/".to_string());
+    }
+    rendered.extend(named(&args.args));
+    rendered.extend(args.vararg.as_deref().map(|arg| render("*", arg)));
+    rendered.extend(named(&args.kwonlyargs));
+    rendered.extend(args.kwarg.as_deref().map(|arg| render("**", arg)));
+    rendered.join(", ")
+}
+
 /// Extract return type as a string
 pub fn extract_returns(returns: Option<&ast::Expr>) -> Option<String> {
-    returns.map(expr_to_string)
+    returns.map(annotation_to_string)
+}
+
+/// Render PEP 695 type parameters as `[T, U: int, *Ts, **P]`, or an empty
+/// string for a non-generic definition
+pub fn format_type_params(type_params: &[ast::TypeParam]) -> String {
+    if type_params.is_empty() {
+        return String::new();
+    }
+    let params: Vec<String> = type_params
+        .iter()
+        .map(|param| match param {
+            ast::TypeParam::TypeVar(var) => match &var.bound {
+                Some(bound) => format!("{}: {}", var.name, annotation_to_string(bound)),
+                None => var.name.to_string(),
+            },
+            ast::TypeParam::ParamSpec(spec) => format!("**{}", spec.name),
+            ast::TypeParam::TypeVarTuple(tuple) => format!("*{}", tuple.name),
+        })
+        .collect();
+    format!("[{}]", params.join(", "))
+}
+
+/// Render a decorator list as strings, without the leading `@`
+pub fn extract_decorators(decorator_list: &[ast::Expr]) -> Vec<String> {
+    decorator_list.iter().map(expr_to_string).collect()
+}
+
+/// Canonicalize spacing in a rendered signature: collapse whitespace runs to a
+/// single space, drop padding inside brackets, and put exactly one space after
+/// each comma (none before). String literals (`'...'`, `"..."`) are kept as
+/// written, since their spacing and punctuation are part of the value.
+pub fn normalize_signature(signature: &str) -> String {
+    let mut out = String::with_capacity(signature.len());
+    let mut quote: Option<char> = None;
+    let mut escaped = false;
+
+    for c in signature.chars() {
+        if let Some(open) = quote {
+            out.push(c);
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == open {
+                quote = None;
+            }
+            continue;
+        }
+        match c {
+            '\'' | '"' => {
+                quote = Some(c);
+                out.push(c);
+            }
+            c if c.is_whitespace() => {
+                if !out.is_empty() && !out.ends_with([' ', '(', '[', '{']) {
+                    out.push(' ');
+                }
+            }
+            ',' => {
+                if out.ends_with(' ') {
+                    out.pop();
+                }
+                out.push_str(", ");
+            }
+            ')' | ']' | '}' => {
+                if out.ends_with(' ') {
+                    out.pop();
+                }
+                out.push(c);
+            }
+            _ => out.push(c),
+        }
+    }
+
+    out.trim_end().to_string()
+}
+
+/// Render a type annotation. A string constant is a forward reference and is
+/// rendered bare (`"Node"` → `Node`), also when nested (`list["Node"]`), except
+/// for `Literal[...]` values and `Annotated[...]` metadata, which stay quoted.
+pub fn annotation_to_string(expr: &ast::Expr) -> String {
+    match expr {
+        ast::Expr::Constant(c) => match &c.value {
+            ast::Constant::Str(s) => s.to_string(),
+            _ => expr_to_string(expr),
+        },
+
+        ast::Expr::Subscript(sub) => {
+            let value = expr_to_string(&sub.value);
+            let slice = match (value.rsplit('.').next(), sub.slice.as_ref()) {
+                (Some("Literal"), slice) => unparenthesized(slice, expr_to_string),
+                (Some("Annotated"), ast::Expr::Tuple(tuple)) if !tuple.elts.is_empty() => {
+                    let metadata = tuple.elts[1..].iter().map(expr_to_string);
+                    std::iter::once(annotation_to_string(&tuple.elts[0]))
+                        .chain(metadata)
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                }
+                (_, slice) => unparenthesized(slice, annotation_to_string),
+            };
+            format!("{}[{}]", value, slice)
+        }
+
+        ast::Expr::Tuple(tuple) => parenthesized(&tuple.elts, annotation_to_string),
+
+        // Callable's parameter list
+        ast::Expr::List(list) => {
+            let elts: Vec<_> = list.elts.iter().map(annotation_to_string).collect();
+            format!("[{}]", elts.join(", "))
+        }
+
+        ast::Expr::BinOp(binop) if binop.op == ast::Operator::BitOr => {
+            format!(
+                "{} | {}",
+                annotation_to_string(&binop.left),
+                annotation_to_string(&binop.right)
+            )
+        }
+
+        _ => expr_to_string(expr),
+    }
 }
 
 /// Convert an expression to a string representation
@@ -107,13 +361,14 @@ pub fn expr_to_string(expr: &ast::Expr) -> String {
         }
 
         ast::Expr::Subscript(sub) => {
-            format!("{}[{}]", expr_to_string(&sub.value), expr_to_string(&sub.slice))
+            format!(
+                "{}[{}]",
+                expr_to_string(&sub.value),
+                unparenthesized(&sub.slice, expr_to_string)
+            )
         }
 
-        ast::Expr::Tuple(tuple) => {
-            let elts: Vec<_> = tuple.elts.iter().map(expr_to_string).collect();
-            elts.join(", ")
-        }
+        ast::Expr::Tuple(tuple) => parenthesized(&tuple.elts, expr_to_string),
 
         ast::Expr::List(list) => {
             let elts: Vec<_> = list.elts.iter().map(expr_to_string).collect();
@@ -130,23 +385,134 @@ pub fn expr_to_string(expr: &ast::Expr) -> String {
 
         ast::Expr::Call(call) => {
             let func = expr_to_string(&call.func);
-            let args: Vec<_> = call.args.iter().map(expr_to_string).collect();
+            let keywords = call.keywords.iter().map(|keyword| match &keyword.arg {
+                Some(arg) => format!("{}={}", arg, expr_to_string(&keyword.value)),
+                None => format!("**{}", expr_to_string(&keyword.value)),
+            });
+            let args: Vec<_> = call.args.iter().map(expr_to_string).chain(keywords).collect();
             format!("{}({})", func, args.join(", "))
         }
 
+        // Parenthesized, as Python requires in defaults and most other contexts
+        ast::Expr::NamedExpr(named) => {
+            format!(
+                "({} := {})",
+                expr_to_string(&named.target),
+                expr_to_string(&named.value)
+            )
+        }
+
+        ast::Expr::Await(await_expr) => format!("await {}", expr_to_string(&await_expr.value)),
+
+        ast::Expr::ListComp(comp) => {
+            format!(
+                "[{}{}]",
+                expr_to_string(&comp.elt),
+                comprehension_clauses(&comp.generators)
+            )
+        }
+
+        ast::Expr::SetComp(comp) => {
+            format!(
+                "{{{}{}}}",
+                expr_to_string(&comp.elt),
+                comprehension_clauses(&comp.generators)
+            )
+        }
+
+        ast::Expr::DictComp(comp) => format!(
+            "{{{}: {}{}}}",
+            expr_to_string(&comp.key),
+            expr_to_string(&comp.value),
+            comprehension_clauses(&comp.generators)
+        ),
+
+        ast::Expr::GeneratorExp(comp) => {
+            format!(
+                "({}{})",
+                expr_to_string(&comp.elt),
+                comprehension_clauses(&comp.generators)
+            )
+        }
+
         _ => "...".to_string(),
     }
 }
 
+/// Render a standalone tuple: `(int, str)`, `(int,)` or `()`
+fn parenthesized(elts: &[ast::Expr], render: fn(&ast::Expr) -> String) -> String {
+    match elts {
+        [elt] => format!("({},)", render(elt)),
+        _ => format!("({})", elts.iter().map(render).collect::<Vec<_>>().join(", ")),
+    }
+}
+
+/// Render a subscript slice or loop target, where a non-empty tuple is written
+/// bare: `Dict[str, int]`, `for key, value in ...`
+fn unparenthesized(expr: &ast::Expr, render: fn(&ast::Expr) -> String) -> String {
+    match expr {
+        ast::Expr::Tuple(tuple) if !tuple.elts.is_empty() => {
+            tuple.elts.iter().map(render).collect::<Vec<_>>().join(", ")
+        }
+        _ => render(expr),
+    }
+}
+
+/// Render the `for ... in ... if ...` clauses of a comprehension
+fn comprehension_clauses(generators: &[ast::Comprehension]) -> String {
+    generators
+        .iter()
+        .map(|generator| {
+            let keyword = if generator.is_async { "async for" } else { "for" };
+            let conditions: String = generator
+                .ifs
+                .iter()
+                .map(|cond| format!(" if {}", expr_to_string(cond)))
+                .collect();
+            format!(
+                " {} {} in {}{}",
+                keyword,
+                unparenthesized(&generator.target, expr_to_string),
+                expr_to_string(&generator.iter),
+                conditions
+            )
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rustpython_parser::ast::Ranged;
     use std::path::PathBuf;
 
     fn fixtures_dir() -> PathBuf {
         PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures")
     }
 
+    #[test]
+    fn test_notebook_source_concatenates_code_cells() {
+        let json = std::fs::read_to_string(fixtures_dir().join("notebook.ipynb")).unwrap();
+        let source = notebook_source(&json).unwrap();
+        let lines: Vec<&str> = source.lines().collect();
+        assert_eq!(lines[0], "# %matplotlib inline");
+        assert_eq!(lines[2], "def area(radius: float) -> float:");
+        assert_eq!(lines[9], "# !pip install numpy");
+        assert!(!source.contains("Analysis"));
+    }
+
+    #[test]
+    fn test_notebook_source_invalid() {
+        assert!(notebook_source("not json").is_err());
+        assert!(notebook_source("{}").is_err());
+    }
+
+    #[test]
+    fn test_parse_file_notebook() {
+        let parsed = parse_file(&fixtures_dir().join("notebook.ipynb")).unwrap();
+        assert_eq!(parsed.module.body.len(), 4);
+    }
+
     #[test]
     fn test_parse_file_success() {
         let path = fixtures_dir().join("functions.py");
@@ -177,6 +543,19 @@ mod tests {
         assert_eq!(parsed.offset_to_line(12), 3); // start of line3
     }
 
+    #[test]
+    fn test_snippet_clamps_to_char_boundaries() {
+        let parsed = ParsedFile {
+            module: ast::ModModule::parse("", "test.py").unwrap(),
+            source: "x = 'é'\n".to_string(),
+        };
+        let range = |start: u32, end: u32| ast::text_size::TextRange::new(start.into(), end.into());
+        assert_eq!(parsed.snippet(range(0, 1)), "x");
+        // 'é' is two bytes (5..7); an offset inside it falls back to its start
+        assert_eq!(parsed.snippet(range(4, 6)), "'");
+        assert_eq!(parsed.snippet(range(0, 100)), "x = 'é'\n");
+    }
+
     #[test]
     fn test_offset_to_line_empty_source() {
         let parsed = ParsedFile {
@@ -494,7 +873,7 @@ mod tests {
                 if func.name.to_string() == "func_with_tuple" {
                     let params = extract_params(&func.args);
                     if let Some(args_type) = params.get("args") {
-                        assert_eq!(args_type, "int, str, bool");
+                        assert_eq!(args_type, "(int, str, bool)");
                         return;
                     }
                 }
@@ -522,6 +901,46 @@ mod tests {
         panic!("Function func_with_callable not found or params not found");
     }
 
+    #[test]
+    fn test_expr_to_string_call_keywords() {
+        let path = fixtures_dir().join("expressions.py");
+        let parsed = parse_file(&path).unwrap();
+
+        for stmt in &parsed.module.body {
+            if let ast::Stmt::FunctionDef(func) = stmt {
+                if func.name.as_str() == "func_with_keyword_default" {
+                    let default = func.args.args[0].default.as_deref().unwrap();
+                    assert_eq!(expr_to_string(default), "Field(3, alias=\"x\", **extra)");
+                    return;
+                }
+            }
+        }
+        panic!("Function func_with_keyword_default not found");
+    }
+
+    #[test]
+    fn test_expr_to_string_tuple_in_subscript_and_default() {
+        let path = fixtures_dir().join("expressions.py");
+        let parsed = parse_file(&path).unwrap();
+
+        for stmt in &parsed.module.body {
+            if let ast::Stmt::FunctionDef(func) = stmt {
+                if func.name.as_str() == "func_with_tuple_default" {
+                    let pair = &func.args.args[0];
+                    let annotation = pair.def.annotation.as_deref().unwrap();
+                    assert_eq!(expr_to_string(annotation), "Tuple[int, str]");
+                    assert_eq!(annotation_to_string(annotation), "Tuple[int, str]");
+                    assert_eq!(expr_to_string(pair.default.as_deref().unwrap()), "(1, \"a\")");
+                    let single = func.args.args[1].default.as_deref().unwrap();
+                    assert_eq!(expr_to_string(single), "(1,)");
+                    assert_eq!(annotation_to_string(func.returns.as_deref().unwrap()), "(int, str)");
+                    return;
+                }
+            }
+        }
+        panic!("Function func_with_tuple_default not found");
+    }
+
     #[test]
     fn test_expr_to_string_complex_nested() {
         let path = fixtures_dir().join("expressions.py");
@@ -549,6 +968,77 @@ mod tests {
         panic!("ComplexAnnotations.nested not found");
     }
 
+    #[test]
+    fn test_expr_to_string_named_expr() {
+        let path = fixtures_dir().join("expressions.py");
+        let parsed = parse_file(&path).unwrap();
+        let mut found_default = false;
+        let mut found_constant = false;
+
+        for stmt in &parsed.module.body {
+            match stmt {
+                ast::Stmt::FunctionDef(func) if func.name.as_str() == "func_with_walrus_default" => {
+                    let default = func.args.args[0].default.as_deref().unwrap();
+                    assert_eq!(expr_to_string(default), "(n := 10)");
+                    found_default = true;
+                }
+                ast::Stmt::Assign(assign) if expr_to_string(&assign.targets[0]) == "WALRUS_CONST" => {
+                    assert_eq!(expr_to_string(&assign.value), "(chunk := 1024)");
+                    found_constant = true;
+                }
+                _ => {}
+            }
+        }
+
+        assert!(found_default, "Should find walrus default");
+        assert!(found_constant, "Should find walrus constant");
+    }
+
+    #[test]
+    fn test_expr_to_string_comprehensions() {
+        let path = fixtures_dir().join("expressions.py");
+        let parsed = parse_file(&path).unwrap();
+        let rendered: BTreeMap<String, String> = parsed
+            .module
+            .body
+            .iter()
+            .filter_map(|stmt| match stmt {
+                ast::Stmt::Assign(assign) => Some((expr_to_string(&assign.targets[0]), expr_to_string(&assign.value))),
+                _ => None,
+            })
+            .collect();
+
+        assert_eq!(rendered["UPPER_NAMES"], "[name.upper() for name in RAW_NAMES if name]");
+        assert_eq!(rendered["UNIQUE_IDS"], "{item.id for item in ITEMS}");
+        assert_eq!(rendered["INDEX"], "{key: value for key, value in PAIRS if value}");
+        assert_eq!(rendered["NESTED"], "[cell for row in GRID for cell in row]");
+    }
+
+    #[test]
+    fn test_expr_to_string_await() {
+        let path = fixtures_dir().join("expressions.py");
+        let parsed = parse_file(&path).unwrap();
+
+        for stmt in &parsed.module.body {
+            if let ast::Stmt::AsyncFunctionDef(func) = stmt {
+                if func.name.as_str() == "func_with_await" {
+                    let ast::Stmt::Assign(assign) = &func.body[0] else {
+                        panic!("expected assignment");
+                    };
+                    assert_eq!(expr_to_string(&assign.value), "await fetch(URL)");
+                    return;
+                }
+            }
+        }
+        panic!("Function func_with_await not found");
+    }
+
+    #[test]
+    fn test_expr_to_string_generator() {
+        let expr = ast::Expr::parse("(x for x in xs)", "test.py").unwrap();
+        assert_eq!(expr_to_string(&expr), "(x for x in xs)");
+    }
+
     #[test]
     fn test_extract_params_empty() {
         let args = ast::Arguments {
@@ -564,6 +1054,68 @@ mod tests {
         assert!(params.is_empty());
     }
 
+    #[test]
+    fn test_parse_file_strips_bom() {
+        let path = fixtures_dir().join("bom.py");
+        assert!(std::fs::read(&path).unwrap().starts_with(b"\xef\xbb\xbf"));
+        let parsed = parse_file(&path).unwrap();
+        assert!(parsed.source.starts_with("def first"));
+        let first = parsed.module.body[0].range();
+        assert_eq!(parsed.offset_to_line(first.start().into()), 1);
+        assert_eq!(parsed.snippet(first), "def first():\n    pass");
+        let second = parsed.module.body[1].range();
+        assert_eq!(parsed.offset_to_line(second.start().into()), 5);
+    }
+
+    #[test]
+    fn test_read_source_rejects_binary() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("corrupt.py");
+        std::fs::write(&path, b"def f():\n    pass\n\0\0\x7fELF").unwrap();
+        let err = read_source(&path).unwrap_err();
+        assert_eq!(err.to_string(), "binary file (NUL byte in the first 8 KB)");
+        assert!(parse_file(&path).is_err());
+
+        assert!(!is_binary(b"x = 1\n"));
+        // Only the first bytes are checked
+        let mut late = vec![b'#'; BINARY_SNIFF_LEN];
+        late.push(0);
+        assert!(!is_binary(&late));
+    }
+
+    #[test]
+    fn test_crlf_line_numbers() {
+        let path = fixtures_dir().join("crlf.py");
+        assert!(std::fs::read(&path).unwrap().windows(2).any(|w| w == b"\r\n"));
+        let parsed = parse_file(&path).unwrap();
+        let lines: Vec<(usize, usize)> = parsed
+            .module
+            .body
+            .iter()
+            .map(|stmt| {
+                let range = stmt.range();
+                (
+                    parsed.offset_to_line(range.start().into()),
+                    parsed.offset_to_line(range.end().into()),
+                )
+            })
+            .collect();
+        // import, first, Second (with a multi-line docstring), third (split parameters)
+        assert_eq!(lines, vec![(1, 1), (4, 5), (8, 12), (15, 17)]);
+    }
+
+    #[test]
+    fn test_offset_to_line_lone_carriage_returns() {
+        let source = "x = 1\r\rdef f():\r    pass\r".to_string();
+        let parsed = ParsedFile {
+            module: ast::ModModule::parse(&source, "test.py").unwrap(),
+            source,
+        };
+        let def = parsed.module.body[1].range();
+        assert_eq!(parsed.offset_to_line(def.start().into()), 3);
+        assert_eq!(parsed.offset_to_line(def.end().into()), 4);
+    }
+
     #[test]
     fn test_offset_to_line_multiline() {
         let source = "def foo():\n    pass\n\ndef bar():\n    return 42\n".to_string();
@@ -683,4 +1235,44 @@ mod tests {
         }
         panic!("function_with_typed_varargs not found");
     }
+
+    #[test]
+    fn test_normalize_signature_collapses_whitespace() {
+        assert_eq!(
+            normalize_signature("def f(x:  Dict[str,   int])"),
+            "def f(x: Dict[str, int])"
+        );
+    }
+
+    #[test]
+    fn test_normalize_signature_commas() {
+        assert_eq!(normalize_signature("Tuple[int ,str,bytes]"), "Tuple[int, str, bytes]");
+    }
+
+    #[test]
+    fn test_normalize_signature_bracket_padding() {
+        assert_eq!(normalize_signature("def f(x: List[ int ] )"), "def f(x: List[int])");
+    }
+
+    #[test]
+    fn test_normalize_signature_multiline_string_annotation() {
+        assert_eq!(
+            normalize_signature("def f(x: Callable[\n    [int],\n    str])"),
+            "def f(x: Callable[[int], str])"
+        );
+    }
+
+    #[test]
+    fn test_normalize_signature_keeps_string_literals() {
+        assert_eq!(
+            normalize_signature(r#"def f(x: Literal[",", "a  b", "( x )"] ,  y: Literal['it\'s ,  ok'])"#),
+            r#"def f(x: Literal[",", "a  b", "( x )"], y: Literal['it\'s ,  ok'])"#
+        );
+    }
+
+    #[test]
+    fn test_normalize_signature_already_canonical() {
+        let sig = "async def fetch(url: str, timeout: int | None) -> dict[str, Any]";
+        assert_eq!(normalize_signature(sig), sig);
+    }
 }
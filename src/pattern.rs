@@ -1,5 +1,7 @@
-use crate::output::{ClassInfo, ClassMap, ModuleNode, ModulesOutput};
-use std::collections::BTreeMap;
+use crate::cli::MatchMode;
+use crate::output::{ClassInfo, ClassMap, Entry, EntryMap, ModuleNode, ModulesOutput};
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap};
 
 /// Extract the subject name from a function signature
 /// "def compute_total(x: int) -> int" -> "compute_total"
@@ -9,8 +11,8 @@ pub fn extract_function_name(signature: &str) -> &str {
     let s = signature.strip_prefix("async ").unwrap_or(signature);
     let s = s.strip_prefix("def ").unwrap_or(s);
 
-    // Take everything up to the first '('
-    s.split('(').next().unwrap_or(s).trim()
+    // Take everything up to the first '(' or '[' (type parameters)
+    s.split(['(', '[']).next().unwrap_or(s).trim()
 }
 
 /// Extract the subject name from a class/enum signature
@@ -19,29 +21,42 @@ pub fn extract_function_name(signature: &str) -> &str {
 pub fn extract_class_name(signature: &str) -> &str {
     let s = signature.strip_prefix("class ").unwrap_or(signature);
 
-    // Take everything up to the first '(' or end of string
-    s.split('(').next().unwrap_or(s).trim()
+    // Take everything up to the first '(' or '[' (type parameters), or end of string
+    s.split(['(', '[']).next().unwrap_or(s).trim()
 }
 
-/// Extract the subject name from a dump signature (handles functions, class.method, and enums)
+/// Extract the subject name from a constant signature
+/// "MAX_RETRIES = 3" -> "MAX_RETRIES"
+/// "TIMEOUT: float = 2.5" -> "TIMEOUT"
+pub fn extract_constant_name(signature: &str) -> &str {
+    signature.split([':', '=']).next().unwrap_or(signature).trim()
+}
+
+/// Extract the subject name from a dump signature (handles functions, class.method, enums, and constants)
 /// "def compute_total(x: int) -> int" -> "compute_total"
 /// "UserService.def create_user(self) -> User" -> "create_user"
 /// "class OrderStatus(Enum)" -> "OrderStatus"
+/// "MAX_RETRIES = 3" -> "MAX_RETRIES"
 pub fn extract_dump_name(signature: &str) -> &str {
-    // Check if it's a class method (contains ".")
-    if let Some(dot_pos) = signature.find('.') {
-        // It's "ClassName.def method_name(...)" or "ClassName.async def method_name(...)"
-        let method_part = &signature[dot_pos + 1..];
-        return extract_function_name(method_part);
-    }
-
     // Check if it's a class/enum
     if signature.starts_with("class ") {
         return extract_class_name(signature);
     }
 
-    // It's a function
-    extract_function_name(signature)
+    // Check if it's a function
+    if signature.starts_with("def ") || signature.starts_with("async def ") {
+        return extract_function_name(signature);
+    }
+
+    // Check if it's a class method: "ClassName.def method_name(...)" or "ClassName.async def method_name(...)"
+    if let Some((_, method_part)) = signature.split_once('.') {
+        if method_part.starts_with("def ") || method_part.starts_with("async def ") {
+            return extract_function_name(method_part);
+        }
+    }
+
+    // It's a constant
+    extract_constant_name(signature)
 }
 
 /// Extract the module name from a path
@@ -125,13 +140,117 @@ fn find_best_match_level_for_pattern<'a>(
     (MatchLevel::NoMatch, vec![])
 }
 
+/// Score a name as a fuzzy match: every pattern character must appear in the
+/// name in order, ignoring case (`cptl` in `compute_total`). Characters that
+/// start the name or a word (`_`, `.`, camelCase) and runs of consecutive
+/// characters score higher. `None` if the pattern is not a subsequence.
+pub fn fuzzy_score(subject: &str, pattern: &str) -> Option<u32> {
+    const MATCH: u32 = 1;
+    const WORD_START: u32 = 8;
+    const NAME_START: u32 = 4;
+    const CONSECUTIVE: u32 = 5;
+
+    let subject: Vec<char> = subject.chars().collect();
+    let pattern: Vec<char> = pattern.chars().collect();
+    if pattern.is_empty() {
+        return Some(0);
+    }
+
+    // Score of a pattern character matched at subject index `i`
+    let bonus = |i: usize| {
+        let word_start = match i.checked_sub(1).map(|prev| subject[prev]) {
+            None => WORD_START + NAME_START,
+            Some(prev) if !prev.is_alphanumeric() => WORD_START,
+            Some(prev) if prev.is_lowercase() && subject[i].is_uppercase() => WORD_START,
+            Some(_) => 0,
+        };
+        MATCH + word_start
+    };
+    let eq = |a: char, b: char| a == b || a.to_lowercase().eq(b.to_lowercase());
+
+    // best[i]: best score with the current pattern character matched at `i`
+    let mut best: Vec<Option<u32>> = (0..subject.len())
+        .map(|i| eq(subject[i], pattern[0]).then(|| bonus(i)))
+        .collect();
+    for &p in &pattern[1..] {
+        let mut next = vec![None; subject.len()];
+        // Best score of the previous character matched anywhere before `i - 1`
+        let mut earlier: Option<u32> = None;
+        for i in 1..subject.len() {
+            if i >= 2 {
+                earlier = earlier.max(best[i - 2]);
+            }
+            if eq(subject[i], p) {
+                let consecutive = best[i - 1].map(|score| score + CONSECUTIVE);
+                next[i] = consecutive.max(earlier).map(|score| score + bonus(i));
+            }
+        }
+        best = next;
+    }
+    best.into_iter().flatten().max()
+}
+
+/// Names matched by the patterns, with their fuzzy match score. By default a
+/// name is kept if it matches any pattern at that pattern's best level; with
+/// `all` it must match every pattern at some level (prefix or substring,
+/// either case). Fuzzy matching replaces the levels with `fuzzy_names`, and
+/// is the only mode that scores names.
+fn matching_names(subjects: &[&str], patterns: &[String], mode: MatchMode) -> HashMap<String, Option<u32>> {
+    if mode.fuzzy {
+        return fuzzy_names(subjects, patterns, mode)
+            .into_iter()
+            .map(|(name, score)| (name, Some(score)))
+            .collect();
+    }
+    if mode.all {
+        return subjects
+            .iter()
+            .filter(|subject| patterns.iter().all(|p| match_level(subject, p) != MatchLevel::NoMatch))
+            .map(|subject| (subject.to_string(), None))
+            .collect();
+    }
+
+    let mut matching = HashMap::new();
+    for pattern in patterns {
+        let (_, matched) = find_best_match_level_for_pattern(subjects.iter().copied(), pattern);
+        matching.extend(matched.into_iter().map(|name| (name.to_string(), None)));
+    }
+    matching
+}
+
+/// Names that are fuzzy matches of any pattern (of every pattern with `all`),
+/// scored by their best (worst with `all`) pattern and ranked best first; ties
+/// go to the first name alphabetically
+fn fuzzy_names(subjects: &[&str], patterns: &[String], mode: MatchMode) -> Vec<(String, u32)> {
+    let mut scores: HashMap<&str, u32> = HashMap::new();
+    for &subject in subjects {
+        let mut each = patterns.iter().map(|pattern| fuzzy_score(subject, pattern));
+        let score = if mode.all {
+            each.try_fold(u32::MAX, |min, score| score.map(|score| min.min(score)))
+        } else {
+            each.flatten().max()
+        };
+        if let Some(score) = score {
+            scores.insert(subject, score);
+        }
+    }
+
+    let mut ranked: Vec<(&str, u32)> = scores.into_iter().collect();
+    ranked.sort_by_key(|&(name, score)| (Reverse(score), name));
+    ranked
+        .into_iter()
+        .map(|(name, score)| (name.to_string(), score))
+        .collect()
+}
+
 /// Filter files output (file -> (signature -> line)) by patterns.
 /// Applies cascading match logic GLOBALLY across all files, not per-file.
 pub fn filter_files_output<F>(
-    files: BTreeMap<String, BTreeMap<String, usize>>,
+    files: BTreeMap<String, EntryMap>,
     patterns: &[String],
     name_extractor: F,
-) -> BTreeMap<String, BTreeMap<String, usize>>
+    mode: MatchMode,
+) -> BTreeMap<String, EntryMap>
 where
     F: Fn(&str) -> &str + Copy,
 {
@@ -140,34 +259,26 @@ where
     }
 
     // Flatten all entries: (file_path, signature, line, extracted_name)
-    let all_entries: Vec<(String, String, usize, String)> = files
+    let all_entries: Vec<(String, String, Entry, String)> = files
         .into_iter()
         .flat_map(|(file_path, entries)| {
-            entries.into_iter().map(move |(sig, line)| {
+            entries.into_iter().map(move |(sig, entry)| {
                 let name = name_extractor(&sig).to_string();
-                (file_path.clone(), sig, line, name)
+                (file_path.clone(), sig, entry, name)
             })
         })
         .collect();
 
-    // For each pattern, find globally which names match at the best level
-    let mut matching_names: std::collections::HashSet<String> = std::collections::HashSet::new();
-
-    for pattern in patterns {
-        let subjects = all_entries.iter().map(|(_, _, _, name)| name.as_str());
-        let (_, matched_names) = find_best_match_level_for_pattern(subjects, pattern);
-
-        for matched in matched_names {
-            matching_names.insert(matched.to_string());
-        }
-    }
+    let subjects: Vec<&str> = all_entries.iter().map(|(_, _, _, name)| name.as_str()).collect();
+    let matching_names = matching_names(&subjects, patterns, mode);
 
     // Re-group by file, filtering to only matching names
-    let mut result: BTreeMap<String, BTreeMap<String, usize>> = BTreeMap::new();
+    let mut result: BTreeMap<String, EntryMap> = BTreeMap::new();
 
-    for (file_path, sig, line, name) in all_entries {
-        if matching_names.contains(&name) {
-            result.entry(file_path).or_default().insert(sig, line);
+    for (file_path, sig, mut entry, name) in all_entries {
+        if let Some(&score) = matching_names.get(&name) {
+            entry.meta.score = score;
+            result.entry(file_path).or_default().insert(sig, entry);
         }
     }
 
@@ -176,7 +287,11 @@ where
 
 /// Filter classes output (file -> (class_sig -> ClassInfo)) by patterns.
 /// Applies cascading match logic GLOBALLY across all files, not per-file.
-pub fn filter_classes_output(files: BTreeMap<String, ClassMap>, patterns: &[String]) -> BTreeMap<String, ClassMap> {
+pub fn filter_classes_output(
+    files: BTreeMap<String, ClassMap>,
+    patterns: &[String],
+    mode: MatchMode,
+) -> BTreeMap<String, ClassMap> {
     if patterns.is_empty() {
         return files;
     }
@@ -192,23 +307,15 @@ pub fn filter_classes_output(files: BTreeMap<String, ClassMap>, patterns: &[Stri
         })
         .collect();
 
-    // For each pattern, find globally which names match at the best level
-    let mut matching_names: std::collections::HashSet<String> = std::collections::HashSet::new();
-
-    for pattern in patterns {
-        let subjects = all_entries.iter().map(|(_, _, _, name)| name.as_str());
-        let (_, matched_names) = find_best_match_level_for_pattern(subjects, pattern);
-
-        for matched in matched_names {
-            matching_names.insert(matched.to_string());
-        }
-    }
+    let subjects: Vec<&str> = all_entries.iter().map(|(_, _, _, name)| name.as_str()).collect();
+    let matching_names = matching_names(&subjects, patterns, mode);
 
     // Re-group by file, filtering to only matching names
     let mut result: BTreeMap<String, ClassMap> = BTreeMap::new();
 
-    for (file_path, class_sig, class_info, name) in all_entries {
-        if matching_names.contains(&name) {
+    for (file_path, class_sig, mut class_info, name) in all_entries {
+        if let Some(&score) = matching_names.get(&name) {
+            class_info.meta.score = score;
             result.entry(file_path).or_default().insert(class_sig, class_info);
         }
     }
@@ -216,6 +323,55 @@ pub fn filter_classes_output(files: BTreeMap<String, ClassMap>, patterns: &[Stri
     result
 }
 
+/// Filter a map keyed by name by patterns matched against its keys
+pub fn filter_names<V>(map: BTreeMap<String, V>, patterns: &[String], mode: MatchMode) -> BTreeMap<String, V> {
+    if patterns.is_empty() {
+        return map;
+    }
+
+    let subjects: Vec<&str> = map.keys().map(String::as_str).collect();
+    let matching = matching_names(&subjects, patterns, mode);
+    map.into_iter()
+        .filter(|(name, _)| matching.contains_key(name))
+        .collect()
+}
+
+/// Filter files output by patterns matched against each file's module name
+/// ("src/tools/cli.py" -> "cli"), keeping whole files.
+/// Applies cascading match logic GLOBALLY across all files.
+pub fn filter_files_by_module(files: BTreeMap<String, EntryMap>, patterns: &[String]) -> BTreeMap<String, EntryMap> {
+    if patterns.is_empty() {
+        return files;
+    }
+
+    let names: Vec<(String, String)> = files
+        .keys()
+        .map(|path| {
+            let name = extract_module_name(path);
+            let name = name.strip_suffix(".py").unwrap_or(name).to_string();
+            (path.clone(), name)
+        })
+        .collect();
+
+    let mut matching_paths: std::collections::HashSet<String> = std::collections::HashSet::new();
+
+    for pattern in patterns {
+        let subjects = names.iter().map(|(_, name)| name.as_str());
+        let (_, matched_names) = find_best_match_level_for_pattern(subjects, pattern);
+
+        for (path, name) in &names {
+            if matched_names.contains(&name.as_str()) {
+                matching_paths.insert(path.clone());
+            }
+        }
+    }
+
+    files
+        .into_iter()
+        .filter(|(path, _)| matching_paths.contains(path))
+        .collect()
+}
+
 /// Filter modules output by patterns (matches against module/package names)
 pub fn filter_modules_output(output: ModulesOutput, patterns: &[String]) -> ModulesOutput {
     if patterns.is_empty() {
@@ -274,7 +430,12 @@ fn filter_module_tree(tree: BTreeMap<String, ModuleNode>, patterns: &[String]) -
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::output::ModuleType;
+    use crate::output::{MemberMap, ModuleType};
+
+    const ALL: MatchMode = MatchMode {
+        all: true,
+        fuzzy: false,
+    };
 
     // ==================== Name Extraction Tests ====================
 
@@ -315,6 +476,13 @@ mod tests {
         assert_eq!(extract_class_name("class Multi(Base1, Base2, Base3)"), "Multi");
     }
 
+    #[test]
+    fn test_extract_names_with_type_params() {
+        assert_eq!(extract_class_name("class Stack[T]"), "Stack");
+        assert_eq!(extract_class_name("class Registry[K: str, V](dict[K, V])"), "Registry");
+        assert_eq!(extract_function_name("def first[T](items: list[T]) -> T"), "first");
+    }
+
     #[test]
     fn test_extract_dump_name_functions() {
         // Regular functions
@@ -336,6 +504,19 @@ mod tests {
         assert_eq!(extract_dump_name("MyClass.def __init__(self)"), "__init__");
     }
 
+    #[test]
+    fn test_extract_dump_name_constants() {
+        assert_eq!(extract_dump_name("MAX_RETRIES = 3"), "MAX_RETRIES");
+        assert_eq!(extract_dump_name("TIMEOUT: float = 2.5"), "TIMEOUT");
+        // A dot in the value doesn't make it a method
+        assert_eq!(extract_dump_name("RATIO = 0.5"), "RATIO");
+    }
+
+    #[test]
+    fn test_extract_dump_name_function_with_dotted_annotation() {
+        assert_eq!(extract_dump_name("def load(path: os.PathLike) -> None"), "load");
+    }
+
     #[test]
     fn test_extract_dump_name_enums() {
         assert_eq!(extract_dump_name("class OrderStatus(Enum)"), "OrderStatus");
@@ -433,14 +614,14 @@ mod tests {
     // ==================== Cascading Match Logic Tests ====================
 
     /// Helper to wrap a single file's functions for testing
-    fn wrap_in_file(funcs: BTreeMap<String, usize>) -> BTreeMap<String, BTreeMap<String, usize>> {
+    fn wrap_in_file(funcs: EntryMap) -> BTreeMap<String, EntryMap> {
         let mut files = BTreeMap::new();
         files.insert("test.py".to_string(), funcs);
         files
     }
 
     /// Helper to get functions from the test file
-    fn get_test_file(files: &BTreeMap<String, BTreeMap<String, usize>>) -> &BTreeMap<String, usize> {
+    fn get_test_file(files: &BTreeMap<String, EntryMap>) -> &EntryMap {
         files.get("test.py").unwrap()
     }
 
@@ -451,13 +632,18 @@ mod tests {
         // - "_test_helper" matches at ContainsCaseSensitive
         // Only "test_function" should be returned because startswith is preferred
 
-        let mut map = BTreeMap::new();
-        map.insert("def test_function() -> None".to_string(), 10);
-        map.insert("def _test_helper() -> None".to_string(), 20);
-        map.insert("def other() -> None".to_string(), 30);
+        let mut map = EntryMap::new();
+        map.insert("def test_function() -> None".to_string(), 10.into());
+        map.insert("def _test_helper() -> None".to_string(), 20.into());
+        map.insert("def other() -> None".to_string(), 30.into());
 
         let patterns = vec!["test".to_string()];
-        let filtered = filter_files_output(wrap_in_file(map), &patterns, extract_function_name);
+        let filtered = filter_files_output(
+            wrap_in_file(map),
+            &patterns,
+            extract_function_name,
+            MatchMode::default(),
+        );
         let funcs = get_test_file(&filtered);
 
         assert_eq!(funcs.len(), 1);
@@ -470,13 +656,18 @@ mod tests {
         // When pattern "helper" is used and no function starts with "helper",
         // it should fall back to contains matching
 
-        let mut map = BTreeMap::new();
-        map.insert("def _test_helper() -> None".to_string(), 10);
-        map.insert("def my_helper_func() -> None".to_string(), 20);
-        map.insert("def other() -> None".to_string(), 30);
+        let mut map = EntryMap::new();
+        map.insert("def _test_helper() -> None".to_string(), 10.into());
+        map.insert("def my_helper_func() -> None".to_string(), 20.into());
+        map.insert("def other() -> None".to_string(), 30.into());
 
         let patterns = vec!["helper".to_string()];
-        let filtered = filter_files_output(wrap_in_file(map), &patterns, extract_function_name);
+        let filtered = filter_files_output(
+            wrap_in_file(map),
+            &patterns,
+            extract_function_name,
+            MatchMode::default(),
+        );
         let funcs = get_test_file(&filtered);
 
         // Both should match via contains since neither starts with "helper"
@@ -490,12 +681,17 @@ mod tests {
         // Pattern "Test" should match "test_func" via case-insensitive startswith
         // but NOT "_test_helper" (which would only match via contains)
 
-        let mut map = BTreeMap::new();
-        map.insert("def test_func() -> None".to_string(), 10);
-        map.insert("def _test_helper() -> None".to_string(), 20);
+        let mut map = EntryMap::new();
+        map.insert("def test_func() -> None".to_string(), 10.into());
+        map.insert("def _test_helper() -> None".to_string(), 20.into());
 
         let patterns = vec!["Test".to_string()];
-        let filtered = filter_files_output(wrap_in_file(map), &patterns, extract_function_name);
+        let filtered = filter_files_output(
+            wrap_in_file(map),
+            &patterns,
+            extract_function_name,
+            MatchMode::default(),
+        );
         let funcs = get_test_file(&filtered);
 
         assert_eq!(funcs.len(), 1);
@@ -509,14 +705,19 @@ mod tests {
         // Pattern "comp" -> startswith matches "compute"
         // "_test_b" should NOT be included (only matches via contains)
 
-        let mut map = BTreeMap::new();
-        map.insert("def test_a() -> None".to_string(), 10);
-        map.insert("def _test_b() -> None".to_string(), 20);
-        map.insert("def compute() -> None".to_string(), 30);
-        map.insert("def other() -> None".to_string(), 40);
+        let mut map = EntryMap::new();
+        map.insert("def test_a() -> None".to_string(), 10.into());
+        map.insert("def _test_b() -> None".to_string(), 20.into());
+        map.insert("def compute() -> None".to_string(), 30.into());
+        map.insert("def other() -> None".to_string(), 40.into());
 
         let patterns = vec!["test".to_string(), "comp".to_string()];
-        let filtered = filter_files_output(wrap_in_file(map), &patterns, extract_function_name);
+        let filtered = filter_files_output(
+            wrap_in_file(map),
+            &patterns,
+            extract_function_name,
+            MatchMode::default(),
+        );
         let funcs = get_test_file(&filtered);
 
         assert_eq!(funcs.len(), 2);
@@ -529,13 +730,18 @@ mod tests {
     fn test_cascading_only_contains_matches() {
         // When a pattern only has contains matches, those are returned
 
-        let mut map = BTreeMap::new();
-        map.insert("def _internal_validator() -> None".to_string(), 10);
-        map.insert("def my_validator_func() -> None".to_string(), 20);
-        map.insert("def other() -> None".to_string(), 30);
+        let mut map = EntryMap::new();
+        map.insert("def _internal_validator() -> None".to_string(), 10.into());
+        map.insert("def my_validator_func() -> None".to_string(), 20.into());
+        map.insert("def other() -> None".to_string(), 30.into());
 
         let patterns = vec!["validator".to_string()];
-        let filtered = filter_files_output(wrap_in_file(map), &patterns, extract_function_name);
+        let filtered = filter_files_output(
+            wrap_in_file(map),
+            &patterns,
+            extract_function_name,
+            MatchMode::default(),
+        );
         let funcs = get_test_file(&filtered);
 
         // Both have "validator" via contains (neither starts with it)
@@ -546,12 +752,17 @@ mod tests {
 
     #[test]
     fn test_cascading_no_matches() {
-        let mut map = BTreeMap::new();
-        map.insert("def foo() -> None".to_string(), 10);
-        map.insert("def bar() -> None".to_string(), 20);
+        let mut map = EntryMap::new();
+        map.insert("def foo() -> None".to_string(), 10.into());
+        map.insert("def bar() -> None".to_string(), 20.into());
 
         let patterns = vec!["xyz".to_string()];
-        let filtered = filter_files_output(wrap_in_file(map), &patterns, extract_function_name);
+        let filtered = filter_files_output(
+            wrap_in_file(map),
+            &patterns,
+            extract_function_name,
+            MatchMode::default(),
+        );
 
         assert!(filtered.is_empty());
     }
@@ -565,16 +776,16 @@ mod tests {
 
         let mut files = BTreeMap::new();
 
-        let mut file1 = BTreeMap::new();
-        file1.insert("def test_foo() -> None".to_string(), 10);
+        let mut file1 = EntryMap::new();
+        file1.insert("def test_foo() -> None".to_string(), 10.into());
         files.insert("file1.py".to_string(), file1);
 
-        let mut file2 = BTreeMap::new();
-        file2.insert("def _test_bar() -> None".to_string(), 20);
+        let mut file2 = EntryMap::new();
+        file2.insert("def _test_bar() -> None".to_string(), 20.into());
         files.insert("file2.py".to_string(), file2);
 
         let patterns = vec!["test".to_string()];
-        let filtered = filter_files_output(files, &patterns, extract_function_name);
+        let filtered = filter_files_output(files, &patterns, extract_function_name, MatchMode::default());
 
         // Only file1.py should be present (file2.py filtered out entirely)
         assert_eq!(filtered.len(), 1);
@@ -584,21 +795,21 @@ mod tests {
 
     // ==================== Files Output Filter Tests ====================
 
-    fn make_files_output() -> BTreeMap<String, BTreeMap<String, usize>> {
+    fn make_files_output() -> BTreeMap<String, EntryMap> {
         let mut files = BTreeMap::new();
 
-        let mut file1 = BTreeMap::new();
-        file1.insert("def compute_total(x: int) -> int".to_string(), 10);
-        file1.insert("def print_summary() -> None".to_string(), 20);
+        let mut file1 = EntryMap::new();
+        file1.insert("def compute_total(x: int) -> int".to_string(), 10.into());
+        file1.insert("def print_summary() -> None".to_string(), 20.into());
         files.insert("src/billing.py".to_string(), file1);
 
-        let mut file2 = BTreeMap::new();
-        file2.insert("def validate_email(email: str) -> bool".to_string(), 10);
-        file2.insert("def compute_hash(data: str) -> str".to_string(), 20);
+        let mut file2 = EntryMap::new();
+        file2.insert("def validate_email(email: str) -> bool".to_string(), 10.into());
+        file2.insert("def compute_hash(data: str) -> str".to_string(), 20.into());
         files.insert("src/utils.py".to_string(), file2);
 
-        let mut file3 = BTreeMap::new();
-        file3.insert("def hello() -> str".to_string(), 10);
+        let mut file3 = EntryMap::new();
+        file3.insert("def hello() -> str".to_string(), 10.into());
         files.insert("src/greet.py".to_string(), file3);
 
         files
@@ -608,7 +819,7 @@ mod tests {
     fn test_filter_files_output_no_patterns() {
         let files = make_files_output();
         let original_len = files.len();
-        let filtered = filter_files_output(files, &[], extract_function_name);
+        let filtered = filter_files_output(files, &[], extract_function_name, MatchMode::default());
         assert_eq!(filtered.len(), original_len);
     }
 
@@ -616,7 +827,7 @@ mod tests {
     fn test_filter_files_output_removes_empty_files() {
         let files = make_files_output();
         let patterns = vec!["hello".to_string()];
-        let filtered = filter_files_output(files, &patterns, extract_function_name);
+        let filtered = filter_files_output(files, &patterns, extract_function_name, MatchMode::default());
 
         // Only greet.py should remain (has hello function)
         assert_eq!(filtered.len(), 1);
@@ -627,7 +838,7 @@ mod tests {
     fn test_filter_files_output_multiple_files_partial_match() {
         let files = make_files_output();
         let patterns = vec!["compute".to_string()];
-        let filtered = filter_files_output(files, &patterns, extract_function_name);
+        let filtered = filter_files_output(files, &patterns, extract_function_name, MatchMode::default());
 
         // billing.py and utils.py both have compute* functions
         assert_eq!(filtered.len(), 2);
@@ -642,42 +853,62 @@ mod tests {
         assert!(filtered["src/utils.py"].contains_key("def compute_hash(data: str) -> str"));
     }
 
+    #[test]
+    fn test_filter_files_by_module_matches_file_stem() {
+        let files = make_files_output();
+        let patterns = vec!["bill".to_string()];
+        let filtered = filter_files_by_module(files, &patterns);
+        assert_eq!(filtered.len(), 1);
+        assert!(filtered.contains_key("src/billing.py"));
+    }
+
+    #[test]
+    fn test_filter_files_by_module_no_patterns() {
+        let files = make_files_output();
+        let original_len = files.len();
+        let filtered = filter_files_by_module(files, &[]);
+        assert_eq!(filtered.len(), original_len);
+    }
+
     // ==================== Classes Output Filter Tests ====================
 
     fn make_classes_output() -> BTreeMap<String, ClassMap> {
         let mut files = BTreeMap::new();
 
         let mut file1_classes = ClassMap::new();
-        let mut user_methods = BTreeMap::new();
-        user_methods.insert("def create(self) -> User".to_string(), 10);
+        let mut user_methods = MemberMap::new();
+        user_methods.insert("def create(self) -> User".to_string(), 10.into());
         file1_classes.insert(
             "class UserService".to_string(),
             ClassInfo {
-                fields: BTreeMap::new(),
+                fields: MemberMap::new(),
                 methods: user_methods,
+                ..Default::default()
             },
         );
 
-        let mut admin_methods = BTreeMap::new();
-        admin_methods.insert("def delete(self) -> None".to_string(), 20);
+        let mut admin_methods = MemberMap::new();
+        admin_methods.insert("def delete(self) -> None".to_string(), 20.into());
         file1_classes.insert(
             "class AdminService".to_string(),
             ClassInfo {
-                fields: BTreeMap::new(),
+                fields: MemberMap::new(),
                 methods: admin_methods,
+                ..Default::default()
             },
         );
 
         files.insert("src/services.py".to_string(), file1_classes);
 
         let mut file2_classes = ClassMap::new();
-        let mut product_methods = BTreeMap::new();
-        product_methods.insert("def list(self) -> list".to_string(), 10);
+        let mut product_methods = MemberMap::new();
+        product_methods.insert("def list(self) -> list".to_string(), 10.into());
         file2_classes.insert(
             "class ProductManager".to_string(),
             ClassInfo {
-                fields: BTreeMap::new(),
+                fields: MemberMap::new(),
                 methods: product_methods,
+                ..Default::default()
             },
         );
         files.insert("src/products.py".to_string(), file2_classes);
@@ -685,11 +916,90 @@ mod tests {
         files
     }
 
+    #[test]
+    fn test_filter_files_output_match_all() {
+        let mut map = EntryMap::new();
+        map.insert("def user_service()".to_string(), 1.into());
+        map.insert("def get_UserService()".to_string(), 2.into());
+        map.insert("def user_repo()".to_string(), 3.into());
+        map.insert("def service_registry()".to_string(), 4.into());
+
+        let patterns = vec!["user".to_string(), "service".to_string()];
+        let filtered = filter_files_output(wrap_in_file(map.clone()), &patterns, extract_function_name, ALL);
+        let names: Vec<_> = get_test_file(&filtered)
+            .keys()
+            .map(|k| extract_function_name(k))
+            .collect();
+        assert_eq!(names, vec!["user_service", "get_UserService"]);
+
+        // Without --all either token is enough
+        let filtered = filter_files_output(
+            wrap_in_file(map),
+            &patterns,
+            extract_function_name,
+            MatchMode::default(),
+        );
+        assert_eq!(get_test_file(&filtered).len(), 3);
+    }
+
+    #[test]
+    fn test_fuzzy_score_subsequence() {
+        assert!(fuzzy_score("compute_total", "cptl").is_some());
+        assert!(fuzzy_score("compute_total", "CPTL").is_some());
+        assert!(fuzzy_score("compute_total", "ctp").is_none());
+        assert!(fuzzy_score("total", "totals").is_none());
+        assert_eq!(fuzzy_score("anything", ""), Some(0));
+    }
+
+    #[test]
+    fn test_fuzzy_score_ranks_word_starts_and_runs() {
+        let score = |subject| fuzzy_score(subject, "cptl").unwrap();
+        // `c` and `t` start words in compute_total but not in script_tools
+        assert!(score("compute_total") > score("script_tools"));
+        assert!(score("compute_total") > score("accept_all"));
+        // Consecutive characters beat scattered ones
+        assert!(fuzzy_score("reload", "load") > fuzzy_score("relocated", "load"));
+        // camelCase humps count as word starts
+        assert!(fuzzy_score("computeTotal", "ct") > fuzzy_score("compact", "ct"));
+    }
+
+    #[test]
+    fn test_filter_files_output_fuzzy_scores() {
+        let mut map = EntryMap::new();
+        map.insert("def script_tools()".to_string(), 1.into());
+        map.insert("def compute_total()".to_string(), 2.into());
+        map.insert("def compute()".to_string(), 3.into());
+        map.insert("def accept_all()".to_string(), 4.into());
+        let patterns = vec!["cptl".to_string()];
+        let fuzzy = MatchMode {
+            fuzzy: true,
+            ..Default::default()
+        };
+
+        let filtered = filter_files_output(wrap_in_file(map), &patterns, extract_function_name, fuzzy);
+        let scores: Vec<_> = get_test_file(&filtered)
+            .iter()
+            .map(|(sig, entry)| (sig.as_str(), entry.meta.score.unwrap()))
+            .collect();
+        assert_eq!(scores.len(), 3);
+        let best = scores.iter().max_by_key(|(_, score)| score).unwrap();
+        assert_eq!(best.0, "def compute_total()");
+    }
+
+    #[test]
+    fn test_filter_classes_output_match_all() {
+        let files = make_classes_output();
+        let patterns = vec!["User".to_string(), "Service".to_string()];
+        let filtered = filter_classes_output(files, &patterns, ALL);
+        let names: Vec<_> = filtered.values().flat_map(|c| c.keys()).collect();
+        assert_eq!(names, vec!["class UserService"]);
+    }
+
     #[test]
     fn test_filter_classes_output_no_patterns() {
         let files = make_classes_output();
         let original_len = files.len();
-        let filtered = filter_classes_output(files, &[]);
+        let filtered = filter_classes_output(files, &[], MatchMode::default());
         assert_eq!(filtered.len(), original_len);
     }
 
@@ -697,7 +1007,7 @@ mod tests {
     fn test_filter_classes_output_single_pattern() {
         let files = make_classes_output();
         let patterns = vec!["User".to_string()];
-        let filtered = filter_classes_output(files, &patterns);
+        let filtered = filter_classes_output(files, &patterns, MatchMode::default());
 
         assert_eq!(filtered.len(), 1);
         assert!(filtered.contains_key("src/services.py"));
@@ -709,7 +1019,7 @@ mod tests {
     fn test_filter_classes_output_removes_empty_files() {
         let files = make_classes_output();
         let patterns = vec!["Product".to_string()];
-        let filtered = filter_classes_output(files, &patterns);
+        let filtered = filter_classes_output(files, &patterns, MatchMode::default());
 
         // Only products.py should remain
         assert_eq!(filtered.len(), 1);
@@ -735,6 +1045,7 @@ mod tests {
             "src/utils/helpers.py".to_string(),
             ModuleNode {
                 node_type: ModuleType::Module,
+                counts: None,
                 children: BTreeMap::new(),
             },
         );
@@ -742,6 +1053,7 @@ mod tests {
             "src/utils/validators.py".to_string(),
             ModuleNode {
                 node_type: ModuleType::Module,
+                counts: None,
                 children: BTreeMap::new(),
             },
         );
@@ -751,6 +1063,7 @@ mod tests {
             "src/models/user.py".to_string(),
             ModuleNode {
                 node_type: ModuleType::Module,
+                counts: None,
                 children: BTreeMap::new(),
             },
         );
@@ -758,6 +1071,7 @@ mod tests {
             "src/models/product.py".to_string(),
             ModuleNode {
                 node_type: ModuleType::Module,
+                counts: None,
                 children: BTreeMap::new(),
             },
         );
@@ -767,6 +1081,7 @@ mod tests {
             "src/utils".to_string(),
             ModuleNode {
                 node_type: ModuleType::Package,
+                counts: None,
                 children: helpers_children,
             },
         );
@@ -774,6 +1089,7 @@ mod tests {
             "src/models".to_string(),
             ModuleNode {
                 node_type: ModuleType::Package,
+                counts: None,
                 children: models_children,
             },
         );
@@ -781,6 +1097,7 @@ mod tests {
             "src/main.py".to_string(),
             ModuleNode {
                 node_type: ModuleType::Module,
+                counts: None,
                 children: BTreeMap::new(),
             },
         );
@@ -789,6 +1106,7 @@ mod tests {
             "src".to_string(),
             ModuleNode {
                 node_type: ModuleType::Package,
+                counts: None,
                 children: src_children,
             },
         );
@@ -0,0 +1,354 @@
+use crate::cli::SortBy;
+use crate::output::{ClassInfo, ClassMap, Entry, EntryMap};
+use crate::pattern::{extract_class_name, extract_dump_name};
+use indexmap::IndexMap;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashSet};
+
+/// How symbols are ordered within each file before serialization, and how
+/// many of them are kept
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SortOrder {
+    pub key: SortBy,
+    pub reverse: bool,
+    /// Keep only the first N symbols of all files together, in this order (`--limit`)
+    pub limit: Option<usize>,
+}
+
+impl SortOrder {
+    pub fn new(key: SortBy, reverse: bool) -> Self {
+        SortOrder {
+            key,
+            reverse,
+            limit: None,
+        }
+    }
+
+    pub fn with_limit(self, limit: Option<usize>) -> Self {
+        SortOrder { limit, ..self }
+    }
+
+    fn apply(&self, ordering: Ordering) -> Ordering {
+        if self.reverse {
+            ordering.reverse()
+        } else {
+            ordering
+        }
+    }
+}
+
+/// Compare two symbols by the active sort key. `Name` compares the name pulled
+/// from each signature, so `async def`s sort among the plain `def`s.
+fn compare_entries(key: SortBy, (a_sig, a): (&String, &Entry), (b_sig, b): (&String, &Entry)) -> Ordering {
+    match key {
+        SortBy::Name => extract_dump_name(a_sig).cmp(extract_dump_name(b_sig)),
+        SortBy::Line => a.line.cmp(&b.line),
+        SortBy::Complexity => a.complexity.cmp(&b.complexity),
+        SortBy::Lines => a.lines().cmp(&b.lines()),
+        SortBy::Score => b.meta.score.cmp(&a.meta.score),
+    }
+}
+
+/// Total complexity of a class: the sum of its methods' complexity
+fn class_complexity(info: &ClassInfo) -> usize {
+    info.methods.values().map(|m| m.complexity).sum()
+}
+
+/// Number of source lines spanned by a class
+fn class_lines(info: &ClassInfo) -> usize {
+    info.end_line.saturating_sub(info.line) + 1
+}
+
+/// Compare two classes by the active sort key
+fn compare_classes(key: SortBy, (a_sig, a): (&String, &ClassInfo), (b_sig, b): (&String, &ClassInfo)) -> Ordering {
+    match key {
+        SortBy::Name => extract_class_name(a_sig).cmp(extract_class_name(b_sig)),
+        SortBy::Line => a.line.cmp(&b.line),
+        SortBy::Complexity => class_complexity(a).cmp(&class_complexity(b)),
+        SortBy::Lines => class_lines(a).cmp(&class_lines(b)),
+        SortBy::Score => b.meta.score.cmp(&a.meta.score),
+    }
+}
+
+/// Sort a single file's symbols in place. Ties on the sort key are broken by
+/// signature text (ascending, also with `--reverse`), so the order never
+/// depends on how the symbols were collected.
+pub fn sort_entries(entries: &mut EntryMap, order: SortOrder) {
+    entries.sort_by(|a_sig, a, b_sig, b| {
+        order
+            .apply(compare_entries(order.key, (a_sig, a), (b_sig, b)))
+            .then_with(|| a_sig.cmp(b_sig))
+    });
+}
+
+/// Sort a single file's classes in place, breaking ties like `sort_entries`.
+/// Fields, methods and nested classes are sorted by the same key.
+pub fn sort_classes(classes: &mut ClassMap, order: SortOrder) {
+    for info in classes.values_mut() {
+        sort_entries(&mut info.fields, order);
+        sort_entries(&mut info.methods, order);
+        sort_classes(&mut info.classes, order);
+    }
+    classes.sort_by(|a_sig, a, b_sig, b| {
+        order
+            .apply(compare_classes(order.key, (a_sig, a), (b_sig, b)))
+            .then_with(|| a_sig.cmp(b_sig))
+    });
+}
+
+/// Keep the first `limit` symbols of all files taken together, ranked by
+/// `compare` and then by signature and file path. Files left empty are dropped.
+fn keep_first<V>(
+    files: &mut BTreeMap<String, IndexMap<String, V>>,
+    limit: Option<usize>,
+    compare: impl Fn((&String, &V), (&String, &V)) -> Ordering,
+) {
+    let Some(limit) = limit else {
+        return;
+    };
+    let mut ranked: Vec<(&String, &String, &V)> = files
+        .iter()
+        .flat_map(|(file, symbols)| symbols.iter().map(move |(sig, value)| (file, sig, value)))
+        .collect();
+    ranked.sort_by(|(a_file, a_sig, a), (b_file, b_sig, b)| {
+        compare((a_sig, a), (b_sig, b))
+            .then_with(|| a_sig.cmp(b_sig))
+            .then_with(|| a_file.cmp(b_file))
+    });
+    let kept: HashSet<(String, String)> = ranked
+        .into_iter()
+        .take(limit)
+        .map(|(file, sig, _)| (file.clone(), sig.clone()))
+        .collect();
+
+    for (file, symbols) in files.iter_mut() {
+        symbols.retain(|sig, _| kept.contains(&(file.clone(), sig.clone())));
+    }
+    files.retain(|_, symbols| !symbols.is_empty());
+}
+
+/// Sort the symbols of every file (file paths keep their own ordering), then
+/// apply the order's limit across all files
+pub fn sort_files(files: &mut BTreeMap<String, EntryMap>, order: SortOrder) {
+    for entries in files.values_mut() {
+        sort_entries(entries, order);
+    }
+    keep_first(files, order.limit, |a, b| order.apply(compare_entries(order.key, a, b)));
+}
+
+/// Sort the classes of every file (file paths keep their own ordering), then
+/// apply the order's limit across all files, counting classes
+pub fn sort_class_files(files: &mut BTreeMap<String, ClassMap>, order: SortOrder) {
+    for classes in files.values_mut() {
+        sort_classes(classes, order);
+    }
+    keep_first(files, order.limit, |a, b| order.apply(compare_classes(order.key, a, b)));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(line: usize, end_line: usize, complexity: usize) -> Entry {
+        Entry {
+            end_line,
+            complexity,
+            ..Entry::new(line)
+        }
+    }
+
+    /// Three symbols whose orderings differ under every key
+    fn sample() -> EntryMap {
+        let mut entries = EntryMap::new();
+        entries.insert("def beta()".to_string(), entry(1, 30, 2));
+        entries.insert("def gamma()".to_string(), entry(40, 42, 9));
+        entries.insert("def alpha()".to_string(), entry(20, 24, 1));
+        entries
+    }
+
+    fn keys(entries: &EntryMap) -> Vec<&str> {
+        entries.keys().map(String::as_str).collect()
+    }
+
+    #[test]
+    fn test_sort_by_name() {
+        let mut entries = sample();
+        sort_entries(&mut entries, SortOrder::new(SortBy::Name, false));
+        assert_eq!(keys(&entries), vec!["def alpha()", "def beta()", "def gamma()"]);
+    }
+
+    #[test]
+    fn test_sort_by_name_ignores_signature_prefix() {
+        let mut entries = EntryMap::new();
+        entries.insert("def fetch(url: str)".to_string(), entry(1, 1, 1));
+        entries.insert("async def close(self)".to_string(), entry(2, 2, 1));
+        entries.insert("MAX_RETRIES: int = 3".to_string(), entry(3, 3, 1));
+        entries.insert("def fetch()".to_string(), entry(4, 4, 1));
+        sort_entries(&mut entries, SortOrder::new(SortBy::Name, false));
+        assert_eq!(
+            keys(&entries),
+            vec![
+                "MAX_RETRIES: int = 3",
+                "async def close(self)",
+                "def fetch()",
+                "def fetch(url: str)"
+            ]
+        );
+
+        let mut classes = ClassMap::new();
+        classes.insert("class Box[T]".to_string(), ClassInfo::default());
+        classes.insert("class BoxSet".to_string(), ClassInfo::default());
+        sort_classes(&mut classes, SortOrder::new(SortBy::Name, false));
+        assert_eq!(
+            classes.keys().map(String::as_str).collect::<Vec<_>>(),
+            vec!["class Box[T]", "class BoxSet"]
+        );
+    }
+
+    #[test]
+    fn test_sort_by_line() {
+        let mut entries = sample();
+        sort_entries(&mut entries, SortOrder::new(SortBy::Line, false));
+        assert_eq!(keys(&entries), vec!["def beta()", "def alpha()", "def gamma()"]);
+    }
+
+    #[test]
+    fn test_sort_by_complexity() {
+        let mut entries = sample();
+        sort_entries(&mut entries, SortOrder::new(SortBy::Complexity, false));
+        assert_eq!(keys(&entries), vec!["def alpha()", "def beta()", "def gamma()"]);
+    }
+
+    #[test]
+    fn test_sort_by_lines() {
+        let mut entries = sample();
+        sort_entries(&mut entries, SortOrder::new(SortBy::Lines, false));
+        assert_eq!(keys(&entries), vec!["def gamma()", "def alpha()", "def beta()"]);
+    }
+
+    #[test]
+    fn test_sort_reverse() {
+        let mut entries = sample();
+        sort_entries(&mut entries, SortOrder::new(SortBy::Complexity, true));
+        assert_eq!(keys(&entries), vec!["def gamma()", "def beta()", "def alpha()"]);
+    }
+
+    #[test]
+    fn test_sort_reverse_by_name() {
+        let mut entries = sample();
+        sort_entries(&mut entries, SortOrder::new(SortBy::Name, true));
+        assert_eq!(keys(&entries), vec!["def gamma()", "def beta()", "def alpha()"]);
+    }
+
+    #[test]
+    fn test_sort_reverse_by_line() {
+        let mut entries = sample();
+        sort_entries(&mut entries, SortOrder::new(SortBy::Line, true));
+        assert_eq!(keys(&entries), vec!["def gamma()", "def alpha()", "def beta()"]);
+    }
+
+    #[test]
+    fn test_sort_ties_broken_by_signature() {
+        let mut entries = EntryMap::new();
+        entries.insert("def zeta()".to_string(), entry(5, 5, 1));
+        entries.insert("def eta()".to_string(), entry(5, 5, 1));
+        entries.insert("def theta()".to_string(), entry(9, 9, 3));
+        entries.insert("def alpha()".to_string(), entry(5, 5, 1));
+
+        sort_entries(&mut entries, SortOrder::new(SortBy::Line, false));
+        assert_eq!(
+            keys(&entries),
+            vec!["def alpha()", "def eta()", "def zeta()", "def theta()"]
+        );
+
+        sort_entries(&mut entries, SortOrder::new(SortBy::Complexity, true));
+        assert_eq!(
+            keys(&entries),
+            vec!["def theta()", "def alpha()", "def eta()", "def zeta()"]
+        );
+    }
+
+    #[test]
+    fn test_sort_files_sorts_each_file() {
+        let mut files = BTreeMap::new();
+        files.insert("b.py".to_string(), sample());
+        files.insert("a.py".to_string(), sample());
+        sort_files(&mut files, SortOrder::new(SortBy::Name, false));
+        for entries in files.values() {
+            assert_eq!(keys(entries), vec!["def alpha()", "def beta()", "def gamma()"]);
+        }
+    }
+
+    #[test]
+    fn test_limit_keeps_top_symbols_across_files() {
+        let mut files = BTreeMap::new();
+        let mut small = EntryMap::new();
+        small.insert("def tiny()".to_string(), entry(1, 2, 1));
+        small.insert("def huge()".to_string(), entry(5, 95, 1));
+        files.insert("a.py".to_string(), small);
+        files.insert("b.py".to_string(), sample());
+
+        // The two largest functions, wherever they are
+        let order = SortOrder::new(SortBy::Lines, true).with_limit(Some(2));
+        sort_files(&mut files, order);
+        let kept: Vec<_> = files
+            .iter()
+            .flat_map(|(file, entries)| entries.keys().map(move |sig| (file.as_str(), sig.as_str())))
+            .collect();
+        assert_eq!(kept, vec![("a.py", "def huge()"), ("b.py", "def beta()")]);
+
+        // Files without a symbol in the top N are dropped
+        let order = SortOrder::new(SortBy::Lines, true).with_limit(Some(1));
+        sort_files(&mut files, order);
+        assert_eq!(files.keys().collect::<Vec<_>>(), vec!["a.py"]);
+    }
+
+    #[test]
+    fn test_sort_classes_by_line_and_complexity() {
+        let mut methods = crate::output::MemberMap::new();
+        methods.insert("def run(self)".to_string(), entry(12, 15, 6));
+        let busy = ClassInfo {
+            methods,
+            line: 10,
+            end_line: 15,
+            ..Default::default()
+        };
+        let idle = ClassInfo {
+            line: 1,
+            end_line: 3,
+            ..Default::default()
+        };
+        let mut classes = ClassMap::new();
+        classes.insert("class Busy".to_string(), busy);
+        classes.insert("class Idle".to_string(), idle);
+
+        sort_classes(&mut classes, SortOrder::new(SortBy::Line, false));
+        assert_eq!(classes.keys().collect::<Vec<_>>(), vec!["class Idle", "class Busy"]);
+
+        sort_classes(&mut classes, SortOrder::new(SortBy::Complexity, true));
+        assert_eq!(classes.keys().collect::<Vec<_>>(), vec!["class Busy", "class Idle"]);
+    }
+
+    #[test]
+    fn test_sort_classes_sorts_members() {
+        let mut methods = crate::output::MemberMap::new();
+        methods.insert("def stop(self)".to_string(), entry(2, 3, 1));
+        methods.insert("def start(self)".to_string(), entry(5, 6, 1));
+        let mut classes = ClassMap::new();
+        classes.insert(
+            "class Engine".to_string(),
+            ClassInfo {
+                methods,
+                ..Default::default()
+            },
+        );
+
+        sort_classes(&mut classes, SortOrder::new(SortBy::Name, false));
+        let methods = &classes["class Engine"].methods;
+        assert_eq!(keys(methods), vec!["def start(self)", "def stop(self)"]);
+
+        sort_classes(&mut classes, SortOrder::new(SortBy::Line, false));
+        let methods = &classes["class Engine"].methods;
+        assert_eq!(keys(methods), vec!["def stop(self)", "def start(self)"]);
+    }
+}
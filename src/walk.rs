@@ -1,5 +1,9 @@
+use crate::archive;
+use crate::config::pyproject_excludes;
+use crate::git::Revision;
 use eyre::{Result, WrapErr};
-use std::path::{Path, PathBuf};
+use std::path::{Component, Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use walkdir::WalkDir;
 
 /// Directories to skip during traversal
@@ -18,8 +22,132 @@ const IGNORE_DIRS: &[&str] = &[
     "*.egg-info",
 ];
 
-/// Collect all Python files from the given targets
-pub fn collect_python_files(targets: &[PathBuf]) -> Result<Vec<PathBuf>> {
+/// File names of generated code skipped with `--skip-generated`
+const GENERATED_FILES: &[&str] = &[
+    // protobuf / gRPC stubs
+    "*_pb2.py",
+    "*_pb2_grpc.py",
+    // Django migrations (0001_initial.py)
+    "[0-9][0-9][0-9][0-9]_*.py",
+];
+
+/// Options controlling file discovery
+#[derive(Debug, Clone, Default)]
+pub struct WalkOptions {
+    /// Descend into directories on the built-in ignore list and skip no
+    /// `pyproject.toml` excludes
+    pub no_ignore: bool,
+    /// Include dot-prefixed files and directories
+    pub hidden: bool,
+    /// Skip files matching the built-in generated-file globs
+    pub skip_generated: bool,
+    /// Extra file name globs to skip
+    pub ignore_files: Vec<String>,
+    /// Globs for files to collect even when an ignore rule would skip them
+    /// (`--include`), matched by name or by path relative to the target
+    pub includes: Vec<String>,
+    /// Also collect Jupyter notebooks (`.ipynb`)
+    pub notebooks: bool,
+    /// Skip files under target directories last modified before this (`--since`)
+    pub since: Option<SystemTime>,
+}
+
+/// Parse a `--since` threshold: a duration back from now (`30m`, `24h`, `7d`,
+/// `2w`, `45s`) or a `YYYY-MM-DD` date (midnight UTC)
+pub fn parse_since(value: &str) -> Result<SystemTime, String> {
+    if let Some(date) = parse_date(value) {
+        return Ok(date);
+    }
+    let split = value.find(|c: char| !c.is_ascii_digit()).unwrap_or(value.len());
+    let (amount, unit) = value.split_at(split);
+    let amount: u64 = amount.parse().map_err(|_| {
+        format!(
+            "expected a duration like 7d, 24h or 30m, or a YYYY-MM-DD date: {}",
+            value
+        )
+    })?;
+    let seconds = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        "w" => 7 * 24 * 60 * 60,
+        _ => return Err(format!("unknown duration unit {:?} (use s, m, h, d or w)", unit)),
+    };
+    SystemTime::now()
+        .checked_sub(Duration::from_secs(amount.saturating_mul(seconds)))
+        .ok_or_else(|| format!("duration out of range: {}", value))
+}
+
+/// Midnight UTC of a `YYYY-MM-DD` date
+fn parse_date(value: &str) -> Option<SystemTime> {
+    let mut parts = value.splitn(3, '-');
+    let year: i64 = parts.next().filter(|p| p.len() == 4)?.parse().ok()?;
+    let month: i64 = parts.next().filter(|p| p.len() == 2)?.parse().ok()?;
+    let day: i64 = parts.next().filter(|p| p.len() == 2)?.parse().ok()?;
+    if !(1..=12).contains(&month) || !(1..=days_in_month(year, month)).contains(&day) {
+        return None;
+    }
+    // Days since 1970-01-01 in the proleptic Gregorian calendar
+    let (y, m) = if month <= 2 { (year - 1, month + 9) } else { (year, month - 3) };
+    let era = y.div_euclid(400);
+    let year_of_era = y - era * 400;
+    let day_of_year = (153 * m + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = era * 146_097 + day_of_era - 719_468;
+    let seconds = u64::try_from(days * 24 * 60 * 60).ok()?;
+    UNIX_EPOCH.checked_add(Duration::from_secs(seconds))
+}
+
+/// Length of a month in the proleptic Gregorian calendar
+fn days_in_month(year: i64, month: i64) -> i64 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Whether a file was modified at or after the `--since` threshold. Files
+/// whose modification time can't be read are kept.
+fn modified_since(path: &Path, options: &WalkOptions) -> bool {
+    let Some(since) = options.since else {
+        return true;
+    };
+    match path.metadata().and_then(|metadata| metadata.modified()) {
+        Ok(modified) => modified >= since,
+        Err(_) => true,
+    }
+}
+
+/// Collect all Python files from the given targets, failing on the first
+/// entry that can't be read
+#[cfg(test)]
+pub fn collect_python_files(targets: &[PathBuf], options: &WalkOptions) -> Result<Vec<PathBuf>> {
+    collect(targets, options, &mut |err| Err(err.into()))
+}
+
+/// Collect all Python files from the given targets. Entries that can't be read
+/// (permission denied, removed mid-walk) are passed to `skip` and left out.
+pub fn collect_python_files_skipping(
+    targets: &[PathBuf],
+    options: &WalkOptions,
+    skip: &mut dyn FnMut(&Path, String),
+) -> Result<Vec<PathBuf>> {
+    collect(targets, options, &mut |err| {
+        let path = err.path().map(Path::to_path_buf).unwrap_or_default();
+        skip(&path, err.to_string());
+        Ok(())
+    })
+}
+
+/// Walk the targets; `on_error` decides whether an unreadable entry fails the walk
+fn collect(
+    targets: &[PathBuf],
+    options: &WalkOptions,
+    on_error: &mut dyn FnMut(walkdir::Error) -> Result<()>,
+) -> Result<Vec<PathBuf>> {
     let mut files = Vec::new();
 
     for target in targets {
@@ -28,11 +156,16 @@ pub fn collect_python_files(targets: &[PathBuf]) -> Result<Vec<PathBuf>> {
         }
 
         if target.is_file() {
-            if is_python_file(target) {
+            if archive::is_archive(target) {
+                // Members are listed below the archive, as `<archive>/<member>`
+                files.extend(archive::list(target, |path| {
+                    is_listed_source(path, Path::new(""), options)
+                })?);
+            } else if is_source_file(target, options) {
                 files.push(target.clone());
             }
         } else if target.is_dir() {
-            collect_from_directory(target, &mut files)
+            collect_from_directory(target, options, &mut files, &mut *on_error)
                 .wrap_err_with(|| format!("Failed to walk directory: {}", target.display()))?;
         }
     }
@@ -42,16 +175,113 @@ pub fn collect_python_files(targets: &[PathBuf]) -> Result<Vec<PathBuf>> {
     Ok(files)
 }
 
-fn collect_from_directory(dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+/// Split a `--files-from` list, one path per line, into the source files to
+/// analyze and the entries that aren't source files. Blank lines are ignored.
+/// Listed files are used as given: ignore rules don't apply to them.
+pub fn parse_file_list(list: &str, options: &WalkOptions) -> (Vec<PathBuf>, Vec<PathBuf>) {
+    let (mut files, others): (Vec<PathBuf>, Vec<PathBuf>) = list
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(PathBuf::from)
+        .partition(|path| is_source_file(path, options));
+    files.sort();
+    files.dedup();
+    (files, others)
+}
+
+/// Collect the Python files of the targets as of a git revision. Targets are
+/// relative to the repository root; the same ignore rules as a walk apply
+/// below each target, except `pyproject.toml` excludes.
+pub fn collect_revision_files(revision: &Revision, targets: &[PathBuf], options: &WalkOptions) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+
+    for target in targets {
+        let listed = revision
+            .list_files(target)?
+            .ok_or_else(|| eyre::eyre!("Path does not exist at revision: {}", target.display()))?;
+        // Listed paths are relative to the root, and so is the target
+        let base: PathBuf = target.components().filter(|c| *c != Component::CurDir).collect();
+        files.extend(listed.into_iter().filter(|path| is_listed_source(path, &base, options)));
+    }
+
+    files.sort();
+    files.dedup();
+    Ok(files)
+}
+
+/// Whether a path listed below `base` (in a git tree or an archive) is a source
+/// file to collect: the same ignore rules as a walk apply to the part of the
+/// path below `base`, except `pyproject.toml` excludes
+fn is_listed_source(path: &Path, base: &Path, options: &WalkOptions) -> bool {
+    let below: Vec<_> = path
+        .strip_prefix(base)
+        .unwrap_or(path)
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().into_owned())
+        .collect();
+    // Like a walk, never filter the target itself: a file named directly
+    // leaves nothing below it
+    let (ignored, included) = match below.split_last() {
+        Some((name, dirs)) => (
+            dirs.iter().any(|dir| should_ignore(dir, options))
+                || should_ignore(name, options)
+                || should_ignore_file(name, options),
+            options
+                .includes
+                .iter()
+                .any(|pattern| glob_matches_entry(pattern, name, &below.join("/"))),
+        ),
+        None => (false, false),
+    };
+    (!ignored || included) && is_source_file(path, options)
+}
+
+fn collect_from_directory(
+    dir: &Path,
+    options: &WalkOptions,
+    files: &mut Vec<PathBuf>,
+    on_error: &mut dyn FnMut(walkdir::Error) -> Result<()>,
+) -> Result<()> {
+    let excludes = if options.no_ignore { Vec::new() } else { pyproject_excludes(dir)? };
+    let ignored = |path: &Path| {
+        let name = path.file_name().map(|n| n.to_string_lossy()).unwrap_or_default();
+        should_ignore(&name, options) || matches_globs(path, dir, &excludes)
+    };
+
     for entry in WalkDir::new(dir)
         .follow_links(false)
         .into_iter()
-        .filter_entry(|e| !should_ignore(e.file_name().to_string_lossy().as_ref()))
+        // Never filter the target itself, so `-t .` or `-t build/` still work.
+        // An ignored directory is still entered if an `--include` may match below it.
+        .filter_entry(|e| {
+            e.depth() == 0
+                || !ignored(e.path())
+                || matches_globs(e.path(), dir, &options.includes)
+                || (e.file_type().is_dir() && may_include_below(e.path(), dir, &options.includes))
+        })
     {
-        let entry = entry?;
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(err) => {
+                on_error(err)?;
+                continue;
+            }
+        };
         let path = entry.path();
+        if !(path.is_file() && is_source_file(path, options) && modified_since(path, options)) {
+            continue;
+        }
 
-        if path.is_file() && is_python_file(path) {
+        // Only `--include` brings in files below an ignored directory
+        let in_ignored_dir = !options.includes.is_empty()
+            && path
+                .ancestors()
+                .skip(1)
+                .take_while(|ancestor| *ancestor != dir)
+                .any(&ignored);
+        let skipped = in_ignored_dir || should_ignore_file(&entry.file_name().to_string_lossy(), options);
+        if !skipped || matches_globs(path, dir, &options.includes) {
             files.push(path.to_path_buf());
         }
     }
@@ -63,15 +293,134 @@ fn is_python_file(path: &Path) -> bool {
     path.extension().is_some_and(|ext| ext == "py")
 }
 
-fn should_ignore(name: &str) -> bool {
-    IGNORE_DIRS.iter().any(|pattern| {
-        if let Some(suffix) = pattern.strip_prefix('*') {
-            // Simple glob: *.egg-info
-            name.ends_with(suffix)
+/// A `.py` file, or a notebook when notebooks are enabled
+fn is_source_file(path: &Path, options: &WalkOptions) -> bool {
+    is_python_file(path) || (options.notebooks && path.extension().is_some_and(|ext| ext == "ipynb"))
+}
+
+fn should_ignore(name: &str, options: &WalkOptions) -> bool {
+    if options.no_ignore {
+        return false;
+    }
+    if !options.hidden && name.starts_with('.') {
+        return true;
+    }
+
+    IGNORE_DIRS.iter().any(|pattern| glob_match(pattern, name))
+}
+
+/// Check an entry against `pyproject.toml` exclude or `--include` globs. A
+/// glob matches the entry's name (`migrations`, `*_pb2.py`) or its path
+/// relative to the target root (`src/legacy`, `tests/fixtures/*`).
+fn matches_globs(path: &Path, root: &Path, globs: &[String]) -> bool {
+    if globs.is_empty() {
+        return false;
+    }
+    let name = path.file_name().map(|n| n.to_string_lossy()).unwrap_or_default();
+    let relative = relative_components(path, root).join("/");
+    globs
+        .iter()
+        .any(|pattern| glob_matches_entry(pattern, &name, &relative))
+}
+
+/// Whether a glob matches an entry's name or its `/`-joined relative path
+fn glob_matches_entry(pattern: &str, name: &str, relative: &str) -> bool {
+    let pattern = pattern.trim_start_matches("./").trim_end_matches('/');
+    glob_match(pattern, name) || glob_match(pattern, relative)
+}
+
+/// Whether an `--include` path glob could match an entry below the directory
+/// `path`: the glob has more components, and its leading ones match the
+/// directory's (`build/*.py` below `build`). Name-only globs re-include
+/// ignored files, but don't reach into ignored directories.
+fn may_include_below(path: &Path, root: &Path, includes: &[String]) -> bool {
+    let dirs = relative_components(path, root);
+    includes.iter().any(|pattern| {
+        let parts: Vec<&str> = pattern.trim_start_matches("./").split('/').collect();
+        parts.len() > dirs.len() && dirs.iter().zip(&parts).all(|(dir, part)| glob_match(part, dir))
+    })
+}
+
+/// Components of `path` below `root`
+fn relative_components(path: &Path, root: &Path) -> Vec<String> {
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().into_owned())
+        .collect()
+}
+
+/// Check a file name against the generated-file and `--ignore-file` globs
+fn should_ignore_file(name: &str, options: &WalkOptions) -> bool {
+    let generated = options.skip_generated && GENERATED_FILES.iter().any(|pattern| glob_match(pattern, name));
+    generated || options.ignore_files.iter().any(|pattern| glob_match(pattern, name))
+}
+
+/// Match a name against a glob: `*` matches any run of characters, `?` any
+/// single character, and `[a-z0-9]` any character in the set
+fn glob_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    glob_match_chars(&pattern, &name)
+}
+
+/// Iterative matcher: on a mismatch, only the last `*` is retried, one
+/// character further along the name, which keeps matching linear in practice
+fn glob_match_chars(pattern: &[char], name: &[char]) -> bool {
+    let (mut p, mut n) = (0, 0);
+    // Pattern position after the last `*`, and the name position it resumes from
+    let mut star: Option<(usize, usize)> = None;
+    while n < name.len() {
+        if pattern.get(p) == Some(&'*') {
+            // Consecutive stars match the same as one
+            while pattern.get(p) == Some(&'*') {
+                p += 1;
+            }
+            star = Some((p, n));
+        } else if let Some(width) = match_one(&pattern[p..], name[n]) {
+            p += width;
+            n += 1;
+        } else if let Some((after, from)) = star {
+            p = after;
+            n = from + 1;
+            star = Some((after, n));
         } else {
-            name == *pattern
+            return false;
         }
-    })
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Width of the pattern token at the start of `pattern` if it matches `c`
+fn match_one(pattern: &[char], c: char) -> Option<usize> {
+    match pattern.first()? {
+        '?' => Some(1),
+        '[' => match pattern.iter().position(|&c| c == ']') {
+            Some(end) if end > 1 => class_matches(&pattern[1..end], c).then_some(end + 1),
+            // Unterminated or empty class: treat `[` literally
+            _ => (c == '[').then_some(1),
+        },
+        &literal => (literal == c).then_some(1),
+    }
+}
+
+/// Check a character against a bracket set such as `a-z0-9_`
+fn class_matches(class: &[char], c: char) -> bool {
+    let mut i = 0;
+    while i < class.len() {
+        if i + 2 < class.len() && class[i + 1] == '-' {
+            if (class[i]..=class[i + 2]).contains(&c) {
+                return true;
+            }
+            i += 3;
+        } else {
+            if class[i] == c {
+                return true;
+            }
+            i += 1;
+        }
+    }
+    false
 }
 
 #[cfg(test)]
@@ -101,38 +450,216 @@ mod tests {
 
     #[test]
     fn test_should_ignore_pycache() {
-        assert!(should_ignore("__pycache__"));
+        assert!(should_ignore("__pycache__", &WalkOptions::default()));
     }
 
     #[test]
     fn test_should_ignore_git() {
-        assert!(should_ignore(".git"));
+        assert!(should_ignore(".git", &WalkOptions::default()));
     }
 
     #[test]
     fn test_should_ignore_venv() {
-        assert!(should_ignore("venv"));
-        assert!(should_ignore(".venv"));
+        assert!(should_ignore("venv", &WalkOptions::default()));
+        assert!(should_ignore(".venv", &WalkOptions::default()));
     }
 
     #[test]
     fn test_should_ignore_egg_info() {
-        assert!(should_ignore("mypackage.egg-info"));
-        assert!(should_ignore("test.egg-info"));
+        assert!(should_ignore("mypackage.egg-info", &WalkOptions::default()));
+        assert!(should_ignore("test.egg-info", &WalkOptions::default()));
     }
 
     #[test]
     fn test_should_not_ignore_regular_dirs() {
-        assert!(!should_ignore("src"));
-        assert!(!should_ignore("tests"));
-        assert!(!should_ignore("app"));
-        assert!(!should_ignore("lib"));
+        assert!(!should_ignore("src", &WalkOptions::default()));
+        assert!(!should_ignore("tests", &WalkOptions::default()));
+        assert!(!should_ignore("app", &WalkOptions::default()));
+        assert!(!should_ignore("lib", &WalkOptions::default()));
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("*.egg-info", "pkg.egg-info"));
+        assert!(glob_match("*_pb2.py", "user_pb2.py"));
+        assert!(!glob_match("*_pb2.py", "user_pb2_grpc.py"));
+        assert!(glob_match("conf?.py", "conf1.py"));
+        assert!(glob_match("[0-9][0-9][0-9][0-9]_*.py", "0001_initial.py"));
+        assert!(!glob_match("[0-9][0-9][0-9][0-9]_*.py", "test_models.py"));
+        assert!(glob_match("venv", "venv"));
+        assert!(!glob_match("venv", "venv2"));
+        assert!(glob_match("**_pb2.py", "user_pb2.py"));
+        assert!(glob_match("*", ""));
+        assert!(glob_match("a*b*c", "aXbYbZc"));
+        assert!(!glob_match("a*b*c", "aXbYbZ"));
+        assert!(glob_match("[x", "[x"));
+    }
+
+    #[test]
+    fn test_glob_match_many_stars() {
+        // Backtracking into every star would take exponential time here
+        let pattern = "*a".repeat(20) + "b";
+        assert!(!glob_match(&pattern, &"a".repeat(60)));
+        assert!(glob_match(&pattern, &("a".repeat(60) + "b")));
+    }
+
+    #[test]
+    fn test_collect_python_files_skip_generated() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        fs::write(temp_dir.path().join("foo_pb2.py"), "# generated").unwrap();
+        fs::write(temp_dir.path().join("foo_pb2_grpc.py"), "# generated").unwrap();
+        fs::write(temp_dir.path().join("foo.py"), "# normal").unwrap();
+        let targets = [temp_dir.path().to_path_buf()];
+
+        let default_files = collect_python_files(&targets, &WalkOptions::default()).unwrap();
+        assert_eq!(default_files.len(), 3);
+
+        let options = WalkOptions {
+            skip_generated: true,
+            ..Default::default()
+        };
+        let files = collect_python_files(&targets, &options).unwrap();
+        assert_eq!(files.len(), 1);
+        assert!(files[0].ends_with("foo.py"));
+    }
+
+    #[test]
+    fn test_collect_python_files_ignore_file_globs() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        fs::write(temp_dir.path().join("settings_local.py"), "# local").unwrap();
+        fs::write(temp_dir.path().join("app.py"), "# app").unwrap();
+        let targets = [temp_dir.path().to_path_buf()];
+
+        let options = WalkOptions {
+            ignore_files: vec!["*_local.py".to_string()],
+            ..Default::default()
+        };
+        let files = collect_python_files(&targets, &options).unwrap();
+        assert_eq!(files.len(), 1);
+        assert!(files[0].ends_with("app.py"));
+    }
+
+    #[test]
+    fn test_collect_python_files_notebooks() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        fs::write(temp_dir.path().join("analysis.ipynb"), "{}").unwrap();
+        fs::write(temp_dir.path().join("main.py"), "# main").unwrap();
+        let targets = [temp_dir.path().to_path_buf()];
+
+        let default_files = collect_python_files(&targets, &WalkOptions::default()).unwrap();
+        assert_eq!(default_files.len(), 1);
+
+        let options = WalkOptions {
+            notebooks: true,
+            ..Default::default()
+        };
+        let files = collect_python_files(&targets, &options).unwrap();
+        assert_eq!(files.len(), 2);
+        assert!(files.iter().any(|f| f.ends_with("analysis.ipynb")));
+    }
+
+    #[test]
+    fn test_collect_python_files_since() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let now = SystemTime::now();
+        let day = Duration::from_secs(24 * 60 * 60);
+        for (name, age) in [("fresh.py", 0), ("recent.py", 2), ("stale.py", 10), ("ancient.py", 400)] {
+            let path = temp_dir.path().join(name);
+            fs::write(&path, "# module").unwrap();
+            let file = fs::File::options().write(true).open(&path).unwrap();
+            file.set_modified(now - day * age).unwrap();
+        }
+        let targets = [temp_dir.path().to_path_buf()];
+        let collect = |since: &str| {
+            let options = WalkOptions {
+                since: Some(parse_since(since).unwrap()),
+                ..Default::default()
+            };
+            let files = collect_python_files(&targets, &options).unwrap();
+            let mut names: Vec<_> = files
+                .iter()
+                .map(|f| f.file_name().unwrap().to_string_lossy().into_owned())
+                .collect();
+            names.sort();
+            names
+        };
+
+        assert_eq!(collect("7d"), vec!["fresh.py", "recent.py"]);
+        assert_eq!(collect("1h"), vec!["fresh.py"]);
+        assert_eq!(collect("2w"), vec!["fresh.py", "recent.py", "stale.py"]);
+        assert_eq!(collect("1970-01-01").len(), 4);
+
+        // Files named directly are always collected
+        let stale = [temp_dir.path().join("stale.py")];
+        let options = WalkOptions {
+            since: Some(parse_since("1d").unwrap()),
+            ..Default::default()
+        };
+        assert_eq!(collect_python_files(&stale, &options).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_parse_file_list() {
+        let list = "src/b.py\nREADME.md\n\n  src/a.py  \r\nnotes/report.ipynb\nsrc/b.py\n";
+        let (files, others) = parse_file_list(list, &WalkOptions::default());
+        assert_eq!(files, vec![PathBuf::from("src/a.py"), PathBuf::from("src/b.py")]);
+        assert_eq!(
+            others,
+            vec![PathBuf::from("README.md"), PathBuf::from("notes/report.ipynb")]
+        );
+
+        let options = WalkOptions {
+            notebooks: true,
+            ..Default::default()
+        };
+        let (files, _) = parse_file_list(list, &options);
+        assert_eq!(files.len(), 3);
+    }
+
+    #[test]
+    fn test_parse_since() {
+        let ago = |value: &str| {
+            let since = parse_since(value).unwrap();
+            SystemTime::now().duration_since(since).unwrap()
+        };
+        assert!(ago("30m") >= Duration::from_secs(30 * 60));
+        assert!(ago("30m") < Duration::from_secs(31 * 60));
+        assert!(ago("24h") >= Duration::from_secs(24 * 60 * 60));
+        assert!(ago("7d") >= Duration::from_secs(7 * 24 * 60 * 60));
+        assert!(ago("7d") < Duration::from_secs(8 * 24 * 60 * 60));
+
+        let epoch = |value: &str| {
+            parse_since(value)
+                .unwrap()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_secs()
+        };
+        assert_eq!(epoch("1970-01-01"), 0);
+        assert_eq!(epoch("2000-03-01"), 951_868_800);
+        assert_eq!(epoch("2024-02-29"), 1_709_164_800);
+
+        assert!(parse_since("7").is_err());
+        assert!(parse_since("7y").is_err());
+        assert!(parse_since("d").is_err());
+        assert!(parse_since("2024-13-01").is_err());
+    }
+
+    #[test]
+    fn test_parse_since_rejects_days_past_month_end() {
+        assert!(parse_since("2024-02-30").is_err());
+        assert!(parse_since("2024-02-31").is_err());
+        assert!(parse_since("2023-02-29").is_err());
+        assert!(parse_since("2100-02-29").is_err());
+        assert!(parse_since("2024-04-31").is_err());
+        assert!(parse_since("2000-02-29").is_ok());
+        assert!(parse_since("2024-12-31").is_ok());
     }
 
     #[test]
     fn test_collect_python_files_single_file() {
         let path = fixtures_dir().join("functions.py");
-        let result = collect_python_files(&[path.clone()]);
+        let result = collect_python_files(std::slice::from_ref(&path), &WalkOptions::default());
         assert!(result.is_ok());
         let files = result.unwrap();
         assert_eq!(files.len(), 1);
@@ -142,7 +669,7 @@ mod tests {
     #[test]
     fn test_collect_python_files_directory() {
         let dir = fixtures_dir();
-        let result = collect_python_files(&[dir]);
+        let result = collect_python_files(&[dir], &WalkOptions::default());
         assert!(result.is_ok());
         let files = result.unwrap();
         // Should find all .py files in fixtures directory
@@ -155,7 +682,7 @@ mod tests {
     #[test]
     fn test_collect_python_files_nonexistent() {
         let path = fixtures_dir().join("nonexistent");
-        let result = collect_python_files(&[path]);
+        let result = collect_python_files(&[path], &WalkOptions::default());
         assert!(result.is_err());
     }
 
@@ -166,7 +693,7 @@ mod tests {
         let txt_file = temp_dir.path().join("test.txt");
         fs::write(&txt_file, "not python").unwrap();
 
-        let result = collect_python_files(&[txt_file]);
+        let result = collect_python_files(&[txt_file], &WalkOptions::default());
         assert!(result.is_ok());
         let files = result.unwrap();
         assert!(files.is_empty()); // Should not include .txt files
@@ -175,7 +702,7 @@ mod tests {
     #[test]
     fn test_collect_python_files_sorted() {
         let dir = fixtures_dir();
-        let result = collect_python_files(&[dir]);
+        let result = collect_python_files(&[dir], &WalkOptions::default());
         assert!(result.is_ok());
         let files = result.unwrap();
 
@@ -188,7 +715,7 @@ mod tests {
     #[test]
     fn test_collect_python_files_nested() {
         let dir = fixtures_dir().join("pkg");
-        let result = collect_python_files(&[dir]);
+        let result = collect_python_files(&[dir], &WalkOptions::default());
         assert!(result.is_ok());
         let files = result.unwrap();
 
@@ -209,7 +736,7 @@ mod tests {
         fs::write(pycache.join("cached.py"), "# cached").unwrap();
         fs::write(temp_dir.path().join("main.py"), "# main").unwrap();
 
-        let result = collect_python_files(&[temp_dir.path().to_path_buf()]);
+        let result = collect_python_files(&[temp_dir.path().to_path_buf()], &WalkOptions::default());
         assert!(result.is_ok());
         let files = result.unwrap();
 
@@ -222,11 +749,161 @@ mod tests {
         let functions = fixtures_dir().join("functions.py");
         let classes = fixtures_dir().join("classes.py");
 
-        let result = collect_python_files(&[functions.clone(), classes.clone()]);
+        let result = collect_python_files(&[functions.clone(), classes.clone()], &WalkOptions::default());
         assert!(result.is_ok());
         let files = result.unwrap();
         assert_eq!(files.len(), 2);
         assert!(files.contains(&functions));
         assert!(files.contains(&classes));
     }
+
+    #[test]
+    fn test_collect_python_files_no_ignore_includes_pycache() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let pycache = temp_dir.path().join("__pycache__");
+        fs::create_dir(&pycache).unwrap();
+        fs::write(pycache.join("cached.py"), "# cached").unwrap();
+        fs::write(temp_dir.path().join("main.py"), "# main").unwrap();
+        let targets = [temp_dir.path().to_path_buf()];
+
+        let default_files = collect_python_files(&targets, &WalkOptions::default()).unwrap();
+        assert_eq!(default_files.len(), 1);
+
+        let options = WalkOptions {
+            no_ignore: true,
+            ..Default::default()
+        };
+        let files = collect_python_files(&targets, &options).unwrap();
+        assert_eq!(files.len(), 2);
+        assert!(files.iter().any(|f| f.ends_with("__pycache__/cached.py")));
+    }
+
+    #[test]
+    fn test_collect_python_files_pyproject_excludes() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let root = temp_dir.path();
+        fs::write(
+            root.join("pyproject.toml"),
+            "[tool.ruff]\nexclude = [\"legacy\", \"src/gen/*.py\"]\n",
+        )
+        .unwrap();
+        for dir in ["legacy", "src/gen", "src/app"] {
+            fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        for file in ["main.py", "legacy/old.py", "src/gen/api.py", "src/app/models.py"] {
+            fs::write(root.join(file), "# code").unwrap();
+        }
+        let targets = [root.to_path_buf()];
+
+        let files = collect_python_files(&targets, &WalkOptions::default()).unwrap();
+        let relative: Vec<_> = files.iter().map(|f| f.strip_prefix(root).unwrap()).collect();
+        assert_eq!(relative, vec![Path::new("main.py"), Path::new("src/app/models.py")]);
+
+        let options = WalkOptions {
+            no_ignore: true,
+            ..Default::default()
+        };
+        assert_eq!(collect_python_files(&targets, &options).unwrap().len(), 4);
+        // Files named directly are never excluded
+        let targets = [root.join("legacy/old.py")];
+        assert_eq!(
+            collect_python_files(&targets, &WalkOptions::default()).unwrap().len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_matches_globs() {
+        let root = Path::new("/repo");
+        let excludes = vec!["./build/".to_string(), "*_pb2.py".to_string(), "tests/data".to_string()];
+        assert!(matches_globs(Path::new("/repo/build"), root, &excludes));
+        assert!(matches_globs(Path::new("/repo/pkg/user_pb2.py"), root, &excludes));
+        assert!(matches_globs(Path::new("/repo/tests/data"), root, &excludes));
+        assert!(!matches_globs(Path::new("/repo/pkg/tests/data"), root, &excludes));
+        assert!(!matches_globs(Path::new("/repo/pkg/user.py"), root, &excludes));
+    }
+
+    #[test]
+    fn test_may_include_below() {
+        let root = Path::new("/repo");
+        let includes = vec!["build/gen/*.py".to_string(), "keep.py".to_string()];
+        assert!(may_include_below(Path::new("/repo/build"), root, &includes));
+        assert!(may_include_below(Path::new("/repo/build/gen"), root, &includes));
+        assert!(!may_include_below(Path::new("/repo/build/gen/deep"), root, &includes));
+        assert!(!may_include_below(Path::new("/repo/dist"), root, &includes));
+    }
+
+    #[test]
+    fn test_collect_python_files_include_overrides_ignores() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let build = temp_dir.path().join("build");
+        fs::create_dir(&build).unwrap();
+        fs::write(build.join("generated_api.py"), "# keep").unwrap();
+        fs::write(build.join("other.py"), "# skip").unwrap();
+        fs::write(temp_dir.path().join("settings_local.py"), "# local").unwrap();
+        let targets = [temp_dir.path().to_path_buf()];
+
+        let options = WalkOptions {
+            ignore_files: vec!["*_local.py".to_string()],
+            includes: vec!["build/generated_api.py".to_string()],
+            ..Default::default()
+        };
+        let files = collect_python_files(&targets, &options).unwrap();
+        assert_eq!(files, vec![build.join("generated_api.py")]);
+
+        // A name-only glob re-includes an ignored file, wherever it is
+        let options = WalkOptions {
+            includes: vec!["*_local.py".to_string()],
+            ..options
+        };
+        let files = collect_python_files(&targets, &options).unwrap();
+        assert_eq!(files, vec![temp_dir.path().join("settings_local.py")]);
+    }
+
+    #[test]
+    fn test_should_ignore_hidden_unless_enabled() {
+        let hidden = WalkOptions {
+            hidden: true,
+            ..Default::default()
+        };
+        assert!(should_ignore(".config", &WalkOptions::default()));
+        assert!(!should_ignore(".config", &hidden));
+        // Built-in ignores still apply with --hidden
+        assert!(should_ignore(".git", &hidden));
+    }
+
+    #[test]
+    fn test_collect_python_files_hidden_dir() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let config = temp_dir.path().join(".config");
+        fs::create_dir(&config).unwrap();
+        fs::write(config.join("settings.py"), "# settings").unwrap();
+        fs::write(temp_dir.path().join(".hidden.py"), "# hidden").unwrap();
+        fs::write(temp_dir.path().join("main.py"), "# main").unwrap();
+        let targets = [temp_dir.path().to_path_buf()];
+
+        let default_files = collect_python_files(&targets, &WalkOptions::default()).unwrap();
+        assert_eq!(default_files.len(), 1);
+        assert!(default_files[0].ends_with("main.py"));
+
+        let options = WalkOptions {
+            hidden: true,
+            ..Default::default()
+        };
+        let files = collect_python_files(&targets, &options).unwrap();
+        assert_eq!(files.len(), 3);
+        assert!(files.iter().any(|f| f.ends_with(".config/settings.py")));
+    }
+
+    #[test]
+    fn test_collect_python_files_dot_target() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let hidden_root = temp_dir.path().join(".tools");
+        fs::create_dir(&hidden_root).unwrap();
+        fs::write(hidden_root.join("run.py"), "# run").unwrap();
+
+        // An explicitly named hidden target is still walked
+        let files = collect_python_files(&[hidden_root], &WalkOptions::default()).unwrap();
+        assert_eq!(files.len(), 1);
+    }
 }
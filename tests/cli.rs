@@ -0,0 +1,176 @@
+use std::path::PathBuf;
+use std::process::Command;
+
+fn fixtures_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures")
+}
+
+fn pyr(args: &[&str]) -> std::process::Output {
+    Command::new(env!("CARGO_BIN_EXE_pyr"))
+        .args(args)
+        .arg("--target")
+        .arg(fixtures_dir().join("functions.py"))
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn test_verbose_diagnostics_go_to_stderr() {
+    let quiet = pyr(&["function"]);
+    let verbose = pyr(&["-v", "function"]);
+    assert!(quiet.status.success() && verbose.status.success());
+
+    assert!(quiet.stderr.is_empty());
+    assert_eq!(verbose.stdout, quiet.stdout);
+    let stderr = String::from_utf8(verbose.stderr).unwrap();
+    assert!(stderr.contains("1 file discovered"));
+    assert!(stderr.contains("walk ") && stderr.contains("serialize "));
+}
+
+#[test]
+fn test_config_file_sets_default_format() {
+    let dir = tempfile::TempDir::new().unwrap();
+    std::fs::write(dir.path().join(".pyr.toml"), "format = \"toml\"\n").unwrap();
+    let run = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_pyr"))
+            .current_dir(dir.path())
+            .args(args)
+            .arg("--target")
+            .arg(fixtures_dir().join("functions.py"))
+            .output()
+            .unwrap()
+    };
+
+    // Not a TTY, so without the config this would be JSON
+    let stdout = String::from_utf8(run(&["function"]).stdout).unwrap();
+    assert!(stdout.starts_with("[files."), "{}", stdout);
+    let stdout = String::from_utf8(run(&["function", "--json"]).stdout).unwrap();
+    assert!(stdout.starts_with('{'));
+}
+
+#[test]
+fn test_rev_reads_files_at_revision() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let git = |args: &[&str]| {
+        let status = Command::new("git")
+            .current_dir(dir.path())
+            .args(["-c", "user.name=pyr", "-c", "user.email=pyr@example.com"])
+            .args(args)
+            .status()
+            .unwrap();
+        assert!(status.success());
+    };
+    git(&["init", "-q"]);
+    std::fs::write(dir.path().join("app.py"), "def old():\n    pass\n").unwrap();
+    git(&["add", "."]);
+    git(&["commit", "-q", "-m", "first"]);
+    std::fs::write(dir.path().join("app.py"), "def new(x: int):\n    pass\n").unwrap();
+    git(&["commit", "-q", "-am", "second"]);
+    // Uncommitted changes are not seen at a revision
+    std::fs::write(dir.path().join("app.py"), "def dirty():\n    pass\n").unwrap();
+
+    let run = |args: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_pyr"))
+            .current_dir(dir.path())
+            .args(args)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        String::from_utf8(output.stdout).unwrap()
+    };
+
+    assert!(run(&["--rev", "HEAD~1", "function"]).contains("def old()"));
+    assert!(run(&["--rev", "HEAD", "function"]).contains("def new(x: int)"));
+    assert!(run(&["function"]).contains("def dirty()"));
+
+    // OLD at the revision, NEW from the working tree
+    let diff = run(&["--rev", "HEAD~1", "diff", ".", "."]);
+    assert!(diff.contains("def old()") && diff.contains("def dirty()"), "{}", diff);
+}
+
+#[test]
+fn test_sorted_output_is_identical_across_runs() {
+    let run = |args: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_pyr"))
+            .args(args)
+            .arg("--target")
+            .arg(fixtures_dir())
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        output.stdout
+    };
+
+    for command in ["function", "class", "dump"] {
+        for sort_by in ["line", "complexity", "lines"] {
+            let expected = run(&["--sort-by", sort_by, command]);
+            for _ in 0..3 {
+                assert!(run(&["--sort-by", sort_by, command]) == expected);
+            }
+            assert!(run(&["--threads", "1", "--sort-by", sort_by, command]) == expected);
+        }
+    }
+}
+
+#[test]
+fn test_binary_file_is_reported_skipped() {
+    let dir = tempfile::TempDir::new().unwrap();
+    std::fs::write(dir.path().join("good.py"), "def kept():\n    pass\n").unwrap();
+    std::fs::write(dir.path().join("corrupt.py"), b"\x00\x01\x02binary\xff").unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_pyr"))
+        .args(["-v", "--json", "function", "--target"])
+        .arg(dir.path())
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("def kept()"), "{}", stdout);
+    assert!(!stdout.contains("corrupt.py"));
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("1 file skipped due to errors"), "{}", stderr);
+    assert!(stderr.contains("corrupt.py: binary file"), "{}", stderr);
+}
+
+#[test]
+fn test_files_from_analyzes_only_listed_files() {
+    let dir = tempfile::TempDir::new().unwrap();
+    for name in ["listed.py", "unlisted.py"] {
+        let stem = name.trim_end_matches(".py");
+        std::fs::write(dir.path().join(name), format!("def {}():\n    pass\n", stem)).unwrap();
+    }
+    std::fs::write(dir.path().join("files.txt"), "listed.py\nCHANGELOG.md\n").unwrap();
+    let run = |args: &[&str], stdin: &[u8]| {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_pyr"))
+            .current_dir(dir.path())
+            .args(args)
+            .stdin(std::process::Stdio::piped())
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped())
+            .spawn()
+            .unwrap();
+        std::io::Write::write_all(&mut child.stdin.take().unwrap(), stdin).unwrap();
+        child.wait_with_output().unwrap()
+    };
+
+    let output = run(&["--json", "--files-from", "files.txt", "function"], b"");
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("def listed()"), "{}", stdout);
+    assert!(!stdout.contains("unlisted"), "{}", stdout);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains("skipping CHANGELOG.md: not a Python file"),
+        "{}",
+        stderr
+    );
+
+    // `-` reads the list from stdin, e.g. piped from `git diff --name-only`
+    let output = run(&["--json", "--files-from", "-", "function"], b"unlisted.py\n");
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(
+        stdout.contains("def unlisted()") && !stdout.contains("def listed()"),
+        "{}",
+        stdout
+    );
+}
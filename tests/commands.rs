@@ -0,0 +1,718 @@
+//! End-to-end tests: run the built binary with real arguments and check its
+//! stdout, so flag wiring and command dispatch in `main` are covered too.
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+use serde_json::{json, Value};
+
+/// `pyr` run from the crate root, so fixture paths in the output are relative
+fn pyr() -> Command {
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_pyr"));
+    cmd.current_dir(env!("CARGO_MANIFEST_DIR"));
+    cmd
+}
+
+/// Run `pyr --json <args>` and parse what it printed
+fn pyr_json(args: &[&str]) -> Value {
+    let output = pyr()
+        .arg("--json")
+        .args(args)
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    serde_json::from_slice(&output).unwrap()
+}
+
+#[test]
+fn test_function() {
+    let value = pyr_json(&["function", "simple", "--public", "-t", "tests/fixtures/functions.py"]);
+    assert_eq!(
+        value,
+        json!({"files": {"tests/fixtures/functions.py": {"def simple_function()": 3}}})
+    );
+}
+
+#[test]
+fn test_class() {
+    let value = pyr_json(&["class", "Account", "--no-self", "-t", "tests/fixtures/receivers.py"]);
+    let account = &value["files"]["tests/fixtures/receivers.py"]["class Account"];
+    assert_eq!(account["methods"]["def deposit(amount: int) -> None"], 2);
+}
+
+#[test]
+fn test_class_key_name() {
+    let value = pyr_json(&["class", "--class-key", "name", "-t", "tests/fixtures/services.py"]);
+    let classes = value["files"]["tests/fixtures/services.py"].as_object().unwrap();
+    assert_eq!(classes.keys().collect::<Vec<_>>(), vec!["BaseService", "UserService"]);
+    assert_eq!(
+        classes["UserService"],
+        json!({
+            "signature": "class UserService(BaseService)",
+            "methods": {"def create_user(self, name: str) -> None": 10}
+        })
+    );
+}
+
+#[test]
+fn test_class_keep_empty() {
+    let value = pyr_json(&["class", "--public", "-t", "tests/fixtures/keep_empty.py"]);
+    let classes = value["files"]["tests/fixtures/keep_empty.py"].as_object().unwrap();
+    assert!(!classes.contains_key("class Internal"));
+
+    let value = pyr_json(&[
+        "class",
+        "--public",
+        "--keep-empty",
+        "-t",
+        "tests/fixtures/keep_empty.py",
+    ]);
+    assert_eq!(
+        value["files"]["tests/fixtures/keep_empty.py"],
+        json!({"class Internal": {}, "class Public": {"fields": {"name: str": 12}}})
+    );
+}
+
+#[test]
+fn test_async_only() {
+    let value = pyr_json(&["function", "--async", "-t", "tests/fixtures/async_mix.py"]);
+    let functions = value["files"]["tests/fixtures/async_mix.py"].as_object().unwrap();
+    assert_eq!(functions.len(), 2);
+    assert!(functions.keys().all(|sig| sig.starts_with("async def ")));
+
+    let value = pyr_json(&["class", "--async", "-t", "tests/fixtures/async_mix.py"]);
+    assert_eq!(
+        value["files"]["tests/fixtures/async_mix.py"],
+        json!({"class Client": {"methods": {"async def get(self, path: str) -> bytes": 17}}})
+    );
+
+    pyr().args(["function", "--async", "--sync"]).assert().failure();
+}
+
+#[test]
+fn test_noreturn() {
+    // Only marked with the flag
+    let value = pyr_json(&["function", "-t", "tests/fixtures/noreturn.py"]);
+    assert_eq!(
+        value["files"]["tests/fixtures/noreturn.py"]["def fail(msg: str) -> NoReturn"],
+        json!(6)
+    );
+
+    let value = pyr_json(&["function", "--noreturn", "-t", "tests/fixtures/noreturn.py"]);
+    assert_eq!(
+        value["files"]["tests/fixtures/noreturn.py"],
+        json!({
+            "def fail(msg: str) -> NoReturn": {"line": 6, "noreturn": true},
+            "def serve(queue)": {"line": 10, "noreturn": true},
+        })
+    );
+
+    let value = pyr_json(&["class", "--noreturn", "-t", "tests/fixtures/noreturn.py"]);
+    assert_eq!(
+        value["files"]["tests/fixtures/noreturn.py"],
+        json!({"class Worker": {"methods": {"def run(self)": {"line": 26, "noreturn": true}}}})
+    );
+}
+
+#[test]
+fn test_context_managers() {
+    // Only marked with the flag
+    let value = pyr_json(&["function", "-t", "tests/fixtures/context_managers.py"]);
+    assert_eq!(
+        value["files"]["tests/fixtures/context_managers.py"]["def opened(path)"],
+        json!(7)
+    );
+
+    let value = pyr_json(&[
+        "class",
+        "--context-managers",
+        "--names-only",
+        "-t",
+        "tests/fixtures/context_managers.py",
+    ]);
+    let classes: Vec<&String> = value["files"]["tests/fixtures/context_managers.py"]
+        .as_object()
+        .unwrap()
+        .keys()
+        .collect();
+    assert_eq!(classes, vec!["AsyncPool", "Lock"]);
+
+    let value = pyr_json(&[
+        "function",
+        "--context-managers",
+        "-t",
+        "tests/fixtures/context_managers.py",
+    ]);
+    assert_eq!(
+        value["files"]["tests/fixtures/context_managers.py"],
+        json!({
+            "def opened(path)": {"line": 7, "context_manager": true},
+            "async def session()": {"line": 16, "context_manager": true},
+        })
+    );
+}
+
+#[test]
+fn test_enum() {
+    let value = pyr_json(&["enum", "--private", "-t", "tests/fixtures/enums.py"]);
+    assert_eq!(
+        value,
+        json!({"files": {"tests/fixtures/enums.py": {"class _InternalStatus(Enum)": 26}}})
+    );
+}
+
+#[test]
+fn test_module() {
+    let value = pyr_json(&["module", "-t", "tests/fixtures/pkg"]);
+    assert_eq!(value["modules"]["subpkg"]["type"], "package");
+    assert_eq!(
+        value["modules"]["subpkg"]["children"]["subpkg/nested.py"]["type"],
+        "module"
+    );
+}
+
+#[test]
+fn test_entrypoint() {
+    let value = pyr_json(&["entrypoint", "-t", "tests/fixtures"]);
+    assert_eq!(value["files"]["tests/fixtures/entrypoint.py"]["__main__"], 9);
+}
+
+#[test]
+fn test_deps() {
+    let value = pyr_json(&["deps", "-t", "tests/fixtures/deps"]);
+    assert_eq!(
+        value["deps"]["tests/fixtures/deps/a.py"],
+        json!(["tests/fixtures/deps/b.py", "tests/fixtures/deps/c.py"])
+    );
+    assert_eq!(
+        value["external"]["tests/fixtures/deps/a.py"],
+        json!(["json", "os.path"])
+    );
+}
+
+#[test]
+fn test_coverage() {
+    let value = pyr_json(&["coverage", "-t", "tests/fixtures/coverage.py"]);
+    assert_eq!(
+        value["total"]["params"],
+        json!({"annotated": 4, "total": 9, "percent": 44.4})
+    );
+    assert_eq!(
+        value["total"]["returns"],
+        json!({"annotated": 3, "total": 6, "percent": 50.0})
+    );
+}
+
+#[test]
+fn test_loc() {
+    let value = pyr_json(&["loc", "-t", "tests/fixtures/loc.py"]);
+    assert_eq!(
+        value["total"],
+        json!({"lines": 19, "blank": 5, "comment": 4, "code": 10})
+    );
+}
+
+#[test]
+fn test_untyped() {
+    let value = pyr_json(&["untyped", "-t", "tests/fixtures/functions.py"]);
+    let untyped = value["files"]["tests/fixtures/functions.py"].as_object().unwrap();
+    assert!(untyped.contains_key("def function_with_args(a, b, c)"));
+    assert!(!untyped.keys().any(|sig| sig.starts_with("def function_with_types")));
+}
+
+#[test]
+fn test_diff() {
+    let value = pyr_json(&["diff", "tests/fixtures/diff/old", "tests/fixtures/diff/new"]);
+    let api = &value["files"]["api.py"];
+    assert_eq!(api["added"], json!(["def retry(times: int) -> None"]));
+    assert_eq!(api["changed"]["parse"]["new"], "def parse(data: bytes) -> dict");
+}
+
+#[test]
+fn test_refs() {
+    let value = pyr_json(&["refs", "helper", "-t", "tests/fixtures/refs.py"]);
+    let scopes: Vec<&str> = value["files"]["tests/fixtures/refs.py"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|site| site["scope"].as_str().unwrap())
+        .collect();
+    assert_eq!(scopes, vec!["process", "Service.run"]);
+}
+
+#[test]
+fn test_effects() {
+    let value = pyr_json(&["effects", "-t", "tests/fixtures/effects.py"]);
+    let effects = value["files"]["tests/fixtures/effects.py"].as_array().unwrap();
+    assert_eq!(
+        effects[1],
+        json!({"line": 13, "kind": "call", "code": "print(\"loading\")"})
+    );
+    assert_eq!(effects[2]["kind"], "loop");
+    assert!(effects.iter().all(|effect| effect["line"] != 7));
+}
+
+#[test]
+fn test_shadows() {
+    let value = pyr_json(&["shadows", "-t", "tests/fixtures/shadows"]);
+    assert_eq!(
+        value["files"],
+        json!({
+            "tests/fixtures/shadows/duplicate_import.py": [
+                {"name": "os", "kind": "duplicate", "lines": [3, 6]}
+            ],
+            "tests/fixtures/shadows/shadowed_import.py": [
+                {"name": "loads", "kind": "shadowed", "lines": [3, 10]}
+            ]
+        })
+    );
+}
+
+#[test]
+fn test_dupes() {
+    let value = pyr_json(&["dupes", "--kind", "class", "-t", "tests/fixtures/dupes"]);
+    let files: Vec<&str> = value["names"]["Settings"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|definition| definition["file"].as_str().unwrap())
+        .collect();
+    assert_eq!(files, vec!["tests/fixtures/dupes/a.py", "tests/fixtures/dupes/b.py"]);
+    assert!(value["names"].get("load").is_none());
+}
+
+#[test]
+fn test_decorators() {
+    let value = pyr_json(&["decorators", "cache", "-t", "tests/fixtures/decorators"]);
+    assert_eq!(
+        value,
+        json!({"decorators": {"cache": {"count": 2, "files": 2, "uses": [
+            {"file": "tests/fixtures/decorators/models.py", "line": 12, "name": "Order.rates"},
+            {"file": "tests/fixtures/decorators/views.py", "line": 14, "name": "about"},
+        ]}}})
+    );
+}
+
+/// Write a wheel holding `members` (archive path, source) to `path`
+fn write_wheel(path: &std::path::Path, members: &[(&str, &[u8])]) {
+    use std::io::Write;
+
+    let mut zip = zip::ZipWriter::new(std::fs::File::create(path).unwrap());
+    for (member, source) in members {
+        zip.start_file(*member, zip::write::SimpleFileOptions::default())
+            .unwrap();
+        zip.write_all(source).unwrap();
+    }
+    zip.finish().unwrap();
+}
+
+#[test]
+fn test_wheel_target() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let wheel = dir.path().join("mypkg-1.0-py3-none-any.whl");
+    let sizes = std::fs::read("tests/fixtures/sizes.py").unwrap();
+    let views = std::fs::read("tests/fixtures/decorators/views.py").unwrap();
+    write_wheel(
+        &wheel,
+        &[
+            ("mypkg/sizes.py", &sizes),
+            ("mypkg/views.py", &views),
+            ("mypkg-1.0.dist-info/METADATA", b"Name: mypkg\n"),
+        ],
+    );
+
+    let wheel = wheel.to_str().unwrap();
+    let value = pyr_json(&["function", "-t", wheel]);
+    assert_eq!(
+        value["files"],
+        json!({
+            format!("{}/mypkg/sizes.py", wheel): {"def small(x)": 4, "def large(rows)": 8},
+            format!("{}/mypkg/views.py", wheel): {"def index()": 8, "def about()": 14, "def helper()": 18},
+        })
+    );
+}
+
+#[test]
+fn test_wheel_targets_with_shared_member_paths() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let first = dir.path().join("a-1.0.whl");
+    let second = dir.path().join("b-1.0.whl");
+    write_wheel(&first, &[("pkg/core.py", b"def from_a():\n    pass\n")]);
+    write_wheel(&second, &[("pkg/core.py", b"def from_b():\n    pass\n")]);
+    std::fs::create_dir(dir.path().join("pkg")).unwrap();
+    std::fs::write(dir.path().join("pkg/core.py"), "def from_disk():\n    pass\n").unwrap();
+
+    let path = |path: std::path::PathBuf| path.to_str().unwrap().to_string();
+    let (first, second, disk) = (path(first), path(second), path(dir.path().join("pkg")));
+
+    let value = pyr_json(&["function", "-t", &first, "-t", &second]);
+    assert_eq!(
+        value["files"],
+        json!({
+            format!("{}/pkg/core.py", first): {"def from_a()": 1},
+            format!("{}/pkg/core.py", second): {"def from_b()": 1},
+        })
+    );
+
+    let value = pyr_json(&["function", "-t", &first, "-t", &disk]);
+    assert_eq!(
+        value["files"],
+        json!({
+            format!("{}/pkg/core.py", first): {"def from_a()": 1},
+            format!("{}/core.py", disk): {"def from_disk()": 1},
+        })
+    );
+}
+
+#[test]
+fn test_stub() {
+    let golden = std::fs::read_to_string("tests/fixtures/stubs/sample.pyi").unwrap();
+    pyr()
+        .args(["stub", "-t", "tests/fixtures/stubs/sample.py"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(golden.trim_end()));
+}
+
+#[test]
+fn test_stub_output_dir_with_sibling_targets() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let out = dir.path().join("out");
+    pyr()
+        .args([
+            "stub",
+            "-t",
+            "tests/fixtures/dupes",
+            "-t",
+            "tests/fixtures/stubs",
+            "--output-dir",
+        ])
+        .arg(&out)
+        .assert()
+        .success();
+    let mut written: Vec<_> = std::fs::read_dir(&out)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .collect();
+    written.sort();
+    assert_eq!(written, vec!["a.pyi", "b.pyi", "sample.pyi"]);
+}
+
+#[test]
+fn test_dump() {
+    let value = pyr_json(&["dump", "--only", "enums", "-t", "tests/fixtures/enums.py"]);
+    let enums = value["files"]["tests/fixtures/enums.py"].as_object().unwrap();
+    assert!(enums.contains_key("class Color(Enum)"));
+    assert!(enums.keys().all(|sig| sig.starts_with("class ")));
+}
+
+#[test]
+fn test_jsonl_by_symbol_emits_one_line_per_symbol() {
+    let classes = pyr_json(&["class", "-t", "tests/fixtures/classes.py"]);
+    let symbols: usize = classes["files"]["tests/fixtures/classes.py"]
+        .as_object()
+        .unwrap()
+        .values()
+        .map(|class| 1 + class.get("methods").and_then(Value::as_object).map_or(0, |m| m.len()))
+        .sum();
+
+    let output = pyr()
+        .args([
+            "--format",
+            "jsonl-by-symbol",
+            "class",
+            "-t",
+            "tests/fixtures/classes.py",
+        ])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let lines: Vec<Value> = String::from_utf8(output)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(lines.len(), symbols);
+    assert_eq!(lines[0]["kind"], "class");
+    let method = lines.iter().find(|line| line["kind"] == "method").unwrap();
+    assert_eq!(method["file"], "tests/fixtures/classes.py");
+    assert!(method["parent"].is_string());
+
+    pyr()
+        .args(["--format", "jsonl-by-symbol", "loc"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "only supported by the function, class, enum and dump",
+        ));
+}
+
+#[test]
+fn test_signature_only_drops_line_numbers() {
+    let value = pyr_json(&[
+        "--signature-only",
+        "function",
+        "simple",
+        "-t",
+        "tests/fixtures/functions.py",
+    ]);
+    assert_eq!(
+        value,
+        json!({"files": {"tests/fixtures/functions.py": ["def simple_function()"]}})
+    );
+
+    let value = pyr_json(&["--signature-only", "class", "-t", "tests/fixtures/classes.py"]);
+    let classes = value["files"]["tests/fixtures/classes.py"].as_object().unwrap();
+    assert_eq!(classes["class SimpleClass"], json!({}));
+    assert_eq!(
+        classes["class ClassWithMethods"]["methods"][0],
+        "def public_method(self) -> None"
+    );
+    let text = value.to_string();
+    assert!(!text.chars().any(|c| c.is_ascii_digit()), "{}", text);
+}
+
+#[test]
+fn test_function_yaml() {
+    pyr()
+        .args([
+            "--format",
+            "yaml",
+            "function",
+            "simple",
+            "-t",
+            "tests/fixtures/functions.py",
+        ])
+        .assert()
+        .success()
+        .stdout("files:\n  tests/fixtures/functions.py:\n    def simple_function(): 3\n");
+}
+
+#[test]
+fn test_nonexistent_path_fails_every_command() {
+    let commands: &[&[&str]] = &[
+        &["function"],
+        &["class"],
+        &["enum"],
+        &["module"],
+        &["entrypoint"],
+        &["deps"],
+        &["coverage"],
+        &["loc"],
+        &["untyped"],
+        &["refs", "helper"],
+        &["effects"],
+        &["shadows"],
+        &["dupes"],
+        &["decorators"],
+        &["stub"],
+        &["dump"],
+    ];
+    for command in commands {
+        pyr()
+            .args(*command)
+            .args(["-t", "tests/fixtures/no_such_dir"])
+            .assert()
+            .failure()
+            .stdout("")
+            .stderr(predicate::str::contains(
+                "Path does not exist: tests/fixtures/no_such_dir",
+            ));
+    }
+
+    pyr()
+        .args(["diff", "tests/fixtures/no_such_dir", "tests/fixtures/diff/new"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Path does not exist: tests/fixtures/no_such_dir",
+        ));
+}
+
+#[test]
+fn test_list_targets() {
+    pyr()
+        .args(["--list-targets", "function", "-t", "tests/fixtures/targets"])
+        .assert()
+        .success()
+        .stdout("tests/fixtures/targets/app.py\ntests/fixtures/targets/util/helpers.py\n");
+    pyr()
+        .args(["--list-targets", "--hidden", "function", "-t", "tests/fixtures/targets"])
+        .assert()
+        .success()
+        .stdout(
+            "tests/fixtures/targets/.cache/cached.py\ntests/fixtures/targets/app.py\ntests/fixtures/targets/util/helpers.py\n",
+        );
+}
+
+#[test]
+fn test_list_targets_null() {
+    let output = pyr()
+        .args(["--list-targets", "-0", "function", "-t", "tests/fixtures/targets"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(!output.stdout.contains(&b'\n'));
+    assert_eq!(
+        output.stdout,
+        b"tests/fixtures/targets/app.py\0tests/fixtures/targets/util/helpers.py\0"
+    );
+
+    pyr()
+        .args(["--null", "function", "-t", "tests/fixtures/targets"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--list-targets"));
+}
+
+#[test]
+fn test_max_symbols() {
+    pyr()
+        .args(["--max-symbols", "2", "function", "-t", "tests/fixtures"])
+        .assert()
+        .failure()
+        .stdout("")
+        .stderr(predicate::str::contains("More than 2 symbols found (--max-symbols)"));
+    pyr()
+        .args(["--max-symbols", "1000", "function", "-t", "tests/fixtures/functions.py"])
+        .assert()
+        .success();
+}
+
+#[test]
+fn test_progress_is_silent_when_piped() {
+    for flags in [&["--progress"][..], &[]] {
+        pyr()
+            .args(flags)
+            .args(["function", "-t", "tests/fixtures"])
+            .assert()
+            .success()
+            .stderr("");
+    }
+    pyr()
+        .args(["--progress", "--no-progress", "function"])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_invalid_arguments_are_rejected() {
+    pyr()
+        .args(["function", "--public", "--private"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+    pyr()
+        .args(["class", "--kind", "struct"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("invalid value 'struct'"));
+    pyr().arg("nonsense").assert().failure();
+}
+
+#[test]
+fn test_per_file_limit() {
+    let assert = pyr()
+        .args(["--json", "--per-file-limit", "2", "function", "-t", "tests/fixtures"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "tests/fixtures/functions.py: showing the first 2 of",
+        ));
+    let value: Value = serde_json::from_slice(&assert.get_output().stdout).unwrap();
+    for (path, functions) in value["files"].as_object().unwrap() {
+        assert!(functions.as_object().unwrap().len() <= 2, "{}", path);
+    }
+
+    pyr().args(["--per-file-limit", "0", "function"]).assert().failure();
+}
+
+#[test]
+fn test_summary_footer_goes_to_stderr() {
+    pyr()
+        .args(["--json", "--summary", "enum", "-t", "tests/fixtures/enums.py"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("--").not())
+        .stderr("-- 4 symbols in 1 file --\n");
+
+    // Off by default when stdout isn't a terminal
+    pyr()
+        .args(["enum", "-t", "tests/fixtures/enums.py"])
+        .assert()
+        .success()
+        .stderr("");
+}
+
+#[test]
+fn test_fuzzy_ranks_by_score() {
+    let order = |args: &[&str]| {
+        let output = pyr()
+            .args([
+                "--fuzzy",
+                "--compact",
+                "--json",
+                "-t",
+                "tests/fixtures/fuzzy.py",
+                "function",
+                "cptl",
+            ])
+            .args(args)
+            .output()
+            .unwrap();
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        let mut names = ["a_cptl", "compute_total", "cxpxtxl"];
+        names.sort_by_key(|name| stdout.find(&format!("def {}()", name)).unwrap());
+        (names, stdout)
+    };
+
+    let (names, stdout) = order(&[]);
+    assert_eq!(names, ["a_cptl", "compute_total", "cxpxtxl"]);
+    assert!(stdout.contains(r#""def a_cptl()":{"line":9,"score":27}"#), "{}", stdout);
+    assert!(!stdout.contains("unrelated"));
+
+    // An explicit sort key still wins
+    let (names, _) = order(&["--sort-by", "line"]);
+    assert_eq!(names, ["cxpxtxl", "compute_total", "a_cptl"]);
+}
+
+#[test]
+fn test_limit_across_files() {
+    let largest = |args: &[&str]| {
+        let value = pyr_json(
+            &[
+                &[
+                    "-t",
+                    "tests/fixtures/sizes.py",
+                    "-t",
+                    "tests/fixtures/dupes",
+                    "function",
+                    "--sort-by",
+                    "lines",
+                ],
+                args,
+            ]
+            .concat(),
+        );
+        value["files"].clone()
+    };
+    // Reverse then limit: the largest functions of all files, not of each
+    assert_eq!(
+        largest(&["--reverse", "--limit", "2"]),
+        json!({
+            "tests/fixtures/dupes/a.py": {"def load(path)": 4},
+            "tests/fixtures/sizes.py": {"def large(rows)": 8},
+        })
+    );
+    assert_eq!(
+        largest(&["--reverse", "--limit", "1"]),
+        json!({"tests/fixtures/sizes.py": {"def large(rows)": 8}})
+    );
+    assert_eq!(
+        largest(&["--limit", "1"]),
+        json!({"tests/fixtures/dupes/a.py": {"def parse(data: bytes) -> bytes": 15}})
+    );
+}
@@ -0,0 +1,29 @@
+"""Test fixture for abstract class detection."""
+import abc
+from abc import ABC, ABCMeta, abstractmethod
+
+
+class Shape(ABC):
+    @abstractmethod
+    def area(self) -> float:
+        pass
+
+    def describe(self) -> str:
+        return "shape"
+
+
+class Repository(metaclass=ABCMeta):
+    @abc.abstractmethod
+    def get(self, key: str) -> object:
+        pass
+
+
+class Plugin:
+    @abstractmethod
+    def run(self) -> None:
+        pass
+
+
+class Concrete:
+    def area(self) -> float:
+        return 1.0
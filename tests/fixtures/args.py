@@ -0,0 +1,14 @@
+def two(a, b):
+    pass
+
+
+def five(a, b: int, *args, key=None, **kwargs):
+    pass
+
+
+class Builder:
+    def reset(self):
+        pass
+
+    def configure(self, host, port, user, password):
+        pass
@@ -0,0 +1,26 @@
+"""Test fixture for --async/--sync: coroutines next to plain functions."""
+
+
+async def fetch(url: str) -> bytes:
+    return b""
+
+
+def parse(data: bytes) -> dict:
+    return {}
+
+
+async def stream(url: str):
+    yield b""
+
+
+class Client:
+    async def get(self, path: str) -> bytes:
+        return await fetch(path)
+
+    def close(self) -> None:
+        pass
+
+
+class Config:
+    def load(self) -> dict:
+        return {}
@@ -0,0 +1,20 @@
+"""Test fixture for attrs classes decorated with imported names."""
+
+from attrs import define, frozen as immutable
+
+from mylib import mutable
+
+
+@define
+class Imported:
+    x: int
+
+
+@immutable
+class Aliased:
+    y: int = 0
+
+
+@mutable
+class Unrelated:
+    z: int
@@ -0,0 +1,6 @@
+﻿def first():
+    pass
+
+
+class Second:
+    pass
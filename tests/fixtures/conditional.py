@@ -0,0 +1,20 @@
+"""Test fixture for conditionally defined classes and functions."""
+import sys
+
+try:
+    from ujson import JSONDecoder
+except ImportError:
+    class JSONDecoder:
+        def decode(self, text: str) -> dict:
+            return {}
+
+if sys.version_info >= (3, 11):
+    def parse_toml(text: str) -> dict:
+        return {}
+else:
+    def parse_toml_legacy(text: str) -> dict:
+        return {}
+
+
+class Always:
+    pass
@@ -0,0 +1,14 @@
+"""Test fixture for module-level constants."""
+from typing import Final
+
+MAX_RETRIES = 3
+DEFAULT_TIMEOUT: float = 2.5
+API_URL: Final = "https://example.com"
+SUPPORTED_FORMATS = ("json", "yaml")
+logger = None
+_PRIVATE_LIMIT = 10
+__version__ = "1.0"
+
+
+def connect(retries: int = MAX_RETRIES) -> None:
+    pass
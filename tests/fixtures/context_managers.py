@@ -0,0 +1,48 @@
+"""Test fixture for context-manager detection."""
+import contextlib
+from contextlib import asynccontextmanager, contextmanager
+
+
+@contextmanager
+def opened(path):
+    handle = open(path)
+    try:
+        yield handle
+    finally:
+        handle.close()
+
+
+@contextlib.asynccontextmanager
+async def session():
+    yield None
+
+
+def plain():
+    pass
+
+
+class Lock:
+    def __enter__(self):
+        return self
+
+    def __exit__(self, *exc):
+        return False
+
+
+class AsyncPool:
+    async def __aenter__(self):
+        return self
+
+    async def __aexit__(self, *exc):
+        return False
+
+
+class HalfOpen:
+    def __enter__(self):
+        return self
+
+
+class Transactions:
+    @asynccontextmanager
+    async def begin(self):
+        yield self
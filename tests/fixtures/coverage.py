@@ -0,0 +1,22 @@
+def typed(a: int, b: str) -> bool:
+    return True
+
+
+def untyped(a, b):
+    return a
+
+
+def partial(a: int, *args, **kwargs) -> None:
+    pass
+
+
+class Service:
+    def handle(self, request: dict) -> dict:
+        return request
+
+    def close(self):
+        pass
+
+    @staticmethod
+    def build(config):
+        return Service()
@@ -0,0 +1,17 @@
+import os
+
+
+def first():
+    pass
+
+
+class Second:
+    def method(self):
+        """Doc
+        string"""
+        return 1
+
+
+def third(a,
+          b):
+    pass
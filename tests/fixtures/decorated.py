@@ -0,0 +1,28 @@
+"""Test fixture for --decorated/--undecorated."""
+
+
+def login_required(func):
+    return func
+
+
+@login_required
+def dashboard(request):
+    pass
+
+
+def health(request):
+    pass
+
+
+class Views:
+    @staticmethod
+    def index(request):
+        pass
+
+    def detail(self, request):
+        pass
+
+
+class Plain:
+    def render(self):
+        pass
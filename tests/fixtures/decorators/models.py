@@ -0,0 +1,13 @@
+"""Test fixture for the decorators command."""
+from functools import cache
+
+
+class Order:
+    @property
+    def total(self):
+        return 0
+
+    @staticmethod
+    @cache
+    def rates():
+        return {}
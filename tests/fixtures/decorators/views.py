@@ -0,0 +1,19 @@
+"""Test fixture for the decorators command."""
+from functools import cache
+
+from app import app
+
+
+@app.route("/")
+def index():
+    pass
+
+
+@app.route("/about")
+@cache
+def about():
+    pass
+
+
+def helper():
+    pass
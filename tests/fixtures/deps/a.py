@@ -0,0 +1,10 @@
+import json
+
+from .b import helper
+from deps import c
+
+
+def run() -> str:
+    import os.path
+
+    return json.dumps(helper(c.VALUE)) + os.path.sep
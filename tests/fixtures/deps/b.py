@@ -0,0 +1,2 @@
+def helper(value: int) -> int:
+    return value
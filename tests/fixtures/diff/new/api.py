@@ -0,0 +1,18 @@
+"""API after the change: `parse` gained a type and `retry` was added."""
+
+
+def fetch(url: str) -> bytes:
+    pass
+
+
+def parse(data: bytes) -> dict:
+    pass
+
+
+def retry(times: int) -> None:
+    pass
+
+
+class Client:
+    def get(self, path: str) -> bytes:
+        pass
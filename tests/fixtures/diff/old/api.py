@@ -0,0 +1,14 @@
+"""API before the change."""
+
+
+def fetch(url: str) -> bytes:
+    pass
+
+
+def parse(data):
+    pass
+
+
+class Client:
+    def get(self, path: str) -> bytes:
+        pass
@@ -0,0 +1,21 @@
+from typing import overload
+
+
+def load(path):
+    return open(path).read()
+
+
+class Settings:
+    debug = False
+
+
+@overload
+def parse(data: str) -> str: ...
+@overload
+def parse(data: bytes) -> bytes: ...
+def parse(data):
+    return data
+
+
+def only_in_a():
+    pass
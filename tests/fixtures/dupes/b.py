@@ -0,0 +1,10 @@
+def load(url, timeout=10):
+    pass
+
+
+class Settings:
+    verbose = True
+
+
+def only_in_b():
+    pass
@@ -0,0 +1,39 @@
+"""Module that does work at import time."""
+
+import logging
+from typing import TYPE_CHECKING
+
+if TYPE_CHECKING:
+    from pathlib import Path
+
+LIMIT = 10
+REGISTRY: dict[str, int] = {}
+logger = logging.getLogger(__name__)
+
+print("loading")
+...
+
+for name in ("a", "b"):
+    REGISTRY[name] = len(name)
+
+try:
+    import ujson as json
+except ImportError:
+    import json
+
+if logger.isEnabledFor(logging.DEBUG):
+    logger.debug("registry: %s", REGISTRY)
+
+with open(__file__) as source:
+    HEADER = source.readline()
+
+assert LIMIT > 0
+
+
+def load(path: "Path") -> dict:
+    print("not at import")
+    return json.loads(path.read_text())
+
+
+class Config:
+    debug = bool(LIMIT)
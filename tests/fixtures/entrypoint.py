@@ -0,0 +1,10 @@
+"""Test fixture for entrypoint detection."""
+import sys
+
+
+def main() -> int:
+    return 0
+
+
+if __name__ == "__main__":
+    sys.exit(main())
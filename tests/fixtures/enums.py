@@ -23,6 +23,10 @@ class Permissions(Flag):
     WRITE = auto()
     EXECUTE = auto()
 
+class _InternalStatus(Enum):
+    IDLE = 0
+    BUSY = 1
+
 # This is NOT an enum, should not be extracted
 class NotAnEnum:
     pass
@@ -0,0 +1,30 @@
+"""Test fixture for __all__ extraction."""
+from enum import Enum
+
+__all__ = ["connect", "Client", "Mode"]
+
+
+def connect(url: str) -> "Client":
+    return Client(url)
+
+
+def disconnect(client: "Client") -> None:
+    pass
+
+
+class Client:
+    def __init__(self, url: str):
+        self.url = url
+
+
+class Helper:
+    def run(self) -> None:
+        pass
+
+
+class Mode(Enum):
+    FAST = 1
+
+
+class Level(Enum):
+    LOW = 1
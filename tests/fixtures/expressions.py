@@ -33,3 +33,30 @@ class ComplexAnnotations:
     def method_with_complex_return(self) -> Dict[str, List[int]]:
         return {}
 
+
+# Assignment expressions (walrus)
+WALRUS_CONST = (chunk := 1024)
+
+def func_with_walrus_default(limit=(n := 10)) -> None:
+    pass
+
+# Comprehensions
+UPPER_NAMES = [name.upper() for name in RAW_NAMES if name]
+UNIQUE_IDS = {item.id for item in ITEMS}
+INDEX = {key: value for key, value in PAIRS if value}
+NESTED = [cell for row in GRID for cell in row]
+
+# Await expressions
+async def func_with_await():
+    result = await fetch(URL)
+    return result
+
+
+# Keyword arguments in a call
+def func_with_keyword_default(size=Field(3, alias="x", **extra)) -> None:
+    pass
+
+
+# Tuples inside a subscript versus standalone tuples
+def func_with_tuple_default(pair: Tuple[int, str] = (1, "a"), single=(1,)) -> (int, str):
+    pass
@@ -0,0 +1,19 @@
+from typing import Annotated, Callable, Literal, Optional
+
+MODE: "str" = "dfs"
+
+
+class Node:
+    parent: "Node | None" = None
+    children: list["Node"]
+
+    def add(self, child: "Node") -> "Node":
+        return child
+
+
+def find(root: Optional["Node"], mode: Literal["dfs", "bfs"] = "dfs") -> "Node | None":
+    return None
+
+
+def label(node: Annotated["Node", "display"], render: Callable[["Node"], str]) -> str:
+    return render(node)
@@ -0,0 +1,14 @@
+def cxpxtxl():
+    pass
+
+
+def compute_total():
+    pass
+
+
+def a_cptl():
+    pass
+
+
+def unrelated():
+    pass
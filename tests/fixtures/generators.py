@@ -0,0 +1,29 @@
+import asyncio
+
+
+def count_up(limit):
+    n = 0
+    while n < limit:
+        yield n
+        n += 1
+
+
+async def ticks(interval):
+    while True:
+        await asyncio.sleep(interval)
+        yield interval
+
+
+def delegate(items):
+    yield from items
+
+
+def plain(items):
+    return [item for item in items]
+
+
+def factory():
+    def inner():
+        yield 1
+
+    return inner
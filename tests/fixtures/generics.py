@@ -0,0 +1,25 @@
+"""PEP 695 generic functions and classes."""
+
+
+def first[T](items: list[T]) -> T:
+    return items[0]
+
+
+def pair[K: str, V](key: K, value: V) -> tuple[K, V]:
+    return (key, value)
+
+
+def call[**P, *Ts](func, *args: *Ts) -> None:
+    func(*args)
+
+
+class Stack[T]:
+    def push(self, item: T) -> None:
+        pass
+
+    def map[U](self, func) -> "Stack[U]":
+        pass
+
+
+class Registry[K: str, V](dict[K, V]):
+    pass
@@ -0,0 +1,12 @@
+"""Test fixture for --keep-empty: one class with only private members."""
+
+
+class Internal:
+    _cache: dict
+
+    def _load(self) -> None:
+        pass
+
+
+class Public:
+    name: str
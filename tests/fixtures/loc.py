@@ -0,0 +1,19 @@
+#!/usr/bin/env python3
+# Fixture with known line counts: 19 lines, 5 blank, 4 comment, 10 code
+import os
+
+
+def greet(name):
+    """Say hello.
+
+    Docstring lines count as code.
+    """
+    # Build the message
+    message = f"Hello, {name}"  # trailing comments don't make a comment line
+    return message
+
+    
+class Greeter:
+        # indented comment
+    def run(self):
+        print(greet(os.getlogin()))
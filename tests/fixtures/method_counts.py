@@ -0,0 +1,18 @@
+class Empty:
+    name = "empty"
+
+
+class Single:
+    def run(self):
+        pass
+
+
+class Service:
+    def start(self):
+        pass
+
+    def stop(self):
+        pass
+
+    def _reset(self):
+        pass
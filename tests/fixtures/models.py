@@ -0,0 +1,35 @@
+from dataclasses import dataclass, field
+from typing import ClassVar
+
+import attr
+from pydantic import BaseModel, Field
+
+
+class User(BaseModel):
+    id: int
+    name: str = Field(..., min_length=1)
+    email: str | None = None
+    tags: list[str] = Field(default_factory=list)
+    age: int = Field(18, ge=0)
+    registry: ClassVar[dict] = {}
+
+    def greet(self) -> str:
+        return self.name
+
+
+@dataclass(frozen=True)
+class Point:
+    x: float
+    y: float = 1.5
+    labels: list[str] = field(default_factory=list)
+    origin = None
+
+
+@attr.s
+class Config:
+    path = attr.ib()
+    retries = attr.ib(default=3)
+
+
+class Plain:
+    name: str = "plain"
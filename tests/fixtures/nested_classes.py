@@ -0,0 +1,19 @@
+class Outer:
+    class Middle:
+        class Inner:
+            class Core:
+                def core(self):
+                    pass
+
+            def inner(self):
+                pass
+
+        def middle(self):
+            pass
+
+    def outer(self):
+        pass
+
+
+class Flat:
+    name: str
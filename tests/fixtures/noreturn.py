@@ -0,0 +1,31 @@
+"""Test fixture for the noreturn marker and --noreturn."""
+import sys
+from typing import NoReturn
+
+
+def fail(msg: str) -> NoReturn:
+    sys.exit(msg)
+
+
+def serve(queue):
+    while True:
+        queue.get()
+
+
+def poll(queue):
+    while True:
+        if queue.empty():
+            break
+
+
+def parse(text):
+    return text.split()
+
+
+class Worker:
+    def run(self):
+        while 1:
+            self.step()
+
+    def step(self):
+        pass
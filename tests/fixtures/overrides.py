@@ -0,0 +1,30 @@
+"""Subclasses overriding base class methods."""
+
+
+class Shape:
+    def area(self) -> float:
+        return 0.0
+
+    def describe(self) -> str:
+        return "shape"
+
+
+class Rectangle(Shape):
+    def area(self) -> float:
+        return 1.0
+
+    def corners(self) -> int:
+        return 4
+
+
+class Square(Rectangle):
+    def area(self) -> float:
+        return 2.0
+
+    def describe(self) -> str:
+        return "square"
+
+
+class Widget(external.Base):
+    def render(self) -> None:
+        pass
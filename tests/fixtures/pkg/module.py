@@ -0,0 +1,11 @@
+def load(path: str) -> str:
+    return path
+
+
+def save(path: str, data: str) -> None:
+    pass
+
+
+class Store:
+    def get(self, key: str) -> str:
+        return key
@@ -0,0 +1,10 @@
+from enum import Enum
+
+
+class Kind(Enum):
+    A = 1
+    B = 2
+
+
+def describe(kind: Kind) -> str:
+    return kind.name
@@ -0,0 +1,14 @@
+"""Test fixture for positional-only parameters."""
+
+
+def clamp(value: float, /, low: float, high: float) -> float:
+    return max(low, min(value, high))
+
+
+class Point:
+    def __init__(self, x, /, y):
+        self.x = x
+        self.y = y
+
+    def scale(self, /, factor: float) -> "Point":
+        return Point(self.x * factor, self.y * factor)
@@ -0,0 +1,19 @@
+class Temperature:
+    def __init__(self, celsius: float):
+        self._celsius = celsius
+
+    @property
+    def celsius(self):
+        return self._celsius
+
+    @celsius.setter
+    def celsius(self, value: float):
+        self._celsius = value
+
+    @celsius.deleter
+    def celsius(self):
+        del self._celsius
+
+    @property
+    def fahrenheit(self) -> float:
+        return self._celsius * 9 / 5 + 32
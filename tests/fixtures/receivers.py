@@ -0,0 +1,14 @@
+class Account:
+    def deposit(self, amount: int) -> None:
+        pass
+
+    @classmethod
+    def open(cls, owner: str) -> "Account":
+        return cls()
+
+    @staticmethod
+    def validate(self, amount: int) -> bool:
+        return amount > 0
+
+    def transfer(target, self) -> None:
+        pass
@@ -0,0 +1,22 @@
+def helper(x):
+    return x * 2
+
+
+def process(items):
+    return [helper(i) for i in items]
+
+
+class Service:
+    def __init__(self, repo):
+        self.repo = repo
+
+    def run(self, value):
+        def log(message):
+            print(message)
+
+        log("running")
+        self.repo.save(helper(value))
+        return self.helper_count()
+
+    def helper_count(self):
+        return 0
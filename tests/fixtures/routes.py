@@ -0,0 +1,13 @@
+from flask import Flask
+
+app = Flask(__name__)
+
+
+@app.route("/users/<int:id>", methods=["GET", "POST"])
+def user(id: int) -> str:
+    return str(id)
+
+
+@app.before_request
+def authenticate() -> None:
+    pass
@@ -0,0 +1,11 @@
+"""Test fixture for --class-key name."""
+
+
+class BaseService:
+    def start(self) -> None:
+        pass
+
+
+class UserService(BaseService):
+    def create_user(self, name: str) -> None:
+        pass
@@ -0,0 +1,14 @@
+"""Imports os twice after a copy-paste."""
+
+import os
+import sys
+from pathlib import Path
+import os
+
+
+def cwd() -> Path:
+    return Path(os.getcwd())
+
+
+def argv() -> list:
+    return sys.argv
@@ -0,0 +1,15 @@
+"""An imported name redefined further down."""
+
+from json import dumps, loads
+from typing import TYPE_CHECKING
+
+if TYPE_CHECKING:
+    from collections.abc import Mapping
+
+
+def loads(text: str) -> "Mapping":
+    return {"text": text}
+
+
+def encode(data: dict) -> str:
+    return dumps(data)
@@ -0,0 +1,50 @@
+"""Test fixture for --min-lines/--max-lines."""
+
+
+def small(x):
+    return x + 1
+
+
+def large(rows):
+    total = 0
+    count = 0
+    for row in rows:
+        if row is None:
+            continue
+        value = row.get("value", 0)
+        if value < 0:
+            value = -value
+        total += value
+        count += 1
+    if count == 0:
+        return 0
+    mean = total / count
+    spread = 0
+    for row in rows:
+        if row is None:
+            continue
+        spread += (row.get("value", 0) - mean) ** 2
+    return spread / count
+
+
+class Report:
+    def title(self):
+        return "report"
+
+    def render(self, rows):
+        lines = []
+        for row in rows:
+            lines.append(str(row))
+        header = self.title()
+        lines.insert(0, header)
+        lines.append("")
+        lines.append("end")
+        body = "\n".join(lines)
+        body = body.strip()
+        body = body.replace("\t", "    ")
+        if not body:
+            return header
+        width = max(len(line) for line in lines)
+        rule = "-" * width
+        body = rule + "\n" + body + "\n" + rule
+        return body
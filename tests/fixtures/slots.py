@@ -0,0 +1,15 @@
+class Point:
+    __slots__ = ("x", "y")
+
+    def __init__(self, x: float, y: float):
+        self.x = x
+        self.y = y
+
+
+class Named:
+    __slots__ = ["name"]
+    kind = "named"
+
+
+class Single:
+    __slots__ = "value"
@@ -0,0 +1,5 @@
+"""Test fixture for signature whitespace normalization."""
+
+
+def messy(mapping: "Dict[str,   int]", pair: "Tuple[ int ,str ]") -> "List[\n    str]":
+    return []
@@ -0,0 +1,34 @@
+import typing
+from typing import NamedTuple, Protocol, TypedDict
+
+
+class Closeable(Protocol):
+    def close(self) -> None: ...
+
+
+class SupportsRead(typing.Protocol[T]):
+    def read(self, size: int) -> T: ...
+
+
+class Movie(TypedDict, total=False):
+    title: str
+    year: int
+
+
+class Point(NamedTuple):
+    x: float
+    y: float = 0.0
+
+    def norm(self) -> float:
+        return (self.x**2 + self.y**2) ** 0.5
+
+
+Pair = NamedTuple("Pair", [("left", int), ("right", int)])
+Size = NamedTuple("Size", width=int, height=int)
+Album = TypedDict("Album", {"name": str, "tracks": list[str]})
+Triple = typing.NamedTuple("Triple", [("a", int), ("b", int), ("c", int)])
+Alias = dict
+
+
+class Plain:
+    label: str
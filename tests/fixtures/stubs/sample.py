@@ -0,0 +1,51 @@
+"""Module used to check generated stubs."""
+
+import os
+from dataclasses import dataclass, field
+from typing import Iterator
+
+MAX_SIZE = 10
+
+
+def read(path: str, /, mode: str = "r", *, encoding=None) -> str:
+    with open(path, mode, encoding=encoding) as f:
+        return f.read()
+
+
+async def stream(*chunks: bytes, **options: int) -> Iterator[bytes]:
+    for chunk in chunks:
+        yield chunk
+
+
+def untyped(a, b=2):
+    return a + b
+
+
+@dataclass
+class Point:
+    """A point in the plane."""
+
+    x: float
+    y: float = 0.5
+    tags: list[str] = field(default_factory=list)
+
+    def norm(self) -> float:
+        return (self.x**2 + self.y**2) ** 0.5
+
+    @staticmethod
+    def origin() -> "Point":
+        return Point(0.5)
+
+
+class Store[T](dict[str, T], metaclass=type):
+    VERSION = 2
+
+    class Entry:
+        pass
+
+    async def fetch(self, key: str) -> T:
+        return self[key]
+
+
+class Empty:
+    """Only a docstring."""
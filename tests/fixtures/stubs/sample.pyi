@@ -0,0 +1,25 @@
+import os
+from dataclasses import dataclass, field
+from typing import Iterator
+
+def read(path: str, /, mode: str = ..., *, encoding=...) -> str: ...
+
+async def stream(*chunks: bytes, **options: int) -> Iterator[bytes]: ...
+
+def untyped(a, b=...): ...
+
+@dataclass
+class Point:
+    x: float
+    y: float = ...
+    tags: list[str] = ...
+    def norm(self) -> float: ...
+    @staticmethod
+    def origin() -> "Point": ...
+
+class Store[T](dict[str, T], metaclass=type):
+    VERSION = ...
+    class Entry: ...
+    async def fetch(self, key: str) -> T: ...
+
+class Empty: ...
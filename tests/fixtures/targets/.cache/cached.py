@@ -0,0 +1 @@
+"""In a hidden directory."""
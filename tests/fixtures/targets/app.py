@@ -0,0 +1 @@
+"""Listed by --list-targets."""
@@ -0,0 +1 @@
+"""In a built-in ignored directory."""
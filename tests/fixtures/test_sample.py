@@ -0,0 +1,32 @@
+import unittest
+
+
+def helper() -> int:
+    return 1
+
+
+def test_foo():
+    assert helper() == 1
+
+
+class TestBar:
+    value = 1
+
+    def setup_method(self):
+        self.value = 1
+
+    def test_value(self):
+        assert self.value == 1
+
+    def test_helper(self):
+        assert helper() == 1
+
+
+class ArithmeticCase(unittest.TestCase):
+    def test_add(self):
+        self.assertEqual(1 + 1, 2)
+
+
+class Plain:
+    def test_not_collected(self):
+        pass